@@ -0,0 +1,147 @@
+use bevy::prelude::*;
+
+use crate::bitboard::Bitboard;
+use crate::energy::Energy;
+use crate::hotkeys::Hotkeys;
+use crate::patterns;
+use crate::spawners::Direction;
+use crate::{update_map, Board, SimCounters, SimRng};
+
+/// Generations between the AI's moves.
+const MOVE_PERIOD: u64 = 40;
+/// Side of the square blocks the heatmap counts cells in.
+const HEAT_BLOCK: u32 = 8;
+/// How far from its target the AI drops a pattern, so it has room to get going.
+const LAUNCH_DISTANCE: i64 = 14;
+/// Patterns the AI fires, cheapest first. All of them head south-east as drawn.
+const ARSENAL: [&str; 2] = ["glider", "gosper-gun"];
+
+/// A computer player for competitive mode. It spends its team's energy on gliders and
+/// guns aimed at the densest part of the other team, paying for them like a player would.
+#[derive(Resource, Default)]
+pub struct Ai {
+    /// Team id the AI plays, or `None` when it's off.
+    pub team: Option<u8>,
+    last_move: u64,
+}
+
+pub struct AiPlugin;
+
+impl Plugin for AiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ai>()
+            .add_system(toggle_ai)
+            .add_system(play.after(update_map));
+    }
+}
+
+/// O turns the AI on playing team 2, or off again.
+fn toggle_ai(keys: Hotkeys, mut ai: ResMut<Ai>) {
+    if keys.just_pressed(KeyCode::O) {
+        ai.team = match ai.team {
+            None => Some(3),
+            Some(_) => None,
+        };
+    }
+}
+
+fn play(
+    counters: Res<SimCounters>,
+    mut ai: ResMut<Ai>,
+    mut energy: ResMut<Energy>,
+    mut rng: ResMut<SimRng>,
+    mut board: ResMut<Board>,
+) {
+    let Some(team) = ai.team else {
+        return;
+    };
+
+    // without energy to spend the AI would have no limit at all
+    if !energy.enabled || counters.generation < ai.last_move + MOVE_PERIOD {
+        return;
+    }
+    ai.last_move = counters.generation;
+
+    let opponent = if team == 2 { 3 } else { 2 };
+    let Some(target) = densest_block(&board, opponent) else {
+        return;
+    };
+
+    // fire from the AI's own side of the board, or from the middle if it has nothing left
+    let home =
+        centroid(&board, team).unwrap_or((board.width() as i64 / 2, board.height() as i64 / 2));
+    let direction = Direction::toward(target.0 - home.0, target.1 - home.1);
+
+    // the biggest pattern it can afford right now
+    let Some(pattern) = ARSENAL.iter().rev().find_map(|&name| {
+        let pattern = patterns::find(name)?;
+        energy
+            .try_spend(team, pattern.cells().count() as f32)
+            .then_some(pattern)
+    }) else {
+        return;
+    };
+
+    let jitter = |rng: &mut SimRng| (rng.next_u64() % 7) as i64 - 3;
+    let (step_x, step_y) = (
+        if direction.is_west() { -1 } else { 1 },
+        if direction.is_north() { 1 } else { -1 },
+    );
+    let launch = (
+        target.0 - step_x * LAUNCH_DISTANCE + jitter(&mut rng),
+        target.1 - step_y * LAUNCH_DISTANCE + jitter(&mut rng),
+    );
+
+    let corner = (
+        launch.0 - pattern.width() as i64 / 2,
+        launch.1 - pattern.height() as i64 / 2,
+    );
+    pattern.stamp(
+        &mut board,
+        corner,
+        (direction.is_west(), direction.is_north()),
+        team,
+    );
+}
+
+/// Center of the `HEAT_BLOCK` sized block holding the most of `team`'s cells.
+fn densest_block(board: &Bitboard, team: u8) -> Option<(i64, i64)> {
+    let columns = board.width().div_ceil(HEAT_BLOCK);
+    let rows = board.height().div_ceil(HEAT_BLOCK);
+    let mut heat = vec![0u32; (columns * rows) as usize];
+
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if board.get(x, y) == team {
+                heat[(y / HEAT_BLOCK * columns + x / HEAT_BLOCK) as usize] += 1;
+            }
+        }
+    }
+
+    let (block, &count) = heat.iter().enumerate().max_by_key(|&(_, count)| count)?;
+    if count == 0 {
+        return None;
+    }
+
+    let (column, row) = (block as u32 % columns, block as u32 / columns);
+    Some((
+        (column * HEAT_BLOCK + HEAT_BLOCK / 2) as i64,
+        (row * HEAT_BLOCK + HEAT_BLOCK / 2) as i64,
+    ))
+}
+
+fn centroid(board: &Bitboard, team: u8) -> Option<(i64, i64)> {
+    let (mut sum_x, mut sum_y, mut count) = (0, 0, 0);
+
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if board.get(x, y) == team {
+                sum_x += x as i64;
+                sum_y += y as i64;
+                count += 1;
+            }
+        }
+    }
+
+    (count > 0).then(|| (sum_x / count, sum_y / count))
+}
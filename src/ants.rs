@@ -0,0 +1,179 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::energy::{Energy, ANT_COST};
+use crate::lang::Strings;
+use crate::picture::overlay_layer;
+use crate::tools::{position_markers, BoardCursor, Brush, OnTile, Tool};
+use crate::CELL_SIZE;
+
+const ANT_COLOR: Color = Color::ORANGE_RED;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Heading {
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Heading {
+    fn right(self) -> Heading {
+        match self {
+            Heading::North => Heading::East,
+            Heading::East => Heading::South,
+            Heading::South => Heading::West,
+            Heading::West => Heading::North,
+        }
+    }
+
+    fn left(self) -> Heading {
+        self.right().right().right()
+    }
+
+    fn delta(self) -> (i32, i32) {
+        match self {
+            Heading::North => (0, 1),
+            Heading::East => (1, 0),
+            Heading::South => (0, -1),
+            Heading::West => (-1, 0),
+        }
+    }
+}
+
+/// A Langton's ant: on a dead cell it turns right, on a live one left, then flips the
+/// cell it's on and moves forward one cell.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Ant {
+    pub x: u32,
+    pub y: u32,
+    pub heading: Heading,
+    pub team: u8, // team of the cells it brings to life
+}
+
+impl Ant {
+    pub fn step(&mut self, board: &mut Bitboard) {
+        let alive = board.get(self.x, self.y) != 0;
+
+        self.heading = if alive {
+            self.heading.left()
+        } else {
+            self.heading.right()
+        };
+
+        if !board.is_wall(self.x, self.y) {
+            board.set(self.x, self.y, if alive { 0 } else { self.team });
+        }
+
+        // turn back instead of walking into a wall or off the edge of a bounded board
+        let (dx, dy) = self.heading.delta();
+        match board.offset(self.x, self.y, dx, dy) {
+            Some((x, y)) if !board.is_wall(x, y) => (self.x, self.y) = (x, y),
+            _ => self.heading = self.heading.right().right(),
+        }
+    }
+}
+
+impl OnTile for Ant {
+    fn tile(&self) -> TilePos {
+        TilePos::new(self.x, self.y)
+    }
+}
+
+/// Whether the Life rule keeps running alongside the ants, or the ants have the board
+/// to themselves.
+#[derive(Resource)]
+pub struct AntSettings {
+    pub life: bool,
+}
+
+impl Default for AntSettings {
+    fn default() -> Self {
+        Self { life: true }
+    }
+}
+
+/// Ants themselves are stepped with the rest of the generation in `Simulation`.
+pub struct AntPlugin;
+
+impl Plugin for AntPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AntSettings>()
+            .add_system(place_ant)
+            .add_system(position_markers::<Ant>)
+            .add_system(ant_panel);
+    }
+}
+
+fn place_ant(
+    mut commands: Commands,
+    mouse: Res<Input<MouseButton>>,
+    (tool, brush): (Res<Tool>, Res<Brush>),
+    cursor: BoardCursor,
+    mut energy: ResMut<Energy>,
+    ant_query: Query<(Entity, &Ant)>,
+) {
+    if *tool != Tool::Ant || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(TilePos { x, y }) = cursor.tile_pos() else {
+        return;
+    };
+
+    if let Some((entity, _)) = ant_query.iter().find(|(_, a)| (a.x, a.y) == (x, y)) {
+        commands.entity(entity).despawn();
+        return;
+    }
+
+    // ants need a team to paint with
+    let team = brush.team;
+    if team == 0 || !energy.try_spend(team, ANT_COST) {
+        return;
+    }
+
+    commands.spawn((
+        Ant {
+            x,
+            y,
+            heading: Heading::North,
+            team,
+        },
+        SpriteBundle {
+            sprite: Sprite {
+                color: ANT_COLOR,
+                custom_size: Some(Vec2::splat(CELL_SIZE * 0.6)),
+                ..default()
+            },
+            ..default()
+        },
+        overlay_layer(),
+    ));
+}
+
+fn ant_panel(
+    tool: Res<Tool>,
+    mut settings: ResMut<AntSettings>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if *tool != Tool::Ant {
+        return;
+    }
+
+    egui::Window::new(strings.get("ants.title"))
+        .id(egui::Id::new("ants"))
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let mut life = settings.life;
+            ui.checkbox(&mut life, strings.get("ants.life"));
+            if life != settings.life {
+                settings.life = life;
+            }
+
+            ui.label(strings.get("ants.help"));
+        });
+}
@@ -0,0 +1,248 @@
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy::time::Stopwatch;
+use bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+
+use crate::bitboard::{Bitboard, Boundary};
+use crate::lang::Strings;
+use crate::markers::{Marker, Markers};
+use crate::rle;
+use crate::rules::{Chances, RuleSet};
+use crate::scenario::BoardSetup;
+use crate::{Board, GameState, TickDuration, MAP_SIZE};
+
+/// Where the sandbox is autosaved, next to the working directory like `assets`.
+const AUTOSAVE_PATH: &str = "autosave.ron";
+/// Seconds between autosaves of a board that changed since the last one.
+const AUTOSAVE_SECS: f32 = 30.0;
+
+/// The sandbox board and settings as written to the autosave slot, and to named slots in
+/// the saves browser.
+#[derive(Serialize, Deserialize)]
+pub struct Snapshot {
+    pub rule: String,
+    pub boundary: String,
+    birth_chance: f64,
+    survival_chance: f64,
+    tick_seconds: f64,
+    /// The board's cells as RLE.
+    cells: String,
+    /// (x, y) of every wall, since RLE has no way to write them.
+    walls: Vec<(u32, u32)>,
+    /// Missing from autosaves written before markers existed.
+    #[serde(default)]
+    markers: Vec<Marker>,
+}
+
+impl Snapshot {
+    pub fn take(
+        board: &Bitboard,
+        rule: &RuleSet,
+        chances: &Chances,
+        tick_seconds: f64,
+        markers: &Markers,
+    ) -> Self {
+        let walls = (0..board.height())
+            .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+            .filter(|&(x, y)| board.is_wall(x, y))
+            .collect();
+
+        Self {
+            rule: rule.to_string(),
+            boundary: board.boundary().name().to_owned(),
+            birth_chance: chances.birth,
+            survival_chance: chances.survival,
+            tick_seconds,
+            cells: rle::encode(board, (0, 0, board.width(), board.height()), rule),
+            walls,
+            markers: markers.0.clone(),
+        }
+    }
+
+    fn read(path: &Path) -> Result<Self, String> {
+        read_ron(path)
+    }
+
+    fn write(&self, path: &Path) -> Result<(), String> {
+        write_ron(self, path)
+    }
+
+    /// Puts the saved board and settings back, leaving whatever was being played.
+    pub fn restore(
+        &self,
+        setup: &mut BoardSetup,
+        chances: &mut Chances,
+        ticker: &mut TickDuration,
+    ) -> Result<(), String> {
+        let rule = RuleSet::parse(&self.rule)?;
+        let board = self.board()?;
+        if !(self.tick_seconds.is_finite() && self.tick_seconds > 0.0) {
+            return Err(format!("invalid tick length {} s", self.tick_seconds));
+        }
+
+        *chances = Chances {
+            birth: self.birth_chance,
+            survival: self.survival_chance,
+        };
+        ticker.1 = self.tick_seconds;
+        setup.load(board, rule);
+        setup.place_markers(self.markers.clone());
+        Ok(())
+    }
+
+    fn board(&self) -> Result<Bitboard, String> {
+        let boundary = Boundary::from_name(&self.boundary)
+            .ok_or_else(|| format!("unknown boundary `{}`", self.boundary))?;
+
+        let mut board = Bitboard::new(MAP_SIZE.0, MAP_SIZE.1);
+        board.set_boundary(boundary);
+        rle::decode(&self.cells)?.stamp(&mut board, (0, 0), |_, _| true);
+        for &(x, y) in &self.walls {
+            if x < board.width() && y < board.height() {
+                board.set_wall(x, y, true);
+            }
+        }
+
+        Ok(board)
+    }
+}
+
+pub fn read_ron<T: for<'de> Deserialize<'de>>(path: &Path) -> Result<T, String> {
+    let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+    ron::from_str(&text).map_err(|error| error.to_string())
+}
+
+/// Writes to a temporary file first so a crash mid-write can't clobber the last save.
+pub fn write_ron<T: Serialize>(value: &T, path: &Path) -> Result<(), String> {
+    let text =
+        ron::ser::to_string_pretty(value, Default::default()).map_err(|error| error.to_string())?;
+    let temporary = path.with_extension("ron.tmp");
+
+    fs::write(&temporary, text).map_err(|error| error.to_string())?;
+    fs::rename(&temporary, path).map_err(|error| error.to_string())
+}
+
+/// Writes the sandbox to an autosave slot every so often, and offers to bring it back on
+/// the next launch.
+pub struct AutosavePlugin;
+
+#[derive(Resource, Default)]
+pub struct Autosave {
+    timer: Stopwatch,
+    /// Whether the board changed since the last autosave.
+    dirty: bool,
+    /// The autosave found at launch, until the player restores or discards it. Nothing is
+    /// written meanwhile, so the save being offered can't be overwritten.
+    recovered: Option<Snapshot>,
+    error: Option<String>,
+}
+
+impl Autosave {
+    /// Stops offering the autosave found at launch, once something newer has been put back.
+    pub fn forget_recovered(&mut self) {
+        self.recovered = None;
+    }
+}
+
+impl Plugin for AutosavePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Autosave>()
+            .add_startup_system(find_autosave)
+            .add_system(autosave)
+            .add_system(recovery_window);
+    }
+}
+
+fn find_autosave(mut autosave: ResMut<Autosave>) {
+    let path = Path::new(AUTOSAVE_PATH);
+    if !path.exists() {
+        return;
+    }
+
+    match Snapshot::read(path) {
+        Ok(snapshot) => autosave.recovered = Some(snapshot),
+        Err(error) => warn!("ignoring unreadable autosave {AUTOSAVE_PATH}: {error}"),
+    }
+}
+
+fn autosave(
+    time: Res<Time>,
+    state: Res<State<GameState>>,
+    mut autosave: ResMut<Autosave>,
+    (board, rule, chances): (Res<Board>, Res<RuleSet>, Res<Chances>),
+    (ticker, markers): (Res<TickDuration>, Res<Markers>),
+) {
+    // scenarios and matches set up their own boards, so only free play is worth keeping
+    if *state.current() != GameState::Sandbox || autosave.recovered.is_some() {
+        return;
+    }
+
+    if board.is_changed() || markers.is_changed() {
+        autosave.dirty = true;
+    }
+
+    if autosave.timer.tick(time.delta()).elapsed_secs() < AUTOSAVE_SECS || !autosave.dirty {
+        return;
+    }
+    autosave.timer.reset();
+    autosave.dirty = false;
+
+    let snapshot = Snapshot::take(&board, &rule, &chances, ticker.1, &markers);
+    if let Err(error) = snapshot.write(Path::new(AUTOSAVE_PATH)) {
+        warn!("couldn't autosave to {AUTOSAVE_PATH}: {error}");
+    }
+}
+
+fn recovery_window(
+    mut egui_context: ResMut<EguiContext>,
+    mut autosave: ResMut<Autosave>,
+    mut setup: BoardSetup,
+    mut chances: ResMut<Chances>,
+    mut ticker: ResMut<TickDuration>,
+    strings: Res<Strings>,
+) {
+    let Some(snapshot) = &autosave.recovered else {
+        return;
+    };
+
+    let mut restore = false;
+    let mut discard = false;
+
+    egui::Window::new(strings.get("autosave.title"))
+        .id(egui::Id::new("restore autosave"))
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let boundary = strings.named("boundary", &snapshot.boundary);
+            ui.label(strings.format(
+                "autosave.found",
+                &[("rule", &snapshot.rule), ("boundary", &boundary)],
+            ));
+
+            if let Some(error) = &autosave.error {
+                ui.colored_label(egui::Color32::LIGHT_RED, error);
+            }
+
+            ui.horizontal(|ui| {
+                restore = ui.button(strings.get("autosave.restore")).clicked();
+                discard = ui.button(strings.get("autosave.discard")).clicked();
+            });
+        });
+
+    if restore {
+        match snapshot.restore(&mut setup, &mut chances, &mut ticker) {
+            Ok(()) => autosave.recovered = None,
+            Err(error) => {
+                autosave.error =
+                    Some(strings.format("autosave.restore-failed", &[("error", &error)]));
+            }
+        }
+    } else if discard {
+        autosave.recovered = None;
+        let _ = fs::remove_file(AUTOSAVE_PATH);
+    }
+}
@@ -0,0 +1,380 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::energy::Energy;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::patterns::PATTERNS;
+use crate::scenario::ActiveScenario;
+use crate::stats::TeamStats;
+use crate::{update_map, Board, SimCounters, TickDuration};
+
+/// Furthest from the trailing team's center of mass a reinforcement is dropped, in tiles.
+const SEARCH_RADIUS: i64 = 16;
+
+/// K opens the balancing policy for team matches. While one team's population falls far
+/// enough behind the other's, it's handed extra energy or sent reinforcement patterns.
+/// Both teams' populations can also be capped, so neither snowballs in a long match.
+pub struct BalancePlugin;
+
+/// The most live cells a team may have before its births are blocked. Set in the balance
+/// panel, by a scenario or for a match, and cleared along with the board whenever it's
+/// replaced, so one doesn't carry into the next.
+#[derive(Resource, Default)]
+pub struct PopulationCap(pub Option<u32>);
+
+impl PopulationCap {
+    pub fn is_active(&self) -> bool {
+        self.0.is_some()
+    }
+
+    /// Undoes the births of teams that were at or over the cap before the step that took
+    /// `before` to `board`, returning how many each had blocked, indexed by team id - 2.
+    pub fn apply(&self, before: &Bitboard, board: &mut Bitboard) -> [u64; 2] {
+        let mut blocked = [0; 2];
+        let Some(cap) = self.0 else {
+            return blocked;
+        };
+
+        let capped = populations(before).map(|population| population >= cap);
+        if !capped.contains(&true) {
+            return blocked;
+        }
+
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let team = board.get(x, y);
+                if let (2..=3, 0) = (team, before.get(x, y)) {
+                    if capped[team as usize - 2] {
+                        board.set(x, y, 0);
+                        blocked[team as usize - 2] += 1;
+                    }
+                }
+            }
+        }
+        blocked
+    }
+}
+
+/// What the trailing team gets.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Handicap {
+    #[default]
+    Off,
+    /// Faster energy regeneration, for competitive mode.
+    Energy,
+    /// A pattern stamped near its cells every so many generations.
+    Reinforcements,
+}
+
+impl Handicap {
+    const ALL: [Handicap; 3] = [Handicap::Off, Handicap::Energy, Handicap::Reinforcements];
+
+    fn name(self) -> &'static str {
+        match self {
+            Handicap::Off => "off",
+            Handicap::Energy => "bonus energy",
+            Handicap::Reinforcements => "reinforcements",
+        }
+    }
+}
+
+#[derive(Resource)]
+struct BalancePolicy {
+    open: bool,
+    handicap: Handicap,
+    /// A team trails once its population is under this share of the leader's.
+    margin: f32,
+    /// Extra energy the trailing team regains per second.
+    bonus_regen: f32,
+    /// Generations between reinforcements.
+    period: u32,
+    pattern: usize, // index into PATTERNS
+    /// The team currently trailing, if the populations are far enough apart.
+    trailing: Option<u8>,
+    /// Generation the last reinforcement went out, or the policy started watching.
+    last_reinforced: u64,
+    /// Reinforcements sent to each team so far, indexed by team id - 2.
+    sent: [u32; 2],
+}
+
+impl Default for BalancePolicy {
+    fn default() -> Self {
+        Self {
+            open: false,
+            handicap: Handicap::Off,
+            margin: 0.6,
+            bonus_regen: 5.0,
+            period: 50,
+            pattern: 0,
+            trailing: None,
+            last_reinforced: 0,
+            sent: [0; 2],
+        }
+    }
+}
+
+impl BalancePolicy {
+    /// The team `populations` (of teams 1 and 2) say is trailing, if either is. A team
+    /// that's been wiped out has lost rather than fallen behind.
+    fn trailing_team(&self, populations: [u32; 2]) -> Option<u8> {
+        let [first, second] = populations;
+        let (low, high, team) = if first <= second {
+            (first, second, 2)
+        } else {
+            (second, first, 3)
+        };
+
+        (low > 0 && (low as f32) < high as f32 * self.margin).then_some(team)
+    }
+}
+
+impl Plugin for BalancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BalancePolicy>()
+            .init_resource::<PopulationCap>()
+            .add_system(toggle_balance)
+            .add_system(watch_teams.after(update_map))
+            .add_system(grant_energy.after(watch_teams))
+            .add_system(reinforce.after(watch_teams))
+            .add_system(balance_panel.after(toggle_balance).after(watch_teams));
+    }
+}
+
+fn toggle_balance(keys: Hotkeys, mut policy: ResMut<BalancePolicy>) {
+    if keys.just_pressed(KeyCode::K) {
+        policy.open = !policy.open;
+    }
+}
+
+/// Live cells of teams 1 and 2.
+fn populations(board: &Bitboard) -> [u32; 2] {
+    let mut populations = [0; 2];
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if let team @ 2..=3 = board.get(x, y) {
+                populations[team as usize - 2] += 1;
+            }
+        }
+    }
+    populations
+}
+
+/// Works out which team is trailing whenever the board changes.
+fn watch_teams(board: Res<Board>, counters: Res<SimCounters>, mut policy: ResMut<BalancePolicy>) {
+    if policy.handicap == Handicap::Off {
+        if policy.trailing.is_some() {
+            policy.trailing = None;
+        }
+        return;
+    }
+    if !(board.is_changed() || policy.is_changed()) {
+        return;
+    }
+
+    let policy = policy.bypass_change_detection();
+    policy.trailing = policy.trailing_team(populations(&board));
+
+    // a reset board starts the reinforcement clock over
+    if counters.generation < policy.last_reinforced {
+        policy.last_reinforced = counters.generation;
+    }
+}
+
+fn grant_energy(
+    time: Res<Time>,
+    (ticker, policy): (Res<TickDuration>, Res<BalancePolicy>),
+    mut energy: ResMut<Energy>,
+) {
+    if policy.handicap != Handicap::Energy || !energy.enabled || ticker.0.paused() {
+        return;
+    }
+
+    if let Some(team) = policy.trailing {
+        energy.grant(team, policy.bonus_regen * time.delta_seconds());
+    }
+}
+
+/// Drops the policy's pattern for the trailing team once a period has gone by, on the
+/// clear patch nearest its center of mass that no scenario locks.
+fn reinforce(
+    (counters, active): (Res<SimCounters>, Res<ActiveScenario>),
+    mut policy: ResMut<BalancePolicy>,
+    mut board: ResMut<Board>,
+) {
+    if policy.handicap != Handicap::Reinforcements
+        || counters.generation < policy.last_reinforced + policy.period as u64
+    {
+        return;
+    }
+    let Some(team) = policy.trailing else {
+        // nobody needs help, so the next period starts whenever someone does
+        policy.bypass_change_detection().last_reinforced = counters.generation;
+        return;
+    };
+
+    let pattern = &PATTERNS[policy.pattern];
+    let (width, height) = (pattern.width() as i64, pattern.height() as i64);
+
+    let (mut sum, mut count) = ((0, 0), 0);
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if board.get(x, y) == team {
+                sum = (sum.0 + x as i64, sum.1 + y as i64);
+                count += 1;
+            }
+        }
+    }
+    // the board can have been edited since the team was found trailing
+    if count == 0 {
+        return;
+    }
+    let center = (sum.0 / count, sum.1 / count);
+
+    // the whole bounding box, with a cell of margin, has to be open and empty
+    let clear = |left: i64, bottom: i64| {
+        (left - 1..left + width + 1).all(|x| {
+            (bottom - 1..bottom + height + 1).all(|y| {
+                (0..board.width() as i64).contains(&x)
+                    && (0..board.height() as i64).contains(&y)
+                    && board.get(x as u32, y as u32) == 0
+                    && !board.is_wall(x as u32, y as u32)
+                    && !active.is_locked(x as u32, y as u32)
+            })
+        })
+    };
+    let spot = (0..=SEARCH_RADIUS)
+        .flat_map(|radius| {
+            (-radius..=radius).flat_map(move |dx| {
+                (-radius..=radius)
+                    .filter(move |dy| dx.abs() == radius || dy.abs() == radius)
+                    .map(move |dy| (dx, dy))
+            })
+        })
+        .map(|(dx, dy)| (center.0 + dx - width / 2, center.1 + dy - height / 2))
+        .find(|&(left, bottom)| clear(left, bottom));
+
+    policy.last_reinforced = counters.generation;
+    if let Some(corner) = spot {
+        pattern.stamp(&mut board, corner, (false, false), team);
+        policy.sent[team as usize - 2] += 1;
+    }
+}
+
+/// A checkbox and limit for a population cap of `cap`, returning the cap they're set to.
+pub fn cap_setting(ui: &mut egui::Ui, strings: &Strings, cap: Option<u32>) -> Option<u32> {
+    let mut capped = cap.is_some();
+    ui.checkbox(&mut capped, strings.get("balance.population-cap"));
+
+    let mut limit = cap.unwrap_or(500);
+    ui.add_enabled(
+        capped,
+        egui::DragValue::new(&mut limit)
+            .clamp_range(1..=1_000_000)
+            .suffix(strings.get("balance.cells-per-team")),
+    );
+
+    capped.then_some(limit)
+}
+
+fn balance_panel(
+    (board, energy, stats): (Res<Board>, Res<Energy>, Res<TeamStats>),
+    (mut policy, mut cap): (ResMut<BalancePolicy>, ResMut<PopulationCap>),
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !policy.open {
+        return;
+    }
+
+    let mut open = true;
+
+    egui::Window::new(strings.get("balance.title"))
+        .id(egui::Id::new("balance"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let policy = &mut *policy;
+
+            ui.horizontal(|ui| {
+                for handicap in Handicap::ALL {
+                    let name = strings.named("handicap", handicap.name());
+                    ui.selectable_value(&mut policy.handicap, handicap, name);
+                }
+            });
+
+            ui.add(
+                egui::Slider::new(&mut policy.margin, 0.1..=0.95)
+                    .text(strings.get("balance.margin")),
+            );
+
+            match policy.handicap {
+                Handicap::Off => {}
+                Handicap::Energy => {
+                    ui.add(
+                        egui::DragValue::new(&mut policy.bonus_regen)
+                            .clamp_range(1.0..=50.0)
+                            .prefix("+")
+                            .suffix(strings.get("balance.energy-per-second")),
+                    );
+                    if !energy.enabled {
+                        ui.label(strings.get("balance.energy-unused"));
+                    }
+                }
+                Handicap::Reinforcements => {
+                    ui.horizontal(|ui| {
+                        egui::ComboBox::from_id_source("reinforcement pattern")
+                            .selected_text(PATTERNS[policy.pattern].name)
+                            .show_ui(ui, |ui| {
+                                for (i, pattern) in PATTERNS.iter().enumerate() {
+                                    ui.selectable_value(&mut policy.pattern, i, pattern.name);
+                                }
+                            });
+                        ui.add(
+                            egui::DragValue::new(&mut policy.period)
+                                .clamp_range(5..=1000)
+                                .prefix(strings.get("balance.every"))
+                                .suffix(strings.get("balance.generations")),
+                        );
+                    });
+                    let [first, second] = policy.sent;
+                    ui.label(
+                        strings.format("balance.sent", &[("first", &first), ("second", &second)]),
+                    );
+                }
+            }
+
+            ui.separator();
+            ui.horizontal(|ui| {
+                let limit = cap_setting(ui, &strings, cap.0);
+                if cap.0 != limit {
+                    cap.0 = limit;
+                }
+            });
+            if cap.is_active() {
+                let [first, second] = stats.teams.map(|tally| tally.blocked);
+                ui.label(strings.format(
+                    "balance.cap-blocked",
+                    &[("first", &first), ("second", &second)],
+                ));
+            }
+
+            ui.separator();
+            let [first, second] = populations(&board);
+            let populations: [(&str, &dyn std::fmt::Display); 2] =
+                [("first", &first), ("second", &second)];
+            ui.label(match (policy.handicap, policy.trailing) {
+                (Handicap::Off, _) => strings.format("balance.populations", &populations),
+                (_, Some(team)) => strings.format(
+                    "balance.helping",
+                    &[populations[0], populations[1], ("team", &(team - 1))],
+                ),
+                (_, None) => strings.format("balance.nobody-trailing", &populations),
+            });
+        });
+
+    if !open {
+        policy.open = false;
+    }
+}
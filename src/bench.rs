@@ -0,0 +1,205 @@
+use std::fs;
+use std::process;
+use std::time::Instant;
+
+use serde_json::json;
+
+use crate::bitboard::{Bitboard, Boundary};
+use crate::patterns;
+use crate::rng::Rng;
+use crate::rules::RuleSet;
+
+const USAGE: &str = "usage: conway_bevy --bench <pattern|soup> <generations> \
+[--size <width>x<height>] [--rule <rulestring>] \
+[--boundary bounded|toroidal|reflective|halo] [--backend bitboard|naive] [--seed <seed>] \
+[--json <path>]";
+
+#[derive(Clone, Copy, Debug)]
+enum Backend {
+    Bitboard,
+    Naive,
+}
+
+impl Backend {
+    fn name(self) -> &'static str {
+        match self {
+            Backend::Bitboard => "bitboard",
+            Backend::Naive => "naive",
+        }
+    }
+}
+
+struct BenchArgs {
+    pattern: String,
+    generations: u64,
+    size: (u32, u32),
+    rule: RuleSet,
+    boundary: Boundary,
+    backend: Backend,
+    seed: u64,
+    json: Option<String>,
+}
+
+/// Runs `--bench` headlessly (no window or ECS) and exits the process.
+pub fn run(args: &[String]) -> ! {
+    let args = parse_args(args).unwrap_or_else(|error| {
+        eprintln!("{error}\n{USAGE}");
+        process::exit(2);
+    });
+
+    let mut board = Bitboard::new(args.size.0, args.size.1);
+    board.set_boundary(args.boundary);
+
+    if args.pattern == "soup" {
+        let mut rng = Rng::new(args.seed);
+
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                match rng.next_u64() % 4 {
+                    0 => board.set(x, y, 2),
+                    1 => board.set(x, y, 3),
+                    _ => {}
+                }
+            }
+        }
+    } else if let Some(pattern) = patterns::find(&args.pattern) {
+        pattern.stamp_centered(&mut board, 2);
+    } else {
+        let names: Vec<_> = patterns::PATTERNS.iter().map(|p| p.name).collect();
+        eprintln!(
+            "unknown pattern `{}`, expected soup or one of: {}",
+            args.pattern,
+            names.join(", ")
+        );
+        process::exit(2);
+    }
+
+    let start = Instant::now();
+    for _ in 0..args.generations {
+        match args.backend {
+            Backend::Bitboard => board.step(&args.rule),
+            Backend::Naive => board.step_naive(&args.rule),
+        }
+    }
+    let wall_time = start.elapsed().as_secs_f64();
+
+    let generations_per_second = args.generations as f64 / wall_time;
+    let peak_memory = peak_memory_kib();
+
+    println!(
+        "pattern:         {} ({}x{} {}, {}, {})",
+        args.pattern,
+        args.size.0,
+        args.size.1,
+        args.boundary.name(),
+        args.rule,
+        args.backend.name()
+    );
+    println!("generations:     {}", args.generations);
+    println!("wall time:       {wall_time:.3} s");
+    println!("generations/sec: {generations_per_second:.1}");
+    println!("population:      {}", board.population());
+    match peak_memory {
+        Some(kib) => println!("peak memory:     {kib} KiB"),
+        None => println!("peak memory:     unavailable"),
+    }
+
+    if let Some(path) = &args.json {
+        // non-finite rates, as for 0 generations, come out as null
+        let json = json!({
+            "pattern": args.pattern,
+            "width": args.size.0,
+            "height": args.size.1,
+            "rule": args.rule.to_string(),
+            "backend": args.backend.name(),
+            "generations": args.generations,
+            "wall_time_secs": wall_time,
+            "generations_per_sec": generations_per_second,
+            "population": board.population(),
+            "peak_memory_kib": peak_memory,
+        });
+
+        if let Err(error) = fs::write(path, format!("{json}\n")) {
+            eprintln!("failed to write {path}: {error}");
+            process::exit(1);
+        }
+    }
+
+    process::exit(0);
+}
+
+fn parse_args(args: &[String]) -> Result<BenchArgs, String> {
+    let mut positional = Vec::new();
+    let mut bench = BenchArgs {
+        pattern: String::new(),
+        generations: 0,
+        size: (1024, 1024),
+        rule: RuleSet::CONWAY,
+        boundary: Boundary::Bounded,
+        backend: Backend::Bitboard,
+        seed: 0,
+        json: None,
+    };
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("missing value for {arg}"))
+        };
+
+        match arg.as_str() {
+            "--size" => {
+                let value = value()?;
+                let (width, height) = value
+                    .split_once('x')
+                    .and_then(|(w, h)| Some((w.parse().ok()?, h.parse().ok()?)))
+                    .filter(|&(w, h)| w > 0 && h > 0)
+                    .ok_or_else(|| format!("invalid size `{value}`"))?;
+                bench.size = (width, height);
+            }
+            "--rule" => bench.rule = RuleSet::parse(value()?)?,
+            "--boundary" => {
+                let value = value()?;
+                bench.boundary = Boundary::from_name(value)
+                    .ok_or_else(|| format!("unknown boundary `{value}`"))?;
+            }
+            "--backend" => {
+                bench.backend = match value()?.as_str() {
+                    "bitboard" => Backend::Bitboard,
+                    "naive" => Backend::Naive,
+                    other => return Err(format!("unknown backend `{other}`")),
+                }
+            }
+            "--seed" => {
+                let value = value()?;
+                bench.seed = value
+                    .parse()
+                    .map_err(|_| format!("invalid seed `{value}`"))?;
+            }
+            "--json" => bench.json = Some(value()?.clone()),
+            _ => positional.push(arg),
+        }
+    }
+
+    let [pattern, generations] = positional[..] else {
+        return Err("expected a pattern and a generation count".to_owned());
+    };
+
+    bench.pattern = pattern.clone();
+    bench.generations = generations
+        .parse()
+        .map_err(|_| format!("invalid generation count `{generations}`"))?;
+
+    Ok(bench)
+}
+
+/// Peak resident set size of this process, where the platform exposes it.
+fn peak_memory_kib() -> Option<u64> {
+    let status = fs::read_to_string("/proc/self/status").ok()?;
+
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmHWM:"))
+        .and_then(|value| value.trim().trim_end_matches("kB").trim().parse().ok())
+}
@@ -0,0 +1,702 @@
+use bevy::utils::tracing::info_span;
+
+use crate::rng::Rng;
+use crate::rules::{Chances, CyclicRule, Neighborhood, RuleSet};
+
+/// What lies past the edges of the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Boundary {
+    /// Cells past the edge don't exist, so edge cells just have fewer neighbors.
+    #[default]
+    Bounded,
+    /// Edges wrap around to the opposite side.
+    Toroidal,
+    /// Cells past the edge mirror the edge cell next to them.
+    Reflective,
+    /// The outermost ring of the board is held dead, so anything reaching it dies.
+    Halo,
+}
+
+impl Boundary {
+    pub const ALL: [Boundary; 4] = [
+        Boundary::Bounded,
+        Boundary::Toroidal,
+        Boundary::Reflective,
+        Boundary::Halo,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Boundary::Bounded => "bounded",
+            Boundary::Toroidal => "toroidal",
+            Boundary::Reflective => "reflective",
+            Boundary::Halo => "dead halo",
+        }
+    }
+
+    /// Boundary with the given name, also accepting `halo` for the dead halo.
+    pub fn from_name(name: &str) -> Option<Boundary> {
+        Boundary::ALL.into_iter().find(|boundary| {
+            boundary.name().trim_start_matches("dead ") == name.trim_start_matches("dead ")
+        })
+    }
+
+    /// Maps a possibly out-of-range coordinate onto the board, or `None` if there's no cell there.
+    fn resolve(self, coord: i64, size: u32) -> Option<u32> {
+        let size = size as i64;
+
+        match self {
+            _ if (0..size).contains(&coord) => Some(coord as u32),
+            Boundary::Bounded | Boundary::Halo => None,
+            Boundary::Toroidal => Some(coord.rem_euclid(size) as u32),
+            Boundary::Reflective => Some(coord.clamp(0, size - 1) as u32),
+        }
+    }
+}
+
+/// Board storage with cell liveness packed into one u64 per 64 cells of a row,
+/// so a whole word of neighbor counts can be computed with a handful of shifts.
+/// Team IDs and ages live in parallel arrays and are only meaningful where the
+/// liveness bit is set.
+#[derive(Clone, Debug)]
+pub struct Bitboard {
+    width: u32,
+    height: u32,
+    stride: usize, // words per row
+    boundary: Boundary,
+    alive: Vec<u64>,
+    walls: Vec<u64>, // never alive and never change
+    teams: Vec<u8>,
+    ages: Vec<u16>, // generations survived since birth
+    next_alive: Vec<u64>,
+    next_teams: Vec<u8>,
+    next_ages: Vec<u16>,
+}
+
+impl Bitboard {
+    pub fn new(width: u32, height: u32) -> Self {
+        let stride = (width as usize).div_ceil(64);
+        let words = stride * height as usize;
+        let cells = width as usize * height as usize;
+
+        Self {
+            width,
+            height,
+            stride,
+            boundary: Boundary::default(),
+            alive: vec![0; words],
+            walls: vec![0; words],
+            teams: vec![0; cells],
+            ages: vec![0; cells],
+            next_alive: vec![0; words],
+            next_teams: vec![0; cells],
+            next_ages: vec![0; cells],
+        }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn boundary(&self) -> Boundary {
+        self.boundary
+    }
+
+    pub fn set_boundary(&mut self, boundary: Boundary) {
+        self.boundary = boundary;
+    }
+
+    /// Team of the cell at (x, y), or 0 if it's dead.
+    pub fn get(&self, x: u32, y: u32) -> u8 {
+        if self.alive[self.word(x, y)] & bit(x) != 0 {
+            self.teams[self.index(x, y)]
+        } else {
+            0
+        }
+    }
+
+    /// Sets the cell at (x, y) to `team`, where 0 kills it. Replaces any wall there.
+    pub fn set(&mut self, x: u32, y: u32, team: u8) {
+        let word = self.word(x, y);
+        self.walls[word] &= !bit(x);
+
+        if team == 0 {
+            self.alive[word] &= !bit(x);
+        } else {
+            self.alive[word] |= bit(x);
+            let index = self.index(x, y);
+            self.teams[index] = team;
+            self.ages[index] = 0;
+        }
+    }
+
+    pub fn is_wall(&self, x: u32, y: u32) -> bool {
+        self.walls[self.word(x, y)] & bit(x) != 0
+    }
+
+    /// Places or removes a wall at (x, y), killing whatever cell was there.
+    pub fn set_wall(&mut self, x: u32, y: u32, wall: bool) {
+        let word = self.word(x, y);
+        self.alive[word] &= !bit(x);
+
+        if wall {
+            self.walls[word] |= bit(x);
+        } else {
+            self.walls[word] &= !bit(x);
+        }
+    }
+
+    /// Whether the two boards hold the same cells and walls, ignoring ages.
+    pub fn same_cells(&self, other: &Bitboard) -> bool {
+        (self.width, self.height, self.boundary) == (other.width, other.height, other.boundary)
+            && self.alive == other.alive
+            && self.walls == other.walls
+            && (0..self.height).all(|y| (0..self.width).all(|x| self.get(x, y) == other.get(x, y)))
+    }
+
+    /// A hash of what `same_cells` compares except the boundary. Unlike std's hashers it's
+    /// FNV-1a, which never changes, so hashes can be written down and checked later.
+    pub fn cell_hash(&self) -> u64 {
+        let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+        let mut add = |byte: u8| {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x0100_0000_01b3);
+        };
+
+        for byte in [self.width, self.height]
+            .into_iter()
+            .flat_map(u32::to_le_bytes)
+        {
+            add(byte);
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let wall = self.is_wall(x, y);
+                add(if wall { u8::MAX } else { self.get(x, y) });
+            }
+        }
+
+        hash
+    }
+
+    /// Copies the (left, bottom, width, height) block of `from`, a board of the same size,
+    /// over this one, ages and all.
+    pub fn copy_area(
+        &mut self,
+        from: &Bitboard,
+        (left, bottom, width, height): (u32, u32, u32, u32),
+    ) {
+        for y in bottom..(bottom + height).min(self.height) {
+            for x in left..(left + width).min(self.width) {
+                let (word, index) = (self.word(x, y), self.index(x, y));
+                let mask = bit(x);
+
+                self.alive[word] = self.alive[word] & !mask | from.alive[word] & mask;
+                self.walls[word] = self.walls[word] & !mask | from.walls[word] & mask;
+                self.teams[index] = from.teams[index];
+                self.ages[index] = from.ages[index];
+            }
+        }
+    }
+
+    /// Every cell as bytes, row by row: a byte per cell holding its team, 0 if it's dead
+    /// or `u8::MAX` for a wall, followed by two little endian bytes per cell of the ages,
+    /// which are 0 for cells that aren't alive.
+    pub fn cell_bytes(&self) -> Vec<u8> {
+        let cells = self.width as usize * self.height as usize;
+        let mut bytes = Vec::with_capacity(cells * 3);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let wall = self.is_wall(x, y);
+                bytes.push(if wall { u8::MAX } else { self.get(x, y) });
+            }
+        }
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let age = if self.get(x, y) != 0 {
+                    self.ages[self.index(x, y)]
+                } else {
+                    0
+                };
+                bytes.extend(age.to_le_bytes());
+            }
+        }
+
+        bytes
+    }
+
+    /// Replaces every cell with ones from `cell_bytes` of a board the same size. Leaves the
+    /// board alone and returns false if `bytes` is the wrong length for it.
+    pub fn set_cell_bytes(&mut self, bytes: &[u8]) -> bool {
+        let cells = self.width as usize * self.height as usize;
+        if bytes.len() != cells * 3 {
+            return false;
+        }
+        let (states, ages) = bytes.split_at(cells);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let index = self.index(x, y);
+                match states[index] {
+                    u8::MAX => self.set_wall(x, y, true),
+                    team => {
+                        self.set(x, y, team);
+                        self.ages[index] =
+                            u16::from_le_bytes([ages[index * 2], ages[index * 2 + 1]]);
+                    }
+                }
+            }
+        }
+
+        true
+    }
+
+    pub fn population(&self) -> u32 {
+        self.alive.iter().map(|word| word.count_ones()).sum()
+    }
+
+    /// Number of live cells on `team`.
+    pub fn team_population(&self, team: u8) -> u32 {
+        let mut population = 0;
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if self.get(x, y) == team {
+                    population += 1;
+                }
+            }
+        }
+
+        population
+    }
+
+    /// Advances the board one generation.
+    pub fn step(&mut self, rule: &RuleSet) {
+        self.step_with(rule, None);
+    }
+
+    /// Advances the board one generation, letting each birth and survival the rule allows
+    /// through only with the given chance.
+    pub fn step_stochastic(&mut self, rule: &RuleSet, chances: &Chances, rng: &mut Rng) {
+        self.step_with(rule, Some((chances, rng)));
+    }
+
+    fn step_with(&mut self, rule: &RuleSet, mut chances: Option<(&Chances, &mut Rng)>) {
+        let empty = vec![0; self.stride];
+        let last_mask = match self.width % 64 {
+            0 => !0,
+            n => (1 << n) - 1,
+        };
+
+        // liveness is worked out a word at a time, with the rule applied to each word's
+        // neighbor counts right after counting them, so the two share a span
+        let counting = info_span!("count_neighbors").entered();
+        for y in 0..self.height as usize {
+            let row = |y: Option<u32>| match y {
+                Some(y) => &self.alive[y as usize * self.stride..(y as usize + 1) * self.stride],
+                None => &empty,
+            };
+            let below = row(self.boundary.resolve(y as i64 - 1, self.height));
+            let middle = row(Some(y as u32));
+            let above = row(self.boundary.resolve(y as i64 + 1, self.height));
+
+            for i in 0..self.stride {
+                let (below_w, below_c, below_e) = self.shifted(below, i);
+                let (middle_w, current, middle_e) = self.shifted(middle, i);
+                let (above_w, above_c, above_e) = self.shifted(above, i);
+
+                let moore;
+                let neighbors: &[u64] = match rule.neighborhood {
+                    Neighborhood::Moore => {
+                        moore = [
+                            below_w, below_c, below_e, middle_w, middle_e, above_w, above_c,
+                            above_e,
+                        ];
+                        &moore
+                    }
+                    // even rows touch the cell up/down and to the west of it, odd rows to the east
+                    Neighborhood::Hex if y % 2 == 0 => {
+                        &[below_w, below_c, middle_w, middle_e, above_w, above_c]
+                    }
+                    Neighborhood::Hex => &[below_c, below_e, middle_w, middle_e, above_c, above_e],
+                };
+
+                // bit-sliced 4 bit counter of live neighbors
+                let mut count = [0u64; 4];
+                for &neighbor in neighbors {
+                    let mut carry = neighbor;
+                    for bit in count.iter_mut() {
+                        let next_carry = *bit & carry;
+                        *bit ^= carry;
+                        carry = next_carry;
+                    }
+                }
+
+                let equals = |n: u32| {
+                    (0..4).fold(!0, |mask, k| {
+                        mask & if n >> k & 1 != 0 { count[k] } else { !count[k] }
+                    })
+                };
+
+                let (mut born, mut survives) = (0, 0);
+                for n in 0..=rule.neighborhood.max_neighbors() {
+                    if rule.birth & 1 << n != 0 {
+                        born |= equals(n);
+                    }
+                    if rule.survival & 1 << n != 0 {
+                        survives |= equals(n);
+                    }
+                }
+
+                if let Some((chances, rng)) = &mut chances {
+                    // only draw for words where the rule has something to decide
+                    if !current & born != 0 {
+                        born &= rng.next_mask(chances.birth);
+                    }
+                    if current & survives != 0 {
+                        survives &= rng.next_mask(chances.survival);
+                    }
+                }
+
+                let walls = self.walls[y * self.stride + i];
+                let mut next = (!current & born | current & survives) & !walls;
+                if i + 1 == self.stride {
+                    next &= last_mask;
+                }
+
+                self.next_alive[y * self.stride + i] = next;
+            }
+        }
+
+        if self.boundary == Boundary::Halo {
+            self.clear_halo();
+        }
+        drop(counting);
+
+        // teams and ages are only resolved for cells that are alive next generation
+        let _span = info_span!("resolve_teams_and_ages").entered();
+        for y in 0..self.height {
+            for i in 0..self.stride {
+                let word_index = y as usize * self.stride + i;
+                let mut word = self.next_alive[word_index];
+
+                while word != 0 {
+                    let x = (i * 64) as u32 + word.trailing_zeros();
+                    word &= word - 1;
+
+                    if !self.age_cell(x, y, rule) {
+                        self.next_alive[word_index] &= !bit(x);
+                        continue;
+                    }
+
+                    let team = self.resolve_team(x, y, rule.neighborhood);
+                    let index = self.index(x, y);
+                    self.next_teams[index] = team;
+                }
+            }
+        }
+
+        self.swap_buffers();
+    }
+
+    /// Advances the board one generation by visiting every cell and its neighbors
+    /// individually, the way the tile-based version did. Much slower than `step`, but
+    /// simple enough to serve as the reference to check and benchmark it against.
+    pub fn step_naive(&mut self, rule: &RuleSet) {
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let count = self
+                    .neighbors(x, y, rule.neighborhood)
+                    .filter(|&(nx, ny)| self.get(nx, ny) != 0)
+                    .count();
+
+                let mask = if self.get(x, y) != 0 {
+                    rule.survival
+                } else {
+                    rule.birth
+                };
+
+                let word = self.word(x, y);
+                if mask & 1 << count != 0 && !self.is_wall(x, y) && self.age_cell(x, y, rule) {
+                    self.next_alive[word] |= bit(x);
+                    let index = self.index(x, y);
+                    self.next_teams[index] = self.resolve_team(x, y, rule.neighborhood);
+                } else {
+                    self.next_alive[word] &= !bit(x);
+                }
+            }
+        }
+
+        if self.boundary == Boundary::Halo {
+            self.clear_halo();
+        }
+
+        self.swap_buffers();
+    }
+
+    /// Advances a cyclic automaton one generation.
+    pub fn step_cyclic(&mut self, rule: &CyclicRule, neighborhood: Neighborhood) {
+        self.step_cells(neighborhood, |state, neighbors| {
+            let successor = (state + 1) % rule.states;
+            let count = neighbors.iter().filter(|&&n| n == successor).count();
+
+            if count >= rule.threshold as usize {
+                successor
+            } else {
+                state
+            }
+        });
+    }
+
+    /// Advances the board one generation with the next state of every cell given by
+    /// `next_state(state, neighbor_states)`. States share storage with teams, with state 0
+    /// kept as a dead cell so the rest of the board API still makes sense. Walls are left
+    /// alone and never passed in.
+    pub fn step_cells(
+        &mut self,
+        neighborhood: Neighborhood,
+        mut next_state: impl FnMut(u8, &[u8]) -> u8,
+    ) {
+        let mut neighbors = Vec::with_capacity(8);
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let state = self.get(x, y);
+                let next = if self.is_wall(x, y) {
+                    0
+                } else {
+                    neighbors.clear();
+                    neighbors.extend(
+                        self.neighbors(x, y, neighborhood)
+                            .map(|(nx, ny)| self.get(nx, ny)),
+                    );
+
+                    next_state(state, &neighbors)
+                };
+
+                let (word, index) = (self.word(x, y), self.index(x, y));
+                if next == 0 {
+                    self.next_alive[word] &= !bit(x);
+                } else {
+                    self.next_alive[word] |= bit(x);
+                    self.next_teams[index] = next;
+                    self.next_ages[index] = if next == state {
+                        self.ages[index].saturating_add(1)
+                    } else {
+                        0
+                    };
+                }
+            }
+        }
+
+        if self.boundary == Boundary::Halo {
+            self.clear_halo();
+        }
+
+        self.swap_buffers();
+    }
+
+    /// Neighbors of (x, y) as seen through the board's boundary. With wrapping or
+    /// mirroring edges, the same cell (or (x, y) itself) can show up more than once.
+    pub fn neighbors(
+        &self,
+        x: u32,
+        y: u32,
+        neighborhood: Neighborhood,
+    ) -> impl Iterator<Item = (u32, u32)> {
+        let (width, height, boundary) = (self.width, self.height, self.boundary);
+
+        neighborhood.offsets(y).iter().filter_map(move |&(dx, dy)| {
+            Some((
+                boundary.resolve(x as i64 + dx as i64, width)?,
+                boundary.resolve(y as i64 + dy as i64, height)?,
+            ))
+        })
+    }
+
+    /// Writes the age the cell at (x, y) has next generation if it's alive then, or
+    /// returns false if that would make it older than the rule allows.
+    fn age_cell(&mut self, x: u32, y: u32, rule: &RuleSet) -> bool {
+        let index = self.index(x, y);
+        let age = if self.get(x, y) != 0 {
+            self.ages[index].saturating_add(1)
+        } else {
+            0
+        };
+
+        if rule.max_age.is_some_and(|max_age| age > max_age) {
+            return false;
+        }
+
+        self.next_ages[index] = age;
+        true
+    }
+
+    fn swap_buffers(&mut self) {
+        std::mem::swap(&mut self.alive, &mut self.next_alive);
+        std::mem::swap(&mut self.teams, &mut self.next_teams);
+        std::mem::swap(&mut self.ages, &mut self.next_ages);
+    }
+
+    /// The cell `(dx, dy)` away from (x, y) as seen through the board's boundary, if any.
+    pub fn offset(&self, x: u32, y: u32, dx: i32, dy: i32) -> Option<(u32, u32)> {
+        Some((
+            self.boundary.resolve(x as i64 + dx as i64, self.width)?,
+            self.boundary.resolve(y as i64 + dy as i64, self.height)?,
+        ))
+    }
+
+    /// Cells connected to (x, y) through edges that are in the same state as it: the same
+    /// team, or all walls, or all dead. Regions continue across wrapping and mirroring
+    /// edges. Returns `None` if the region has more than `limit` cells.
+    pub fn region(
+        &self,
+        x: u32,
+        y: u32,
+        neighborhood: Neighborhood,
+        limit: usize,
+    ) -> Option<Vec<(u32, u32)>> {
+        let mut seen = vec![false; self.width as usize * self.height as usize];
+        self.flood(x, y, neighborhood, limit, &mut seen)
+    }
+
+    /// Every region on the board once, as `region` finds them.
+    pub fn regions(&self, neighborhood: Neighborhood) -> Vec<Vec<(u32, u32)>> {
+        let mut seen = vec![false; self.width as usize * self.height as usize];
+        let mut regions = Vec::new();
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                if !seen[self.index(x, y)] {
+                    regions.extend(self.flood(x, y, neighborhood, usize::MAX, &mut seen));
+                }
+            }
+        }
+
+        regions
+    }
+
+    /// `region`, skipping cells already marked in `seen` and marking the ones it finds.
+    fn flood(
+        &self,
+        x: u32,
+        y: u32,
+        neighborhood: Neighborhood,
+        limit: usize,
+        seen: &mut [bool],
+    ) -> Option<Vec<(u32, u32)>> {
+        let state = |x, y| (self.get(x, y), self.is_wall(x, y));
+        let target = state(x, y);
+
+        seen[self.index(x, y)] = true;
+
+        let mut region = vec![(x, y)];
+        let mut next = 0;
+
+        while let Some(&(x, y)) = region.get(next) {
+            next += 1;
+
+            for &(dx, dy) in neighborhood.adjacent(y) {
+                let Some((nx, ny)) = self.offset(x, y, dx, dy) else {
+                    continue;
+                };
+
+                let index = self.index(nx, ny);
+                if seen[index] || state(nx, ny) != target {
+                    continue;
+                }
+
+                seen[index] = true;
+                region.push((nx, ny));
+
+                if region.len() > limit {
+                    return None;
+                }
+            }
+        }
+
+        Some(region)
+    }
+
+    /// Kills the outermost ring of next generation's cells.
+    fn clear_halo(&mut self) {
+        let height = self.height as usize;
+        let last_x = self.width - 1;
+
+        for y in 0..height {
+            let row = &mut self.next_alive[y * self.stride..(y + 1) * self.stride];
+
+            if y == 0 || y == height - 1 {
+                row.fill(0);
+            } else {
+                row[0] &= !bit(0);
+                row[last_x as usize / 64] &= !bit(last_x);
+            }
+        }
+    }
+
+    /// Word `i` of `row` along with its west and east neighbors shifted into place,
+    /// carrying bits across word boundaries and filling the edges per the boundary.
+    fn shifted(&self, row: &[u64], i: usize) -> (u64, u64, u64) {
+        let word = row[i];
+        let prev = if i > 0 { row[i - 1] } else { 0 };
+        let next = row.get(i + 1).copied().unwrap_or(0);
+
+        let mut west = word << 1 | prev >> 63;
+        let mut east = word >> 1 | next << 63;
+
+        let last_x = self.width - 1;
+        let cell = |x: u32| row[x as usize / 64] >> (x % 64) & 1;
+        let edge = |x: i64| match self.boundary.resolve(x, self.width) {
+            Some(x) => cell(x),
+            None => 0,
+        };
+
+        if i == 0 {
+            west |= edge(-1);
+        }
+        if i == last_x as usize / 64 {
+            east |= edge(last_x as i64 + 1) << (last_x % 64);
+        }
+
+        (west, word, east)
+    }
+
+    /// Team a cell at (x, y) takes when it's alive next generation: the team shared
+    /// by all of its team neighbors, or neither (1) if they disagree or there are none.
+    pub fn resolve_team(&self, x: u32, y: u32, neighborhood: Neighborhood) -> u8 {
+        let mut team = 0;
+
+        for (nx, ny) in self.neighbors(x, y, neighborhood) {
+            match self.get(nx, ny) {
+                0 | 1 => continue,
+                neighbor if team == 0 => team = neighbor,
+                neighbor if team != neighbor => return 1,
+                _ => {}
+            }
+        }
+
+        if team == 0 {
+            1
+        } else {
+            team
+        }
+    }
+
+    fn word(&self, x: u32, y: u32) -> usize {
+        y as usize * self.stride + x as usize / 64
+    }
+
+    fn index(&self, x: u32, y: u32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+}
+
+fn bit(x: u32) -> u64 {
+    1 << (x % 64)
+}
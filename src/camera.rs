@@ -0,0 +1,182 @@
+use bevy::input::mouse::{MouseMotion, MouseScrollUnit, MouseWheel};
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::window::{WindowId, WindowResized};
+use bevy_ecs_tilemap::prelude::*;
+
+pub const MIN_ZOOM: f32 = 0.125;
+pub const MAX_ZOOM: f32 = 16.0;
+
+pub type TilemapGeometry<'a> = (
+    &'a TilemapSize,
+    &'a TilemapGridSize,
+    &'a TilemapType,
+    &'a Transform,
+);
+
+/// Scroll to zoom around the cursor, middle-drag to pan. Resizing the window refits the
+/// camera to the whole board.
+pub struct CameraPlugin;
+
+/// The camera looking at the main board. Other cameras, like the split view's, follow it.
+#[derive(Component)]
+pub struct MainCamera;
+
+/// Where the cursor is relative to the center of `camera`'s viewport, y up, or `None`
+/// if it's outside the viewport.
+pub fn cursor_in_view(window: &Window, camera: &Camera) -> Option<Vec2> {
+    let cursor = window.cursor_position()?;
+    let (min, max) = camera.logical_viewport_rect()?;
+
+    // viewports are measured from the top left, the cursor from the bottom left
+    let cursor = Vec2::new(cursor.x, window.height() - cursor.y);
+    if cursor.cmplt(min).any() || cursor.cmpge(max).any() {
+        return None;
+    }
+
+    let offset = cursor - (min + max) / 2.0;
+    Some(Vec2::new(offset.x, -offset.y))
+}
+
+/// The logical size of `camera`'s share of the window, which the split view may have made
+/// only part of it.
+pub fn view_size(window: &Window, camera: &Camera) -> Vec2 {
+    let view = Vec2::new(window.width(), window.height());
+    let Some(viewport) = &camera.viewport else {
+        return view;
+    };
+
+    let physical = Vec2::new(
+        window.physical_width() as f32,
+        window.physical_height() as f32,
+    );
+    view * viewport.physical_size.as_vec2() / physical.max(Vec2::ONE)
+}
+
+/// Where `world` shows up in the window, in logical pixels from the top left like egui
+/// measures, if it's inside `camera`'s viewport.
+pub fn view_position(
+    (camera, transform, projection): (&Camera, &Transform, &OrthographicProjection),
+    world: Vec2,
+) -> Option<Vec2> {
+    let (min, max) = camera.logical_viewport_rect()?;
+    let offset = (world - transform.translation.truncate()) / projection.scale;
+    let position = (min + max) / 2.0 + Vec2::new(offset.x, -offset.y);
+
+    (position.cmpge(min).all() && position.cmplt(max).all()).then_some(position)
+}
+
+/// Where the center of `tile` shows up in the window, like `view_position`.
+pub fn tile_view_position(
+    camera: (&Camera, &Transform, &OrthographicProjection),
+    (grid_size, map_type, tilemap_transform): (&TilemapGridSize, &TilemapType, &Transform),
+    tile: TilePos,
+) -> Option<Vec2> {
+    let center = tile.center_in_world(grid_size, map_type);
+    view_position(camera, tilemap_transform.translation.truncate() + center)
+}
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(zoom_camera)
+            .add_system(pan_camera)
+            .add_system(refit_camera);
+    }
+}
+
+fn zoom_camera(
+    mut scroll: EventReader<MouseWheel>,
+    windows: Res<Windows>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+    view_query: Query<&Camera>,
+) {
+    let lines: f32 = scroll
+        .iter()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y / 40.0,
+        })
+        .sum();
+
+    if lines == 0.0 {
+        return;
+    }
+
+    let (mut transform, mut projection) = camera_query.single_mut();
+    let old_scale = projection.scale;
+    projection.scale = (old_scale * 0.9f32.powf(lines)).clamp(MIN_ZOOM, MAX_ZOOM);
+
+    // keep the world point under the cursor fixed while zooming, in whichever view it's
+    // over since the others follow the main camera
+    let window = windows.get_primary().unwrap();
+    if let Some(offset) = view_query
+        .iter()
+        .filter(|camera| camera.target == RenderTarget::Window(WindowId::primary()))
+        .find_map(|camera| cursor_in_view(window, camera))
+    {
+        let shift = offset * (old_scale - projection.scale);
+        transform.translation += shift.extend(0.0);
+    }
+}
+
+fn pan_camera(
+    mouse: Res<Input<MouseButton>>,
+    mut motion: EventReader<MouseMotion>,
+    mut camera_query: Query<(&mut Transform, &OrthographicProjection), With<MainCamera>>,
+) {
+    let delta: Vec2 = motion.iter().map(|event| event.delta).sum();
+
+    if !mouse.pressed(MouseButton::Middle) || delta == Vec2::ZERO {
+        return;
+    }
+
+    let (mut transform, projection) = camera_query.single_mut();
+
+    // motion is y-down while the world is y-up
+    transform.translation.x -= delta.x * projection.scale;
+    transform.translation.y += delta.y * projection.scale;
+}
+
+/// Centers the main camera on the board, zoomed so all of it fits the camera's share of
+/// the resized window.
+pub fn refit_camera(
+    mut resized: EventReader<WindowResized>,
+    windows: Res<Windows>,
+    mut camera_query: Query<
+        (&Camera, &mut Transform, &mut OrthographicProjection),
+        With<MainCamera>,
+    >,
+    tilemap_query: Query<TilemapGeometry, Without<Camera>>,
+) {
+    if resized
+        .iter()
+        .filter(|event| event.id == WindowId::primary())
+        .count()
+        == 0
+    {
+        return;
+    }
+
+    let (Some(window), Ok((camera, mut transform, mut projection)), Ok(tilemap)) = (
+        windows.get_primary(),
+        camera_query.get_single_mut(),
+        tilemap_query.get_single(),
+    ) else {
+        return;
+    };
+    let (map_size, grid_size, map_type, tilemap_transform) = tilemap;
+
+    let view = view_size(window, camera);
+    if view.min_element() <= 0.0 {
+        return;
+    }
+
+    let first = TilePos::new(0, 0).center_in_world(grid_size, map_type);
+    let last = TilePos::new(map_size.x.saturating_sub(1), map_size.y.saturating_sub(1))
+        .center_in_world(grid_size, map_type);
+    let extent = (last - first).abs() + Vec2::new(grid_size.x, grid_size.y);
+    projection.scale = (extent / view).max_element().clamp(MIN_ZOOM, MAX_ZOOM);
+
+    let center = tilemap_transform.translation.truncate() + (first + last) / 2.0;
+    transform.translation = center.extend(transform.translation.z);
+}
@@ -0,0 +1,189 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::camera::MainCamera;
+use crate::follow::follow_camera;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::{FastForward, SimCounters, TickDuration, UnlimitedSpeed};
+
+/// P opens the camera path, a track of camera keyframes tied to generations. While it
+/// plays, the camera glides between the keyframes as the simulation runs through their
+/// generations, so a seeded run started over from the same board plays back with the
+/// same pans and zooms.
+pub struct CameraPathPlugin;
+
+/// Where the camera is at one generation of the path.
+#[derive(Clone, Copy, Debug)]
+struct Keyframe {
+    generation: u64,
+    position: Vec2,
+    scale: f32,
+}
+
+#[derive(Resource, Default)]
+struct CameraPath {
+    open: bool,
+    playing: bool,
+    /// Sorted by generation, at most one per generation.
+    keyframes: Vec<Keyframe>,
+}
+
+impl CameraPath {
+    /// Adds `keyframe`, replacing any already at its generation.
+    fn insert(&mut self, keyframe: Keyframe) {
+        match self
+            .keyframes
+            .binary_search_by_key(&keyframe.generation, |existing| existing.generation)
+        {
+            Ok(i) => self.keyframes[i] = keyframe,
+            Err(i) => self.keyframes.insert(i, keyframe),
+        }
+    }
+
+    /// The camera's position and scale at `generation`, which may be fractional partway
+    /// through a tick. Eased between keyframes and held before the first and after the
+    /// last.
+    fn at(&self, generation: f64) -> Option<(Vec2, f32)> {
+        let next = self
+            .keyframes
+            .iter()
+            .position(|keyframe| keyframe.generation as f64 > generation);
+
+        let (from, to) = match next {
+            None => (self.keyframes.last()?, self.keyframes.last()?),
+            Some(0) => (&self.keyframes[0], &self.keyframes[0]),
+            Some(i) => (&self.keyframes[i - 1], &self.keyframes[i]),
+        };
+        if from.generation == to.generation {
+            return Some((from.position, from.scale));
+        }
+
+        let t = ((generation - from.generation as f64) / (to.generation - from.generation) as f64)
+            as f32;
+        let t = t * t * (3.0 - 2.0 * t);
+
+        // zooming evenly means easing the scale's logarithm rather than the scale
+        let scale = (from.scale.ln() + (to.scale.ln() - from.scale.ln()) * t).exp();
+        Some((from.position.lerp(to.position, t), scale))
+    }
+}
+
+impl Plugin for CameraPathPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CameraPath>()
+            .add_system(toggle_path)
+            .add_system(path_panel.after(toggle_path))
+            .add_system(play_path.after(path_panel).after(follow_camera));
+    }
+}
+
+fn toggle_path(keys: Hotkeys, mut path: ResMut<CameraPath>) {
+    if keys.just_pressed(KeyCode::P) {
+        path.open = !path.open;
+    }
+}
+
+fn path_panel(
+    counters: Res<SimCounters>,
+    mut path: ResMut<CameraPath>,
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !path.open {
+        return;
+    }
+
+    let mut open = true;
+
+    egui::Window::new(strings.get("camera-path.title"))
+        .id(egui::Id::new("camera path"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                let generation = counters.generation;
+                let add =
+                    ui.button(strings.format("camera-path.add", &[("generation", &generation)]));
+                if add.clicked() {
+                    if let Ok((transform, projection)) = camera_query.get_single() {
+                        path.insert(Keyframe {
+                            generation: counters.generation,
+                            position: transform.translation.truncate(),
+                            scale: projection.scale,
+                        });
+                    }
+                }
+
+                let mut playing = path.playing;
+                let toggled = ui
+                    .add_enabled(
+                        !path.keyframes.is_empty(),
+                        egui::Checkbox::new(&mut playing, strings.get("camera-path.play")),
+                    )
+                    .changed();
+                if toggled {
+                    path.playing = playing;
+                }
+            });
+
+            let mut removed = None;
+            for (i, keyframe) in path.keyframes.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(strings.format(
+                        "camera-path.keyframe",
+                        &[
+                            ("generation", &keyframe.generation),
+                            ("x", &format!("{:.0}", keyframe.position.x)),
+                            ("y", &format!("{:.0}", keyframe.position.y)),
+                            ("zoom", &format!("{:.2}", 1.0 / keyframe.scale)),
+                        ],
+                    ));
+                    if ui.small_button("x").clicked() {
+                        removed = Some(i);
+                    }
+                });
+            }
+            if let Some(i) = removed {
+                path.keyframes.remove(i);
+                path.playing &= !path.keyframes.is_empty();
+            }
+
+            if path.keyframes.is_empty() {
+                ui.label(strings.get("camera-path.help"));
+            }
+        });
+
+    if !open {
+        path.open = false;
+    }
+}
+
+/// Puts the camera where the path says it is at the current generation, partway through
+/// the tick when the simulation isn't running flat out.
+fn play_path(
+    path: Res<CameraPath>,
+    counters: Res<SimCounters>,
+    (ticker, fast_forward, unlimited): (Res<TickDuration>, Res<FastForward>, Res<UnlimitedSpeed>),
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+) {
+    if !path.playing {
+        return;
+    }
+
+    let progress = if ticker.0.paused() || unlimited.enabled {
+        0.0
+    } else {
+        (ticker.0.elapsed_secs_f64() * fast_forward.speedup() / ticker.1).clamp(0.0, 1.0)
+    };
+    let (Some((position, scale)), Ok((mut transform, mut projection))) = (
+        path.at(counters.generation as f64 + progress),
+        camera_query.get_single_mut(),
+    ) else {
+        return;
+    };
+
+    transform.translation = position.extend(transform.translation.z);
+    projection.scale = scale;
+}
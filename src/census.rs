@@ -0,0 +1,238 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::bitboard::Bitboard;
+use crate::patterns::Pattern;
+use crate::rules::RuleSet;
+
+/// Objects with more cells than this are never in the dictionary, so they aren't
+/// canonicalized at all.
+const MAX_OBJECT_CELLS: usize = 32;
+
+/// Common Conway's Life objects, in one phase each. The other phases are found by running
+/// them.
+const OBJECTS: &[(Pattern, u32)] = &[
+    (
+        Pattern {
+            name: "block",
+            rows: &["OO", "OO"],
+        },
+        1,
+    ),
+    (
+        Pattern {
+            name: "beehive",
+            rows: &[".OO.", "O..O", ".OO."],
+        },
+        1,
+    ),
+    (
+        Pattern {
+            name: "loaf",
+            rows: &[".OO.", "O..O", ".O.O", "..O."],
+        },
+        1,
+    ),
+    (
+        Pattern {
+            name: "boat",
+            rows: &["OO.", "O.O", ".O."],
+        },
+        1,
+    ),
+    (
+        Pattern {
+            name: "ship",
+            rows: &["OO.", "O.O", ".OO"],
+        },
+        1,
+    ),
+    (
+        Pattern {
+            name: "tub",
+            rows: &[".O.", "O.O", ".O."],
+        },
+        1,
+    ),
+    (
+        Pattern {
+            name: "pond",
+            rows: &[".OO.", "O..O", "O..O", ".OO."],
+        },
+        1,
+    ),
+    (
+        Pattern {
+            name: "blinker",
+            rows: &["OOO"],
+        },
+        2,
+    ),
+    (
+        Pattern {
+            name: "toad",
+            rows: &[".OOO", "OOO."],
+        },
+        2,
+    ),
+    (
+        Pattern {
+            name: "beacon",
+            rows: &["OO..", "OO..", "..OO", "..OO"],
+        },
+        2,
+    ),
+    (
+        Pattern {
+            name: "glider",
+            rows: &[".O.", "..O", "OOO"],
+        },
+        4,
+    ),
+    (
+        Pattern {
+            name: "lwss",
+            rows: &[".O..O", "O....", "O...O", "OOOO."],
+        },
+        4,
+    ),
+];
+
+/// Names the connected groups of live cells on a Conway's Life board by looking them up,
+/// in every phase, rotation and reflection, in a small dictionary of common objects.
+#[derive(Resource)]
+pub struct Census {
+    known: HashMap<Vec<(u32, u32)>, &'static str>,
+}
+
+impl Default for Census {
+    fn default() -> Self {
+        let mut known = HashMap::new();
+
+        for (pattern, period) in OBJECTS {
+            let margin = 4;
+            let mut board =
+                Bitboard::new(pattern.width() + margin * 2, pattern.height() + margin * 2);
+            for (x, y) in pattern.cells() {
+                board.set(x + margin, y + margin, 2);
+            }
+
+            for _ in 0..*period {
+                let cells: Vec<_> = (0..board.height())
+                    .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+                    .filter(|&(x, y)| board.get(x, y) != 0)
+                    .collect();
+                known.insert(canonical(&cells), pattern.name);
+
+                board.step(&RuleSet::CONWAY);
+            }
+        }
+
+        Census { known }
+    }
+}
+
+impl Census {
+    /// How many of each known object are on the board, most common first, along with how
+    /// many groups of cells weren't recognized. Only meaningful under Conway's rule.
+    pub fn count(&self, board: &Bitboard) -> (Vec<(&'static str, u32)>, u32) {
+        let mut counts: HashMap<&'static str, u32> = HashMap::new();
+        let mut unknown = 0;
+
+        for object in objects(board) {
+            let name = (object.len() <= MAX_OBJECT_CELLS)
+                .then(|| self.known.get(&canonical(&object)))
+                .flatten();
+
+            match name {
+                Some(name) => *counts.entry(name).or_default() += 1,
+                None => unknown += 1,
+            }
+        }
+
+        let mut counts: Vec<_> = counts.into_iter().collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        (counts, unknown)
+    }
+}
+
+/// Groups of live cells, whatever their team, with no gap of two dead cells between their
+/// parts. Cells merely touching would split objects like the beacon in half of their
+/// phases. Groups continue across wrapping and mirroring edges.
+fn objects(board: &Bitboard) -> Vec<Vec<(u32, u32)>> {
+    let width = board.width() as usize;
+    let mut seen = vec![false; width * board.height() as usize];
+    let mut objects = Vec::new();
+
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if seen[y as usize * width + x as usize] || board.get(x, y) == 0 {
+                continue;
+            }
+            seen[y as usize * width + x as usize] = true;
+
+            let mut object = vec![(x, y)];
+            let mut next = 0;
+
+            while let Some(&(x, y)) = object.get(next) {
+                next += 1;
+
+                let nearby = (-2..=2).flat_map(|dy| (-2..=2).map(move |dx| (dx, dy)));
+                for (nx, ny) in nearby.filter_map(|(dx, dy)| board.offset(x, y, dx, dy)) {
+                    let index = ny as usize * width + nx as usize;
+                    if !seen[index] && board.get(nx, ny) != 0 {
+                        seen[index] = true;
+                        object.push((nx, ny));
+                    }
+                }
+            }
+
+            objects.push(object);
+        }
+    }
+
+    objects
+}
+
+/// The same key for a set of cells wherever it is and however it's turned or mirrored:
+/// the smallest of its eight orientations, each moved to the origin and sorted.
+fn canonical(cells: &[(u32, u32)]) -> Vec<(u32, u32)> {
+    // (swap x and y, then mirror x, then mirror y)
+    let mut orientations = Vec::with_capacity(8);
+    for swap in [false, true] {
+        for flip_x in [false, true] {
+            for flip_y in [false, true] {
+                orientations.push((swap, flip_x, flip_y));
+            }
+        }
+    }
+
+    orientations
+        .iter()
+        .map(|&(swap, flip_x, flip_y)| {
+            let turned: Vec<_> = cells
+                .iter()
+                .map(|&(x, y)| {
+                    let (x, y) = if swap {
+                        (y as i64, x as i64)
+                    } else {
+                        (x as i64, y as i64)
+                    };
+                    (if flip_x { -x } else { x }, if flip_y { -y } else { y })
+                })
+                .collect();
+            let left = turned.iter().map(|cell| cell.0).min().unwrap_or(0);
+            let bottom = turned.iter().map(|cell| cell.1).min().unwrap_or(0);
+
+            let mut key: Vec<_> = turned
+                .iter()
+                .map(|&(x, y)| ((x - left) as u32, (y - bottom) as u32))
+                .collect();
+            key.sort_unstable();
+            key
+        })
+        .min()
+        .unwrap_or_default()
+}
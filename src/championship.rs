@@ -0,0 +1,232 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::lang::Strings;
+use crate::scenario::BoardSetup;
+use crate::tournament::Tournament;
+use crate::{GameState, Palette, MAP_SIZE};
+
+/// Side of the box each player designs their seed in.
+const SEED_SIZE: u32 = 16;
+/// Most live cells a seed may have.
+const CELL_BUDGET: usize = 40;
+/// Side of a cell in the editor, in points.
+const EDITOR_CELL: f32 = 14.0;
+
+/// A championship is a match whose rounds start from seeds the players design themselves.
+/// Before each round, each player in turn draws a seed in a private editor, inside a
+/// fixed box and with a fixed number of cells, passing the screen between the two. The
+/// seeds are then placed on opposite halves of the board, the second player's mirrored so
+/// both face the middle, and the round is played out like any other match round.
+pub struct ChampionshipPlugin;
+
+#[derive(Resource)]
+struct Designs {
+    /// Each player's seed, row by row from the top.
+    seeds: [Vec<bool>; 2],
+    /// The player designing now.
+    designer: usize,
+    /// Whether the editor is hidden until the next player is ready, so they don't see the
+    /// last player's seed.
+    handing_over: bool,
+    /// Whether a drag is bringing cells to life or clearing them.
+    painting: bool,
+}
+
+impl Default for Designs {
+    fn default() -> Self {
+        Self {
+            seeds: [(); 2].map(|_| vec![false; (SEED_SIZE * SEED_SIZE) as usize]),
+            designer: 0,
+            handing_over: false,
+            painting: true,
+        }
+    }
+}
+
+impl Designs {
+    fn cells(&self, player: usize) -> usize {
+        self.seeds[player].iter().filter(|&&live| live).count()
+    }
+
+    /// Whether `player`'s seed can be played: no more cells than the budget, and at least
+    /// one so the round isn't decided before it starts.
+    fn is_valid(&self, player: usize) -> bool {
+        (1..=CELL_BUDGET).contains(&self.cells(player))
+    }
+}
+
+impl Plugin for ChampionshipPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Designs>()
+            .add_system_set(SystemSet::on_enter(GameState::Designing).with_system(start_designing))
+            .add_system_set(SystemSet::on_update(GameState::Designing).with_system(editor));
+    }
+}
+
+/// Clears the board and the seeds, so the last round's board doesn't give a seed away.
+fn start_designing(mut designs: ResMut<Designs>, mut setup: BoardSetup) {
+    *designs = Designs::default();
+    setup.reset(Bitboard::new(MAP_SIZE.0, MAP_SIZE.1));
+}
+
+/// Places the seeds on opposite halves of an empty board, centered in each.
+fn seeded_board(seeds: &[Vec<bool>; 2]) -> Bitboard {
+    let mut board = Bitboard::new(MAP_SIZE.0, MAP_SIZE.1);
+    let (width, height) = (board.width(), board.height());
+    let left = (width / 4).saturating_sub(SEED_SIZE / 2);
+    let top = (height / 2 + SEED_SIZE / 2).min(height);
+
+    for row in 0..SEED_SIZE {
+        for column in 0..SEED_SIZE {
+            let (x, Some(y)) = (left + column, top.checked_sub(row + 1)) else {
+                continue;
+            };
+            if x >= width / 2 {
+                continue;
+            }
+
+            let cell = (row * SEED_SIZE + column) as usize;
+            if seeds[0][cell] {
+                board.set(x, y, 2);
+            }
+            if seeds[1][cell] {
+                board.set(width - 1 - x, y, 3);
+            }
+        }
+    }
+
+    board
+}
+
+/// The seed grid, painted by clicking or dragging over cells.
+fn seed_grid(ui: &mut egui::Ui, designs: &mut Designs, palette: &Palette) {
+    let player = designs.designer;
+    let side = SEED_SIZE as f32 * EDITOR_CELL;
+    let (rect, response) =
+        ui.allocate_exact_size(egui::vec2(side, side), egui::Sense::click_and_drag());
+
+    if let Some(pointer) = response.interact_pointer_pos() {
+        let offset = (pointer - rect.min) / EDITOR_CELL;
+        let (column, row) = (offset.x.floor(), offset.y.floor());
+        let inside =
+            (0.0..SEED_SIZE as f32).contains(&column) && (0.0..SEED_SIZE as f32).contains(&row);
+
+        if inside {
+            let cell = (row as u32 * SEED_SIZE + column as u32) as usize;
+            if response.drag_started() || response.clicked() {
+                designs.painting = !designs.seeds[player][cell];
+            }
+
+            // the budget stops cells being added, never removed
+            let room = designs.cells(player) < CELL_BUDGET;
+            if designs.seeds[player][cell] != designs.painting && (room || !designs.painting) {
+                designs.seeds[player][cell] = designs.painting;
+            }
+        }
+    }
+
+    let [r, g, b, _] = palette[(player + 2) % palette.len()]
+        .as_rgba_f32()
+        .map(|c| (c * 255.0) as u8);
+    let live = egui::Color32::from_rgb(r, g, b);
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 0.0, egui::Color32::from_gray(20));
+    for row in 0..SEED_SIZE {
+        for column in 0..SEED_SIZE {
+            let min = rect.min + egui::vec2(column as f32, row as f32) * EDITOR_CELL;
+            let cell = egui::Rect::from_min_size(min, egui::Vec2::splat(EDITOR_CELL));
+            let color = if designs.seeds[player][(row * SEED_SIZE + column) as usize] {
+                live
+            } else {
+                egui::Color32::from_gray(45)
+            };
+            painter.rect_filled(cell.shrink(1.0), 0.0, color);
+        }
+    }
+}
+
+fn editor(
+    mut egui_context: ResMut<EguiContext>,
+    (mut designs, palette): (ResMut<Designs>, Res<Palette>),
+    mut tournament: ResMut<Tournament>,
+    mut state: ResMut<State<GameState>>,
+    strings: Res<Strings>,
+) {
+    let mut done = false;
+
+    egui::Window::new(strings.get("championship.title"))
+        .id(egui::Id::new("championship"))
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let designs = &mut *designs;
+            let player = designs.designer;
+
+            ui.label(strings.format(
+                "championship.round",
+                &[
+                    ("round", &(tournament.rounds_played() + 1)),
+                    ("best-of", &tournament.best_of),
+                    ("first", &tournament.score(2)),
+                    ("second", &tournament.score(3)),
+                ],
+            ));
+
+            let number = player + 1;
+            if designs.handing_over {
+                ui.heading(strings.format("championship.hand-over", &[("player", &number)]));
+                ui.label(strings.get("championship.hidden"));
+                if ui.button(strings.get("championship.ready")).clicked() {
+                    designs.handing_over = false;
+                }
+                return;
+            }
+
+            ui.heading(strings.format("championship.design", &[("player", &number)]));
+            let help = if player == 0 {
+                "championship.help-left"
+            } else {
+                "championship.help-right"
+            };
+            ui.label(strings.format(help, &[("size", &SEED_SIZE)]));
+
+            seed_grid(ui, designs, &palette);
+
+            let cells = designs.cells(player);
+            ui.label(strings.format(
+                "championship.cells",
+                &[("cells", &cells), ("budget", &CELL_BUDGET)],
+            ));
+
+            ui.horizontal(|ui| {
+                if ui.button(strings.get("championship.clear")).clicked() {
+                    designs.seeds[player].fill(false);
+                }
+                if ui
+                    .add_enabled(
+                        designs.is_valid(player),
+                        egui::Button::new(strings.get("championship.lock-in")),
+                    )
+                    .clicked()
+                {
+                    if player == 0 {
+                        designs.designer = 1;
+                        designs.handing_over = true;
+                    } else {
+                        done = true;
+                    }
+                }
+            });
+            if cells == 0 {
+                ui.label(strings.get("championship.empty-seed"));
+            }
+        });
+
+    if done && designs.is_valid(0) && designs.is_valid(1) {
+        tournament.seeded = Some(seeded_board(&designs.seeds));
+        let _ = state.set(GameState::Round);
+    }
+}
@@ -0,0 +1,826 @@
+use std::io::Read;
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+use bevy::tasks::{IoTaskPool, Task};
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiClipboard, EguiContext};
+use futures_lite::future;
+
+use crate::energy::Energy;
+use crate::freeze::Frozen;
+use crate::hotkeys::Hotkeys;
+use crate::image_import;
+use crate::lang::Strings;
+use crate::pattern_files::{self, Format};
+use crate::picture::overlay_layer;
+use crate::rle::{self, Clip};
+use crate::rules::RuleSet;
+use crate::scenario::{ActiveScenario, BoardSetup};
+use crate::share;
+use crate::subboard::OpenSubBoard;
+use crate::svg;
+use crate::tools::{BoardCursor, Tool};
+use crate::undo::UndoHistory;
+use crate::{Board, GameState, Palette, MAP_SIZE, TEAM_COLORS};
+
+/// Copies the selection (or the whole board) to the OS clipboard as RLE with
+/// Ctrl+Shift+C, and pastes RLE from it with Ctrl+Shift+V. Pattern files dropped onto the
+/// window or downloaded from a URL are pasted the same way, and whole boards travel as
+/// share codes.
+pub struct ClipboardPlugin;
+
+/// The area the select tool last dragged out, as (left, bottom, width, height).
+#[derive(Resource, Default)]
+struct Selection {
+    anchor: Option<TilePos>,
+    area: Option<(u32, u32, u32, u32)>,
+    moving: Option<Move>,
+}
+
+impl Selection {
+    fn contains(&self, tile: TilePos) -> bool {
+        self.area.is_some_and(|(left, bottom, width, height)| {
+            (left..left + width).contains(&tile.x) && (bottom..bottom + height).contains(&tile.y)
+        })
+    }
+
+    /// The selection as it's drawn, following the cursor while it's being moved.
+    fn shown_area(&self) -> Option<(i64, i64, u32, u32)> {
+        let (left, bottom, width, height) = self.area?;
+        let (dx, dy) = self.moving.as_ref().map_or((0, 0), |moving| moving.offset);
+
+        Some((left as i64 + dx, bottom as i64 + dy, width, height))
+    }
+}
+
+/// The selection's cells being dragged from the tile they were grabbed by.
+struct Move {
+    clip: Clip,
+    grab: TilePos,
+    offset: (i64, i64),
+}
+
+/// The clip waiting to be stamped, and how the last copy or paste went.
+#[derive(Resource, Default)]
+pub(crate) struct Pasting {
+    clip: Option<Clip>,
+    status: String,
+}
+
+impl Pasting {
+    /// Switches to the paste tool with a freshly read clip, or reports why it couldn't be read.
+    pub fn start(&mut self, clip: Result<Clip, String>, tool: &mut Tool, strings: &Strings) {
+        match clip {
+            Ok(clip) => {
+                self.status = strings.format(
+                    "clipboard.pasting",
+                    &[("width", &clip.width), ("height", &clip.height)],
+                );
+                self.clip = Some(clip);
+                *tool = Tool::Paste;
+            }
+            Err(error) => {
+                self.status = strings.format("clipboard.paste-failed", &[("error", &error)]);
+            }
+        }
+    }
+}
+
+/// Where and how the panel's Save button writes the selection.
+#[derive(Resource)]
+struct SaveSettings {
+    path: String,
+    format: Format,
+}
+
+impl Default for SaveSettings {
+    fn default() -> Self {
+        Self {
+            path: "selection.rle".to_owned(),
+            format: Format::Rle,
+        }
+    }
+}
+
+/// Largest pattern file a download reads, well past any pattern that fits on a board.
+const MAX_DOWNLOAD: u64 = 4 << 20;
+
+/// The URL typed into the panel, and the download of it in flight, if any.
+#[derive(Resource, Default)]
+struct Download {
+    url: String,
+    task: Option<Task<Result<String, String>>>,
+}
+
+/// A share code to replace the board with, sent by the panel.
+struct LoadShareCode(String);
+
+/// Shades the selection.
+#[derive(Component)]
+struct SelectionOverlay;
+
+/// A see-through preview of the clip under the cursor.
+#[derive(Component)]
+struct PasteGhost;
+
+pub type TilemapGeometry<'a> = (&'a TilemapGridSize, &'a TilemapType, &'a Transform);
+type OverlayParts<'a> = (
+    &'a mut Transform,
+    &'a mut Sprite,
+    &'a mut Visibility,
+    &'a mut Handle<Image>,
+    Option<&'a PasteGhost>,
+);
+type OverlayFilter = (
+    Or<(With<SelectionOverlay>, With<PasteGhost>)>,
+    Without<TileStorage>,
+);
+
+impl Plugin for ClipboardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Selection>()
+            .init_resource::<Pasting>()
+            .init_resource::<SaveSettings>()
+            .init_resource::<Download>()
+            .add_event::<LoadShareCode>()
+            .add_startup_system(spawn_overlays)
+            .add_system(select_area)
+            .add_system(copy_paste)
+            .add_system(drop_file)
+            .add_system(finish_download)
+            .add_system(
+                paste_clip
+                    .after(copy_paste)
+                    .after(drop_file)
+                    .after(finish_download),
+            )
+            .add_system(draw_overlays.after(select_area).after(paste_clip))
+            .add_system(clipboard_panel)
+            .add_system(load_share_code.after(clipboard_panel));
+    }
+}
+
+fn spawn_overlays(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, 0.2),
+                ..default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        SelectionOverlay,
+        overlay_layer(),
+    ));
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, 0.6),
+                ..default()
+            },
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        PasteGhost,
+        overlay_layer(),
+    ));
+}
+
+/// Drags out a new selection, or moves the current one when the drag starts inside it.
+fn select_area(
+    mouse: Res<Input<MouseButton>>,
+    tool: Res<Tool>,
+    cursor: BoardCursor,
+    mut selection: ResMut<Selection>,
+    scenario: Res<ActiveScenario>,
+    (mut board, mut history): (ResMut<Board>, ResMut<UndoHistory>),
+) {
+    if *tool != Tool::Select {
+        if selection.moving.is_some() {
+            selection.moving = None;
+        }
+        return;
+    }
+
+    if mouse.just_pressed(MouseButton::Right) {
+        *selection = Selection::default();
+        return;
+    }
+
+    if mouse.just_pressed(MouseButton::Left) {
+        match (cursor.tile_pos(), selection.area) {
+            (Some(tile), Some(area)) if selection.contains(tile) => {
+                let mut clip = Clip::copy(&board, area);
+                clip.cells
+                    .retain(|&(x, y, _)| !scenario.is_locked(area.0 + x, area.1 + y));
+
+                selection.anchor = None;
+                selection.moving = Some(Move {
+                    clip,
+                    grab: tile,
+                    offset: (0, 0),
+                });
+            }
+            (tile, _) => selection.anchor = tile,
+        }
+    }
+
+    if selection.moving.is_some() {
+        move_selection(
+            &mouse,
+            &cursor,
+            &mut selection,
+            &scenario,
+            &mut board,
+            &mut history,
+        );
+        return;
+    }
+
+    let (Some(anchor), Some(tile)) = (selection.anchor, cursor.tile_pos()) else {
+        return;
+    };
+    if !mouse.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let area = (
+        anchor.x.min(tile.x),
+        anchor.y.min(tile.y),
+        anchor.x.abs_diff(tile.x) + 1,
+        anchor.y.abs_diff(tile.y) + 1,
+    );
+    if selection.area != Some(area) {
+        selection.area = Some(area);
+    }
+}
+
+/// Follows the cursor with the grabbed cells, then on release clears them from where they
+/// were and stamps them where they were dropped, replacing what was there.
+fn move_selection(
+    mouse: &Input<MouseButton>,
+    cursor: &BoardCursor,
+    selection: &mut Selection,
+    scenario: &ActiveScenario,
+    board: &mut Board,
+    history: &mut UndoHistory,
+) {
+    if mouse.pressed(MouseButton::Left) {
+        let moving = selection.moving.as_mut().unwrap();
+        if let Some(tile) = cursor.tile_pos() {
+            let offset = (
+                tile.x as i64 - moving.grab.x as i64,
+                tile.y as i64 - moving.grab.y as i64,
+            );
+            if moving.offset != offset {
+                moving.offset = offset;
+            }
+        }
+        return;
+    }
+
+    let Some(Move { clip, offset, .. }) = selection.moving.take() else {
+        return;
+    };
+    let Some((left, bottom, width, height)) = selection.area else {
+        return;
+    };
+    if offset == (0, 0) {
+        return;
+    }
+
+    history.record(board);
+
+    for &(x, y, _) in &clip.cells {
+        board.set(left + x, bottom + y, 0);
+    }
+
+    let (new_left, new_bottom) = (left as i64 + offset.0, bottom as i64 + offset.1);
+    let (right, top) = (new_left + width as i64, new_bottom + height as i64);
+    for y in new_bottom.max(0)..top.min(board.height() as i64) {
+        for x in new_left.max(0)..right.min(board.width() as i64) {
+            let (x, y) = (x as u32, y as u32);
+            if board.get(x, y) != 0 && !scenario.is_locked(x, y) {
+                board.set(x, y, 0);
+            }
+        }
+    }
+
+    for &(x, y, team) in &clip.cells {
+        let (x, y) = (new_left + x as i64, new_bottom + y as i64);
+        if !(0..board.width() as i64).contains(&x) || !(0..board.height() as i64).contains(&y) {
+            continue;
+        }
+
+        let (x, y) = (x as u32, y as u32);
+        if !scenario.is_locked(x, y) && !board.is_wall(x, y) {
+            board.set(x, y, team);
+        }
+    }
+
+    // the selection follows the cells, trimmed to the part still on the board
+    let (clamped_left, clamped_bottom) = (new_left.max(0), new_bottom.max(0));
+    let (clamped_right, clamped_top) = (
+        right.min(board.width() as i64),
+        top.min(board.height() as i64),
+    );
+    selection.area = (clamped_left < clamped_right && clamped_bottom < clamped_top).then(|| {
+        (
+            clamped_left as u32,
+            clamped_bottom as u32,
+            (clamped_right - clamped_left) as u32,
+            (clamped_top - clamped_bottom) as u32,
+        )
+    });
+}
+
+fn copy_paste(
+    keys: Hotkeys,
+    mut clipboard: ResMut<EguiClipboard>,
+    (board, rule): (Res<Board>, Res<RuleSet>),
+    selection: Res<Selection>,
+    mut pasting: ResMut<Pasting>,
+    mut tool: ResMut<Tool>,
+    strings: Res<Strings>,
+) {
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+    if !(ctrl && shift) {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::C) {
+        let area = selection
+            .area
+            .unwrap_or((0, 0, board.width(), board.height()));
+
+        clipboard.set_contents(&rle::encode(&board, area, &rule));
+        pasting.status = strings.format(
+            "clipboard.copied",
+            &[("width", &area.2), ("height", &area.3)],
+        );
+    }
+
+    if keys.just_pressed(KeyCode::V) {
+        let decoded = clipboard
+            .get_contents()
+            .ok_or_else(|| strings.get("clipboard.nothing-to-paste").to_owned())
+            .and_then(|text| pattern_files::decode(Format::detect(&text), &text));
+
+        pasting.start(decoded, &mut tool, &strings);
+    }
+}
+
+/// Loads a pattern file dropped onto the window. Drops land where the cursor is, and the
+/// ghost already follows the cursor, so the clip shows up centered under the drop.
+fn drop_file(
+    mut drops: EventReader<FileDragAndDrop>,
+    mut pasting: ResMut<Pasting>,
+    mut tool: ResMut<Tool>,
+    strings: Res<Strings>,
+) {
+    for drop in drops.iter() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = drop {
+            // pictures go to the image importer
+            if image_import::is_image(path_buf) {
+                continue;
+            }
+            let clip = pattern_files::load(path_buf);
+            match &clip {
+                Ok(_) => info!(path = %path_buf.display(), "loaded a pattern file"),
+                Err(error) => {
+                    warn!(path = %path_buf.display(), %error, "couldn't load a pattern file")
+                }
+            }
+            pasting.start(clip, &mut tool, &strings);
+        }
+    }
+}
+
+/// The address of the pattern file behind `url`. LifeWiki article links like
+/// `conwaylife.com/wiki/Gosper_glider_gun` point at a page, not a pattern, so they're sent
+/// to the wiki's RLE file for the article instead.
+fn pattern_url(url: &str) -> String {
+    let url = url.trim();
+
+    match url.split_once("conwaylife.com/wiki/") {
+        Some((_, article)) if !article.contains('.') => {
+            let name: String = article
+                .trim_start_matches("Pattern:")
+                .chars()
+                .filter(|c| c.is_alphanumeric())
+                .flat_map(char::to_lowercase)
+                .collect();
+            format!("https://conwaylife.com/patterns/{name}.rle")
+        }
+        _ => url.to_owned(),
+    }
+}
+
+/// Fetches a pattern file off the main thread.
+fn fetch(url: String) -> Task<Result<String, String>> {
+    IoTaskPool::get().spawn(async move {
+        let response = ureq::get(&url)
+            .timeout(Duration::from_secs(20))
+            .call()
+            .map_err(|error| error.to_string())?;
+
+        let mut text = String::new();
+        response
+            .into_reader()
+            .take(MAX_DOWNLOAD)
+            .read_to_string(&mut text)
+            .map_err(|error| error.to_string())?;
+
+        Ok(text)
+    })
+}
+
+/// Pastes a finished download like a pattern read from the clipboard.
+fn finish_download(
+    mut download: ResMut<Download>,
+    mut pasting: ResMut<Pasting>,
+    mut tool: ResMut<Tool>,
+    strings: Res<Strings>,
+) {
+    let Some(task) = &mut download.task else {
+        return;
+    };
+    let Some(result) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    download.task = None;
+
+    let decoded = result
+        .map_err(|error| strings.format("clipboard.download-failed", &[("error", &error)]))
+        .and_then(|text| pattern_files::decode(Format::detect(&text), &text));
+    match &decoded {
+        Ok(_) => info!(url = %download.url, "downloaded a pattern"),
+        Err(error) => warn!(url = %download.url, %error, "couldn't download a pattern"),
+    }
+    pasting.start(decoded, &mut tool, &strings);
+}
+
+/// Stamps the clip centered on the clicked tile, charging each team
+/// for its cells. Cells over a scenario's locked regions are left out.
+fn paste_clip(
+    mouse: Res<Input<MouseButton>>,
+    mut tool: ResMut<Tool>,
+    cursor: BoardCursor,
+    pasting: Res<Pasting>,
+    scenario: Res<ActiveScenario>,
+    (mut energy, mut history): (ResMut<Energy>, ResMut<UndoHistory>),
+    mut board: ResMut<Board>,
+) {
+    if *tool != Tool::Paste {
+        return;
+    }
+
+    if mouse.just_pressed(MouseButton::Right) {
+        *tool = Tool::Select;
+        return;
+    }
+
+    let (Some(clip), Some(tile)) = (&pasting.clip, cursor.tile_pos()) else {
+        return;
+    };
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let cost = |team: u8| clip.cells.iter().filter(|cell| cell.2 == team).count() as f32;
+    if !(energy.can_afford(2, cost(2)) && energy.can_afford(3, cost(3))) {
+        return;
+    }
+    energy.try_spend(2, cost(2));
+    energy.try_spend(3, cost(3));
+
+    history.record(&board);
+    clip.stamp(&mut board, clip.centered_on(tile.x, tile.y), |x, y| {
+        !scenario.is_locked(x, y)
+    });
+}
+
+/// Rebuilds the ghost's texture from a freshly pasted clip.
+fn ghost_image(clip: &Clip) -> Image {
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: clip.width,
+            height: clip.height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+
+    // image rows go top to bottom while clip rows go bottom to top
+    for &(x, y, team) in &clip.cells {
+        let offset = ((clip.height - 1 - y) * clip.width + x) as usize * 4;
+        let color = TEAM_COLORS[team as usize % TEAM_COLORS.len()];
+
+        image.data[offset..offset + 4]
+            .copy_from_slice(&color.as_rgba_f32().map(|c| (c * 255.0) as u8));
+    }
+
+    image
+}
+
+/// Center and size in world space of the (left, bottom, width, height) block of tiles,
+/// which may hang off the board. Hex rows are treated as a square grid.
+/// Where a (left, bottom, width, height) block of tiles is in the world, and its size there.
+pub fn block_in_world(
+    (grid_size, map_type, tilemap_transform): TilemapGeometry,
+    (left, bottom, width, height): (i64, i64, u32, u32),
+) -> (Vec3, Vec2) {
+    let origin = TilePos::new(0, 0).center_in_world(grid_size, map_type);
+    let size = Vec2::new(width as f32 * grid_size.x, height as f32 * grid_size.y);
+    let corner = Vec2::new(left as f32 * grid_size.x, bottom as f32 * grid_size.y);
+    // tile positions are tile centers, so the block's middle is half a tile in from its corner
+    let center = origin + corner + (size - Vec2::new(grid_size.x, grid_size.y)) / 2.0;
+
+    (tilemap_transform.translation + center.extend(2.0), size)
+}
+
+fn draw_overlays(
+    tool: Res<Tool>,
+    (selection, pasting): (Res<Selection>, Res<Pasting>),
+    cursor: BoardCursor,
+    mut images: ResMut<Assets<Image>>,
+    tilemap_query: Query<TilemapGeometry, With<TileStorage>>,
+    mut overlay_query: Query<OverlayParts, OverlayFilter>,
+    mut showing_move: Local<bool>,
+) {
+    let Ok(geometry) = tilemap_query.get_single() else {
+        return;
+    };
+
+    // the ghost doubles as the preview of a selection being moved
+    let moving = selection.moving.as_ref().filter(|_| *tool == Tool::Select);
+    let move_started = moving.is_some() && !*showing_move;
+    *showing_move = moving.is_some();
+
+    for (mut transform, mut sprite, mut visibility, mut texture, ghost) in overlay_query.iter_mut()
+    {
+        let block = if ghost.is_some() {
+            if move_started {
+                *texture = images.add(ghost_image(&moving.unwrap().clip));
+            } else if pasting.is_changed() {
+                if let Some(clip) = &pasting.clip {
+                    *texture = images.add(ghost_image(clip));
+                }
+            }
+
+            if moving.is_some() {
+                selection.shown_area()
+            } else {
+                let clip = pasting.clip.as_ref().filter(|_| *tool == Tool::Paste);
+                clip.zip(cursor.tile_pos()).map(|(clip, tile)| {
+                    let (left, bottom) = clip.centered_on(tile.x, tile.y);
+                    (left, bottom, clip.width, clip.height)
+                })
+            }
+        } else {
+            selection.shown_area().filter(|_| *tool == Tool::Select)
+        };
+
+        if visibility.is_visible != block.is_some() {
+            visibility.is_visible = block.is_some();
+        }
+
+        if let Some(block) = block {
+            let (translation, size) = block_in_world(geometry, block);
+            transform.translation = translation;
+            sprite.custom_size = Some(size);
+        }
+    }
+}
+
+/// Share codes hold a rule and edges too, so like those they only change in the sandbox.
+fn load_share_code(
+    mut events: EventReader<LoadShareCode>,
+    state: Res<State<GameState>>,
+    mut pasting: ResMut<Pasting>,
+    strings: Res<Strings>,
+    mut setup: BoardSetup,
+) {
+    for LoadShareCode(code) in events.iter() {
+        if *state.current() != GameState::Sandbox {
+            pasting.status = strings.get("clipboard.share-sandbox-only").to_owned();
+            continue;
+        }
+
+        pasting.status = match share::decode(code) {
+            Ok((board, rule)) => {
+                setup.load(board, rule);
+                strings.get("clipboard.share-loaded").to_owned()
+            }
+            Err(error) => strings.format("clipboard.share-failed", &[("error", &error)]),
+        };
+    }
+}
+
+fn clipboard_panel(
+    (tool, mut pasting): (Res<Tool>, ResMut<Pasting>),
+    (mut save, mut download): (ResMut<SaveSettings>, ResMut<Download>),
+    (board, rule, palette): (Res<Board>, Res<RuleSet>, Res<Palette>),
+    (selection, mut frozen): (Res<Selection>, ResMut<Frozen>),
+    (mut clipboard, strings): (ResMut<EguiClipboard>, Res<Strings>),
+    (mut share_events, mut sub_board_events): (
+        EventWriter<LoadShareCode>,
+        EventWriter<OpenSubBoard>,
+    ),
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !matches!(*tool, Tool::Select | Tool::Paste) {
+        return;
+    }
+
+    egui::Window::new(strings.get("clipboard.title"))
+        .id(egui::Id::new("clipboard"))
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(strings.get("clipboard.help-select"));
+            ui.label(strings.get("clipboard.help-copy"));
+            ui.label(strings.get("clipboard.help-paste"));
+            ui.label(strings.get("clipboard.help-drop"));
+            ui.separator();
+
+            let save = &mut *save;
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut save.path);
+
+                let before = save.format;
+                egui::ComboBox::from_id_source("save format")
+                    .selected_text(save.format.name())
+                    .show_ui(ui, |ui| {
+                        for format in Format::ALL {
+                            ui.selectable_value(&mut save.format, format, format.name());
+                        }
+                    });
+                if save.format != before {
+                    let path = Path::new(&save.path).with_extension(save.format.extension());
+                    save.path = path.display().to_string();
+                }
+
+                if ui.button(strings.get("clipboard.save")).clicked() {
+                    let area = selection
+                        .area
+                        .unwrap_or((0, 0, board.width(), board.height()));
+                    let path = Path::new(&save.path);
+
+                    let saved = pattern_files::save(path, save.format, &board, area, &rule);
+                    pasting.status = match saved {
+                        Ok(()) => {
+                            info!(
+                                path = %save.path,
+                                format = save.format.name(),
+                                width = area.2,
+                                height = area.3,
+                                "saved a pattern"
+                            );
+                            strings.format(
+                                "clipboard.saved",
+                                &[
+                                    ("width", &area.2),
+                                    ("height", &area.3),
+                                    ("path", &save.path),
+                                ],
+                            )
+                        }
+                        Err(error) => {
+                            warn!(path = %save.path, %error, "couldn't save a pattern");
+                            strings.format("clipboard.save-failed", &[("error", &error)])
+                        }
+                    };
+                }
+
+                // a picture of the cells rather than a pattern, for documents and wikis
+                if ui.button(strings.get("clipboard.export-svg")).clicked() {
+                    let area = selection
+                        .area
+                        .unwrap_or((0, 0, board.width(), board.height()));
+                    let path = Path::new(&save.path).with_extension("svg");
+
+                    pasting.status = match svg::save(&path, &board, area, &palette.0) {
+                        Ok(()) => {
+                            info!(
+                                path = %path.display(),
+                                width = area.2,
+                                height = area.3,
+                                "exported an SVG"
+                            );
+                            strings.format(
+                                "clipboard.exported",
+                                &[
+                                    ("width", &area.2),
+                                    ("height", &area.3),
+                                    ("path", &path.display()),
+                                ],
+                            )
+                        }
+                        Err(error) => {
+                            warn!(path = %path.display(), %error, "couldn't export an SVG");
+                            strings.format("clipboard.export-failed", &[("error", &error)])
+                        }
+                    };
+                }
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let area = selection.area;
+                if ui
+                    .add_enabled(
+                        area.is_some(),
+                        egui::Button::new(strings.get("clipboard.freeze")),
+                    )
+                    .clicked()
+                {
+                    let area = area.unwrap();
+                    frozen.thaw(area);
+                    frozen.regions.push(area);
+                }
+                if ui
+                    .add_enabled(
+                        area.is_some(),
+                        egui::Button::new(strings.get("clipboard.thaw")),
+                    )
+                    .clicked()
+                {
+                    frozen.thaw(area.unwrap());
+                }
+                if ui
+                    .add_enabled(
+                        !frozen.is_empty(),
+                        egui::Button::new(strings.get("clipboard.thaw-all")),
+                    )
+                    .clicked()
+                {
+                    frozen.regions.clear();
+                }
+            });
+            if ui
+                .add_enabled(
+                    selection.area.is_some(),
+                    egui::Button::new(strings.get("clipboard.sub-board")),
+                )
+                .clicked()
+            {
+                sub_board_events.send(OpenSubBoard(selection.area.unwrap()));
+            }
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut download.url);
+
+                let idle = download.task.is_none();
+                if ui
+                    .add_enabled(idle, egui::Button::new(strings.get("clipboard.load-url")))
+                    .clicked()
+                {
+                    let url = pattern_url(&download.url);
+                    pasting.status = strings.format("clipboard.downloading", &[("url", &url)]);
+                    download.task = Some(fetch(url));
+                }
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                let usual_size = (board.width(), board.height()) == MAP_SIZE;
+                if ui
+                    .add_enabled(
+                        usual_size,
+                        egui::Button::new(strings.get("clipboard.copy-share")),
+                    )
+                    .on_disabled_hover_text(strings.get("clipboard.share-size"))
+                    .clicked()
+                {
+                    let code = share::encode(&board, &rule);
+                    let length = code.len();
+                    pasting.status =
+                        strings.format("clipboard.share-copied", &[("length", &length)]);
+                    clipboard.set_contents(&code);
+                }
+
+                if ui.button(strings.get("clipboard.load-share")).clicked() {
+                    match clipboard.get_contents() {
+                        Some(code) => share_events.send(LoadShareCode(code)),
+                        None => pasting.status = strings.get("clipboard.empty").to_owned(),
+                    }
+                }
+            });
+
+            if !pasting.status.is_empty() {
+                ui.separator();
+                ui.label(&pasting.status);
+            }
+        });
+}
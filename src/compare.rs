@@ -0,0 +1,304 @@
+use bevy::core_pipeline::clear_color::ClearColorConfig;
+use bevy::prelude::*;
+use bevy::render::camera::Viewport;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::camera::MainCamera;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::rules::{Neighborhood, RuleSet, PRESETS};
+use crate::{update_map, Board, GameState, SimCounters, CELL_SIZE, TEAM_COLORS, WALL_COLOR};
+
+/// Runs a copy of the board under a second rule next to the main one, each in its own
+/// half of the window, so the two rules can be watched side by side.
+pub struct ComparePlugin;
+
+/// The second board and the rule it runs under, stepped whenever the main board is.
+#[derive(Resource, Default)]
+struct Comparison {
+    board: Option<Bitboard>,
+    rule: RuleSet,
+    rule_text: String,
+    error: Option<String>,
+    generation: u64,
+}
+
+/// Follows the main camera, looking at the comparison board instead.
+#[derive(Component)]
+struct CompareCamera;
+
+#[derive(Component)]
+struct CompareSprite;
+
+type SplitEntities = Or<(With<CompareCamera>, With<CompareSprite>)>;
+type FollowerFilter = (With<CompareCamera>, Without<MainCamera>);
+
+impl Plugin for ComparePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Comparison>()
+            .add_system(toggle_comparison)
+            .add_system(step_comparison.after(update_map))
+            .add_system(layout_views.after(toggle_comparison))
+            .add_system(follow_camera.after(layout_views))
+            .add_system(draw_comparison.after(step_comparison))
+            .add_system(compare_panel);
+    }
+}
+
+/// How far the comparison board sits from the main one, far enough that neither camera
+/// sees the other's board at ordinary zoom levels.
+fn world_offset(board: &Bitboard) -> Vec3 {
+    Vec3::X * board.width().max(board.height()) as f32 * CELL_SIZE * 4.0
+}
+
+/// V splits the view, copying the board to run under the next square-grid preset.
+fn toggle_comparison(
+    mut commands: Commands,
+    keys: Hotkeys,
+    state: Res<State<GameState>>,
+    (board, rule, counters): (Res<Board>, Res<RuleSet>, Res<SimCounters>),
+    mut comparison: ResMut<Comparison>,
+    mut images: ResMut<Assets<Image>>,
+    view_query: Query<Entity, SplitEntities>,
+) {
+    // Ctrl+Shift+V belongs to the clipboard
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if !keys.just_pressed(KeyCode::V) || ctrl || *state.current() != GameState::Sandbox {
+        return;
+    }
+
+    if comparison.board.take().is_some() {
+        for entity in view_query.iter() {
+            commands.entity(entity).despawn();
+        }
+        return;
+    }
+
+    let presets: Vec<RuleSet> = PRESETS
+        .iter()
+        .map(|preset| RuleSet::parse(preset.rule).unwrap())
+        .filter(|preset| preset.neighborhood == Neighborhood::Moore)
+        .collect();
+    let current = presets
+        .iter()
+        .position(|&preset| preset == rule.without_max_age());
+    comparison.rule = presets[current.map_or(0, |i| (i + 1) % presets.len())];
+    comparison.rule_text = comparison.rule.to_string();
+    comparison.error = None;
+    comparison.generation = counters.generation;
+
+    let mut image = Image::new_fill(
+        Extent3d {
+            width: board.width(),
+            height: board.height(),
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+
+    let size = Vec2::new(board.width() as f32, board.height() as f32) * CELL_SIZE;
+
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                custom_size: Some(size),
+                ..default()
+            },
+            texture: images.add(image),
+            // the main tilemap is centered on the origin
+            transform: Transform::from_translation(world_offset(&board)),
+            ..default()
+        },
+        CompareSprite,
+    ));
+    commands.spawn((
+        Camera2dBundle {
+            camera: Camera {
+                priority: 1,
+                ..default()
+            },
+            // the main camera has already cleared the window
+            camera_2d: Camera2d {
+                clear_color: ClearColorConfig::None,
+            },
+            ..default()
+        },
+        CompareCamera,
+    ));
+
+    comparison.board = Some(board.0.clone());
+}
+
+fn step_comparison(counters: Res<SimCounters>, mut comparison: ResMut<Comparison>) {
+    // checked first so the texture is only redrawn when something stepped
+    if comparison.generation >= counters.generation {
+        return;
+    }
+
+    let comparison = &mut *comparison;
+    let Some(board) = &mut comparison.board else {
+        return;
+    };
+
+    while comparison.generation < counters.generation {
+        board.step(&comparison.rule);
+        comparison.generation += 1;
+    }
+}
+
+/// Gives each camera half the window while split, and the main camera all of it otherwise.
+fn layout_views(
+    windows: Res<Windows>,
+    comparison: Res<Comparison>,
+    mut main_query: Query<&mut Camera, (With<MainCamera>, Without<CompareCamera>)>,
+    mut compare_query: Query<&mut Camera, With<CompareCamera>>,
+) {
+    let Some(window) = windows.get_primary() else {
+        return;
+    };
+    let size = UVec2::new(window.physical_width(), window.physical_height());
+    let half = UVec2::new(size.x / 2, size.y);
+
+    let viewport = |left: u32| Viewport {
+        physical_position: UVec2::new(left, 0),
+        physical_size: half,
+        ..default()
+    };
+
+    let main = comparison.board.as_ref().map(|_| viewport(0));
+    for mut camera in main_query.iter_mut() {
+        if camera.viewport.as_ref().map(|v| v.physical_size)
+            != main.as_ref().map(|v| v.physical_size)
+        {
+            camera.viewport = main.clone();
+        }
+    }
+    for mut camera in compare_query.iter_mut() {
+        if camera.viewport.as_ref().map(|v| v.physical_size) != Some(half) {
+            camera.viewport = Some(viewport(size.x - half.x));
+        }
+    }
+}
+
+/// Keeps the comparison camera on the same part of its board as the main camera.
+fn follow_camera(
+    board: Res<Board>,
+    main_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    mut compare_query: Query<(&mut Transform, &mut OrthographicProjection), FollowerFilter>,
+) {
+    let Ok((main_transform, main_projection)) = main_query.get_single() else {
+        return;
+    };
+
+    for (mut transform, mut projection) in compare_query.iter_mut() {
+        transform.translation = main_transform.translation + world_offset(&board);
+        if projection.scale != main_projection.scale {
+            projection.scale = main_projection.scale;
+        }
+    }
+}
+
+fn draw_comparison(
+    comparison: Res<Comparison>,
+    mut images: ResMut<Assets<Image>>,
+    sprite_query: Query<&Handle<Image>, With<CompareSprite>>,
+) {
+    let Some(board) = &comparison.board else {
+        return;
+    };
+    if !comparison.is_changed() {
+        return;
+    }
+    let Some(image) = sprite_query
+        .get_single()
+        .ok()
+        .and_then(|handle| images.get_mut(handle))
+    else {
+        return;
+    };
+
+    // image rows go top to bottom while board rows go bottom to top
+    for y in 0..board.height() {
+        let row = (board.height() - 1 - y) as usize * board.width() as usize;
+
+        for x in 0..board.width() {
+            let offset = (row + x as usize) * 4;
+            image.data[offset..offset + 4].copy_from_slice(&board_texel(board, x, y));
+        }
+    }
+}
+
+/// The color of cell (x, y) in textures drawn from a board, with dead cells transparent.
+pub fn board_texel(board: &Bitboard, x: u32, y: u32) -> [u8; 4] {
+    if board.is_wall(x, y) {
+        WALL_COLOR.as_rgba_f32().map(|c| (c * 255.0) as u8)
+    } else {
+        match board.get(x, y) {
+            0 => [0; 4],
+            team => TEAM_COLORS[team as usize % TEAM_COLORS.len()]
+                .as_rgba_f32()
+                .map(|c| (c * 255.0) as u8),
+        }
+    }
+}
+
+fn compare_panel(
+    mut egui_context: ResMut<EguiContext>,
+    board: Res<Board>,
+    mut comparison: ResMut<Comparison>,
+    strings: Res<Strings>,
+) {
+    if comparison.board.is_none() {
+        return;
+    }
+
+    egui::Window::new(strings.get("compare.title"))
+        .id(egui::Id::new("compare"))
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label(strings.get("compare.right-rule"));
+                // typing doesn't change the right board, so it isn't redrawn for it
+                ui.text_edit_singleline(&mut comparison.bypass_change_detection().rule_text);
+
+                if ui.button(strings.get("compare.apply")).clicked() {
+                    comparison.error = match RuleSet::parse(&comparison.rule_text) {
+                        // drawn as a square grid, so hex rules can't be shown
+                        Ok(rule) if rule.neighborhood == Neighborhood::Hex => {
+                            Some(strings.get("compare.no-hex").to_owned())
+                        }
+                        Ok(rule) => {
+                            comparison.rule = rule;
+                            None
+                        }
+                        Err(error) => Some(error),
+                    };
+                }
+            });
+
+            if let Some(error) = &comparison.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            if let Some(right) = &comparison.board {
+                ui.label(strings.format(
+                    "compare.populations",
+                    &[
+                        ("left", &board.population()),
+                        ("right", &right.population()),
+                    ],
+                ));
+            }
+
+            if ui.button(strings.get("compare.copy-left")).clicked() {
+                comparison.board = Some(board.0.clone());
+            }
+        });
+}
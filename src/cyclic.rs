@@ -0,0 +1,116 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::rules::{Automaton, CyclicRule, RuleSet};
+use crate::{Board, GameState, Palette, SimRng};
+
+/// Switches the board between Life and the cyclic automaton with C, which makes a
+/// good demo: a random soup of states organizes itself into spirals.
+pub struct CyclicPlugin;
+
+/// Cyclic rule used the next time the mode is switched on, kept while it's off.
+#[derive(Resource, Default)]
+struct CyclicSettings(CyclicRule);
+
+impl Plugin for CyclicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<CyclicSettings>()
+            .add_system(toggle_cyclic)
+            .add_system(cyclic_panel);
+    }
+}
+
+fn toggle_cyclic(
+    keys: Hotkeys,
+    settings: Res<CyclicSettings>,
+    mut automaton: ResMut<Automaton>,
+    mut palette: ResMut<Palette>,
+    mut rng: ResMut<SimRng>,
+    mut board: ResMut<Board>,
+    state: Res<State<GameState>>,
+) {
+    // Ctrl+Shift+C belongs to the clipboard
+    let ctrl = keys.any_pressed([KeyCode::LControl, KeyCode::RControl]);
+    if !keys.just_pressed(KeyCode::C) || ctrl || *state.current() != GameState::Sandbox {
+        return;
+    }
+
+    match *automaton {
+        Automaton::Cyclic(_) => {
+            *automaton = Automaton::Life;
+            *palette = Palette::default();
+            seed(&mut board, &mut rng, 1);
+        }
+        _ => {
+            *automaton = Automaton::Cyclic(settings.0);
+            *palette = cyclic_palette(settings.0.states);
+            seed(&mut board, &mut rng, settings.0.states);
+        }
+    }
+}
+
+/// Evenly spaced hues, with state 0 left dark like an empty cell.
+fn cyclic_palette(states: u8) -> Palette {
+    let mut colors = vec![Color::BLACK];
+    colors.extend(
+        (1..states).map(|state| Color::hsl(360.0 * state as f32 / states as f32, 0.8, 0.55)),
+    );
+
+    Palette(colors)
+}
+
+/// Fills every cell that isn't a wall with a uniformly random state, where a single
+/// state clears the board.
+fn seed(board: &mut Board, rng: &mut SimRng, states: u8) {
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if !board.is_wall(x, y) {
+                let state = (rng.next_u64() % states as u64) as u8;
+                board.set(x, y, state);
+            }
+        }
+    }
+}
+
+fn cyclic_panel(
+    mut egui_context: ResMut<EguiContext>,
+    rule: Res<RuleSet>,
+    mut settings: ResMut<CyclicSettings>,
+    mut automaton: ResMut<Automaton>,
+    mut palette: ResMut<Palette>,
+    (mut rng, mut board): (ResMut<SimRng>, ResMut<Board>),
+    strings: Res<Strings>,
+) {
+    let Automaton::Cyclic(current) = *automaton else {
+        return;
+    };
+
+    egui::Window::new(strings.get("cyclic.title"))
+        .id(egui::Id::new("cyclic"))
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let mut cyclic = current;
+            let max_threshold = rule.neighborhood.max_neighbors() as u8;
+
+            let states = egui::Slider::new(&mut cyclic.states, 2..=16);
+            ui.add(states.text(strings.get("cyclic.states")));
+            let threshold = egui::Slider::new(&mut cyclic.threshold, 1..=max_threshold);
+            ui.add(threshold.text(strings.get("cyclic.threshold")));
+
+            let reseed = ui.button(strings.get("cyclic.reseed")).clicked();
+
+            if cyclic != current {
+                *automaton = Automaton::Cyclic(cyclic);
+                settings.0 = cyclic;
+            }
+            if cyclic.states != current.states {
+                *palette = cyclic_palette(cyclic.states);
+            }
+            if reseed || cyclic.states != current.states {
+                seed(&mut board, &mut rng, cyclic.states);
+            }
+        });
+}
@@ -0,0 +1,277 @@
+use std::f32::consts::TAU;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::ecs::system::SystemParam;
+use bevy::input::mouse::{MouseMotion, MouseWheel};
+use bevy::prelude::*;
+use bevy::time::Stopwatch;
+use bevy_egui::{egui, EguiContext};
+
+use crate::ants::Ant;
+use crate::bitboard::{Bitboard, Boundary};
+use crate::camera::MainCamera;
+use crate::fog::Fog;
+use crate::lang::Strings;
+use crate::patterns;
+use crate::rng::Rng;
+use crate::rules::{Automaton, RuleSet};
+use crate::spawners::Spawner;
+use crate::{Board, GameState, Palette, TickDuration, CELL_SIZE};
+
+/// Seconds without input before the demo starts.
+const IDLE_SECS: f32 = 90.0;
+/// Seconds each show runs before the next one is loaded.
+const SHOW_SECS: f32 = 40.0;
+/// Seconds for one loop of the camera's drift.
+const DRIFT_PERIOD: f32 = 120.0;
+
+/// Patterns the demo picks from and how many copies of each it scatters around.
+const SHOWS: &[(&str, usize)] = &[
+    ("acorn", 1),
+    ("r-pentomino", 1),
+    ("diehard", 3),
+    ("gosper-gun", 2),
+    ("glider", 12),
+    ("lwss", 8),
+];
+
+/// Attract mode: once the sandbox has sat idle for a while, runs a slideshow of patterns
+/// and soups with a slowly drifting camera, and puts everything back on the next input.
+pub struct DemoPlugin;
+
+#[derive(Resource)]
+struct Demo {
+    idle: Stopwatch,
+    show: Stopwatch,
+    caption: &'static str,
+    rng: Rng,
+    saved: Option<Sandbox>,
+}
+
+impl Default for Demo {
+    fn default() -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+
+        Self {
+            idle: Stopwatch::new(),
+            show: Stopwatch::new(),
+            caption: "",
+            rng: Rng::new(seed),
+            saved: None,
+        }
+    }
+}
+
+impl Demo {
+    /// A fresh board the same size as `board`, with either a soup or a few copies of one
+    /// of the `SHOWS` patterns.
+    fn next_show(&mut self, board: &Bitboard) -> Bitboard {
+        let (width, height) = (board.width(), board.height());
+        let mut show = Bitboard::new(width, height);
+        // spaceships wrap around instead of piling up on the edges
+        show.set_boundary(Boundary::Toroidal);
+
+        let pick = self.rng.next_u64() as usize % (SHOWS.len() + 1);
+        let Some(&(name, copies)) = SHOWS.get(pick) else {
+            for y in height / 4..height * 3 / 4 {
+                for x in width / 4..width * 3 / 4 {
+                    match self.rng.next_u64() % 6 {
+                        0 => show.set(x, y, 2),
+                        1 => show.set(x, y, 3),
+                        _ => {}
+                    }
+                }
+            }
+
+            self.caption = "soup";
+            return show;
+        };
+
+        let pattern = patterns::find(name).unwrap();
+        for copy in 0..copies {
+            let left = (self.rng.next_u64() % width as u64) as i64;
+            let bottom = (self.rng.next_u64() % height as u64) as i64;
+            let bits = self.rng.next_u64();
+            let flips = (bits & 1 != 0, bits & 2 != 0);
+
+            pattern.stamp(&mut show, (left, bottom), flips, 2 + copy as u8 % 2);
+        }
+
+        self.caption = name;
+        show
+    }
+}
+
+/// Sandbox settings the demo overrides, to be put back when it ends.
+struct Sandbox {
+    board: Bitboard,
+    rule: RuleSet,
+    automaton: Automaton,
+    palette: Vec<Color>,
+    viewer: Option<u8>,
+    paused: bool,
+    camera: (Transform, f32),
+}
+
+/// Everything the demo changes about the sandbox.
+#[derive(SystemParam)]
+struct Scene<'w, 's> {
+    board: ResMut<'w, Board>,
+    rule: ResMut<'w, RuleSet>,
+    automaton: ResMut<'w, Automaton>,
+    palette: ResMut<'w, Palette>,
+    fog: ResMut<'w, Fog>,
+    ticker: ResMut<'w, TickDuration>,
+    camera_query: Query<
+        'w,
+        's,
+        (&'static mut Transform, &'static mut OrthographicProjection),
+        With<MainCamera>,
+    >,
+}
+
+impl Scene<'_, '_> {
+    /// Switches to plain Life with nothing covered or paused, returning what was there.
+    fn take(&mut self) -> Sandbox {
+        let (transform, projection) = self.camera_query.single();
+        let saved = Sandbox {
+            board: self.board.0.clone(),
+            rule: *self.rule,
+            automaton: *self.automaton,
+            palette: self.palette.0.clone(),
+            viewer: self.fog.viewer,
+            paused: self.ticker.0.paused(),
+            camera: (*transform, projection.scale),
+        };
+
+        *self.rule = RuleSet::CONWAY;
+        *self.automaton = Automaton::Life;
+        *self.palette = Palette::default();
+        self.fog.viewer = None;
+        self.ticker.0.unpause();
+
+        saved
+    }
+
+    fn restore(&mut self, saved: Sandbox) {
+        self.board.0 = saved.board;
+        *self.rule = saved.rule;
+        *self.automaton = saved.automaton;
+        self.palette.0 = saved.palette;
+        self.fog.viewer = saved.viewer;
+        // set either way, since the key that ended the demo may also have toggled it
+        if saved.paused {
+            self.ticker.0.pause();
+        } else {
+            self.ticker.0.unpause();
+        }
+
+        let (mut transform, mut projection) = self.camera_query.single_mut();
+        *transform = saved.camera.0;
+        projection.scale = saved.camera.1;
+    }
+}
+
+/// Any key, button, mouse movement or scrolling this frame.
+#[derive(SystemParam)]
+struct Activity<'w, 's> {
+    keys: Res<'w, Input<KeyCode>>,
+    mouse: Res<'w, Input<MouseButton>>,
+    motion: EventReader<'w, 's, MouseMotion>,
+    scroll: EventReader<'w, 's, MouseWheel>,
+}
+
+impl Activity<'_, '_> {
+    fn any(&mut self) -> bool {
+        // both readers are drained so old events don't count next frame
+        let moved = self.motion.iter().count() > 0;
+        let scrolled = self.scroll.iter().count() > 0;
+
+        moved
+            || scrolled
+            || self.keys.get_just_pressed().next().is_some()
+            || self.mouse.get_just_pressed().next().is_some()
+    }
+}
+
+type Agents = Or<(With<Spawner>, With<Ant>)>;
+
+impl Plugin for DemoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Demo>()
+            .add_system(watch_idle)
+            .add_system_set(SystemSet::on_enter(GameState::Demo).with_system(start_demo))
+            .add_system_set(
+                SystemSet::on_update(GameState::Demo)
+                    .with_system(run_demo)
+                    .with_system(demo_caption),
+            )
+            .add_system_set(SystemSet::on_exit(GameState::Demo).with_system(end_demo));
+    }
+}
+
+/// Starts the demo after `IDLE_SECS` of no input in the sandbox, and ends it on any input.
+/// Boards with spawners or ants are left alone, since those would keep acting on the demo.
+fn watch_idle(
+    time: Res<Time>,
+    mut activity: Activity,
+    mut demo: ResMut<Demo>,
+    mut state: ResMut<State<GameState>>,
+    agent_query: Query<(), Agents>,
+) {
+    if activity.any() {
+        demo.idle.reset();
+
+        if *state.current() == GameState::Demo {
+            let _ = state.pop();
+        }
+        return;
+    }
+
+    let idle = demo.idle.tick(time.delta()).elapsed_secs();
+    if idle >= IDLE_SECS && *state.current() == GameState::Sandbox && agent_query.is_empty() {
+        let _ = state.push(GameState::Demo);
+    }
+}
+
+fn start_demo(mut scene: Scene, mut demo: ResMut<Demo>) {
+    demo.saved = Some(scene.take());
+    scene.board.0 = demo.next_show(&scene.board);
+    demo.show.reset();
+}
+
+fn run_demo(time: Res<Time>, mut scene: Scene, mut demo: ResMut<Demo>) {
+    let elapsed = demo.show.tick(time.delta()).elapsed_secs();
+    if elapsed >= SHOW_SECS || scene.board.population() == 0 {
+        scene.board.0 = demo.next_show(&scene.board);
+        demo.show.reset();
+    }
+
+    // a slow figure eight around the middle of the board, breathing in and out
+    let t = time.elapsed_seconds() / DRIFT_PERIOD * TAU;
+    let reach = scene.board.width().min(scene.board.height()) as f32 * CELL_SIZE / 4.0;
+    let (mut transform, mut projection) = scene.camera_query.single_mut();
+
+    transform.translation.x = reach * t.sin();
+    transform.translation.y = reach * (2.0 * t).sin() / 2.0;
+    projection.scale = 0.9 + 0.3 * (3.0 * t).sin();
+}
+
+fn end_demo(mut scene: Scene, mut demo: ResMut<Demo>) {
+    if let Some(saved) = demo.saved.take() {
+        scene.restore(saved);
+    }
+}
+
+fn demo_caption(mut egui_context: ResMut<EguiContext>, demo: Res<Demo>, strings: Res<Strings>) {
+    egui::Window::new("Demo")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let show = strings.named("demo", demo.caption);
+            ui.label(strings.format("demo.caption", &[("show", &show)]));
+        });
+}
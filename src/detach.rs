@@ -0,0 +1,144 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::render_graph::RenderGraph;
+use bevy::render::view::RenderLayers;
+use bevy::render::RenderApp;
+use bevy::window::{CreateWindow, WindowClosed, WindowId};
+use bevy_egui::{egui, setup_pipeline, EguiContext, RenderGraphConfig};
+
+use crate::hotkeys::Hotkeys;
+
+/// Render graph node drawing egui into the panel window.
+const PANEL_EGUI_PASS: &str = "panel_egui_pass";
+/// A render layer nothing is on, so the panel window's camera only clears it for egui.
+const EMPTY_LAYER: u8 = 31;
+
+/// F4 moves the HUD, the toolbar, the team stats and the divergence graph out into a
+/// window of their own, which can go on another monitor so the board's window stays clean
+/// for recording. F4 again, or closing that window, brings them back.
+pub struct DetachPlugin;
+
+/// The second OS window the panels can be moved to. Its id is made up front, since egui
+/// only draws into windows it was given a render pass for when the app was built.
+#[derive(Resource)]
+pub struct PanelWindow {
+    id: WindowId,
+    open: bool,
+    camera: Option<Entity>,
+}
+
+/// The egui context of whichever window the detachable panels are in right now.
+#[derive(SystemParam)]
+pub struct PanelContext<'w, 's> {
+    egui: ResMut<'w, EguiContext>,
+    window: Res<'w, PanelWindow>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
+}
+
+impl PanelContext<'_, '_> {
+    pub fn ctx(&mut self) -> &egui::Context {
+        // the window takes a frame to show up after it's asked for
+        let id = self.window.id;
+        if self.window.open && self.egui.try_ctx_for_window_mut(id).is_some() {
+            self.egui.ctx_for_window_mut(id)
+        } else {
+            self.egui.ctx_mut()
+        }
+    }
+}
+
+impl Plugin for DetachPlugin {
+    fn build(&self, app: &mut App) {
+        let id = WindowId::new();
+        app.insert_resource(PanelWindow {
+            id,
+            open: false,
+            camera: None,
+        })
+        .add_system(toggle_detached)
+        .add_system(track_closed_windows);
+
+        // headless apps have no renderer to draw the window with
+        if let Ok(render_app) = app.get_sub_app_mut(RenderApp) {
+            let mut graph = render_app.world.resource_mut::<RenderGraph>();
+            setup_pipeline(
+                &mut graph,
+                RenderGraphConfig {
+                    window_id: id,
+                    egui_pass: PANEL_EGUI_PASS,
+                },
+            );
+        }
+    }
+}
+
+fn toggle_detached(
+    mut commands: Commands,
+    keys: Hotkeys,
+    mut windows: ResMut<Windows>,
+    mut create_window: EventWriter<CreateWindow>,
+    mut panels: ResMut<PanelWindow>,
+) {
+    if !keys.just_pressed(KeyCode::F4) {
+        return;
+    }
+
+    if panels.open {
+        // the rest is undone once the window reports it's closed
+        if let Some(window) = windows.get_mut(panels.id) {
+            window.close();
+        }
+        return;
+    }
+
+    create_window.send(CreateWindow {
+        id: panels.id,
+        descriptor: WindowDescriptor {
+            width: 360.0,
+            height: 640.0,
+            title: "Conway controls".to_owned(),
+            ..default()
+        },
+    });
+    // egui draws over whatever is already there, so something has to clear the window
+    let camera = commands
+        .spawn((
+            Camera2dBundle {
+                camera: Camera {
+                    target: RenderTarget::Window(panels.id),
+                    ..default()
+                },
+                ..default()
+            },
+            RenderLayers::layer(EMPTY_LAYER),
+        ))
+        .id();
+    panels.open = true;
+    panels.camera = Some(camera);
+}
+
+/// Brings the panels back when their window closes, and closes it when the board's window
+/// does, so the app doesn't keep running with only the panels left.
+fn track_closed_windows(
+    mut commands: Commands,
+    mut closed: EventReader<WindowClosed>,
+    mut windows: ResMut<Windows>,
+    mut panels: ResMut<PanelWindow>,
+) {
+    for event in closed.iter() {
+        if event.id == WindowId::primary() {
+            if let Some(window) = windows.get_mut(panels.id) {
+                window.close();
+            }
+        } else if event.id == panels.id {
+            panels.open = false;
+            if let Some(camera) = panels.camera.take() {
+                commands.entity(camera).despawn();
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::diagnostic::{
+    Diagnostic, DiagnosticId, Diagnostics, EntityCountDiagnosticsPlugin, FrameTimeDiagnosticsPlugin,
+};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::undo::UndoHistory;
+use crate::{Board, SimCounters};
+
+pub const SIM_TICKS_PER_SECOND: DiagnosticId =
+    DiagnosticId::from_u128(0x6c1f_3a52_88d4_4b0e_9d6a_2f1e_7c3b_5a01);
+pub const LIVE_CELLS: DiagnosticId =
+    DiagnosticId::from_u128(0x6c1f_3a52_88d4_4b0e_9d6a_2f1e_7c3b_5a02);
+pub const HISTORY_MEMORY: DiagnosticId =
+    DiagnosticId::from_u128(0x6c1f_3a52_88d4_4b0e_9d6a_2f1e_7c3b_5a03);
+
+/// F3 overlay with frame time, simulation speed, population, entity count and the memory
+/// the edit history takes up.
+pub struct DiagnosticsOverlayPlugin;
+
+#[derive(Resource, Default)]
+struct OverlayVisible(bool);
+
+impl Plugin for DiagnosticsOverlayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .add_plugin(EntityCountDiagnosticsPlugin)
+            .init_resource::<OverlayVisible>()
+            .add_startup_system(setup_diagnostics)
+            .add_system(measure_sim)
+            .add_system(toggle_overlay)
+            .add_system(overlay);
+    }
+}
+
+fn setup_diagnostics(mut diagnostics: ResMut<Diagnostics>) {
+    diagnostics.add(Diagnostic::new(SIM_TICKS_PER_SECOND, "sim_tps", 20));
+    diagnostics.add(Diagnostic::new(LIVE_CELLS, "live_cells", 1));
+    diagnostics.add(Diagnostic::new(HISTORY_MEMORY, "history_memory", 1).with_suffix("B"));
+}
+
+fn measure_sim(
+    time: Res<Time>,
+    counters: Res<SimCounters>,
+    (board, history): (Res<Board>, Res<UndoHistory>),
+    mut last_generation: Local<u64>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    let delta = time.delta_seconds_f64();
+    if delta > 0.0 {
+        let generations = counters.generation - *last_generation;
+        diagnostics.add_measurement(SIM_TICKS_PER_SECOND, || generations as f64 / delta);
+    }
+    *last_generation = counters.generation;
+
+    diagnostics.add_measurement(LIVE_CELLS, || board.population() as f64);
+    diagnostics.add_measurement(HISTORY_MEMORY, || history.memory_used() as f64);
+}
+
+fn toggle_overlay(keys: Hotkeys, mut visible: ResMut<OverlayVisible>) {
+    if keys.just_pressed(KeyCode::F3) {
+        visible.0 = !visible.0;
+    }
+}
+
+fn overlay(
+    visible: Res<OverlayVisible>,
+    (diagnostics, history): (Res<Diagnostics>, Res<UndoHistory>),
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !visible.0 {
+        return;
+    }
+
+    let value = |id| diagnostics.get(id).and_then(|d| d.smoothed());
+    let latest = |id| diagnostics.get(id).and_then(|d| d.value());
+
+    egui::Window::new("diagnostics")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_TOP, [-8.0, 8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            if let (Some(frame_time), Some(fps)) = (
+                value(FrameTimeDiagnosticsPlugin::FRAME_TIME),
+                value(FrameTimeDiagnosticsPlugin::FPS),
+            ) {
+                ui.label(strings.format(
+                    "diagnostics.frame",
+                    &[
+                        ("ms", &format!("{frame_time:.2}")),
+                        ("fps", &format!("{fps:.0}")),
+                    ],
+                ));
+            }
+
+            if let Some(tps) = value(SIM_TICKS_PER_SECOND) {
+                ui.label(strings.format("diagnostics.sim", &[("tps", &format!("{tps:.0}"))]));
+            }
+
+            if let Some(cells) = latest(LIVE_CELLS) {
+                let cells = format!("{cells:.0}");
+                ui.label(strings.format("diagnostics.cells", &[("cells", &cells)]));
+            }
+
+            if let Some(entities) = latest(EntityCountDiagnosticsPlugin::ENTITY_COUNT) {
+                let entities = format!("{entities:.0}");
+                ui.label(strings.format("diagnostics.entities", &[("entities", &entities)]));
+            }
+
+            if let Some(bytes) = latest(HISTORY_MEMORY) {
+                ui.label(strings.format(
+                    "diagnostics.history",
+                    &[
+                        ("kib", &format!("{:.0}", bytes / 1024.0)),
+                        ("boards", &history.checkpoint_count()),
+                    ],
+                ));
+            }
+        });
+}
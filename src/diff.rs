@@ -0,0 +1,249 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::overlay::{board_overlay_image, place_over_board};
+use crate::pattern_files;
+use crate::picture::overlay_layer;
+use crate::rules::{Neighborhood, RuleSet};
+use crate::Board;
+
+/// Cells live only in the first board.
+const REMOVED_COLOR: [u8; 4] = [240, 70, 70, 200];
+/// Cells live only in the second board.
+const ADDED_COLOR: [u8; 4] = [80, 230, 100, 200];
+/// Cells live in both but on different teams, or a wall in only one of them.
+const CHANGED_COLOR: [u8; 4] = [250, 210, 60, 200];
+
+/// D opens a panel holding two snapshots, taken from the board or loaded from pattern
+/// files, and highlights the cells where snapshot A differs from snapshot B or from the
+/// live board. Useful for checking whether a rule change or a refactor of the stepping
+/// code altered how a pattern evolves.
+pub struct DiffPlugin;
+
+/// What snapshot A is compared against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Against {
+    /// The live board, so the overlay follows it as it steps.
+    #[default]
+    Board,
+    Snapshot,
+}
+
+#[derive(Resource, Default)]
+struct BoardDiff {
+    open: bool,
+    snapshots: [Option<Bitboard>; 2],
+    against: Against,
+    /// Pattern files typed into the panel for each snapshot.
+    paths: [String; 2],
+    error: Option<String>,
+    /// Cells that differ in the shown comparison, or `None` if nothing is compared.
+    differences: Option<u32>,
+}
+
+#[derive(Component)]
+struct DiffSprite;
+
+impl Plugin for DiffPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BoardDiff>()
+            .add_startup_system(spawn_diff_sprite)
+            .add_system(toggle_diff)
+            .add_system(diff_panel.after(toggle_diff))
+            .add_system(draw_diff.after(diff_panel));
+    }
+}
+
+/// How cell (x, y) of `after` differs from `before`, as an overlay color. Cells off the
+/// edge of either board count as dead.
+fn difference(before: &Bitboard, after: &Bitboard, x: u32, y: u32) -> Option<[u8; 4]> {
+    let cell = |board: &Bitboard| {
+        if x >= board.width() || y >= board.height() {
+            (0, false)
+        } else {
+            (board.get(x, y), board.is_wall(x, y))
+        }
+    };
+    let ((old, old_wall), (new, new_wall)) = (cell(before), cell(after));
+
+    match (old, new) {
+        _ if old_wall != new_wall => Some(CHANGED_COLOR),
+        (old, new) if old == new => None,
+        (_, 0) => Some(REMOVED_COLOR),
+        (0, _) => Some(ADDED_COLOR),
+        _ => Some(CHANGED_COLOR),
+    }
+}
+
+/// A snapshot the size of the board with the pattern in `path` centered on it.
+fn load_snapshot(path: &str, board: &Bitboard) -> Result<Bitboard, String> {
+    let clip = pattern_files::load(Path::new(path.trim()))?;
+
+    let mut snapshot = Bitboard::new(board.width(), board.height());
+    snapshot.set_boundary(board.boundary());
+    clip.stamp(
+        &mut snapshot,
+        clip.centered_on(board.width() / 2, board.height() / 2),
+        |_, _| true,
+    );
+
+    Ok(snapshot)
+}
+
+fn spawn_diff_sprite(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        DiffSprite,
+        overlay_layer(),
+    ));
+}
+
+fn toggle_diff(keys: Hotkeys, mut diff: ResMut<BoardDiff>) {
+    if keys.just_pressed(KeyCode::D) {
+        diff.open = !diff.open;
+    }
+}
+
+fn diff_panel(
+    board: Res<Board>,
+    mut diff: ResMut<BoardDiff>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !diff.open {
+        return;
+    }
+
+    let mut open = true;
+
+    egui::Window::new(strings.get("diff.title"))
+        .id(egui::Id::new("board diff"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let diff = &mut *diff;
+
+            for (index, name) in ["A", "B"].into_iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(strings.format("diff.snapshot", &[("name", &name)]));
+                    match &diff.snapshots[index] {
+                        Some(snapshot) => ui.label(
+                            strings.format("diff.cells", &[("cells", &snapshot.population())]),
+                        ),
+                        None => ui.label(strings.get("diff.empty")),
+                    };
+                    if ui.button(strings.get("diff.take")).clicked() {
+                        diff.snapshots[index] = Some(board.0.clone());
+                        diff.error = None;
+                    }
+                });
+                ui.horizontal(|ui| {
+                    ui.text_edit_singleline(&mut diff.paths[index]);
+                    if ui.button(strings.get("diff.load")).clicked() {
+                        match load_snapshot(&diff.paths[index], &board) {
+                            Ok(snapshot) => {
+                                diff.snapshots[index] = Some(snapshot);
+                                diff.error = None;
+                            }
+                            Err(error) => diff.error = Some(error),
+                        }
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("diff.compare"));
+                let board = strings.get("diff.against-board");
+                ui.selectable_value(&mut diff.against, Against::Board, board);
+                let snapshot = strings.get("diff.against-snapshot");
+                ui.selectable_value(&mut diff.against, Against::Snapshot, snapshot);
+            });
+
+            if let Some(error) = &diff.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            match diff.differences {
+                Some(0) => ui.label(strings.get("diff.same")),
+                Some(count) => ui.label(strings.format("diff.differ", &[("cells", &count)])),
+                None => ui.label(strings.get("diff.nothing")),
+            };
+        });
+
+    if !open {
+        diff.open = false;
+    }
+}
+
+/// Redraws the overlay texture when either side of the comparison changes.
+fn draw_diff(
+    (board, rule): (Res<Board>, Res<RuleSet>),
+    mut diff: ResMut<BoardDiff>,
+    mut images: ResMut<Assets<Image>>,
+    mut sprite_query: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<DiffSprite>,
+    >,
+) {
+    let Ok((mut handle, mut sprite, mut transform, mut visibility)) = sprite_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let after = match diff.against {
+        Against::Board => Some(&board.0),
+        Against::Snapshot => diff.snapshots[1].as_ref(),
+    };
+    // the texture is a square grid, so hex boards can't be overlaid
+    let shown = match (&diff.snapshots[0], after) {
+        (Some(before), Some(after)) if diff.open && rule.neighborhood == Neighborhood::Moore => {
+            Some((before, after))
+        }
+        _ => None,
+    };
+
+    if visibility.is_visible != shown.is_some() {
+        visibility.is_visible = shown.is_some();
+    }
+    let Some((before, after)) = shown else {
+        if diff.differences.is_some() {
+            diff.differences = None;
+        }
+        return;
+    };
+    if !(diff.is_changed() || board.is_changed() || rule.is_changed()) {
+        return;
+    }
+
+    let (width, height) = (board.width(), board.height());
+    let mut differences = 0;
+    let image = board_overlay_image(width, height, |x, y| {
+        let texel = difference(before, after, x, y)?;
+        differences += 1;
+        Some(texel)
+    });
+
+    match images.get_mut(&handle) {
+        Some(existing) => *existing = image,
+        None => *handle = images.add(image),
+    }
+
+    // placed like the zoomed-out board sprite, just above the tiles
+    place_over_board((width, height), &mut sprite, &mut transform, 0.5);
+
+    // bypasses change detection so measuring doesn't redraw the overlay next frame
+    diff.bypass_change_detection().differences = Some(differences);
+}
@@ -0,0 +1,342 @@
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use bevy::window::{PresentMode, WindowFocused, WindowId, WindowMode};
+use bevy::winit::{UpdateMode, WinitSettings};
+use bevy_egui::{egui, EguiContext};
+
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::picture::PictureMode;
+use crate::subboard::SubBoard;
+use crate::{FastForward, GameState, RunTo, TickDuration, UnlimitedSpeed};
+
+/// Longest an idle app waits for input before drawing a frame anyway, focused and not.
+const IDLE_WAIT: (Duration, Duration) = (Duration::from_millis(250), Duration::from_secs(2));
+
+/// F11 toggles fullscreen and F10 opens the display settings, where the window can also
+/// be made borderless, vsync turned off, the frame rate capped, unlimited speed switched to
+/// auto speed and the language picked. The simulation pauses while the window is out of
+/// focus unless that's turned off there too.
+pub struct DisplayPlugin;
+
+#[derive(Resource)]
+struct DisplaySettings {
+    open: bool,
+    /// Most frames drawn per second, or `None` to draw as fast as the present mode allows.
+    /// Ignored while the simulation runs at unlimited speed or to a generation.
+    fps_cap: Option<u32>,
+    pause_unfocused: bool,
+    /// Whether to stop drawing continuously while nothing is running.
+    idle_low_power: bool,
+    /// Whether losing focus is what paused the simulation, so regaining it resumes it.
+    paused_for_focus: bool,
+    /// Whether the next key pressed becomes the fast-forward key.
+    rebinding: bool,
+}
+
+impl Default for DisplaySettings {
+    fn default() -> Self {
+        Self {
+            open: false,
+            fps_cap: Some(60),
+            pause_unfocused: true,
+            idle_low_power: true,
+            paused_for_focus: false,
+            rebinding: false,
+        }
+    }
+}
+
+impl Plugin for DisplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DisplaySettings>()
+            .add_system(display_keys)
+            .add_system(display_panel.after(display_keys))
+            .add_system(pause_on_focus)
+            .add_system(idle_when_paused.after(pause_on_focus))
+            .add_system_to_stage(CoreStage::Last, cap_frame_rate);
+    }
+}
+
+/// Switches between a window and borderless fullscreen on the current monitor.
+fn toggle_fullscreen(window: &mut Window) {
+    window.set_mode(match window.mode() {
+        WindowMode::Windowed => WindowMode::BorderlessFullscreen,
+        _ => WindowMode::Windowed,
+    });
+}
+
+fn display_keys(
+    keys: Hotkeys,
+    mut windows: ResMut<Windows>,
+    mut settings: ResMut<DisplaySettings>,
+    mut fast_forward: ResMut<FastForward>,
+) {
+    if settings.rebinding {
+        // Escape gives up on rebinding
+        if let Some(&key) = keys.get_just_pressed().next() {
+            if key != KeyCode::Escape {
+                fast_forward.key = key;
+            }
+            settings.rebinding = false;
+        }
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::F10) {
+        settings.open = !settings.open;
+    }
+
+    if keys.just_pressed(KeyCode::F11) {
+        if let Some(window) = windows.get_primary_mut() {
+            toggle_fullscreen(window);
+        }
+    }
+}
+
+fn display_panel(
+    mut windows: ResMut<Windows>,
+    (mut settings, mut fast_forward): (ResMut<DisplaySettings>, ResMut<FastForward>),
+    mut unlimited: ResMut<UnlimitedSpeed>,
+    (mut strings, mut picture): (ResMut<Strings>, ResMut<PictureMode>),
+    mut egui_context: ResMut<EguiContext>,
+) {
+    let Some(window) = windows.get_primary_mut() else {
+        return;
+    };
+    if !settings.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut language = strings.code.clone();
+
+    egui::Window::new(strings.get("display.title"))
+        .id(egui::Id::new("Display"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let mut fullscreen = window.mode() != WindowMode::Windowed;
+            if ui
+                .checkbox(&mut fullscreen, strings.get("display.fullscreen"))
+                .changed()
+            {
+                toggle_fullscreen(window);
+            }
+
+            // decorations only matter in a window
+            let mut borderless = !window.decorations();
+            let response = ui.add_enabled(
+                !fullscreen,
+                egui::Checkbox::new(&mut borderless, strings.get("display.borderless")),
+            );
+            if response.changed() {
+                window.set_decorations(!borderless);
+            }
+
+            let mut vsync = window.present_mode() != PresentMode::AutoNoVsync;
+            if ui
+                .checkbox(&mut vsync, strings.get("display.vsync"))
+                .changed()
+            {
+                window.set_present_mode(if vsync {
+                    PresentMode::AutoVsync
+                } else {
+                    PresentMode::AutoNoVsync
+                });
+            }
+
+            ui.horizontal(|ui| {
+                let mut capped = settings.fps_cap.is_some();
+                ui.checkbox(&mut capped, strings.get("display.frame-cap"));
+
+                let mut fps = settings.fps_cap.unwrap_or(60);
+                ui.add_enabled(
+                    capped,
+                    egui::DragValue::new(&mut fps)
+                        .clamp_range(10..=480)
+                        .suffix(strings.get("display.fps")),
+                );
+
+                let fps_cap = capped.then_some(fps);
+                if settings.fps_cap != fps_cap {
+                    settings.fps_cap = fps_cap;
+                }
+            });
+            ui.label(strings.get("display.cap-ignored"));
+
+            ui.horizontal(|ui| {
+                let mut auto = unlimited.auto.is_some();
+                ui.checkbox(&mut auto, strings.get("display.auto-speed"));
+
+                let mut target = unlimited
+                    .auto
+                    .map_or(16, |target| target.as_millis() as u64);
+                ui.add_enabled(
+                    auto,
+                    egui::DragValue::new(&mut target)
+                        .clamp_range(5..=100)
+                        .suffix(strings.get("display.ms-frames")),
+                );
+
+                let target = auto.then_some(Duration::from_millis(target));
+                if unlimited.auto != target {
+                    unlimited.auto = target;
+                }
+            });
+            ui.label(strings.get("display.auto-speed-vsync"));
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("display.hold"));
+                let key = if settings.rebinding {
+                    strings.get("display.press-a-key").to_owned()
+                } else {
+                    format!("{:?}", fast_forward.key)
+                };
+                if ui.button(key).clicked() {
+                    settings.rebinding = true;
+                }
+
+                let mut factor = fast_forward.factor;
+                ui.add(
+                    egui::DragValue::new(&mut factor)
+                        .clamp_range(2.0..=64.0)
+                        .prefix(strings.get("display.run-factor"))
+                        .suffix("x"),
+                );
+                if factor != fast_forward.factor {
+                    fast_forward.factor = factor;
+                }
+            });
+
+            ui.checkbox(
+                &mut settings.pause_unfocused,
+                strings.get("display.pause-unfocused"),
+            );
+            ui.checkbox(
+                &mut settings.idle_low_power,
+                strings.get("display.low-power"),
+            );
+            ui.checkbox(
+                &mut picture.hide_cursor,
+                strings.get("display.picture-cursor"),
+            );
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("display.language"));
+                let chosen = strings
+                    .languages
+                    .iter()
+                    .find(|(code, _)| *code == language)
+                    .map_or(language.clone(), |(_, name)| name.clone());
+                egui::ComboBox::from_id_source("language")
+                    .selected_text(chosen)
+                    .show_ui(ui, |ui| {
+                        for (code, name) in &strings.languages {
+                            ui.selectable_value(&mut language, code.clone(), name);
+                        }
+                    });
+            });
+
+            let width = format!("{:.0}", window.width());
+            let height = format!("{:.0}", window.height());
+            let scale = format!("{:.2}", window.scale_factor());
+            ui.label(strings.format(
+                "display.window-size",
+                &[("width", &width), ("height", &height), ("scale", &scale)],
+            ));
+        });
+
+    if language != strings.code {
+        if let Err(error) = strings.choose(&language) {
+            warn!("couldn't switch to language {language}: {error}");
+        }
+    }
+
+    if !open {
+        settings.open = false;
+    }
+}
+
+fn pause_on_focus(
+    mut focused: EventReader<WindowFocused>,
+    mut settings: ResMut<DisplaySettings>,
+    mut ticker: ResMut<TickDuration>,
+) {
+    let Some(focused) = focused
+        .iter()
+        .filter(|event| event.id == WindowId::primary())
+        .fold(None, |_, event| Some(event.focused))
+    else {
+        return;
+    };
+
+    if !focused && settings.pause_unfocused && !ticker.0.paused() {
+        ticker.0.pause();
+        settings.paused_for_focus = true;
+    } else if focused && settings.paused_for_focus {
+        settings.paused_for_focus = false;
+        // left alone if the setting was turned off in the meantime
+        if settings.pause_unfocused {
+            ticker.0.unpause();
+        }
+    }
+}
+
+/// Lets winit sleep until input arrives while the board is paused and nothing else moves
+/// on its own, and runs continuously again as soon as something does.
+fn idle_when_paused(
+    settings: Res<DisplaySettings>,
+    (ticker, run_to): (Res<TickDuration>, Res<RunTo>),
+    (state, sub_board): (Res<State<GameState>>, Res<SubBoard>),
+    mut winit: ResMut<WinitSettings>,
+) {
+    let idle = settings.idle_low_power
+        && ticker.0.paused()
+        && run_to.0.is_none()
+        && *state.current() != GameState::Demo
+        && !sub_board.is_running();
+
+    if idle == matches!(winit.focused_mode, UpdateMode::Reactive { .. }) {
+        return;
+    }
+
+    let (focused, unfocused) = if idle {
+        (
+            UpdateMode::Reactive {
+                max_wait: IDLE_WAIT.0,
+            },
+            UpdateMode::ReactiveLowPower {
+                max_wait: IDLE_WAIT.1,
+            },
+        )
+    } else {
+        (UpdateMode::Continuous, UpdateMode::Continuous)
+    };
+    winit.focused_mode = focused;
+    winit.unfocused_mode = unfocused;
+}
+
+/// Sleeps out the rest of the frame when a cap is set, so an idle or slow running board
+/// doesn't spin a core redrawing the same picture.
+fn cap_frame_rate(
+    settings: Res<DisplaySettings>,
+    (ticker, unlimited, run_to): (Res<TickDuration>, Res<UnlimitedSpeed>, Res<RunTo>),
+    mut last_frame: Local<Option<Instant>>,
+) {
+    let flat_out = (unlimited.enabled && !ticker.0.paused()) || run_to.0.is_some();
+
+    if let (Some(fps), Some(last_frame), false) = (settings.fps_cap, *last_frame, flat_out) {
+        let frame = Duration::from_secs_f64(1.0 / fps as f64);
+        if let Some(rest) = frame.checked_sub(last_frame.elapsed()) {
+            std::thread::sleep(rest);
+        }
+    }
+
+    *last_frame = Some(Instant::now());
+}
@@ -0,0 +1,158 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_egui::egui::plot::{Legend, Line, Plot, PlotPoints};
+
+use crate::detach::PanelContext;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::rules::RuleSet;
+use crate::simulation::{diverge, Divergence};
+use crate::Board;
+
+/// Most generations a report runs for, since it runs within a single frame.
+const MAX_GENERATIONS: u64 = 5000;
+
+/// J opens a report running copies of the board under two rules, to see how soon and how
+/// far a tweak to a rule takes the board somewhere else.
+pub struct DivergencePlugin;
+
+#[derive(Resource)]
+struct DivergenceReport {
+    open: bool,
+    rule_texts: (String, String),
+    generations: u64,
+    result: Option<(Divergence, (RuleSet, RuleSet))>,
+    error: Option<String>,
+}
+
+impl Default for DivergenceReport {
+    fn default() -> Self {
+        Self {
+            open: false,
+            rule_texts: (RuleSet::CONWAY.to_string(), "B36/S23".to_owned()),
+            generations: 500,
+            result: None,
+            error: None,
+        }
+    }
+}
+
+impl Plugin for DivergencePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<DivergenceReport>()
+            .add_system(toggle_report)
+            .add_system(divergence_panel.after(toggle_report));
+    }
+}
+
+/// Opening the report starts the first rule off as the board's.
+fn toggle_report(keys: Hotkeys, rule: Res<RuleSet>, mut report: ResMut<DivergenceReport>) {
+    if keys.just_pressed(KeyCode::J) {
+        report.open = !report.open;
+        if report.open {
+            report.rule_texts.0 = rule.to_string();
+        }
+    }
+}
+
+fn divergence_panel(
+    board: Res<Board>,
+    (mut report, strings): (ResMut<DivergenceReport>, Res<Strings>),
+    mut panels: PanelContext,
+) {
+    if !report.open {
+        return;
+    }
+
+    let mut open = true;
+
+    egui::Window::new(strings.get("divergence.title"))
+        .id(egui::Id::new("divergence"))
+        .open(&mut open)
+        .show(panels.ctx(), |ui| {
+            let report = &mut *report;
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("divergence.left"));
+                ui.text_edit_singleline(&mut report.rule_texts.0);
+            });
+            ui.horizontal(|ui| {
+                ui.label(strings.get("divergence.right"));
+                ui.text_edit_singleline(&mut report.rule_texts.1);
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("divergence.generations"));
+                ui.add(
+                    egui::DragValue::new(&mut report.generations).clamp_range(1..=MAX_GENERATIONS),
+                );
+
+                if ui.button(strings.get("divergence.run")).clicked() {
+                    let rules = RuleSet::parse(&report.rule_texts.0)
+                        .and_then(|left| Ok((left, RuleSet::parse(&report.rule_texts.1)?)));
+                    match rules {
+                        Ok(rules) => {
+                            report.result =
+                                Some((diverge(&board, rules, report.generations), rules));
+                            report.error = None;
+                        }
+                        Err(error) => report.error = Some(error),
+                    }
+                }
+            });
+            ui.label(strings.get("divergence.help"));
+
+            if let Some(error) = &report.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            let Some((divergence, (left, right))) = &report.result else {
+                return;
+            };
+
+            ui.separator();
+
+            match divergence.first_difference {
+                Some(generation) => ui.label(strings.format(
+                    "divergence.first-difference",
+                    &[("generation", &generation)],
+                )),
+                None => ui.label(strings.get("divergence.never-differ")),
+            };
+
+            let generations = divergence.populations.len() - 1;
+            let (left_population, right_population) = divergence.populations[generations];
+            ui.label(strings.format(
+                "divergence.final",
+                &[
+                    ("generations", &generations),
+                    ("left", &left_population),
+                    ("right", &right_population),
+                    ("differing", &divergence.differing),
+                ],
+            ));
+
+            let curve = |population: fn(&(u32, u32)) -> u32| {
+                divergence
+                    .populations
+                    .iter()
+                    .enumerate()
+                    .map(|(generation, counts)| [generation as f64, population(counts) as f64])
+                    .collect::<PlotPoints>()
+            };
+
+            Plot::new("divergence populations")
+                .height(160.0)
+                .include_y(0.0)
+                .allow_scroll(false)
+                .legend(Legend::default())
+                .show(ui, |plot| {
+                    plot.line(Line::new(curve(|counts| counts.0)).name(left.to_string()));
+                    plot.line(Line::new(curve(|counts| counts.1)).name(right.to_string()));
+                });
+        });
+
+    if !open {
+        report.open = false;
+    }
+}
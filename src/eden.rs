@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContext};
+use futures_lite::future;
+
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::predecessor::{find_predecessor, Search};
+use crate::rules::{Automaton, RuleSet};
+use crate::undo::UndoHistory;
+use crate::{Board, GameState};
+
+/// N opens a search for a board that steps into the current one, looking no further than a
+/// cell past the live cells. Boards with no predecessor at all are Gardens of Eden. The
+/// search is exhaustive, so it's only practical for small patterns, and runs off the main
+/// thread.
+pub struct EdenPlugin;
+
+#[derive(Resource)]
+struct EdenSearch {
+    open: bool,
+    /// Most cell values a search tries before giving up, in millions.
+    budget: u64,
+    task: Option<Task<Search>>,
+    result: Option<Search>,
+}
+
+impl Default for EdenSearch {
+    fn default() -> Self {
+        Self {
+            open: false,
+            budget: 20,
+            task: None,
+            result: None,
+        }
+    }
+}
+
+impl Plugin for EdenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EdenSearch>()
+            .add_system(toggle_search)
+            .add_system(finish_search)
+            .add_system(eden_panel.after(toggle_search).after(finish_search));
+    }
+}
+
+fn toggle_search(keys: Hotkeys, mut search: ResMut<EdenSearch>) {
+    if keys.just_pressed(KeyCode::N) {
+        search.open = !search.open;
+    }
+}
+
+fn finish_search(mut search: ResMut<EdenSearch>) {
+    let Some(task) = &mut search.task else {
+        return;
+    };
+    let Some(result) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    search.task = None;
+    search.result = Some(result);
+}
+
+fn eden_panel(
+    state: Res<State<GameState>>,
+    (rule, automaton): (Res<RuleSet>, Res<Automaton>),
+    (mut board, mut history): (ResMut<Board>, ResMut<UndoHistory>),
+    (mut search, strings): (ResMut<EdenSearch>, Res<Strings>),
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !search.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut place = false;
+
+    egui::Window::new(strings.get("eden.title"))
+        .id(egui::Id::new("predecessor search"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let search = &mut *search;
+
+            ui.label(strings.get("eden.help"));
+
+            // the solver only knows birth/survival rules
+            if *automaton != Automaton::Life {
+                ui.label(strings.get("eden.life-only"));
+                return;
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("eden.budget"));
+                ui.add(
+                    egui::DragValue::new(&mut search.budget)
+                        .clamp_range(1..=1000)
+                        .suffix(strings.get("eden.millions")),
+                );
+
+                let idle = search.task.is_none();
+                if ui
+                    .add_enabled(idle, egui::Button::new(strings.get("eden.search")))
+                    .clicked()
+                {
+                    let (target, rule) = (board.0.clone(), *rule);
+                    let budget = search.budget * 1_000_000;
+                    search.task = Some(
+                        AsyncComputeTaskPool::get()
+                            .spawn(async move { find_predecessor(&target, &rule, budget) }),
+                    );
+                    search.result = None;
+                }
+            });
+
+            if search.task.is_some() {
+                ui.label(strings.get("eden.searching"));
+            }
+
+            match &search.result {
+                Some(Search::Found(predecessor)) => {
+                    ui.label(strings.format("eden.found", &[("cells", &predecessor.population())]));
+                    // scenarios don't let the whole board be swapped out
+                    place = ui
+                        .add_enabled(
+                            *state.current() == GameState::Sandbox,
+                            egui::Button::new(strings.get("eden.place")),
+                        )
+                        .clicked();
+                }
+                Some(Search::NoneWithinBounds) => {
+                    ui.label(strings.get("eden.none"));
+                }
+                Some(Search::GaveUp) => {
+                    ui.label(strings.get("eden.gave-up"));
+                }
+                None => {}
+            }
+        });
+
+    if place {
+        if let Some(Search::Found(predecessor)) = search.result.take() {
+            if (predecessor.width(), predecessor.height()) == (board.width(), board.height()) {
+                history.record(&board);
+                board.0 = predecessor;
+            }
+        }
+    }
+
+    if !open {
+        search.open = false;
+    }
+}
@@ -0,0 +1,102 @@
+use bevy::prelude::*;
+
+use crate::hotkeys::Hotkeys;
+use crate::TickDuration;
+
+/// Energy a team's pool holds when full.
+const MAX_ENERGY: f32 = 100.0;
+/// Energy each pool regains per second while the simulation is running.
+const REGEN_PER_SECOND: f32 = 5.0;
+/// Cost of placing a spawner, per cell of the pattern it emits.
+pub const SPAWNER_COST_PER_CELL: f32 = 4.0;
+pub const ANT_COST: f32 = 20.0;
+
+/// Competitive mode's per-team energy pools. Placing cells spends the placing team's
+/// energy, so everything that adds cells on a team's behalf goes through `try_spend`.
+#[derive(Resource)]
+pub struct Energy {
+    pub enabled: bool,
+    pools: [f32; 2], // indexed by team id - 2
+}
+
+impl Default for Energy {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            pools: [MAX_ENERGY; 2],
+        }
+    }
+}
+
+impl Energy {
+    /// Spends `cost` from `team`'s pool if it can afford it. Always succeeds outside
+    /// competitive mode, and for cells that don't belong to a team.
+    pub fn try_spend(&mut self, team: u8, cost: f32) -> bool {
+        if !self.enabled {
+            return true;
+        }
+
+        let Some(pool) = team
+            .checked_sub(2)
+            .and_then(|i| self.pools.get_mut(i as usize))
+        else {
+            return true;
+        };
+
+        if *pool < cost {
+            return false;
+        }
+
+        *pool -= cost;
+        true
+    }
+
+    /// Whether `try_spend` would succeed, for placements that charge several teams at once.
+    pub fn can_afford(&self, team: u8, cost: f32) -> bool {
+        let pool = team.checked_sub(2).and_then(|i| self.pools.get(i as usize));
+
+        !self.enabled || pool.is_none_or(|&pool| pool >= cost)
+    }
+
+    /// Tops up `team`'s pool by `amount`, up to a full pool.
+    pub fn grant(&mut self, team: u8, amount: f32) {
+        if let Some(pool) = self.pools.get_mut(team as usize - 2) {
+            *pool = (*pool + amount).min(MAX_ENERGY);
+        }
+    }
+
+    /// `team`'s energy as a fraction of a full pool.
+    pub fn fraction(&self, team: u8) -> f32 {
+        self.pools[team as usize - 2] / MAX_ENERGY
+    }
+}
+
+pub struct EnergyPlugin;
+
+impl Plugin for EnergyPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Energy>()
+            .add_system(toggle_energy)
+            .add_system(regenerate);
+    }
+}
+
+/// E switches competitive mode on and off, starting both teams on a full pool.
+fn toggle_energy(keys: Hotkeys, mut energy: ResMut<Energy>) {
+    if keys.just_pressed(KeyCode::E) {
+        *energy = Energy {
+            enabled: !energy.enabled,
+            ..default()
+        };
+    }
+}
+
+fn regenerate(time: Res<Time>, ticker: Res<TickDuration>, mut energy: ResMut<Energy>) {
+    if !energy.enabled || ticker.0.paused() {
+        return;
+    }
+
+    for pool in energy.pools.iter_mut() {
+        *pool = (*pool + REGEN_PER_SECOND * time.delta_seconds()).min(MAX_ENERGY);
+    }
+}
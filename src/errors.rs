@@ -0,0 +1,119 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+/// How long a toast stays up after the last time its error was reported.
+const TOAST_TIME: Duration = Duration::from_secs(6);
+/// Most toasts shown at once. Older ones are dropped to make room.
+const MAX_TOASTS: usize = 4;
+
+/// Errors a system recovered from but the player should hear about. Each one is logged
+/// and shown as a toast in the corner for a few seconds, with repeats of the same error
+/// folded into one toast. Any system can send `AppError`s, or read them to react.
+pub struct ErrorsPlugin;
+
+/// Something went wrong that the app carried on past.
+#[derive(Clone, Debug)]
+pub struct AppError {
+    /// The part of the app it happened in, like `"tiles"`.
+    pub source: &'static str,
+    pub message: String,
+}
+
+impl AppError {
+    pub fn new(source: &'static str, message: impl Into<String>) -> Self {
+        Self {
+            source,
+            message: message.into(),
+        }
+    }
+}
+
+struct Toast {
+    error: AppError,
+    /// How many times it's been reported while shown.
+    count: u32,
+    /// Seconds since startup it was last reported.
+    reported: f64,
+}
+
+#[derive(Resource, Default)]
+struct Toasts(Vec<Toast>);
+
+impl Plugin for ErrorsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AppError>()
+            .init_resource::<Toasts>()
+            .add_system_to_stage(CoreStage::PostUpdate, collect_errors)
+            .add_system(show_toasts);
+    }
+}
+
+/// Logs every error sent this frame and turns it into a toast. Runs after the frame's
+/// systems so errors they send show up the same frame.
+fn collect_errors(time: Res<Time>, mut errors: EventReader<AppError>, mut toasts: ResMut<Toasts>) {
+    let now = time.elapsed_seconds_f64();
+
+    for error in errors.iter() {
+        error!("{}: {}", error.source, error.message);
+
+        let same = toasts.0.iter_mut().find(|toast| {
+            toast.error.source == error.source && toast.error.message == error.message
+        });
+        match same {
+            Some(toast) => {
+                toast.count += 1;
+                toast.reported = now;
+            }
+            None => {
+                if toasts.0.len() == MAX_TOASTS {
+                    toasts.0.remove(0);
+                }
+                toasts.0.push(Toast {
+                    error: error.clone(),
+                    count: 1,
+                    reported: now,
+                });
+            }
+        }
+    }
+}
+
+fn show_toasts(time: Res<Time>, mut toasts: ResMut<Toasts>, mut egui_context: ResMut<EguiContext>) {
+    let now = time.elapsed_seconds_f64();
+    toasts
+        .0
+        .retain(|toast| now - toast.reported < TOAST_TIME.as_secs_f64());
+    if toasts.0.is_empty() {
+        return;
+    }
+
+    let mut dismissed = None;
+
+    egui::Area::new("error toasts")
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            for (index, toast) in toasts.0.iter().enumerate() {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.horizontal(|ui| {
+                        let text = match toast.count {
+                            1 => format!("{}: {}", toast.error.source, toast.error.message),
+                            count => format!(
+                                "{}: {} (x{count})",
+                                toast.error.source, toast.error.message
+                            ),
+                        };
+                        ui.colored_label(egui::Color32::LIGHT_RED, text);
+                        if ui.small_button("x").clicked() {
+                            dismissed = Some(index);
+                        }
+                    });
+                });
+            }
+        });
+
+    if let Some(index) = dismissed {
+        toasts.0.remove(index);
+    }
+}
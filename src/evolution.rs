@@ -0,0 +1,337 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContext};
+use futures_lite::future;
+
+use crate::clipboard::Pasting;
+use crate::evolve::{Candidate, Evolution, EvolveConfig, Objective};
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::rle::{self, Clip};
+use crate::rules::{Automaton, RuleSet};
+use crate::tools::Tool;
+
+/// Where evolved patterns are written, next to the working directory like `saves`. They're
+/// plain RLE files, so they can be dropped on the window to paste them.
+const LIBRARY_DIR: &str = "patterns";
+/// How many of the fittest candidates a finished run writes to the library.
+const SAVED_CANDIDATES: usize = 3;
+
+/// F8 opens an experimental search that evolves small starting patterns toward an
+/// objective under the current rule, scoring each candidate on a headless board off the
+/// main thread, and writes the best ones to `patterns/` when the run is over.
+pub struct EvolutionPlugin;
+
+#[derive(Resource)]
+struct EvolutionSearch {
+    open: bool,
+    config: EvolveConfig,
+    /// Generations bred before the run stops.
+    generations: u32,
+    /// The latest finished generation, and the best fitness of every one before it.
+    latest: Option<Evolution>,
+    progress: Vec<f64>,
+    task: Option<Task<Evolution>>,
+    stopping: bool,
+    message: Option<String>,
+}
+
+impl Default for EvolutionSearch {
+    fn default() -> Self {
+        Self {
+            open: false,
+            config: EvolveConfig::default(),
+            generations: 40,
+            latest: None,
+            progress: Vec::new(),
+            task: None,
+            stopping: false,
+            message: None,
+        }
+    }
+}
+
+impl EvolutionSearch {
+    /// Writes the fittest few distinct candidates to the library, returning what to tell
+    /// the player.
+    fn save_best(&self, strings: &Strings) -> String {
+        let Some(evolution) = &self.latest else {
+            return String::new();
+        };
+
+        let mut saved: Vec<&Candidate> = Vec::new();
+        for candidate in evolution.candidates() {
+            if saved.len() == SAVED_CANDIDATES {
+                break;
+            }
+            if candidate.fitness > 0.0 && saved.iter().all(|s| s.cells != candidate.cells) {
+                saved.push(candidate);
+            }
+        }
+
+        let written = fs::create_dir_all(LIBRARY_DIR).and_then(|()| {
+            saved
+                .iter()
+                .enumerate()
+                .map(|(rank, candidate)| write_candidate(evolution, candidate, rank))
+                .collect::<Result<Vec<PathBuf>, _>>()
+        });
+        match written {
+            Ok(paths) => {
+                for path in &paths {
+                    info!(path = %path.display(), "saved an evolved pattern");
+                }
+                strings.format(
+                    "evolve.saved",
+                    &[("count", &paths.len()), ("dir", &LIBRARY_DIR)],
+                )
+            }
+            Err(error) => strings.format("evolve.save-failed", &[("error", &error)]),
+        }
+    }
+}
+
+fn write_candidate(
+    evolution: &Evolution,
+    candidate: &Candidate,
+    rank: usize,
+) -> std::io::Result<PathBuf> {
+    let config = &evolution.config;
+    let objective = config.objective.name().replace(' ', "-");
+    let name = format!("evolved-{objective}-{:.0}-{}", candidate.fitness, rank + 1);
+    let path = Path::new(LIBRARY_DIR).join(format!("{name}.rle"));
+
+    let size = config.seed_size;
+    let board = candidate.board(size);
+    let text = format!(
+        "#N {name}\n#C Evolved for {} at {} generations under {}, bred for {} \
+         generations, scoring {:.0}.\n{}",
+        config.objective.name(),
+        config.objective.generations(),
+        config.rule,
+        evolution.generation + 1,
+        candidate.fitness,
+        rle::encode(&board, (0, 0, size, size), &config.rule)
+    );
+    fs::write(&path, text)?;
+    Ok(path)
+}
+
+/// The candidate as a clip, rows from the top like pattern files.
+fn clip(candidate: &Candidate, size: u32) -> Result<Clip, String> {
+    let board = candidate.board(size);
+    let points: Vec<(i64, i64, u8)> = (0..size)
+        .flat_map(|y| (0..size).map(move |x| (x, y)))
+        .filter(|&(x, y)| board.get(x, y) != 0)
+        .map(|(x, y)| (x as i64, (size - 1 - y) as i64, 2))
+        .collect();
+    Clip::from_points(&points)
+}
+
+impl Plugin for EvolutionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EvolutionSearch>()
+            .add_system(toggle_search)
+            .add_system(breed)
+            .add_system(evolution_panel.after(toggle_search).after(breed));
+    }
+}
+
+fn toggle_search(keys: Hotkeys, mut search: ResMut<EvolutionSearch>) {
+    if keys.just_pressed(KeyCode::F8) {
+        search.open = !search.open;
+    }
+}
+
+/// Collects each generation as it's bred, and starts on the next until the run is over.
+fn breed(mut search: ResMut<EvolutionSearch>, strings: Res<Strings>) {
+    let Some(task) = &mut search.task else {
+        return;
+    };
+    let Some(evolution) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    search.task = None;
+    search.progress.push(evolution.best().fitness);
+
+    let finished = search.stopping || evolution.generation + 1 >= search.generations;
+    if !finished {
+        let mut next = evolution.clone();
+        search.task = Some(AsyncComputeTaskPool::get().spawn(async move {
+            next.step();
+            next
+        }));
+    }
+    search.latest = Some(evolution);
+
+    if finished {
+        search.stopping = false;
+        search.message = Some(search.save_best(&strings));
+    }
+}
+
+/// The objective picker, keeping each objective's generation count while switching.
+fn objective_choice(ui: &mut egui::Ui, objective: &mut Objective, strings: &Strings) {
+    let generations = objective.generations();
+    ui.horizontal(|ui| {
+        for choice in [
+            Objective::Population {
+                generation: generations,
+            },
+            Objective::Lifespan { limit: generations },
+            Objective::FarEdge { limit: generations },
+        ] {
+            let name = strings.named("evolve", choice.name());
+            if ui
+                .selectable_label(objective.name() == choice.name(), name)
+                .clicked()
+            {
+                *objective = choice;
+            }
+        }
+    });
+
+    let mut generations = generations;
+    ui.horizontal(|ui| {
+        ui.label(strings.get("evolve.objective-generations"));
+        ui.add(egui::DragValue::new(&mut generations).clamp_range(1..=5000));
+    });
+    *objective = match *objective {
+        Objective::Population { .. } => Objective::Population {
+            generation: generations,
+        },
+        Objective::Lifespan { .. } => Objective::Lifespan { limit: generations },
+        Objective::FarEdge { .. } => Objective::FarEdge { limit: generations },
+    };
+}
+
+fn evolution_panel(
+    (rule, automaton): (Res<RuleSet>, Res<Automaton>),
+    (mut tool, mut pasting): (ResMut<Tool>, ResMut<Pasting>),
+    (mut search, strings): (ResMut<EvolutionSearch>, Res<Strings>),
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !search.open {
+        return;
+    }
+
+    let mut open = true;
+    let (mut paste, mut save) = (None, false);
+
+    egui::Window::new(strings.get("evolve.title"))
+        .id(egui::Id::new("evolution"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let search = &mut *search;
+
+            ui.label(strings.get("evolve.help"));
+
+            // candidates are scored by the headless engine, which only steps birth/survival
+            if *automaton != Automaton::Life {
+                ui.label(strings.get("evolve.life-only"));
+                return;
+            }
+
+            let running = search.task.is_some();
+            ui.add_enabled_ui(!running, |ui| {
+                objective_choice(ui, &mut search.config.objective, &strings);
+
+                let config = &mut search.config;
+                ui.horizontal(|ui| {
+                    ui.label(strings.get("evolve.seed-size"));
+                    ui.add(egui::DragValue::new(&mut config.seed_size).clamp_range(3..=24));
+                    ui.label(strings.get("evolve.population"));
+                    ui.add(egui::DragValue::new(&mut config.population).clamp_range(4..=256));
+                });
+                ui.horizontal(|ui| {
+                    ui.label(strings.get("evolve.generations"));
+                    ui.add(egui::DragValue::new(&mut search.generations).clamp_range(1..=1000));
+                    ui.add(
+                        egui::Slider::new(&mut config.mutation, 0.0..=0.2)
+                            .text(strings.get("evolve.mutation")),
+                    );
+                });
+            });
+
+            ui.horizontal(|ui| {
+                if !running && ui.button(strings.get("evolve.start")).clicked() {
+                    let config = EvolveConfig {
+                        rule: *rule,
+                        ..search.config.clone()
+                    };
+                    let seed = SystemTime::now()
+                        .duration_since(UNIX_EPOCH)
+                        .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+                    search.task = Some(
+                        AsyncComputeTaskPool::get()
+                            .spawn(async move { Evolution::new(config, seed) }),
+                    );
+                    search.latest = None;
+                    search.progress.clear();
+                    search.message = None;
+                }
+                if running
+                    && ui
+                        .add_enabled(
+                            !search.stopping,
+                            egui::Button::new(strings.get("evolve.stop")),
+                        )
+                        .clicked()
+                {
+                    search.stopping = true;
+                }
+            });
+
+            if running {
+                ui.label(strings.format(
+                    "evolve.running",
+                    &[("generation", &(search.progress.len() + 1))],
+                ));
+            }
+
+            if let Some(evolution) = &search.latest {
+                let best = evolution.best();
+                ui.label(strings.format(
+                    "evolve.best",
+                    &[
+                        ("generation", &evolution.generation),
+                        ("fitness", &format!("{:.0}", best.fitness)),
+                        ("cells", &best.cells.iter().filter(|&&live| live).count()),
+                    ],
+                ));
+                if search.progress.len() > 1 {
+                    let first = search.progress[0];
+                    ui.label(
+                        strings.format("evolve.improved", &[("from", &format!("{first:.0}"))]),
+                    );
+                }
+
+                ui.horizontal(|ui| {
+                    if ui.button(strings.get("evolve.paste")).clicked() {
+                        paste = Some(clip(best, evolution.config.seed_size));
+                    }
+                    save = !running && ui.button(strings.get("evolve.save")).clicked();
+                });
+            }
+
+            if let Some(message) = &search.message {
+                ui.label(message);
+            }
+        });
+
+    if let Some(clip) = paste {
+        pasting.start(clip, &mut tool, &strings);
+    }
+    if save {
+        search.message = Some(search.save_best(&strings));
+    }
+
+    if !open {
+        search.open = false;
+    }
+}
@@ -0,0 +1,267 @@
+//! Evolving small starting patterns toward an objective with a simple genetic algorithm,
+//! scoring each candidate by running it headlessly.
+
+use crate::bitboard::{Bitboard, Boundary};
+use crate::rng::Rng;
+use crate::rules::RuleSet;
+use crate::simulation::Simulation;
+
+/// What a pattern is bred for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Objective {
+    /// As many live cells as possible at `generation`.
+    Population { generation: u64 },
+    /// As many generations as possible before dying out or settling into still lifes and
+    /// blinkers, up to `limit`. Longer oscillators are taken as still going.
+    Lifespan { limit: u64 },
+    /// Starting against the left edge, a live cell as far right as possible within
+    /// `limit` generations, and crossing the whole arena sooner beats later.
+    FarEdge { limit: u64 },
+}
+
+impl Objective {
+    pub fn name(self) -> &'static str {
+        match self {
+            Objective::Population { .. } => "population",
+            Objective::Lifespan { .. } => "lifespan",
+            Objective::FarEdge { .. } => "far edge",
+        }
+    }
+
+    /// The number of generations the objective runs for.
+    pub fn generations(self) -> u64 {
+        match self {
+            Objective::Population { generation } => generation,
+            Objective::Lifespan { limit } | Objective::FarEdge { limit } => limit,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct EvolveConfig {
+    pub objective: Objective,
+    pub rule: RuleSet,
+    /// Side of the square box candidates are drawn in.
+    pub seed_size: u32,
+    /// The bounded board candidates are run on, big enough to give them room to grow.
+    pub arena: (u32, u32),
+    /// Candidates per generation.
+    pub population: usize,
+    /// Chance of each cell of a child flipping.
+    pub mutation: f64,
+    /// How many of the best candidates pass into the next generation unchanged.
+    pub elite: usize,
+}
+
+impl Default for EvolveConfig {
+    fn default() -> Self {
+        Self {
+            objective: Objective::Lifespan { limit: 1000 },
+            rule: RuleSet::CONWAY,
+            seed_size: 8,
+            arena: (64, 64),
+            population: 32,
+            mutation: 0.03,
+            elite: 2,
+        }
+    }
+}
+
+/// Share of cells alive in the first, random, generation of candidates.
+const INITIAL_DENSITY: f64 = 0.35;
+/// Candidates drawn for each parent chosen, the fittest of them winning.
+const TOURNAMENT_SIZE: usize = 3;
+
+/// A starting pattern and how well it did.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Candidate {
+    /// The seed box's cells, indexed like `Bitboard::get`.
+    pub cells: Vec<bool>,
+    pub fitness: f64,
+}
+
+impl Candidate {
+    /// The candidate alone on a board the size of its seed box, as team 2.
+    pub fn board(&self, seed_size: u32) -> Bitboard {
+        let mut board = Bitboard::new(seed_size, seed_size);
+        for (i, _) in self.cells.iter().enumerate().filter(|(_, &live)| live) {
+            board.set(i as u32 % seed_size, i as u32 / seed_size, 2);
+        }
+        board
+    }
+}
+
+/// A population of candidates, bred a generation at a time.
+#[derive(Clone, Debug)]
+pub struct Evolution {
+    pub config: EvolveConfig,
+    /// This generation's candidates, fittest first.
+    candidates: Vec<Candidate>,
+    pub generation: u32,
+    rng: Rng,
+}
+
+impl Evolution {
+    /// A first generation of random candidates, already scored.
+    pub fn new(config: EvolveConfig, seed: u64) -> Self {
+        let mut rng = Rng::new(seed);
+        let cells = (config.seed_size * config.seed_size) as usize;
+        let genomes = (0..config.population.max(1))
+            .map(|_| {
+                (0..cells)
+                    .map(|_| chance(&mut rng) < INITIAL_DENSITY)
+                    .collect()
+            })
+            .collect();
+
+        let mut evolution = Self {
+            config,
+            candidates: Vec::new(),
+            generation: 0,
+            rng,
+        };
+        evolution.score(genomes);
+        evolution
+    }
+
+    /// This generation's candidates, fittest first.
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    pub fn best(&self) -> &Candidate {
+        &self.candidates[0]
+    }
+
+    /// Breeds the next generation: the elite carry over, and the rest are children of
+    /// parents picked by tournament, crossed at a random cell and then mutated.
+    pub fn step(&mut self) {
+        let size = self.candidates.len();
+        let elite = self.config.elite.min(size);
+        let mut genomes: Vec<Vec<bool>> = self.candidates[..elite]
+            .iter()
+            .map(|candidate| candidate.cells.clone())
+            .collect();
+
+        while genomes.len() < size {
+            let mother = self.pick_parent();
+            let father = self.pick_parent();
+            let cut = (self.rng.next_u64() % mother.len().max(1) as u64) as usize;
+
+            let mut child: Vec<bool> = mother[..cut]
+                .iter()
+                .chain(&father[cut..])
+                .copied()
+                .collect();
+            for cell in &mut child {
+                if chance(&mut self.rng) < self.config.mutation {
+                    *cell = !*cell;
+                }
+            }
+            genomes.push(child);
+        }
+
+        self.score(genomes);
+        self.generation += 1;
+    }
+
+    fn pick_parent(&mut self) -> Vec<bool> {
+        // candidates are sorted, so the lowest index drawn is the fittest
+        let size = self.candidates.len() as u64;
+        let winner = (0..TOURNAMENT_SIZE)
+            .map(|_| (self.rng.next_u64() % size) as usize)
+            .min()
+            .unwrap_or(0);
+        self.candidates[winner].cells.clone()
+    }
+
+    fn score(&mut self, genomes: Vec<Vec<bool>>) {
+        self.candidates = genomes
+            .into_iter()
+            .map(|cells| Candidate {
+                fitness: fitness(&self.config, &cells),
+                cells,
+            })
+            .collect();
+        self.candidates
+            .sort_by(|a, b| b.fitness.total_cmp(&a.fitness));
+    }
+}
+
+/// A uniform draw from [0, 1).
+fn chance(rng: &mut Rng) -> f64 {
+    (rng.next_u64() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// How well `cells` does at the objective, run alone in the arena. Higher is better.
+pub fn fitness(config: &EvolveConfig, cells: &[bool]) -> f64 {
+    let (width, height) = config.arena;
+    let size = config.seed_size.min(width).min(height);
+    let mut board = Bitboard::new(width, height);
+    board.set_boundary(Boundary::Bounded);
+
+    // against the left edge to cross the arena, in the middle otherwise
+    let left = match config.objective {
+        Objective::FarEdge { .. } => 0,
+        _ => (width - size) / 2,
+    };
+    let bottom = (height - size) / 2;
+    for y in 0..size {
+        for x in 0..size {
+            if cells[(y * config.seed_size + x) as usize] {
+                board.set(left + x, bottom + y, 2);
+            }
+        }
+    }
+    if board.population() == 0 {
+        return 0.0;
+    }
+
+    let mut sim = Simulation::new(board, config.rule);
+    match config.objective {
+        Objective::Population { generation } => {
+            sim.step_n(generation);
+            sim.board.population() as f64
+        }
+        Objective::Lifespan { limit } => {
+            // the two boards before this one, to spot still lifes and blinkers
+            let mut earlier: [Option<Bitboard>; 2] = [None, None];
+            while sim.generation < limit {
+                earlier = [Some(sim.board.clone()), earlier[0].take()];
+                sim.step();
+
+                let settled = earlier
+                    .iter()
+                    .flatten()
+                    .any(|before| before.same_cells(&sim.board));
+                if sim.board.population() == 0 || settled {
+                    break;
+                }
+            }
+            sim.generation as f64
+        }
+        Objective::FarEdge { limit } => {
+            let mut furthest = 0;
+            while sim.generation < limit {
+                sim.step();
+                if let Some(column) = rightmost_column(&sim.board) {
+                    furthest = furthest.max(column);
+                }
+                if furthest == width - 1 {
+                    // crossing sooner scores higher than any pattern that doesn't cross
+                    return width as f64 + (limit - sim.generation) as f64;
+                }
+                if sim.board.population() == 0 {
+                    break;
+                }
+            }
+            furthest as f64
+        }
+    }
+}
+
+fn rightmost_column(board: &Bitboard) -> Option<u32> {
+    (0..board.width())
+        .rev()
+        .find(|&x| (0..board.height()).any(|y| board.get(x, y) != 0))
+}
@@ -0,0 +1,68 @@
+use crate::bitboard::Bitboard;
+
+/// Data kept alongside every cell of a board and carried through its steps, like energy,
+/// how long a team has held a cell, or terrain, so extensions can attach their own
+/// without touching the step loop.
+pub trait CellExtra: Clone + Default {
+    /// Whether the payload never does anything, so stepping can skip it altogether.
+    const INERT: bool = false;
+
+    /// Whether a cell of `team` may be born here. Births it refuses are undone right
+    /// after the step, before `update` sees them.
+    fn allows_birth(&self, _team: u8) -> bool {
+        true
+    }
+
+    /// Called for every open cell after each step with the team it had and has now,
+    /// 0 for dead.
+    fn update(&mut self, _before: u8, _after: u8) {}
+}
+
+impl CellExtra for () {
+    const INERT: bool = true;
+}
+
+/// One payload per cell of a board, indexed like `Bitboard::get`.
+#[derive(Clone, Debug)]
+pub struct ExtraLayer<E> {
+    width: u32,
+    cells: Vec<E>,
+}
+
+impl<E: CellExtra> ExtraLayer<E> {
+    pub fn new(width: u32, height: u32) -> Self {
+        Self {
+            width,
+            cells: vec![E::default(); width as usize * height as usize],
+        }
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> &E {
+        &self.cells[(y * self.width + x) as usize]
+    }
+
+    pub fn get_mut(&mut self, x: u32, y: u32) -> &mut E {
+        &mut self.cells[(y * self.width + x) as usize]
+    }
+
+    /// Runs the hooks over the step that took `before` to `board`, undoing the births the
+    /// payloads refuse.
+    pub fn after_step(&mut self, before: &Bitboard, board: &mut Bitboard) {
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                if board.is_wall(x, y) {
+                    continue;
+                }
+
+                let (old, mut new) = (before.get(x, y), board.get(x, y));
+                let extra = self.get_mut(x, y);
+                if old == 0 && new != 0 && !extra.allows_birth(new) {
+                    board.set(x, y, 0);
+                    new = 0;
+                }
+
+                extra.update(old, new);
+            }
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+
+use crate::bitboard::Bitboard;
+use crate::hotkeys::Hotkeys;
+use crate::{sync_tiles, update_map, Board};
+
+/// How far, in cells, a team sees from each of its live cells.
+const SIGHT_RADIUS: u32 = 6;
+
+/// Fog of war for team games: only cells near the viewing team's own cells are drawn,
+/// and the rest of the board is covered.
+#[derive(Resource)]
+pub struct Fog {
+    /// Team id whose view is drawn, or `None` with the fog off.
+    pub viewer: Option<u8>,
+    pub radius: u32,
+    seen: Vec<bool>,
+    width: u32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            viewer: None,
+            radius: SIGHT_RADIUS,
+            seen: Vec::new(),
+            width: 0,
+        }
+    }
+}
+
+impl Fog {
+    /// Whether the cell at (x, y) is drawn as it is rather than covered. Cells the view
+    /// hasn't been worked out for yet stay covered.
+    pub fn is_visible(&self, x: u32, y: u32) -> bool {
+        self.viewer.is_none()
+            || self
+                .seen
+                .get((y * self.width + x) as usize)
+                .copied()
+                .unwrap_or(false)
+    }
+
+    /// Marks every cell within `radius` (in both axes) of a `viewer` cell as seen, by
+    /// spreading the viewer's cells along rows and then along columns.
+    fn compute(&mut self, board: &Bitboard, viewer: u8) {
+        let (width, height) = (board.width() as usize, board.height() as usize);
+        let radius = self.radius as usize;
+
+        let mut rows = vec![false; width * height];
+        for y in 0..height {
+            // distance from the last viewer cell seen going left, then going right
+            let mut last = None;
+            for x in 0..width {
+                if board.get(x as u32, y as u32) == viewer {
+                    last = Some(x);
+                }
+                rows[y * width + x] = last.is_some_and(|last| x - last <= radius);
+            }
+
+            let mut last = None;
+            for x in (0..width).rev() {
+                if board.get(x as u32, y as u32) == viewer {
+                    last = Some(x);
+                }
+                rows[y * width + x] |= last.is_some_and(|last| last - x <= radius);
+            }
+        }
+
+        self.seen.clear();
+        self.seen.resize(width * height, false);
+        self.width = width as u32;
+
+        for x in 0..width {
+            let mut last = None;
+            for y in 0..height {
+                if rows[y * width + x] {
+                    last = Some(y);
+                }
+                self.seen[y * width + x] = last.is_some_and(|last| y - last <= radius);
+            }
+
+            let mut last = None;
+            for y in (0..height).rev() {
+                if rows[y * width + x] {
+                    last = Some(y);
+                }
+                self.seen[y * width + x] |= last.is_some_and(|last| last - y <= radius);
+            }
+        }
+    }
+}
+
+pub struct FogPlugin;
+
+impl Plugin for FogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Fog>()
+            .add_system(switch_viewer)
+            .add_system(update_fog.after(update_map).before(sync_tiles));
+    }
+}
+
+/// F cycles between no fog, team 1's view and team 2's view.
+fn switch_viewer(keys: Hotkeys, mut fog: ResMut<Fog>) {
+    if keys.just_pressed(KeyCode::F) {
+        fog.viewer = match fog.viewer {
+            None => Some(2),
+            Some(2) => Some(3),
+            Some(_) => None,
+        };
+    }
+}
+
+fn update_fog(board: Res<Board>, mut fog: ResMut<Fog>) {
+    let Some(viewer) = fog.viewer else {
+        return;
+    };
+
+    if board.is_changed() || fog.is_changed() {
+        fog.compute(&board, viewer);
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::camera::{view_size, MainCamera, TilemapGeometry, MAX_ZOOM, MIN_ZOOM};
+use crate::hotkeys::Hotkeys;
+use crate::Board;
+
+/// How quickly the camera closes in on what it's following, per second.
+const FOLLOW_RATE: f32 = 3.0;
+/// How much room is left around the live cells when the camera frames them.
+const FRAME_MARGIN: f32 = 1.5;
+/// Fewest tiles across the camera zooms in to, so a lone glider isn't blown up to fill
+/// the window.
+const MIN_FRAMED_TILES: f32 = 24.0;
+
+/// G cycles the camera between following the live cells' bounding box, panning and
+/// zooming to keep all of them framed, following just their center of mass, and staying
+/// put. Middle-dragging hands the camera back, and so does scrolling while it frames the
+/// cells. On a toroidal board, cells wrapping around an edge stretch the box across it.
+pub struct FollowPlugin;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+enum Follow {
+    #[default]
+    Off,
+    Bounds,
+    Center,
+}
+
+/// Where the camera is headed, in tile coordinates, measured whenever the board changes.
+#[derive(Resource, Default)]
+pub struct FollowTarget {
+    mode: Follow,
+    /// The live cells' center and the size of the box around them, if there are any.
+    target: Option<(Vec2, Vec2)>,
+}
+
+impl Plugin for FollowPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FollowTarget>()
+            .add_system(toggle_follow)
+            .add_system(measure_target.after(toggle_follow))
+            .add_system(follow_camera.after(measure_target));
+    }
+}
+
+fn toggle_follow(
+    keys: Hotkeys,
+    mouse: Res<Input<MouseButton>>,
+    mut scroll: EventReader<MouseWheel>,
+    mut follow: ResMut<FollowTarget>,
+) {
+    let mode = if keys.just_pressed(KeyCode::G) {
+        match follow.mode {
+            Follow::Off => Follow::Bounds,
+            Follow::Bounds => Follow::Center,
+            Follow::Center => Follow::Off,
+        }
+    } else if mouse.pressed(MouseButton::Middle)
+        || (follow.mode == Follow::Bounds && scroll.iter().count() > 0)
+    {
+        Follow::Off
+    } else {
+        return;
+    };
+
+    if follow.mode != mode {
+        follow.mode = mode;
+    }
+}
+
+fn measure_target(board: Res<Board>, mut follow: ResMut<FollowTarget>) {
+    if follow.mode == Follow::Off || !(board.is_changed() || follow.is_changed()) {
+        return;
+    }
+
+    let mut min = UVec2::splat(u32::MAX);
+    let mut max = UVec2::ZERO;
+    let (mut sum, mut count) = (Vec2::ZERO, 0);
+
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if board.get(x, y) != 0 {
+                let tile = UVec2::new(x, y);
+                min = min.min(tile);
+                max = max.max(tile);
+                sum += tile.as_vec2();
+                count += 1;
+            }
+        }
+    }
+
+    // bypasses change detection so measuring doesn't retrigger itself next frame
+    follow.bypass_change_detection().target = (count > 0).then(|| {
+        let size = (max - min).as_vec2() + Vec2::ONE;
+        match follow.mode {
+            Follow::Center => (sum / count as f32, size),
+            _ => ((min + max).as_vec2() / 2.0, size),
+        }
+    });
+}
+
+pub fn follow_camera(
+    time: Res<Time>,
+    windows: Res<Windows>,
+    follow: Res<FollowTarget>,
+    mut camera_query: Query<
+        (&Camera, &mut Transform, &mut OrthographicProjection),
+        With<MainCamera>,
+    >,
+    tilemap_query: Query<TilemapGeometry, Without<Camera>>,
+) {
+    let Some((center, size)) = follow.target.filter(|_| follow.mode != Follow::Off) else {
+        return;
+    };
+    let (Some(window), Ok((camera, mut transform, mut projection)), Ok(tilemap)) = (
+        windows.get_primary(),
+        camera_query.get_single_mut(),
+        tilemap_query.get_single(),
+    ) else {
+        return;
+    };
+    let (map_size, grid_size, map_type, tilemap_transform) = tilemap;
+
+    // tile coordinates map onto the world between the first and last tiles' centers,
+    // which also keeps hex boards close enough
+    let first = TilePos::new(0, 0).center_in_world(grid_size, map_type);
+    let last = TilePos::new(map_size.x.saturating_sub(1), map_size.y.saturating_sub(1))
+        .center_in_world(grid_size, map_type);
+    let tiles = Vec2::new(map_size.x as f32, map_size.y as f32);
+    let per_tile = (last - first) / (tiles - 1.0).max(Vec2::ONE);
+    let target = tilemap_transform.translation.truncate() + first + center * per_tile;
+
+    let blend = 1.0 - (-FOLLOW_RATE * time.delta_seconds()).exp();
+    let position = transform.translation.truncate().lerp(target, blend);
+    transform.translation = position.extend(transform.translation.z);
+
+    if follow.mode == Follow::Bounds {
+        let view = view_size(window, camera);
+        if view.min_element() <= 0.0 {
+            return;
+        }
+
+        let tile = Vec2::new(grid_size.x, grid_size.y);
+        let framed = (size * FRAME_MARGIN).max(Vec2::splat(MIN_FRAMED_TILES)) * tile;
+        let scale = (framed / view).max_element().clamp(MIN_ZOOM, MAX_ZOOM);
+        projection.scale += (scale - projection.scale) * blend;
+    }
+}
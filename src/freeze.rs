@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::clipboard::{block_in_world, TilemapGeometry};
+use crate::picture::overlay_layer;
+
+const FROZEN_COLOR: Color = Color::rgba(0.4, 0.7, 1.0, 0.25);
+
+/// Rectangles of the board the rule leaves alone, so their cells stay exactly as drawn
+/// while the rest of a construction is tried out. Frozen cells still count as neighbors
+/// of the cells around them. Regions are frozen and thawed from the clipboard panel.
+pub struct FreezePlugin;
+
+#[derive(Resource, Default)]
+pub struct Frozen {
+    /// (left, bottom, width, height) of each frozen region.
+    pub regions: Vec<(u32, u32, u32, u32)>,
+}
+
+impl Frozen {
+    pub fn is_empty(&self) -> bool {
+        self.regions.is_empty()
+    }
+
+    /// Thaws any regions overlapping `area`.
+    pub fn thaw(&mut self, (left, bottom, width, height): (u32, u32, u32, u32)) {
+        self.regions.retain(|&(x, y, w, h)| {
+            x >= left + width || left >= x + w || y >= bottom + height || bottom >= y + h
+        });
+    }
+}
+
+#[derive(Component)]
+struct FrozenOverlay;
+
+impl Plugin for FreezePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Frozen>().add_system(draw_frozen);
+    }
+}
+
+/// Tints the frozen regions, redrawn whenever they change.
+fn draw_frozen(
+    mut commands: Commands,
+    frozen: Res<Frozen>,
+    tilemap_query: Query<TilemapGeometry, With<TileStorage>>,
+    overlay_query: Query<Entity, With<FrozenOverlay>>,
+) {
+    let Ok(geometry) = tilemap_query.get_single() else {
+        return;
+    };
+    if !frozen.is_changed() {
+        return;
+    }
+
+    for entity in overlay_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    for &(left, bottom, width, height) in &frozen.regions {
+        let (translation, size) =
+            block_in_world(geometry, (left as i64, bottom as i64, width, height));
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: FROZEN_COLOR,
+                    custom_size: Some(size),
+                    ..default()
+                },
+                transform: Transform::from_translation(translation),
+                ..default()
+            },
+            FrozenOverlay,
+            overlay_layer(),
+        ));
+    }
+}
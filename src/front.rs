@@ -0,0 +1,223 @@
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+
+use crate::bitboard::Bitboard;
+use crate::hotkeys::Hotkeys;
+use crate::overlay::{board_overlay_image, place_over_board};
+use crate::picture::overlay_layer;
+use crate::rules::{Neighborhood, RuleSet};
+use crate::{update_map, Board, Palette};
+
+/// Teams whose deaths are reported, by their ids on the board.
+const TEAMS: [u8; 2] = [2, 3];
+/// How long a marker takes to fade out, in seconds.
+const FLASH_SECS: f32 = 0.8;
+/// How opaque a fresh marker is, out of 255.
+const FLASH_ALPHA: f32 = 220.0;
+
+/// A toggles front line markers: while two teams fight, a brief flash of the losing
+/// team's color shows wherever one of its cells was taken over or died next to the
+/// enemy, so the front line of a battle stands out.
+pub struct FrontLinePlugin;
+
+/// Whether the front line is marked. Deaths are only reported while it is.
+#[derive(Resource, Default)]
+pub struct FrontLine {
+    pub enabled: bool,
+}
+
+/// A team cell dying or being taken over by another team, sent every generation while
+/// the front line is marked.
+pub struct CellDied {
+    pub x: u32,
+    pub y: u32,
+    pub team: u8,
+    /// The team that took the cell, or one the cell died next to, if any.
+    pub enemy: Option<u8>,
+}
+
+/// What stepping the simulation needs to report deaths.
+#[derive(SystemParam)]
+pub struct DeathReports<'w, 's> {
+    front: Res<'w, FrontLine>,
+    deaths: EventWriter<'w, 's, CellDied>,
+}
+
+impl DeathReports<'_, '_> {
+    /// Whether deaths are wanted, and so whether the board before each step has to be kept.
+    pub fn is_wanted(&self) -> bool {
+        self.front.enabled
+    }
+
+    /// Sends a `CellDied` for every team cell that died in the step that took `before` to
+    /// `after`.
+    pub fn report(&mut self, before: &Bitboard, after: &Bitboard, neighborhood: Neighborhood) {
+        if self.is_wanted() {
+            report_deaths(before, after, neighborhood, &mut self.deaths);
+        }
+    }
+}
+
+fn report_deaths(
+    before: &Bitboard,
+    after: &Bitboard,
+    neighborhood: Neighborhood,
+    deaths: &mut EventWriter<CellDied>,
+) {
+    if (before.width(), before.height()) != (after.width(), after.height()) {
+        return;
+    }
+
+    for y in 0..before.height() {
+        for x in 0..before.width() {
+            let (team, now) = (before.get(x, y), after.get(x, y));
+            if team == now || !TEAMS.contains(&team) {
+                continue;
+            }
+
+            let enemy = if TEAMS.contains(&now) {
+                Some(now)
+            } else {
+                before
+                    .neighbors(x, y, neighborhood)
+                    .map(|(nx, ny)| before.get(nx, ny))
+                    .find(|&neighbor| neighbor != team && TEAMS.contains(&neighbor))
+            };
+            deaths.send(CellDied { x, y, team, enemy });
+        }
+    }
+}
+
+/// Markers fading out, indexed like `Bitboard::get`: the team that lost the cell and
+/// the seconds left before the marker is gone.
+#[derive(Resource, Default)]
+struct Flashes {
+    size: (u32, u32),
+    cells: Vec<Option<(u8, f32)>>,
+    /// Markers still showing, so the texture isn't redrawn when there are none.
+    live: usize,
+}
+
+#[derive(Component)]
+struct FrontSprite;
+
+impl Plugin for FrontLinePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FrontLine>()
+            .init_resource::<Flashes>()
+            .add_event::<CellDied>()
+            .add_startup_system(spawn_front_sprite)
+            .add_system(toggle_front)
+            .add_system(collect_flashes.after(update_map))
+            .add_system(draw_flashes.after(collect_flashes).after(toggle_front));
+    }
+}
+
+fn spawn_front_sprite(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        FrontSprite,
+        overlay_layer(),
+    ));
+}
+
+fn toggle_front(keys: Hotkeys, mut front: ResMut<FrontLine>) {
+    if keys.just_pressed(KeyCode::A) {
+        front.enabled = !front.enabled;
+    }
+}
+
+/// Starts a marker for every death next to the enemy and fades out the rest.
+fn collect_flashes(
+    time: Res<Time>,
+    (board, front): (Res<Board>, Res<FrontLine>),
+    mut deaths: EventReader<CellDied>,
+    mut flashes: ResMut<Flashes>,
+) {
+    let size = (board.width(), board.height());
+    if flashes.size != size || !front.enabled {
+        if flashes.size != size || flashes.live > 0 {
+            *flashes = Flashes {
+                size,
+                cells: vec![None; size.0 as usize * size.1 as usize],
+                live: 0,
+            };
+        }
+        deaths.clear();
+        return;
+    }
+
+    let flashes = &mut *flashes;
+    if flashes.live > 0 {
+        let delta = time.delta_seconds();
+        for cell in flashes.cells.iter_mut() {
+            if let Some((_, left)) = cell {
+                *left -= delta;
+                if *left <= 0.0 {
+                    *cell = None;
+                    flashes.live -= 1;
+                }
+            }
+        }
+    }
+
+    for death in deaths.iter() {
+        if death.enemy.is_none() || death.x >= size.0 || death.y >= size.1 {
+            continue;
+        }
+        let cell = &mut flashes.cells[(death.y * size.0 + death.x) as usize];
+        if cell.is_none() {
+            flashes.live += 1;
+        }
+        *cell = Some((death.team, FLASH_SECS));
+    }
+}
+
+/// Redraws the markers over the board while any are showing.
+fn draw_flashes(
+    (flashes, rule, palette): (Res<Flashes>, Res<RuleSet>, Res<Palette>),
+    mut images: ResMut<Assets<Image>>,
+    mut sprite_query: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<FrontSprite>,
+    >,
+) {
+    let Ok((mut handle, mut sprite, mut transform, mut visibility)) = sprite_query.get_single_mut()
+    else {
+        return;
+    };
+
+    // the texture is a square grid, so hex boards aren't marked
+    let shown = flashes.live > 0 && rule.neighborhood == Neighborhood::Moore;
+    if visibility.is_visible != shown {
+        visibility.is_visible = shown;
+    }
+    if !shown || !flashes.is_changed() {
+        return;
+    }
+
+    let (width, height) = flashes.size;
+    let image = board_overlay_image(width, height, |x, y| {
+        let (team, left) = flashes.cells[(y * width + x) as usize]?;
+        let [r, g, b, _] = palette[team as usize % palette.len()]
+            .as_rgba_f32()
+            .map(|c| (c * 255.0) as u8);
+        let alpha = (FLASH_ALPHA * left / FLASH_SECS) as u8;
+        Some([r, g, b, alpha])
+    });
+
+    match images.get_mut(&handle) {
+        Some(existing) => *existing = image,
+        None => *handle = images.add(image),
+    }
+
+    place_over_board((width, height), &mut sprite, &mut transform, 0.55);
+}
@@ -0,0 +1,241 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::clipboard::Pasting;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::patterns::{Gun, GUNS};
+use crate::rle::Clip;
+use crate::tools::{BoardCursor, Tool};
+use crate::Board;
+
+/// The diagonals a glider can travel along, with y pointing up, and their names' keys.
+const HEADINGS: [((i64, i64), &str); 4] = [
+    ((1, 1), "up-right"),
+    ((1, -1), "down-right"),
+    ((-1, -1), "down-left"),
+    ((-1, 1), "up-left"),
+];
+
+/// X opens the gun designer: pick the way the gliders should go and how often, and it
+/// hands the paste tool a gun from the pattern dictionary mirrored to fire that way. While
+/// it's held, the designer checks the spot under the cursor for cells in the gun's way or
+/// in its gliders' lane.
+pub struct GunPlugin;
+
+#[derive(Resource)]
+struct GunDesigner {
+    open: bool,
+    heading: (i64, i64),
+    period: u32,
+    team: u8,
+    /// The gun and how it's mirrored, while the paste tool holds it.
+    held: Option<(&'static Gun, (bool, bool))>,
+}
+
+impl Default for GunDesigner {
+    fn default() -> Self {
+        Self {
+            open: false,
+            heading: (1, -1),
+            period: 30,
+            team: 2,
+            held: None,
+        }
+    }
+}
+
+/// What's in the way of a gun placed with its bottom left corner somewhere.
+struct Collisions {
+    /// Live cells and walls on or right around the gun.
+    footprint: usize,
+    /// The first cell in its gliders' lane, with how far down the lane it is.
+    lane: Option<((u32, u32), i64)>,
+}
+
+impl Plugin for GunPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GunDesigner>()
+            .add_system(toggle_designer)
+            .add_system(gun_panel.after(toggle_designer));
+    }
+}
+
+fn toggle_designer(keys: Hotkeys, mut designer: ResMut<GunDesigner>) {
+    if keys.just_pressed(KeyCode::X) {
+        designer.open = !designer.open;
+    }
+}
+
+/// Checks the board around a gun mirrored by `flips` with its corner at (left, bottom).
+fn collisions(
+    board: &Bitboard,
+    gun: &Gun,
+    flips: (bool, bool),
+    (left, bottom): (i64, i64),
+) -> Collisions {
+    let pattern = gun.pattern();
+    let (width, height) = (pattern.width() as i64, pattern.height() as i64);
+    let on_board = |x: i64, y: i64| {
+        ((0..board.width() as i64).contains(&x) && (0..board.height() as i64).contains(&y))
+            .then_some((x as u32, y as u32))
+    };
+    let blocked = |(x, y): (u32, u32)| board.get(x, y) != 0 || board.is_wall(x, y);
+    let near_gun = |x: i64, y: i64| {
+        (left - 1..=left + width).contains(&x) && (bottom - 1..=bottom + height).contains(&y)
+    };
+
+    let mut footprint = 0;
+    for y in bottom - 1..=bottom + height {
+        for x in left - 1..=left + width {
+            footprint += on_board(x, y).is_some_and(blocked) as usize;
+        }
+    }
+
+    // the glider sweeps the 3x3 block around its middle as it goes
+    let (exit_x, exit_y) = gun.exit_flipped(flips);
+    let heading = (
+        if flips.0 {
+            -gun.heading.0
+        } else {
+            gun.heading.0
+        },
+        if flips.1 {
+            -gun.heading.1
+        } else {
+            gun.heading.1
+        },
+    );
+    let mut lane = None;
+    let mut distance = 0;
+    let (mut x, mut y) = (left + exit_x, bottom + exit_y);
+
+    while lane.is_none() && (on_board(x, y).is_some() || near_gun(x, y)) {
+        lane = (-1..=1)
+            .flat_map(|dy| (-1..=1).map(move |dx| (x + dx, y + dy)))
+            .filter(|&(x, y)| !near_gun(x, y))
+            .filter_map(|(x, y)| on_board(x, y))
+            .find(|&cell| blocked(cell))
+            .map(|cell| (cell, distance));
+
+        (x, y) = (x + heading.0, y + heading.1);
+        distance += 1;
+    }
+
+    Collisions { footprint, lane }
+}
+
+fn gun_panel(
+    (board, cursor): (Res<Board>, BoardCursor),
+    (mut tool, mut pasting): (ResMut<Tool>, ResMut<Pasting>),
+    (mut designer, strings): (ResMut<GunDesigner>, Res<Strings>),
+    mut egui_context: ResMut<EguiContext>,
+) {
+    // the gun goes once the paste tool does
+    if *tool != Tool::Paste && designer.held.is_some() {
+        designer.held = None;
+    }
+    if !designer.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut pick_up = None;
+
+    egui::Window::new(strings.get("gun.title"))
+        .id(egui::Id::new("gun designer"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let designer = &mut *designer;
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("gun.heading"));
+                for (heading, name) in HEADINGS {
+                    ui.selectable_value(&mut designer.heading, heading, strings.named("gun", name));
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("gun.period"));
+                ui.add(egui::DragValue::new(&mut designer.period).clamp_range(1..=10000));
+                ui.label(strings.get("gun.team"));
+                for team in [2, 3] {
+                    ui.selectable_value(
+                        &mut designer.team,
+                        team,
+                        strings.format("gun.team-number", &[("team", &(team - 1))]),
+                    );
+                }
+            });
+
+            let Some(gun) = GUNS.iter().find(|gun| gun.period == designer.period) else {
+                let periods: Vec<String> = GUNS.iter().map(|gun| gun.period.to_string()).collect();
+                ui.label(strings.format("gun.no-gun", &[("periods", &periods.join(", "))]));
+                return;
+            };
+
+            if ui
+                .button(strings.format("gun.pick-up", &[("gun", &gun.pattern)]))
+                .clicked()
+            {
+                pick_up = Some(gun);
+            }
+
+            let Some((held, flips)) = designer.held else {
+                return;
+            };
+            let Some(TilePos { x, y }) = cursor.tile_pos() else {
+                ui.label(strings.get("gun.aim"));
+                return;
+            };
+
+            let pattern = held.pattern();
+            let corner = (
+                x as i64 - (pattern.width() / 2) as i64,
+                y as i64 - (pattern.height() / 2) as i64,
+            );
+            let report = collisions(&board, held, flips, corner);
+
+            if report.footprint > 0 {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    strings.format("gun.in-the-way", &[("cells", &report.footprint)]),
+                );
+            }
+            match report.lane {
+                Some(((x, y), distance)) => ui.colored_label(
+                    egui::Color32::YELLOW,
+                    strings.format(
+                        "gun.lane-blocked",
+                        &[("x", &x), ("y", &y), ("distance", &distance)],
+                    ),
+                ),
+                None if report.footprint == 0 => ui.label(strings.get("gun.clear")),
+                None => ui.label(strings.get("gun.lane-clear")),
+            };
+        });
+
+    if let Some(gun) = pick_up {
+        let flips = gun.aim(designer.heading);
+        let pattern = gun.pattern();
+        let team = designer.team;
+        let clip = Clip {
+            width: pattern.width(),
+            height: pattern.height(),
+            cells: pattern
+                .cells_flipped(flips.0, flips.1)
+                .map(|(x, y)| (x, y, team))
+                .collect(),
+        };
+
+        pasting.start(Ok(clip), &mut tool, &strings);
+        designer.held = Some((gun, flips));
+    }
+
+    if !open {
+        designer.open = false;
+    }
+}
@@ -0,0 +1,217 @@
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+
+use bevy::prelude::*;
+use rhai::{CallFnOptions, Dynamic, Engine, Scope, AST, INT};
+
+use crate::bitboard::Bitboard;
+use crate::patterns;
+use crate::script::sandboxed_engine;
+
+const DEFAULT_HOOKS: &str = "assets/scripts/hooks.rhai";
+/// Most `on_cell_born` calls one generation makes, so a board exploding with births
+/// doesn't stall the frame. Births past this aren't reported.
+const MAX_BIRTH_CALLS: usize = 4096;
+/// Most lines of script output kept for the script window.
+const MAX_LOG_LINES: usize = 8;
+/// Teams scripts are told about the extinction of, by their ids on the board.
+const TEAMS: [u8; 2] = [2, 3];
+
+/// What a hook asked the game to do, applied once the hook returns.
+#[derive(Clone, Debug)]
+enum Action {
+    Pause,
+    SetCell(INT, INT, INT),
+    Stamp(String, INT, INT, INT),
+    Log(String),
+}
+
+/// Event callbacks from a rhai script, run after each generation. A script defines any of
+/// `on_generation(generation)`, `on_extinction(team)` and `on_cell_born(x, y, team)`, and
+/// they can call `pause()`, `set_cell(x, y, team)`, `stamp(pattern, x, y)` (also with a
+/// trailing `team`) to place a built-in pattern centered on (x, y), and `log(text)`.
+/// Like rule scripts, hooks can't touch the filesystem or loop forever.
+#[derive(Resource)]
+pub struct Hooks {
+    engine: Engine,
+    ast: Option<AST>,
+    actions: Arc<Mutex<Vec<Action>>>,
+    pub path: String,
+    pub error: Option<String>,
+    /// Recent `log` lines, oldest first.
+    pub log: Vec<String>,
+    pause_requested: bool,
+}
+
+impl Default for Hooks {
+    fn default() -> Self {
+        let mut engine = sandboxed_engine();
+        let actions: Arc<Mutex<Vec<Action>>> = Arc::default();
+
+        let queue = actions.clone();
+        engine.register_fn("pause", move || queue.lock().unwrap().push(Action::Pause));
+        let queue = actions.clone();
+        engine.register_fn("set_cell", move |x: INT, y: INT, team: INT| {
+            queue.lock().unwrap().push(Action::SetCell(x, y, team));
+        });
+        let queue = actions.clone();
+        engine.register_fn("stamp", move |name: &str, x: INT, y: INT| {
+            queue
+                .lock()
+                .unwrap()
+                .push(Action::Stamp(name.to_owned(), x, y, 2));
+        });
+        let queue = actions.clone();
+        engine.register_fn("stamp", move |name: &str, x: INT, y: INT, team: INT| {
+            queue
+                .lock()
+                .unwrap()
+                .push(Action::Stamp(name.to_owned(), x, y, team));
+        });
+        let queue = actions.clone();
+        engine.register_fn("log", move |text: Dynamic| {
+            queue.lock().unwrap().push(Action::Log(text.to_string()));
+        });
+
+        Self {
+            engine,
+            ast: None,
+            actions,
+            path: DEFAULT_HOOKS.to_owned(),
+            error: None,
+            log: Vec::new(),
+            pause_requested: false,
+        }
+    }
+}
+
+impl Hooks {
+    pub fn load(&mut self) -> Result<(), String> {
+        let ast = self
+            .engine
+            .compile_file(PathBuf::from(&self.path))
+            .map_err(|error| error.to_string())?;
+
+        if !["on_generation", "on_extinction", "on_cell_born"]
+            .iter()
+            .any(|&name| self.defines(&ast, name))
+        {
+            return Err("the script defines none of the event hooks".to_owned());
+        }
+
+        self.ast = Some(ast);
+        Ok(())
+    }
+
+    pub fn unload(&mut self) {
+        self.ast = None;
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        self.ast.is_some()
+    }
+
+    /// Whether a hook called `pause()` since this was last asked.
+    pub fn take_pause(&mut self) -> bool {
+        std::mem::take(&mut self.pause_requested)
+    }
+
+    pub fn pause_requested(&self) -> bool {
+        self.pause_requested
+    }
+
+    fn defines(&self, ast: &AST, name: &str) -> bool {
+        ast.iter_functions().any(|f| f.name == name)
+    }
+
+    /// Runs the hooks for the generation that turned `before` into `board`, then applies
+    /// what they asked for. A script error unloads the hooks.
+    pub fn after_generation(&mut self, before: &Bitboard, board: &mut Bitboard, generation: u64) {
+        let Some(ast) = self.ast.take() else {
+            return;
+        };
+
+        let mut calls: Vec<(&str, Vec<Dynamic>)> = Vec::new();
+        if self.defines(&ast, "on_generation") {
+            calls.push(("on_generation", vec![Dynamic::from(generation as INT)]));
+        }
+        if self.defines(&ast, "on_extinction") {
+            for team in TEAMS {
+                if before.team_population(team) > 0 && board.team_population(team) == 0 {
+                    calls.push(("on_extinction", vec![Dynamic::from(team as INT)]));
+                }
+            }
+        }
+        if self.defines(&ast, "on_cell_born") {
+            let births = (0..board.height())
+                .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+                .filter(|&(x, y)| board.get(x, y) != 0 && before.get(x, y) == 0)
+                .take(MAX_BIRTH_CALLS);
+
+            for (x, y) in births {
+                let team = board.get(x, y);
+                calls.push((
+                    "on_cell_born",
+                    vec![(x as INT).into(), (y as INT).into(), (team as INT).into()],
+                ));
+            }
+        }
+
+        let mut scope = Scope::new();
+        for (name, args) in calls {
+            let result = self.engine.call_fn_with_options::<Dynamic>(
+                CallFnOptions::new().eval_ast(false),
+                &mut scope,
+                &ast,
+                name,
+                args,
+            );
+
+            if let Err(error) = result {
+                self.error = Some(format!("{name}: {error}"));
+                self.actions.lock().unwrap().clear();
+                return;
+            }
+        }
+
+        self.ast = Some(ast);
+        let actions = std::mem::take(&mut *self.actions.lock().unwrap());
+        for action in actions {
+            self.apply(action, board);
+        }
+    }
+
+    fn apply(&mut self, action: Action, board: &mut Bitboard) {
+        let on_board = |x: INT, y: INT| {
+            (0..board.width() as INT).contains(&x) && (0..board.height() as INT).contains(&y)
+        };
+
+        match action {
+            Action::Pause => self.pause_requested = true,
+            Action::SetCell(x, y, team) => {
+                if on_board(x, y) && (0..=u8::MAX as INT).contains(&team) {
+                    board.set(x as u32, y as u32, team as u8);
+                }
+            }
+            Action::Stamp(name, x, y, team) => {
+                let Some(pattern) = patterns::find(&name) else {
+                    self.error = Some(format!("stamp: no built-in pattern `{name}`"));
+                    return;
+                };
+                let team = team.clamp(1, u8::MAX as INT) as u8;
+                let corner = (
+                    x - (pattern.width() / 2) as INT,
+                    y - (pattern.height() / 2) as INT,
+                );
+                pattern.stamp(board, corner, (false, false), team);
+            }
+            Action::Log(line) => {
+                info!("hook: {line}");
+                if self.log.len() == MAX_LOG_LINES {
+                    self.log.remove(0);
+                }
+                self.log.push(line);
+            }
+        }
+    }
+}
@@ -0,0 +1,58 @@
+use std::marker::PhantomData;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_egui::{EguiContext, EguiSystem};
+
+/// Keeps keyboard shortcuts from firing while an egui text field has the keyboard, so
+/// typing a path or a rule into a panel doesn't also run whatever its letters are bound
+/// to.
+pub struct HotkeysPlugin;
+
+/// Whether egui wanted the keyboard as of this frame's start.
+#[derive(Resource, Default)]
+pub struct KeyboardTaken(bool);
+
+/// The keyboard as shortcuts see it: like `Input<KeyCode>`, but with nothing pressed
+/// while egui wants the keyboard.
+#[derive(SystemParam)]
+pub struct Hotkeys<'w, 's> {
+    keys: Res<'w, Input<KeyCode>>,
+    taken: Res<'w, KeyboardTaken>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
+}
+
+impl Hotkeys<'_, '_> {
+    pub fn pressed(&self, key: KeyCode) -> bool {
+        !self.taken.0 && self.keys.pressed(key)
+    }
+
+    pub fn any_pressed(&self, keys: impl IntoIterator<Item = KeyCode>) -> bool {
+        !self.taken.0 && self.keys.any_pressed(keys)
+    }
+
+    pub fn just_pressed(&self, key: KeyCode) -> bool {
+        !self.taken.0 && self.keys.just_pressed(key)
+    }
+
+    pub fn get_just_pressed(&self) -> impl Iterator<Item = &KeyCode> {
+        self.keys.get_just_pressed().filter(|_| !self.taken.0)
+    }
+}
+
+impl Plugin for HotkeysPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<KeyboardTaken>().add_system_to_stage(
+            CoreStage::PreUpdate,
+            watch_keyboard.after(EguiSystem::BeginFrame),
+        );
+    }
+}
+
+fn watch_keyboard(mut egui_context: ResMut<EguiContext>, mut taken: ResMut<KeyboardTaken>) {
+    let wanted = egui_context.ctx_mut().wants_keyboard_input();
+    if taken.0 != wanted {
+        taken.0 = wanted;
+    }
+}
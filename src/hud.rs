@@ -0,0 +1,263 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::TilePos;
+use bevy_egui::{egui, EguiContext, EguiPlugin};
+
+use crate::detach::PanelContext;
+use crate::energy::Energy;
+use crate::lang::Strings;
+use crate::rules::{Automaton, Chances, RuleSet};
+use crate::tools::{BoardCursor, Brush, Pointing, Symmetry, Tool};
+use crate::{Board, FastForward, Palette, RunTo, SimCounters, TickDuration, UnlimitedSpeed};
+
+pub struct HudPlugin;
+
+/// Whether the pointer is over (or dragging) an egui window, so board clicks can be ignored.
+#[derive(Resource, Default)]
+pub struct PointerOverUi(pub bool);
+
+impl Plugin for HudPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(EguiPlugin)
+            .init_resource::<PointerOverUi>()
+            .add_system(hud)
+            .add_system(rule_panel)
+            .add_system(toolbar)
+            .add_system(track_pointer);
+    }
+}
+
+fn track_pointer(mut egui_context: ResMut<EguiContext>, mut over_ui: ResMut<PointerOverUi>) {
+    let ctx = egui_context.ctx_mut();
+    let over = ctx.is_pointer_over_area() || ctx.wants_pointer_input();
+
+    if over_ui.0 != over {
+        over_ui.0 = over;
+    }
+}
+
+fn hud(
+    mut panels: PanelContext,
+    (ticker, unlimited, mut run_to): (Res<TickDuration>, Res<UnlimitedSpeed>, ResMut<RunTo>),
+    (fast_forward, counters): (Res<FastForward>, Res<SimCounters>),
+    (rule, automaton): (Res<RuleSet>, Res<Automaton>),
+    (board, strings): (Res<Board>, Res<Strings>),
+    (tool, symmetry, cursor): (Res<Tool>, Res<Symmetry>, BoardCursor),
+    (energy, mut target): (Res<Energy>, Local<u64>),
+) {
+    egui::Window::new("hud")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_TOP, [8.0, 8.0])
+        .show(panels.ctx(), |ui| {
+            ui.label(strings.format("hud.generation", &[("generation", &counters.generation)]));
+
+            match (*automaton, rule.name()) {
+                (Automaton::Cyclic(cyclic), _) => ui.label(strings.format(
+                    "hud.cyclic",
+                    &[("states", &cyclic.states), ("threshold", &cyclic.threshold)],
+                )),
+                (Automaton::Scripted, _) => ui.label(strings.get("hud.scripted")),
+                (Automaton::Life, Some(name)) => {
+                    ui.label(strings.format("hud.rule", &[("name", &name), ("rule", &*rule)]))
+                }
+                (Automaton::Life, None) => ui.label(rule.to_string()),
+            };
+            let boundary = strings.named("boundary", board.boundary().name());
+            ui.label(strings.format("hud.edges", &[("boundary", &boundary)]));
+            let tool = strings.named("tool", tool.name());
+            ui.label(strings.format("hud.tool", &[("tool", &tool)]));
+            match cursor.pointing() {
+                Pointing::Tile(TilePos { x, y }) => {
+                    ui.label(strings.format("hud.cursor", &[("x", &x), ("y", &y)]))
+                }
+                Pointing::Locked(TilePos { x, y }) => {
+                    ui.label(strings.format("hud.cursor-locked", &[("x", &x), ("y", &y)]))
+                }
+                Pointing::OffBoard => ui.label(strings.get("hud.cursor-off-board")),
+                Pointing::Ui | Pointing::Nothing => ui.label(strings.get("hud.cursor-none")),
+            };
+            if *symmetry != Symmetry::None {
+                let name = strings.named("symmetry", symmetry.name());
+                ui.label(strings.format("hud.symmetry", &[("symmetry", &name)]));
+            }
+
+            let speed = if let Some(target) = run_to.0 {
+                strings.format("hud.running-to", &[("target", &target)])
+            } else if ticker.0.paused() {
+                strings.get("hud.paused").to_owned()
+            } else if let (true, Some(target)) = (unlimited.enabled, unlimited.auto) {
+                let target = target.as_millis().to_string();
+                strings.format("hud.auto-speed", &[("target", &target)])
+            } else if unlimited.enabled {
+                strings.get("hud.unlimited").to_owned()
+            } else if fast_forward.held {
+                let target = format!("{:.0}", fast_forward.speedup() / ticker.1);
+                strings.format("hud.target-fast-forward", &[("speed", &target)])
+            } else {
+                let target = format!("{:.0}", 1.0 / ticker.1);
+                strings.format("hud.target", &[("speed", &target)])
+            };
+
+            let measured = format!("{:.0}", counters.generations_per_second);
+            ui.label(strings.format("hud.speed", &[("speed", &measured), ("state", &speed)]));
+
+            ui.horizontal(|ui| {
+                ui.add(egui::DragValue::new(&mut *target).speed(10.0));
+
+                if run_to.0.is_some() {
+                    if ui.button(strings.get("hud.stop")).clicked() {
+                        run_to.0 = None;
+                    }
+                } else if ui.button(strings.get("hud.run-to")).clicked()
+                    && *target > counters.generation
+                {
+                    run_to.0 = Some(*target);
+                }
+            });
+
+            if energy.enabled {
+                for team in [2, 3] {
+                    let fraction = energy.fraction(team);
+                    let percent = format!("{:.0}", fraction * 100.0);
+                    ui.add(egui::ProgressBar::new(fraction).desired_width(120.0).text(
+                        strings.format(
+                            "hud.energy",
+                            &[("team", &(team - 1)), ("percent", &percent)],
+                        ),
+                    ));
+                }
+            }
+        });
+}
+
+/// Buttons for the editing tools, mirroring their hotkeys, and the palette the brush
+/// picks its team from.
+fn toolbar(
+    mut panels: PanelContext,
+    mut tool: ResMut<Tool>,
+    (mut brush, palette, automaton): (ResMut<Brush>, Res<Palette>, Res<Automaton>),
+    (mut symmetry, strings): (ResMut<Symmetry>, Res<Strings>),
+) {
+    egui::Window::new("toolbar")
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_CENTER, [8.0, 0.0])
+        .show(panels.ctx(), |ui| {
+            // edited on copies so the resources only read as changed on a click
+            let mut selected = *tool;
+            for (candidate, key) in Tool::ALL {
+                let number = match key {
+                    KeyCode::Minus => "-".to_owned(),
+                    KeyCode::Equals => "=".to_owned(),
+                    KeyCode::LBracket => "[".to_owned(),
+                    KeyCode::RBracket => "]".to_owned(),
+                    KeyCode::Semicolon => ";".to_owned(),
+                    _ => format!("{key:?}").trim_start_matches("Key").to_owned(),
+                };
+                ui.selectable_value(
+                    &mut selected,
+                    candidate,
+                    format!("{number} {}", strings.named("tool", candidate.name())),
+                );
+            }
+            if *tool == Tool::Paste {
+                ui.label(strings.get("toolbar.pasting"));
+            }
+            if selected != *tool {
+                *tool = selected;
+            }
+
+            ui.separator();
+
+            // a cyclic palette can be shorter than the team one the brush was picked from
+            let mut team = if (brush.team as usize) < palette.len() {
+                brush.team
+            } else {
+                0
+            };
+            for candidate in 1..palette.len() as u8 {
+                let [r, g, b, _] = palette[candidate as usize].as_rgba_f32();
+                let color = egui::Color32::from_rgb(
+                    (r * 255.0) as u8,
+                    (g * 255.0) as u8,
+                    (b * 255.0) as u8,
+                );
+                let name = match (*automaton, candidate) {
+                    (Automaton::Cyclic(_), state) => {
+                        strings.format("toolbar.state", &[("state", &state)])
+                    }
+                    (_, 1) => strings.get("toolbar.neither").to_owned(),
+                    (_, team) => strings.format("toolbar.team", &[("team", &(team - 1))]),
+                };
+                let text = egui::RichText::new(name).color(color);
+                ui.selectable_value(&mut team, candidate, text);
+            }
+            ui.selectable_value(&mut team, 0, strings.get("toolbar.erase"));
+            if team != brush.team {
+                brush.team = team;
+            }
+
+            ui.separator();
+
+            let mut mirrored = *symmetry;
+            let symmetry_name = |symmetry: Symmetry| strings.named("symmetry", symmetry.name());
+            egui::ComboBox::from_id_source("symmetry")
+                .selected_text(
+                    strings.format("hud.symmetry", &[("symmetry", &symmetry_name(mirrored))]),
+                )
+                .show_ui(ui, |ui| {
+                    for candidate in Symmetry::ALL {
+                        ui.selectable_value(&mut mirrored, candidate, symmetry_name(candidate));
+                    }
+                });
+            if mirrored != *symmetry {
+                *symmetry = mirrored;
+            }
+        });
+}
+
+/// Rule knobs that aren't part of picking a preset.
+fn rule_panel(
+    mut egui_context: ResMut<EguiContext>,
+    mut rule: ResMut<RuleSet>,
+    mut chances: ResMut<Chances>,
+    strings: Res<Strings>,
+) {
+    egui::Window::new(strings.get("rule-options.title"))
+        .id(egui::Id::new("Rule options"))
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_BOTTOM, [-8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let percent = |value: f64, _| format!("{:.0}%", value * 100.0);
+
+            ui.add(
+                egui::Slider::new(&mut chances.birth, 0.0..=1.0)
+                    .text(strings.get("rule-options.birth-chance"))
+                    .custom_formatter(percent),
+            );
+            ui.add(
+                egui::Slider::new(&mut chances.survival, 0.0..=1.0)
+                    .text(strings.get("rule-options.survival-chance"))
+                    .custom_formatter(percent),
+            );
+
+            // edited on a copy so the rule only reads as changed when it actually is
+            let mut max_age = rule.max_age;
+            ui.horizontal(|ui| {
+                let mut limited = max_age.is_some();
+                ui.checkbox(&mut limited, strings.get("rule-options.max-age"));
+
+                match (limited, &mut max_age) {
+                    (true, Some(age)) => {
+                        ui.add(egui::DragValue::new(age).clamp_range(1..=u16::MAX));
+                    }
+                    (true, None) => max_age = Some(100),
+                    (false, _) => max_age = None,
+                }
+            });
+
+            if max_age != rule.max_age {
+                rule.max_age = max_age;
+            }
+        });
+}
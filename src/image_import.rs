@@ -0,0 +1,198 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy::render::render_resource::TextureFormat;
+use bevy::render::texture::{CompressedImageFormats, ImageType};
+use bevy_egui::{egui, EguiContext};
+
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::raster::{Fit, Raster, RasterOptions};
+use crate::undo::UndoHistory;
+use crate::{Board, GameState, Palette};
+
+/// F6 opens the image importer, which turns a PNG into a starting board: dark pixels come
+/// alive, or light ones, and their hues can pick their teams. Dropping a PNG on the window
+/// opens it here instead of pasting it.
+pub struct ImageImportPlugin;
+
+#[derive(Resource, Default)]
+struct ImageImport {
+    open: bool,
+    path: String,
+    picture: Option<Raster>,
+    options: RasterOptions,
+    by_hue: bool,
+    error: Option<String>,
+}
+
+impl ImageImport {
+    fn load(&mut self) {
+        match load_png(Path::new(self.path.trim())) {
+            Ok(picture) => {
+                info!(
+                    path = self.path.trim(),
+                    width = picture.width,
+                    height = picture.height,
+                    "loaded a picture"
+                );
+                self.picture = Some(picture);
+                self.error = None;
+            }
+            Err(error) => {
+                warn!(path = self.path.trim(), %error, "couldn't load a picture");
+                self.picture = None;
+                self.error = Some(error);
+            }
+        }
+    }
+}
+
+/// Whether `path` is a picture the importer takes, rather than a pattern file.
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|extension| extension.eq_ignore_ascii_case("png"))
+}
+
+fn load_png(path: &Path) -> Result<Raster, String> {
+    let bytes = std::fs::read(path).map_err(|error| error.to_string())?;
+    let image = Image::from_buffer(
+        &bytes,
+        ImageType::Extension("png"),
+        CompressedImageFormats::NONE,
+        true,
+    )
+    .map_err(|error| error.to_string())?;
+    let image = image
+        .convert(TextureFormat::Rgba8UnormSrgb)
+        .ok_or("the picture's pixel format isn't supported")?;
+
+    let size = image.size();
+    Ok(Raster {
+        width: size.x as u32,
+        height: size.y as u32,
+        pixels: image
+            .data
+            .chunks_exact(4)
+            .map(|pixel| [pixel[0], pixel[1], pixel[2], pixel[3]])
+            .collect(),
+    })
+}
+
+impl Plugin for ImageImportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ImageImport>()
+            .add_system(toggle_import)
+            .add_system(drop_image)
+            .add_system(import_panel.after(toggle_import).after(drop_image));
+    }
+}
+
+fn toggle_import(keys: Hotkeys, mut import: ResMut<ImageImport>) {
+    if keys.just_pressed(KeyCode::F6) {
+        import.open = !import.open;
+    }
+}
+
+fn drop_image(mut drops: EventReader<FileDragAndDrop>, mut import: ResMut<ImageImport>) {
+    for drop in drops.iter() {
+        if let FileDragAndDrop::DroppedFile { path_buf, .. } = drop {
+            if is_image(path_buf) {
+                import.path = path_buf.display().to_string();
+                import.load();
+                import.open = true;
+            }
+        }
+    }
+}
+
+fn import_panel(
+    (state, palette): (Res<State<GameState>>, Res<Palette>),
+    (mut board, mut history): (ResMut<Board>, ResMut<UndoHistory>),
+    (mut import, strings): (ResMut<ImageImport>, Res<Strings>),
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !import.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut place = false;
+
+    egui::Window::new(strings.get("image.title"))
+        .id(egui::Id::new("image import"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let import = &mut *import;
+
+            ui.label(strings.get("image.help"));
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("image.path"));
+                ui.text_edit_singleline(&mut import.path);
+                if ui.button(strings.get("image.load")).clicked() {
+                    import.load();
+                }
+            });
+
+            if let Some(error) = &import.error {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    strings.format("image.unreadable", &[("error", error)]),
+                );
+            }
+
+            let options = &mut import.options;
+            ui.add(
+                egui::Slider::new(&mut options.threshold, 0.0..=1.0)
+                    .text(strings.get("image.threshold")),
+            );
+            ui.checkbox(&mut options.light_alive, strings.get("image.light-alive"));
+            ui.checkbox(&mut import.by_hue, strings.get("image.by-hue"));
+            ui.horizontal(|ui| {
+                for fit in Fit::ALL {
+                    ui.selectable_value(
+                        &mut options.fit,
+                        fit,
+                        strings.get(&format!("image.fit-{}", fit.name())),
+                    );
+                }
+            });
+
+            let Some(picture) = &import.picture else {
+                return;
+            };
+            ui.label(strings.format(
+                "image.loaded",
+                &[("width", &picture.width), ("height", &picture.height)],
+            ));
+            // scenarios don't let the whole board be swapped out
+            place = ui
+                .add_enabled(
+                    *state.current() == GameState::Sandbox,
+                    egui::Button::new(strings.get("image.place")),
+                )
+                .clicked();
+        });
+
+    if place {
+        if let Some(picture) = &import.picture {
+            // the teams' colors, as the hues pixels are matched against
+            let hue = |team: usize| palette.0[team % palette.0.len()].as_hsla_f32()[0];
+            let options = RasterOptions {
+                team_hues: import.by_hue.then(|| [hue(2), hue(3)]),
+                ..import.options
+            };
+
+            let mut placed = picture.to_board((board.width(), board.height()), &options);
+            placed.set_boundary(board.boundary());
+            history.record(&board);
+            board.0 = placed;
+        }
+    }
+
+    if !open {
+        import.open = false;
+    }
+}
@@ -0,0 +1,321 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::camera::{tile_view_position, MainCamera};
+use crate::lang::Strings;
+use crate::tools::{BoardCursor, Tool};
+use crate::{update_map, Board, Palette, SimCounters};
+
+/// Most generations the timeline keeps before forgetting the oldest.
+const MAX_GENERATIONS: usize = 2000;
+/// Most cell changes the timeline keeps, so a busy board doesn't pile up too many.
+const MAX_CHANGES: usize = 1 << 22;
+/// What walls are recorded as, past any team.
+const WALL: u8 = u8::MAX;
+/// Size of the strip chart, in points.
+const STRIP_SIZE: (f32, f32) = (360.0, 28.0);
+
+/// The inspect tool: click a cell to see what it held over the recorded timeline, as a
+/// strip chart from the oldest recorded generation to now. The timeline keeps every
+/// change to every cell for the last couple of thousand generations; generations run
+/// together in one frame, at unlimited speed or running to a generation, are recorded as
+/// one. Undoing, redoing and replacing the board start the timeline over.
+pub struct InspectPlugin;
+
+/// Starts the inspect tool's timeline over from the board as it is, for anything that
+/// replaces the board with one that doesn't follow on from the last recorded generation:
+/// undoing, redoing and anything resetting the board.
+pub struct RestartTimeline;
+
+/// Every cell's changes since `base`, generation by generation.
+#[derive(Resource, Default)]
+struct Timeline {
+    /// The board at the oldest recorded generation.
+    base: Option<Bitboard>,
+    base_generation: u64,
+    /// The board as last recorded, to find what changed in the next.
+    last: Option<Bitboard>,
+    /// Each generation's changes as (cell index, what it became), in order. Edits land in
+    /// their own entries even within a generation.
+    changes: VecDeque<(u64, Vec<(u32, u8)>)>,
+    total: usize,
+}
+
+/// What (x, y) holds: its team, 0 if dead, or `WALL`.
+fn state(board: &Bitboard, x: u32, y: u32) -> u8 {
+    if board.is_wall(x, y) {
+        WALL
+    } else {
+        board.get(x, y)
+    }
+}
+
+impl Timeline {
+    fn restart(&mut self, board: &Bitboard, generation: u64) {
+        *self = Timeline {
+            base: Some(board.clone()),
+            base_generation: generation,
+            last: Some(board.clone()),
+            ..default()
+        };
+    }
+
+    fn record(&mut self, board: &Bitboard, generation: u64) {
+        let Some(last) = &self.last else {
+            self.restart(board, generation);
+            return;
+        };
+        // a board of another size can't follow on from what was recorded
+        if (last.width(), last.height()) != (board.width(), board.height()) {
+            self.restart(board, generation);
+            return;
+        }
+
+        let width = board.width();
+        let mut changed = Vec::new();
+        for y in 0..board.height() {
+            for x in 0..width {
+                let now = state(board, x, y);
+                if now != state(last, x, y) {
+                    changed.push((y * width + x, now));
+                }
+            }
+        }
+
+        if !changed.is_empty() {
+            self.total += changed.len();
+            self.changes.push_back((generation, changed));
+        }
+        self.last = Some(board.clone());
+
+        while self.changes.len() > MAX_GENERATIONS || self.total > MAX_CHANGES {
+            self.forget_oldest();
+        }
+    }
+
+    /// Folds the oldest changes into the base.
+    fn forget_oldest(&mut self) {
+        let (Some((generation, changed)), Some(base)) = (self.changes.pop_front(), &mut self.base)
+        else {
+            return;
+        };
+
+        let width = base.width();
+        for (cell, now) in &changed {
+            let (x, y) = (cell % width, cell / width);
+            if *now == WALL {
+                base.set_wall(x, y, true);
+            } else {
+                base.set_wall(x, y, false);
+                base.set(x, y, *now);
+            }
+        }
+        self.total -= changed.len();
+        self.base_generation = generation;
+    }
+
+    /// What (x, y) held from each generation it changed in on, starting with the oldest
+    /// recorded generation.
+    fn cell_history(&self, x: u32, y: u32) -> Vec<(u64, u8)> {
+        let Some(base) = &self.base else {
+            return Vec::new();
+        };
+        if x >= base.width() || y >= base.height() {
+            return Vec::new();
+        }
+
+        let cell = y * base.width() + x;
+        let mut history = vec![(self.base_generation, state(base, x, y))];
+        for (generation, changed) in &self.changes {
+            if let Some(&(_, now)) = changed.iter().find(|&&(index, _)| index == cell) {
+                // an edit in the same generation replaces what the step left
+                match history.last_mut() {
+                    Some(last) if last.0 == *generation => last.1 = now,
+                    _ => history.push((*generation, now)),
+                }
+            }
+        }
+        history
+    }
+}
+
+/// The cell picked with the inspect tool.
+#[derive(Resource, Default)]
+struct Inspected(Option<TilePos>);
+
+type CameraView<'a> = (&'a Camera, &'a Transform, &'a OrthographicProjection);
+
+impl Plugin for InspectPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<RestartTimeline>()
+            .init_resource::<Timeline>()
+            .init_resource::<Inspected>()
+            .add_system(record_timeline.after(update_map))
+            .add_system(pick_cell)
+            .add_system(inspect_panel.after(record_timeline).after(pick_cell));
+    }
+}
+
+fn record_timeline(
+    board: Res<Board>,
+    counters: Res<SimCounters>,
+    mut restarts: EventReader<RestartTimeline>,
+    mut timeline: ResMut<Timeline>,
+) {
+    if restarts.iter().count() > 0 {
+        timeline.restart(&board, counters.generation);
+    } else if board.is_changed() || timeline.last.is_none() {
+        timeline.record(&board, counters.generation);
+    }
+}
+
+fn pick_cell(
+    mouse: Res<Input<MouseButton>>,
+    tool: Res<Tool>,
+    cursor: BoardCursor,
+    mut inspected: ResMut<Inspected>,
+) {
+    if *tool != Tool::Inspect {
+        return;
+    }
+    if mouse.just_pressed(MouseButton::Left) {
+        if let Some(tile) = cursor.tile_pos() {
+            inspected.0 = Some(tile);
+        }
+    }
+    if mouse.just_pressed(MouseButton::Right) {
+        inspected.0 = None;
+    }
+}
+
+fn state_name(strings: &Strings, state: u8) -> String {
+    match state {
+        0 => strings.get("inspect.dead").to_owned(),
+        WALL => strings.get("inspect.wall").to_owned(),
+        team => strings.format("inspect.team", &[("team", &(team - 1))]),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn inspect_panel(
+    tool: Res<Tool>,
+    (timeline, inspected): (Res<Timeline>, Res<Inspected>),
+    (counters, palette): (Res<SimCounters>, Res<Palette>),
+    camera_query: Query<CameraView, With<MainCamera>>,
+    tilemap_query: Query<(&TilemapGridSize, &TilemapType, &Transform), With<TileStorage>>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if *tool != Tool::Inspect {
+        return;
+    }
+
+    // ringed on the board, like the measure tool's ends
+    if let (Some(tile), Ok(camera), Ok(tilemap)) = (
+        inspected.0,
+        camera_query.get_single(),
+        tilemap_query.get_single(),
+    ) {
+        if let Some(center) = tile_view_position(camera, tilemap, tile) {
+            let painter = egui_context
+                .ctx_mut()
+                .layer_painter(egui::LayerId::background());
+            painter.circle_stroke(
+                egui::pos2(center.x, center.y),
+                8.0,
+                egui::Stroke::new(2.0, egui::Color32::from_rgb(255, 220, 60)),
+            );
+        }
+    }
+
+    egui::Window::new(strings.get("inspect.title"))
+        .id(egui::Id::new("inspect"))
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let Some(TilePos { x, y }) = inspected.0 else {
+                ui.label(strings.get("inspect.help"));
+                return;
+            };
+
+            let history = timeline.cell_history(x, y);
+            let Some(&(first, _)) = history.first() else {
+                ui.label(strings.get("inspect.nothing"));
+                return;
+            };
+            let now = counters.generation.max(first);
+            let span = (now - first + 1) as f32;
+
+            ui.label(strings.format(
+                "inspect.recorded",
+                &[("x", &x), ("y", &y), ("first", &first), ("now", &now)],
+            ));
+
+            // each run of the same state is a bar as long as it lasted
+            let (rect, response) = ui
+                .allocate_exact_size(egui::vec2(STRIP_SIZE.0, STRIP_SIZE.1), egui::Sense::hover());
+            let painter = ui.painter_at(rect);
+            painter.rect_filled(rect, 0.0, egui::Color32::from_gray(25));
+
+            let color = |state: u8| match state {
+                0 => egui::Color32::from_gray(25),
+                WALL => egui::Color32::from_gray(130),
+                team => {
+                    let [r, g, b, _] = palette[team as usize % palette.len()]
+                        .as_rgba_f32()
+                        .map(|c| (c * 255.0) as u8);
+                    egui::Color32::from_rgb(r, g, b)
+                }
+            };
+            let position =
+                |generation: u64| rect.left() + (generation - first) as f32 / span * rect.width();
+            for (i, &(from, state)) in history.iter().enumerate() {
+                let to = history.get(i + 1).map_or(now + 1, |&(next, _)| next);
+                let bar = egui::Rect::from_x_y_ranges(
+                    position(from)..=position(to).max(position(from) + 1.0),
+                    rect.y_range(),
+                );
+                painter.rect_filled(bar, 0.0, color(state));
+            }
+
+            if let Some(pointer) = response.hover_pos() {
+                let generation =
+                    first + ((pointer.x - rect.left()) / rect.width() * span).max(0.0) as u64;
+                let state = history
+                    .iter()
+                    .rev()
+                    .find(|&&(from, _)| from <= generation)
+                    .map_or(0, |&(_, state)| state);
+                let state = state_name(&strings, state);
+                response.on_hover_text(strings.format(
+                    "inspect.at",
+                    &[("generation", &generation), ("state", &state)],
+                ));
+            }
+
+            let alive: u64 = history
+                .iter()
+                .enumerate()
+                .filter(|(_, &(_, state))| state != 0 && state != WALL)
+                .map(|(i, &(from, _))| history.get(i + 1).map_or(now + 1, |&(next, _)| next) - from)
+                .sum();
+            let (last_change, last_state) = *history.last().unwrap();
+            ui.label(strings.format(
+                "inspect.alive",
+                &[
+                    ("alive", &alive),
+                    ("generations", &(now - first + 1)),
+                    ("changes", &(history.len() - 1)),
+                ],
+            ));
+            let last_state = state_name(&strings, last_state);
+            ui.label(strings.format(
+                "inspect.since",
+                &[("state", &last_state), ("generation", &last_change)],
+            ));
+        });
+}
@@ -0,0 +1,126 @@
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::autosave::read_ron;
+
+/// Where translations are dropped in, one `<code>.ron` per language.
+const LANG_DIR: &str = "assets/lang";
+/// Built in, so the app has its strings even without the assets directory.
+const ENGLISH: &str = include_str!("../assets/lang/en.ron");
+
+/// Looks up on-screen text in the chosen language's bundle, falling back to English for
+/// anything the bundle leaves out. The language is picked in the display settings.
+pub struct LangPlugin;
+
+/// One language's strings, keyed like `hud.generation`, with `{name}` placeholders.
+#[derive(Deserialize)]
+struct Bundle {
+    name: String,
+    strings: HashMap<String, String>,
+}
+
+#[derive(Resource)]
+pub struct Strings {
+    english: Bundle,
+    /// The chosen language's bundle, unless that's English.
+    chosen: Option<Bundle>,
+    /// Code of the chosen language, like `en`.
+    pub code: String,
+    /// Code and name of every language in the assets, English first.
+    pub languages: Vec<(String, String)>,
+}
+
+impl Default for Strings {
+    fn default() -> Self {
+        let english: Bundle = ron::from_str(ENGLISH).expect("the built-in strings are valid");
+
+        Self {
+            languages: vec![("en".to_owned(), english.name.clone())],
+            english,
+            chosen: None,
+            code: "en".to_owned(),
+        }
+    }
+}
+
+impl Strings {
+    fn lookup(&self, key: &str) -> Option<&str> {
+        self.chosen
+            .iter()
+            .chain([&self.english])
+            .find_map(|bundle| bundle.strings.get(key))
+            .map(String::as_str)
+    }
+
+    /// The text for `key`, or the key itself if no bundle has it.
+    pub fn get<'a>(&'a self, key: &'a str) -> &'a str {
+        self.lookup(key).unwrap_or(key)
+    }
+
+    /// The translation of a name from one of the app's own lists, like a tool's, kept
+    /// under `<group>.<name>`. Falls back to the name itself.
+    pub fn named<'a>(&'a self, group: &str, name: &'a str) -> &'a str {
+        self.lookup(&format!("{group}.{name}")).unwrap_or(name)
+    }
+
+    /// The text for `key` with each `{name}` in it replaced by its value in `args`.
+    pub fn format(&self, key: &str, args: &[(&str, &dyn Display)]) -> String {
+        let mut text = self.get(key).to_owned();
+        for (name, value) in args {
+            text = text.replace(&format!("{{{name}}}"), &value.to_string());
+        }
+        text
+    }
+
+    /// Switches to the language `code`, read from the assets when it isn't English.
+    pub fn choose(&mut self, code: &str) -> Result<(), String> {
+        self.chosen = if code == "en" {
+            None
+        } else {
+            let path = Path::new(LANG_DIR).join(code).with_extension("ron");
+            Some(read_ron(&path)?)
+        };
+        self.code = code.to_owned();
+        Ok(())
+    }
+}
+
+impl Plugin for LangPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Strings>()
+            .add_startup_system(find_languages);
+    }
+}
+
+fn find_languages(mut strings: ResMut<Strings>) {
+    let Ok(entries) = fs::read_dir(LANG_DIR) else {
+        return;
+    };
+
+    let mut found: Vec<(String, String)> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .filter_map(|path| {
+            let code = path.file_stem()?.to_str()?.to_owned();
+            if code == "en" {
+                return None;
+            }
+            match read_ron::<Bundle>(&path) {
+                Ok(bundle) => Some((code, bundle.name)),
+                Err(error) => {
+                    warn!("ignoring unreadable language {}: {error}", path.display());
+                    None
+                }
+            }
+        })
+        .collect();
+    found.sort();
+
+    strings.languages.extend(found);
+}
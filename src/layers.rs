@@ -0,0 +1,114 @@
+use crate::bitboard::Bitboard;
+use crate::rules::{Neighborhood, RuleSet};
+use crate::simulation::Simulation;
+
+/// How the upper of two stacked layers and the lower one read each other once both have
+/// stepped on their own.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Coupling {
+    /// The layers don't see each other.
+    #[default]
+    None,
+    /// Upper cells eat the lower cells under them, and starve unless the lower layer had
+    /// a live cell under or next to them.
+    Predation,
+    /// Lower cells under an upper cell are sheltered and can't die.
+    Shelter,
+}
+
+impl Coupling {
+    pub const ALL: [Coupling; 3] = [Coupling::None, Coupling::Predation, Coupling::Shelter];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Coupling::None => "independent",
+            Coupling::Predation => "predation",
+            Coupling::Shelter => "shelter",
+        }
+    }
+
+    /// Applies the coupling to the step that took the layers from `before` to `after`,
+    /// all four boards being the same size.
+    pub fn apply(
+        self,
+        neighborhood: Neighborhood,
+        (lower_before, upper_before): (&Bitboard, &Bitboard),
+        (lower, upper): (&mut Bitboard, &mut Bitboard),
+    ) {
+        if self == Coupling::None {
+            return;
+        }
+
+        for y in 0..lower.height() {
+            for x in 0..lower.width() {
+                match self {
+                    Coupling::None => {}
+                    Coupling::Predation => {
+                        if upper.get(x, y) == 0 {
+                            continue;
+                        }
+
+                        let fed = lower_before.get(x, y) != 0
+                            || lower_before
+                                .neighbors(x, y, neighborhood)
+                                .any(|(nx, ny)| lower_before.get(nx, ny) != 0);
+                        if fed {
+                            lower.set(x, y, 0);
+                        } else {
+                            upper.set(x, y, 0);
+                        }
+                    }
+                    Coupling::Shelter => {
+                        let team = lower_before.get(x, y);
+                        if team != 0 && upper_before.get(x, y) != 0 && lower.get(x, y) == 0 {
+                            lower.set(x, y, team);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Two boards of the same size stacked on each other, each stepped under its own rule
+/// and then coupled.
+#[derive(Clone, Debug)]
+pub struct LayeredSimulation {
+    pub lower: Simulation,
+    pub upper: Simulation,
+    pub coupling: Coupling,
+}
+
+impl LayeredSimulation {
+    pub fn step(&mut self) {
+        let before = (self.lower.board.clone(), self.upper.board.clone());
+        self.lower.step();
+        self.upper.step();
+
+        self.coupling.apply(
+            self.lower.rule.neighborhood,
+            (&before.0, &before.1),
+            (&mut self.lower.board, &mut self.upper.board),
+        );
+    }
+
+    /// Runs `generations` generations.
+    pub fn step_n(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+}
+
+/// Steps `upper` under `rule` and couples it to `lower`, which `before` was just stepped
+/// into by the caller, for layers kept outside a `LayeredSimulation`.
+pub fn step_upper(
+    coupling: Coupling,
+    rule: &RuleSet,
+    before: &Bitboard,
+    (lower, upper): (&mut Bitboard, &mut Bitboard),
+) {
+    let upper_before = upper.clone();
+    upper.step(rule);
+    coupling.apply(rule.neighborhood, (before, &upper_before), (lower, upper));
+}
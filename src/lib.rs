@@ -0,0 +1,22 @@
+//! The board and rules without the game around them, so they can be driven headlessly,
+//! e.g. by regression tests checking that a rewrite of the stepping code still runs
+//! known patterns the same way, or by other crates analysing simulations through
+//! `view::BoardView`.
+
+pub mod bitboard;
+pub mod evolve;
+pub mod extra;
+pub mod layers;
+pub mod macrocell;
+pub mod patterns;
+pub mod predecessor;
+pub mod raster;
+pub mod replay;
+pub mod rle;
+pub mod rng;
+pub mod rules;
+pub mod simulation;
+pub mod snapshot;
+#[cfg(feature = "test-support")]
+pub mod testing;
+pub mod view;
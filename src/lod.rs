@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::camera::MainCamera;
+use crate::overlay::{board_overlay_image, draw_board_texels, place_over_board};
+use crate::rules::{Neighborhood, RuleSet};
+use crate::{Board, CellLooks};
+
+/// Camera scale above which the board is drawn as one textured quad instead of tiles.
+const LOD_SCALE: f32 = 4.0;
+
+/// Draws the board as a single sprite when zoomed far out, with one texel per cell,
+/// so the tile entities don't have to be kept in sync while they're too small to see.
+pub struct LodPlugin;
+
+/// Whether the zoomed-out board texture is currently shown instead of the tilemap.
+#[derive(Resource, Default)]
+pub struct Lod {
+    pub active: bool,
+}
+
+#[derive(Component)]
+struct LodSprite;
+
+impl Plugin for LodPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Lod>()
+            .add_startup_system(spawn_lod_sprite)
+            .add_system(switch_lod)
+            .add_system(update_lod_texture.after(switch_lod));
+    }
+}
+
+fn spawn_lod_sprite(mut commands: Commands, mut images: ResMut<Assets<Image>>, board: Res<Board>) {
+    let size = (board.width(), board.height());
+    let image = board_overlay_image(size.0, size.1, |_, _| None);
+
+    let (mut sprite, mut transform) = default();
+    place_over_board(size, &mut sprite, &mut transform, 0.0);
+
+    commands.spawn((
+        SpriteBundle {
+            sprite,
+            texture: images.add(image),
+            transform,
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        LodSprite,
+    ));
+}
+
+fn switch_lod(
+    mut lod: ResMut<Lod>,
+    rule: Res<RuleSet>,
+    camera_query: Query<&OrthographicProjection, With<MainCamera>>,
+    mut tilemap_query: Query<&mut Visibility, (With<TileStorage>, Without<LodSprite>)>,
+    mut sprite_query: Query<&mut Visibility, With<LodSprite>>,
+) {
+    // the texture is a square grid, so hex boards always use the tilemap
+    let active =
+        camera_query.single().scale >= LOD_SCALE && rule.neighborhood == Neighborhood::Moore;
+    if active != lod.active {
+        lod.active = active;
+    }
+
+    // checked every frame since the tilemap is respawned when the board shape changes
+    for mut visibility in tilemap_query.iter_mut() {
+        if visibility.is_visible == active {
+            visibility.is_visible = !active;
+        }
+    }
+    for mut visibility in sprite_query.iter_mut() {
+        if visibility.is_visible != active {
+            visibility.is_visible = active;
+        }
+    }
+}
+
+fn update_lod_texture(
+    lod: Res<Lod>,
+    looks: CellLooks,
+    mut images: ResMut<Assets<Image>>,
+    sprite_query: Query<&Handle<Image>, With<LodSprite>>,
+) {
+    if !lod.active || !(looks.is_changed() || lod.is_changed()) {
+        return;
+    }
+
+    let Some(image) = images.get_mut(sprite_query.single()) else {
+        return;
+    };
+
+    let _span = info_span!("sync_lod_texture").entered();
+
+    let size = (looks.board.width(), looks.board.height());
+    draw_board_texels(&mut image.data, size, |x, y| {
+        Some(match looks.look(x, y) {
+            (TileVisible(true), TileColor(color)) => color.as_rgba_f32().map(|c| (c * 255.0) as u8),
+            _ => [0; 4],
+        })
+    });
+}
@@ -0,0 +1,315 @@
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::utils::tracing::field::{Field, Visit};
+use bevy::utils::tracing::{self, Level, Subscriber};
+use bevy_egui::{egui, EguiContext};
+use serde_json::{json, Map, Value};
+use tracing_subscriber::layer::{Context, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{fmt, EnvFilter, Layer, Registry};
+
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::rules::{Automaton, RuleSet};
+
+/// Where the log files are written, next to the working directory like `saves`.
+const LOGS_DIR: &str = "logs";
+/// The log file of the running session. Earlier ones are kept as `conway.1.log` and on.
+const LOG_FILE: &str = "conway";
+/// How many earlier log files are kept besides the current one.
+const KEPT_LOGS: u32 = 3;
+/// Size a log file is rotated out at, so a long run doesn't fill the disk.
+const MAX_LOG_BYTES: u64 = 4 << 20;
+/// Most entries the viewer keeps. The log files keep everything.
+const MAX_ENTRIES: usize = 2000;
+/// What's logged when `RUST_LOG` doesn't say, like Bevy's own logger.
+const DEFAULT_FILTER: &str = "info,wgpu=error";
+
+/// Whether this is a profiling build, which logs through Bevy's `LogPlugin` instead.
+pub const PROFILING: bool = cfg!(any(feature = "trace_chrome", feature = "trace_tracy"));
+
+/// Logs everything through `tracing` to the terminal, to a JSON lines file under `logs/`
+/// that's rotated at each start and once it grows past a few megabytes, and to an in-app
+/// viewer F7 opens. Loads, saves, rule changes and downloads log structured fields along
+/// with their message, so a long headless or networked run can be pieced together after.
+/// It takes the place of Bevy's `LogPlugin` and goes before the default plugins, so their
+/// startup logs are caught too, except in profiling builds, which keep Bevy's so their
+/// spans still reach the profiler.
+pub struct LogsPlugin;
+
+/// One logged event.
+struct Entry {
+    /// Seconds since the app started.
+    time: f32,
+    level: Level,
+    target: String,
+    message: String,
+    fields: Vec<(&'static str, String)>,
+}
+
+/// Everything logged so far, shared between the tracing layer and the viewer.
+#[derive(Default)]
+struct Journal {
+    entries: VecDeque<Entry>,
+    file: Option<File>,
+    written: u64,
+}
+
+impl Journal {
+    fn push(&mut self, entry: Entry) {
+        if let Some(file) = &mut self.file {
+            let mut line = json!({
+                "time": SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map_or(0.0, |elapsed| elapsed.as_secs_f64()),
+                "level": entry.level.as_str(),
+                "target": entry.target,
+                "message": entry.message,
+            });
+            if !entry.fields.is_empty() {
+                let fields: Map<String, Value> = entry
+                    .fields
+                    .iter()
+                    .map(|(name, value)| (name.to_string(), Value::from(value.as_str())))
+                    .collect();
+                line["fields"] = Value::Object(fields);
+            }
+
+            let line = format!("{line}\n");
+            // a log that can't be written isn't worth stopping the app over
+            if file.write_all(line.as_bytes()).is_ok() {
+                self.written += line.len() as u64;
+            } else {
+                self.file = None;
+            }
+            if self.written >= MAX_LOG_BYTES {
+                self.file = open_log_file();
+                self.written = 0;
+            }
+        }
+
+        if self.entries.len() == MAX_ENTRIES {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+fn log_path(index: u32) -> PathBuf {
+    match index {
+        0 => Path::new(LOGS_DIR).join(format!("{LOG_FILE}.log")),
+        index => Path::new(LOGS_DIR).join(format!("{LOG_FILE}.{index}.log")),
+    }
+}
+
+/// Moves the log files one place down, dropping the oldest, and starts a new one.
+fn open_log_file() -> Option<File> {
+    fs::create_dir_all(LOGS_DIR).ok()?;
+    for index in (0..KEPT_LOGS).rev() {
+        // the older ones don't exist until enough runs have gone by
+        let _ = fs::rename(log_path(index), log_path(index + 1));
+    }
+    File::create(log_path(0)).ok()
+}
+
+/// The tracing layer that feeds the journal.
+struct JournalLayer {
+    journal: Arc<Mutex<Journal>>,
+    started: Instant,
+}
+
+/// Pulls an event's message and fields out as text.
+#[derive(Default)]
+struct Fields {
+    message: String,
+    fields: Vec<(&'static str, String)>,
+}
+
+impl Visit for Fields {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "message" => self.message = value.to_owned(),
+            name => self.fields.push((name, value.to_owned())),
+        }
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        match field.name() {
+            "message" => self.message = format!("{value:?}"),
+            name => self.fields.push((name, format!("{value:?}"))),
+        }
+    }
+}
+
+impl<S: Subscriber> Layer<S> for JournalLayer {
+    fn on_event(&self, event: &tracing::Event<'_>, _: Context<'_, S>) {
+        let mut fields = Fields::default();
+        event.record(&mut fields);
+
+        let metadata = event.metadata();
+        let entry = Entry {
+            time: self.started.elapsed().as_secs_f32(),
+            level: *metadata.level(),
+            target: metadata.target().to_owned(),
+            message: fields.message,
+            fields: fields.fields,
+        };
+        if let Ok(mut journal) = self.journal.lock() {
+            journal.push(entry);
+        }
+    }
+}
+
+#[derive(Resource)]
+struct LogViewer {
+    open: bool,
+    journal: Arc<Mutex<Journal>>,
+    /// Whether the journal gets anything. It doesn't when another logger was set up first.
+    capturing: bool,
+    /// Least severe level shown.
+    level: Level,
+    filter: String,
+}
+
+impl Plugin for LogsPlugin {
+    fn build(&self, app: &mut App) {
+        let journal = Arc::new(Mutex::new(Journal {
+            file: open_log_file(),
+            ..default()
+        }));
+
+        let filter = EnvFilter::try_from_default_env()
+            .or_else(|_| EnvFilter::try_new(DEFAULT_FILTER))
+            .unwrap();
+        let capturing = !PROFILING
+            && Registry::default()
+                .with(filter)
+                .with(fmt::Layer::default())
+                .with(JournalLayer {
+                    journal: journal.clone(),
+                    started: Instant::now(),
+                })
+                .try_init()
+                .is_ok();
+
+        app.insert_resource(LogViewer {
+            open: false,
+            journal,
+            capturing,
+            level: Level::INFO,
+            filter: String::new(),
+        })
+        .add_system(toggle_viewer)
+        .add_system(log_rule_changes)
+        .add_system(log_viewer.after(toggle_viewer));
+    }
+}
+
+fn toggle_viewer(keys: Hotkeys, mut viewer: ResMut<LogViewer>) {
+    if keys.just_pressed(KeyCode::F7) {
+        viewer.open = !viewer.open;
+    }
+}
+
+fn log_rule_changes(rule: Res<RuleSet>, automaton: Res<Automaton>) {
+    // the rule set at startup isn't a change
+    if (rule.is_changed() && !rule.is_added()) || (automaton.is_changed() && !automaton.is_added())
+    {
+        info!(
+            target: "conway::rule",
+            rule = %*rule,
+            automaton = ?*automaton,
+            "rule changed"
+        );
+    }
+}
+
+fn level_color(level: Level) -> egui::Color32 {
+    match level {
+        Level::ERROR => egui::Color32::LIGHT_RED,
+        Level::WARN => egui::Color32::GOLD,
+        Level::INFO => egui::Color32::LIGHT_GRAY,
+        _ => egui::Color32::GRAY,
+    }
+}
+
+fn log_viewer(
+    mut viewer: ResMut<LogViewer>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !viewer.open {
+        return;
+    }
+
+    let mut open = true;
+
+    egui::Window::new(strings.get("logs.title"))
+        .id(egui::Id::new("log viewer"))
+        .open(&mut open)
+        .default_width(520.0)
+        .show(egui_context.ctx_mut(), |ui| {
+            let viewer = &mut *viewer;
+
+            if !viewer.capturing {
+                ui.label(strings.get("logs.not-capturing"));
+                return;
+            }
+            ui.label(strings.format("logs.file", &[("path", &log_path(0).display().to_string())]));
+
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_id_source("log level")
+                    .selected_text(viewer.level.as_str())
+                    .show_ui(ui, |ui| {
+                        for level in [Level::ERROR, Level::WARN, Level::INFO, Level::DEBUG] {
+                            ui.selectable_value(&mut viewer.level, level, level.as_str());
+                        }
+                    });
+                ui.label(strings.get("logs.filter"));
+                ui.text_edit_singleline(&mut viewer.filter);
+            });
+
+            let Ok(journal) = viewer.journal.lock() else {
+                return;
+            };
+            let filter = viewer.filter.to_lowercase();
+
+            egui::ScrollArea::vertical()
+                .max_height(320.0)
+                .stick_to_bottom(true)
+                .show(ui, |ui| {
+                    for entry in &journal.entries {
+                        // more verbose levels compare greater
+                        if entry.level > viewer.level {
+                            continue;
+                        }
+
+                        let mut line = format!(
+                            "{:>8.2} {:<5} {}: {}",
+                            entry.time,
+                            entry.level.as_str(),
+                            entry.target,
+                            entry.message
+                        );
+                        for (name, value) in &entry.fields {
+                            line.push_str(&format!(" {name}={value}"));
+                        }
+                        if !filter.is_empty() && !line.to_lowercase().contains(&filter) {
+                            continue;
+                        }
+
+                        ui.colored_label(level_color(entry.level), line);
+                    }
+                });
+        });
+
+    if !open {
+        viewer.open = false;
+    }
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+
+use crate::bitboard::Bitboard;
+use crate::rle::Clip;
+use crate::rules::RuleSet;
+
+/// Most live cells a macrocell file may expand to. The board is flat, so a file packing
+/// a huge repetitive pattern into a few nodes can't be loaded whole.
+const MAX_CELLS: u64 = 1 << 22;
+/// Deepest tree whose side still fits in a u32. The cells it holds have to be much closer
+/// together than that, see `Clip::from_points`.
+const MAX_LEVEL: u32 = 32;
+
+/// A macrocell tree node. `Empty` only ever appears as child 0.
+enum Node {
+    Empty,
+    /// Two-state 8x8 leaf, one byte per row from the top, bit x set for live cells.
+    Leaf([u8; 8]),
+    /// Multi-state 2x2 leaf, as northwest, northeast, southwest and southeast states.
+    States([u8; 4]),
+    Branch {
+        level: u32,
+        children: [usize; 4],
+    },
+}
+
+impl Node {
+    fn level(&self) -> Option<u32> {
+        match self {
+            Node::Empty => None,
+            Node::Leaf(_) => Some(3),
+            Node::States(_) => Some(1),
+            Node::Branch { level, .. } => Some(*level),
+        }
+    }
+}
+
+/// Reads Golly's macrocell format: a tree of nodes, one per line and each numbered by its
+/// line, whose children are earlier lines or 0 for empty. The last node is the root. Live
+/// two-state cells are team 1 and multi-state cells keep their state as the team id.
+pub fn decode(text: &str) -> Result<Clip, String> {
+    let mut nodes = vec![Node::Empty];
+    let mut population: Vec<u64> = vec![0];
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('[') || line.starts_with('#') {
+            continue;
+        }
+
+        let (node, count) = if line.starts_with(['.', '*', '$']) {
+            parse_leaf(line)?
+        } else {
+            parse_branch(line, &nodes, &population)?
+        };
+        nodes.push(node);
+        population.push(count);
+    }
+
+    let root = nodes.len() - 1;
+    if population[root] > MAX_CELLS {
+        return Err(format!(
+            "the pattern has {} live cells, more than the {MAX_CELLS} that can be loaded",
+            population[root]
+        ));
+    }
+
+    let mut points = Vec::new();
+    expand(&nodes, &population, root, (0, 0), &mut points);
+    Clip::from_points(&points)
+}
+
+fn parse_leaf(line: &str) -> Result<(Node, u64), String> {
+    let mut rows = [0u8; 8];
+    let lines = line.strip_suffix('$').unwrap_or(line).split('$');
+
+    for (y, row) in lines.enumerate() {
+        if y >= 8 || row.len() > 8 {
+            return Err(format!("leaf `{line}` is bigger than 8x8"));
+        }
+
+        for (x, c) in row.chars().enumerate() {
+            match c {
+                '*' => rows[y] |= 1 << x,
+                '.' => {}
+                _ => return Err(format!("unexpected `{c}` in leaf `{line}`")),
+            }
+        }
+    }
+
+    let count = rows.iter().map(|row| row.count_ones() as u64).sum();
+    Ok((Node::Leaf(rows), count))
+}
+
+fn parse_branch(line: &str, nodes: &[Node], population: &[u64]) -> Result<(Node, u64), String> {
+    let invalid = || format!("invalid node `{line}`");
+    let numbers = line
+        .split_whitespace()
+        .map(|number| number.parse::<u64>().map_err(|_| invalid()))
+        .collect::<Result<Vec<_>, _>>()?;
+    let [level, a, b, c, d] = numbers[..] else {
+        return Err(invalid());
+    };
+
+    if level == 1 {
+        let states = [a, b, c, d].map(|state| state.min(u8::MAX as u64) as u8);
+        let count = states.iter().filter(|&&state| state != 0).count() as u64;
+        return Ok((Node::States(states), count));
+    }
+
+    if !(2..=MAX_LEVEL as u64).contains(&level) {
+        return Err(invalid());
+    }
+
+    let mut children = [0; 4];
+    let mut count = 0u64;
+    for (child, &index) in children.iter_mut().zip(&[a, b, c, d]) {
+        let index = index as usize;
+        let node = nodes.get(index).ok_or_else(invalid)?;
+
+        // children refer back to earlier nodes exactly one level down
+        if node
+            .level()
+            .is_some_and(|child_level| child_level as u64 != level - 1)
+        {
+            return Err(format!("node `{line}` mixes levels"));
+        }
+
+        *child = index;
+        count = count.saturating_add(population[index]);
+    }
+
+    Ok((
+        Node::Branch {
+            level: level as u32,
+            children,
+        },
+        count,
+    ))
+}
+
+/// Collects the live cells of `index` with its top left corner at `corner`, y down.
+fn expand(
+    nodes: &[Node],
+    population: &[u64],
+    index: usize,
+    corner: (i64, i64),
+    points: &mut Vec<(i64, i64, u8)>,
+) {
+    // a deep chain of branches over nothing but empty leaves would take forever to walk
+    if population[index] == 0 {
+        return;
+    }
+    let (left, top) = corner;
+
+    match &nodes[index] {
+        Node::Empty => {}
+        Node::Leaf(rows) => {
+            for (y, row) in rows.iter().enumerate() {
+                for x in (0..8).filter(|x| row & 1 << x != 0) {
+                    points.push((left + x, top + y as i64, 2));
+                }
+            }
+        }
+        Node::States(states) => {
+            let offsets = [(0, 0), (1, 0), (0, 1), (1, 1)];
+            for (&state, (x, y)) in states.iter().zip(offsets) {
+                if state != 0 {
+                    points.push((left + x, top + y, state));
+                }
+            }
+        }
+        Node::Branch { level, children } => {
+            let half = 1i64 << (level - 1);
+            let offsets = [(0, 0), (half, 0), (0, half), (half, half)];
+
+            for (&child, (x, y)) in children.iter().zip(offsets) {
+                expand(nodes, population, child, (left + x, top + y), points);
+            }
+        }
+    }
+}
+
+/// Writes the (left, bottom, width, height) block of the board as macrocell, sharing
+/// every repeated node. Blocks holding only team 1 use two-state 8x8 leaves, anything
+/// else multi-state leaves with the team ids as states.
+pub fn encode(board: &Bitboard, area: (u32, u32, u32, u32), rule: &RuleSet) -> String {
+    let (left, bottom, width, height) = area;
+    let two_state = (0..height)
+        .flat_map(|y| (0..width).map(move |x| (x, y)))
+        .all(|(x, y)| matches!(board.get(left + x, bottom + y), 0 | 2));
+
+    let mut tree = Tree {
+        board,
+        area,
+        leaf_level: if two_state { 3 } else { 1 },
+        lines: Vec::new(),
+        index: HashMap::new(),
+    };
+
+    let side = width.max(height).max(1).next_power_of_two();
+    let level = side.trailing_zeros().max(tree.leaf_level + 1);
+    tree.node((0, 0), level);
+
+    let mut text = format!("[M2] (conway_bevy)\n#R {rule}\n");
+    for line in &tree.lines {
+        text.push_str(line);
+        text.push('\n');
+    }
+    text
+}
+
+struct Tree<'a> {
+    board: &'a Bitboard,
+    area: (u32, u32, u32, u32),
+    leaf_level: u32,
+    lines: Vec<String>,
+    index: HashMap<String, usize>,
+}
+
+impl Tree<'_> {
+    /// The cell at (x, y) from the area's top left corner, y down, or 0 outside the area.
+    fn cell(&self, x: u64, y: u64) -> u8 {
+        let (left, bottom, width, height) = self.area;
+
+        if x < width as u64 && y < height as u64 {
+            self.board
+                .get(left + x as u32, bottom + height - 1 - y as u32)
+        } else {
+            0
+        }
+    }
+
+    /// Writes the node covering the 2^level square at `corner` and its children, returning
+    /// its line number, or 0 if it's empty.
+    fn node(&mut self, (x0, y0): (u64, u64), level: u32) -> usize {
+        let line = if level == 1 && self.leaf_level == 1 {
+            let states = [(0, 0), (1, 0), (0, 1), (1, 1)].map(|(x, y)| self.cell(x0 + x, y0 + y));
+            if states == [0; 4] {
+                return 0;
+            }
+
+            format!("1 {} {} {} {}", states[0], states[1], states[2], states[3])
+        } else if level == 3 && self.leaf_level == 3 {
+            let rows: Vec<String> = (0..8)
+                .map(|y| {
+                    let row: String = (0..8)
+                        .map(|x| {
+                            if self.cell(x0 + x, y0 + y) != 0 {
+                                '*'
+                            } else {
+                                '.'
+                            }
+                        })
+                        .collect();
+                    row.trim_end_matches('.').to_owned()
+                })
+                .collect();
+            let Some(last) = rows.iter().rposition(|row| !row.is_empty()) else {
+                return 0;
+            };
+
+            rows[..=last].iter().map(|row| format!("{row}$")).collect()
+        } else {
+            let half = 1u64 << (level - 1);
+            let offsets = [(0, 0), (half, 0), (0, half), (half, half)];
+            let children = offsets.map(|(x, y)| self.node((x0 + x, y0 + y), level - 1));
+            if children == [0; 4] {
+                return 0;
+            }
+
+            format!(
+                "{level} {} {} {} {}",
+                children[0], children[1], children[2], children[3]
+            )
+        };
+
+        if let Some(&index) = self.index.get(&line) {
+            return index;
+        }
+
+        self.lines.push(line.clone());
+        self.index.insert(line, self.lines.len());
+        self.lines.len()
+    }
+}
@@ -1,36 +1,329 @@
-use bevy::input::Input;
+mod ai;
+mod ants;
+mod autosave;
+mod balance;
+mod bench;
+mod camera;
+mod camera_path;
+mod census;
+mod championship;
+mod clipboard;
+mod compare;
+mod cyclic;
+mod demo;
+mod detach;
+mod diagnostics;
+mod diff;
+mod display;
+mod divergence;
+mod eden;
+mod energy;
+mod errors;
+mod evolution;
+mod fog;
+mod follow;
+mod freeze;
+mod front;
+mod gun;
+mod hooks;
+mod hotkeys;
+mod hud;
+mod image_import;
+mod inspect;
+mod lang;
+mod lod;
+mod logs;
+mod markers;
+mod measure;
+mod overlay;
+mod pattern_files;
+mod picture;
+mod pipe;
+#[cfg(feature = "remote")]
+mod remote;
+mod replays;
+mod rule_editor;
+mod saves;
+mod scenario;
+mod script;
+mod session;
+mod shapes;
+mod share;
+mod slowmo;
+mod spawners;
+mod stack;
+mod stats;
+mod subboard;
+mod svg;
+mod team_rules;
+mod terrain;
+mod tools;
+mod tournament;
+mod tutorial;
+mod undo;
+mod wrap;
+mod zones;
+
+use std::collections::VecDeque;
+use std::marker::PhantomData;
+use std::time::{Duration, Instant};
+
+use ai::AiPlugin;
+use ants::{Ant, AntPlugin, AntSettings};
+use autosave::AutosavePlugin;
+use balance::{BalancePlugin, PopulationCap};
+use bevy::ecs::schedule::ShouldRun;
+use bevy::ecs::system::SystemParam;
+use bevy::log::LogPlugin;
 use bevy::prelude::*;
 use bevy::time::Stopwatch;
-use bevy_ecs_tilemap::helpers::square_grid::neighbors::Neighbors;
 use bevy_ecs_tilemap::prelude::*;
+use camera::{CameraPlugin, MainCamera};
+use camera_path::CameraPathPlugin;
+use championship::ChampionshipPlugin;
+use clipboard::ClipboardPlugin;
+use compare::ComparePlugin;
+use conway_bevy::bitboard::{Bitboard, Boundary};
+use conway_bevy::simulation::{apply_overrides, StepOverride};
+use conway_bevy::{
+    bitboard, evolve, layers, macrocell, patterns, predecessor, raster, replay, rle, rng, rules,
+    simulation, snapshot,
+};
+use cyclic::CyclicPlugin;
+use demo::DemoPlugin;
+use detach::DetachPlugin;
+use diagnostics::DiagnosticsOverlayPlugin;
+use diff::DiffPlugin;
+use display::DisplayPlugin;
+use divergence::DivergencePlugin;
+use eden::EdenPlugin;
+use energy::EnergyPlugin;
+use errors::{AppError, ErrorsPlugin};
+use evolution::EvolutionPlugin;
+use fog::{Fog, FogPlugin};
+use follow::FollowPlugin;
+use freeze::{FreezePlugin, Frozen};
+use front::{DeathReports, FrontLinePlugin};
+use gun::GunPlugin;
+use hooks::Hooks;
+use hotkeys::{Hotkeys, HotkeysPlugin};
+use hud::HudPlugin;
+use image_import::ImageImportPlugin;
+use inspect::InspectPlugin;
+use lang::LangPlugin;
+use lod::{Lod, LodPlugin};
+use logs::LogsPlugin;
+use markers::MarkerPlugin;
+use measure::MeasurePlugin;
+use picture::PicturePlugin;
+use replays::ReplayPlugin;
+use rng::Rng;
+use rule_editor::RuleEditorPlugin;
+use rules::{Automaton, Chances, Neighborhood, RuleSet, PRESETS};
+use saves::SavesPlugin;
+use scenario::ScenarioPlugin;
+use script::{ScriptPlugin, ScriptRule};
+use session::SessionPlugin;
+use shapes::ShapesPlugin;
+use slowmo::SlowMotionPlugin;
+use spawners::{Spawner, SpawnerPlugin};
+use stack::{StackPlugin, UpperLayer};
+use stats::{StatsPlugin, TeamStats};
+use subboard::SubBoardPlugin;
+use team_rules::{TeamRules, TeamRulesPlugin};
+use terrain::{Terrain, TerrainPlugin};
+use tools::ToolsPlugin;
+use tournament::TournamentPlugin;
+use tutorial::TutorialPlugin;
+use undo::UndoPlugin;
+use wrap::WrapPreviewPlugin;
+use zones::{Zones, ZonesPlugin};
 
 const MAP_SIZE: (u32, u32) = (64, 64);
 const CELL_SIZE: f32 = 8.0;
+const HEX_CELL_HEIGHT: f32 = 9.0; // pointy-top hex that's CELL_SIZE wide
 const TEAM_COLORS: [Color; 4] = [
     Color::WHITE,         // empty, shouldn't be visible
     Color::YELLOW_GREEN,  // neither
     Color::MIDNIGHT_BLUE, // team 1
     Color::PINK,          // team 2
 ];
+const WALL_COLOR: Color = Color::DARK_GRAY;
+const FOG_COLOR: Color = Color::rgb(0.08, 0.08, 0.1);
+const SIM_SEED: u64 = 0;
+
+/// Whether the board is in free play or a scenario, and how the scenario is going.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum GameState {
+    Sandbox,
+    ScenarioSelect,
+    Playing,
+    Won,
+    Lost,
+    /// A round of a `Tournament` match is being played.
+    Round,
+    RoundOver,
+    MatchOver,
+    /// The players are designing their seeds for a championship round.
+    Designing,
+    /// The idle attract mode, pushed over the sandbox.
+    Demo,
+}
+
+#[derive(Resource, Deref, DerefMut)]
+struct Board(Bitboard);
+
+/// Colors for each team or cyclic state, indexed by the value `Bitboard::get` returns.
+#[derive(Resource, Deref, DerefMut)]
+struct Palette(Vec<Color>);
+
+impl Default for Palette {
+    fn default() -> Self {
+        Palette(TEAM_COLORS.to_vec())
+    }
+}
 
-#[derive(Component, Clone, Copy, Debug)]
-struct Cell(usize, usize); // team, new team
+/// Randomness used by the simulation itself, seeded so runs can be replayed.
+#[derive(Resource, Deref, DerefMut)]
+struct SimRng(Rng);
 
 #[derive(Resource)]
 struct TickDuration(Stopwatch, f64);
 
+/// When enabled, ignores the tick duration and runs as many generations as fit in
+/// `frame_budget` every frame, syncing the tiles once afterwards.
+#[derive(Resource)]
+struct UnlimitedSpeed {
+    enabled: bool,
+    frame_budget: Duration,
+    /// How long auto speed wants each whole frame to take, drawing included, if it's on.
+    /// It then runs however many generations it measured will fit instead of stepping
+    /// for `frame_budget`, so a big board doesn't stutter and a small one isn't held back.
+    auto: Option<Duration>,
+}
+
+/// Most generations auto speed runs in one frame.
+const MAX_AUTO_BATCH: f64 = 1_000_000.0;
+
+/// What auto speed measured of the last few frames.
+struct AutoSpeed {
+    /// Generations the next frame runs.
+    batch: f64,
+    /// Seconds one generation takes to step, averaged over recent frames.
+    step_cost: Option<f64>,
+    /// Seconds the last frame spent stepping.
+    stepped: f64,
+}
+
+impl Default for AutoSpeed {
+    fn default() -> Self {
+        Self {
+            batch: 1.0,
+            step_cost: None,
+            stepped: 0.0,
+        }
+    }
+}
+
+impl AutoSpeed {
+    /// Sizes the next batch to fill what `target` leaves after the rest of the last frame,
+    /// which took `frame` seconds in all, given that this one's `generations` took
+    /// `stepped` seconds.
+    fn adapt(&mut self, frame: f64, target: f64, stepped: f64, generations: u32) {
+        if generations > 0 {
+            let cost = stepped / generations as f64;
+            self.step_cost = Some(self.step_cost.map_or(cost, |old| old * 0.8 + cost * 0.2));
+        }
+        let rest = (frame - self.stepped).max(0.0);
+        self.stepped = stepped;
+
+        let Some(cost) = self.step_cost.filter(|&cost| cost > 0.0) else {
+            return;
+        };
+        // halfway there each frame, so one slow frame doesn't throw it off
+        let ideal = (target - rest).max(0.0) / cost;
+        self.batch = (self.batch + (ideal - self.batch) / 2.0).clamp(1.0, MAX_AUTO_BATCH);
+    }
+}
+
+/// While `key` is held, ticks come `factor` times as often, on top of whatever the tick
+/// duration is. Unlimited speed and Run to already go as fast as they can, so they ignore it.
+#[derive(Resource)]
+struct FastForward {
+    key: KeyCode,
+    factor: f64,
+    held: bool,
+}
+
+impl Default for FastForward {
+    fn default() -> Self {
+        Self {
+            key: KeyCode::Tab,
+            factor: 8.0,
+            held: false,
+        }
+    }
+}
+
+impl FastForward {
+    /// How many times faster than the tick duration generations are due right now.
+    fn speedup(&self) -> f64 {
+        if self.held {
+            self.factor
+        } else {
+            1.0
+        }
+    }
+}
+
+/// A generation to run to as fast as possible, pausing once it's reached. Set from the
+/// HUD or with `--run-to <generation>` on the command line.
+#[derive(Resource, Default)]
+struct RunTo(Option<u64>);
+
+#[derive(Resource, Default)]
+struct SimCounters {
+    generation: u64,
+    generations_per_second: f64,
+    window: Stopwatch,
+    window_generations: u32,
+}
+
 fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Some(bench) = args.iter().position(|arg| arg == "--bench") {
+        bench::run(&args[bench + 1..]);
+    }
+    if args.iter().any(|arg| arg == "--pipe") {
+        pipe::run();
+    }
+
+    let run_to = args.iter().position(|arg| arg == "--run-to").map(|flag| {
+        let value = args.get(flag + 1).map_or("", String::as_str);
+        value.parse().unwrap_or_else(|_| {
+            eprintln!("invalid generation `{value}`\nusage: conway_bevy [--run-to <generation>]");
+            std::process::exit(2);
+        })
+    });
+
+    let mut plugins = DefaultPlugins.set(WindowPlugin {
+        window: WindowDescriptor {
+            width: 512.0,
+            height: 512.0,
+            title: "Conway".to_owned(),
+            ..Default::default()
+        },
+        ..default()
+    });
+    if !logs::PROFILING {
+        plugins = plugins.disable::<LogPlugin>();
+    }
+
     App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            window: WindowDescriptor {
-                width: 512.0,
-                height: 512.0,
-                title: "Conway".to_owned(),
-                ..Default::default()
-            },
-            ..default()
-        }))
+        .add_plugin(LogsPlugin)
+        .add_plugins(plugins)
         .add_plugin(GamePlugin)
+        .insert_resource(RunTo(run_to))
         .run();
 }
 
@@ -39,51 +332,145 @@ pub struct GamePlugin;
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.add_plugin(TilemapPlugin)
+            .add_plugin(LangPlugin)
+            .add_plugin(HotkeysPlugin)
+            .add_plugin(ErrorsPlugin)
+            .add_plugin(HudPlugin)
+            .add_plugin(DiagnosticsOverlayPlugin)
+            .add_plugin(CameraPlugin)
+            .add_plugin(DisplayPlugin)
+            .add_plugin(LodPlugin)
+            .add_plugin(ToolsPlugin)
+            .add_plugin(SpawnerPlugin)
+            .add_plugin(CyclicPlugin)
+            .add_plugin(AntPlugin)
+            .add_plugin(ScriptPlugin)
+            .add_plugin(ScenarioPlugin)
+            .add_plugin(EnergyPlugin)
+            .add_plugin(FogPlugin)
+            .add_plugin(AiPlugin)
+            .add_plugin(TournamentPlugin)
+            .add_plugin(ChampionshipPlugin)
+            .add_plugin(DemoPlugin)
+            .add_plugin(ComparePlugin)
+            .add_plugin(ClipboardPlugin)
+            .add_plugin(AutosavePlugin)
+            .add_plugin(UndoPlugin)
+            .add_plugin(ShapesPlugin)
+            .add_plugin(StatsPlugin)
+            .add_plugin(MarkerPlugin)
+            .add_plugin(MeasurePlugin)
+            .add_plugin(InspectPlugin)
+            .add_plugin(DiffPlugin)
+            .add_plugin(FreezePlugin)
+            .add_plugin(SubBoardPlugin)
+            .add_plugin(WrapPreviewPlugin)
+            .add_plugin(SlowMotionPlugin)
+            .add_plugin(TerrainPlugin)
+            .add_plugin(StackPlugin)
+            .add_plugin(BalancePlugin)
+            .add_plugin(FollowPlugin)
+            .add_plugin(CameraPathPlugin)
+            .add_plugin(SavesPlugin)
+            .add_plugin(SessionPlugin)
+            .add_plugin(TutorialPlugin)
+            .add_plugin(RuleEditorPlugin)
+            .add_plugin(DivergencePlugin)
+            .add_plugin(TeamRulesPlugin)
+            .add_plugin(ZonesPlugin)
+            .add_plugin(EdenPlugin)
+            .add_plugin(GunPlugin)
+            .add_plugin(ReplayPlugin)
+            .add_plugin(DetachPlugin)
+            .add_plugin(PicturePlugin)
+            .add_plugin(ImageImportPlugin)
+            .add_plugin(EvolutionPlugin)
+            .add_plugin(FrontLinePlugin)
+            .add_state(GameState::Sandbox)
             .insert_resource(TickDuration(Stopwatch::default(), 0.05))
+            .insert_resource(UnlimitedSpeed {
+                enabled: false,
+                frame_budget: Duration::from_millis(12),
+                auto: None,
+            })
+            .init_resource::<SimCounters>()
+            .init_resource::<RunTo>()
+            .init_resource::<FastForward>()
+            .init_resource::<RuleSet>()
+            .init_resource::<Chances>()
+            .init_resource::<Automaton>()
+            .init_resource::<Palette>()
+            .insert_resource(SimRng(Rng::new(SIM_SEED)))
+            .insert_resource(Board(Bitboard::new(MAP_SIZE.0, MAP_SIZE.1)))
             .add_startup_system(startup)
-            .add_system(update_map)
-            .add_system(mouse_input)
+            .add_system(update_map.with_run_criteria(simulating))
+            .add_event::<RebuildTilemap>()
+            .add_system(sync_tiles.after(update_map))
+            .add_system(rebuild_tilemap.after(sync_tiles))
             .add_system(keyboard_input);
+
+        #[cfg(feature = "remote")]
+        app.add_plugin(remote::RemotePlugin);
     }
 }
 
-fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(Camera2dBundle::default());
+fn startup(mut commands: Commands, asset_server: Res<AssetServer>, looks: CellLooks) {
+    commands.spawn((Camera2dBundle::default(), MainCamera));
 
-    let texture_handle: Handle<Image> = asset_server.load("tile.png");
+    spawn_tilemap(&mut commands, &asset_server, &looks, Neighborhood::Moore);
+}
+
+fn spawn_tilemap(
+    commands: &mut Commands,
+    asset_server: &AssetServer,
+    looks: &CellLooks,
+    neighborhood: Neighborhood,
+) {
+    let (texture_handle, map_type, tile_size): (Handle<Image>, _, _) = match neighborhood {
+        Neighborhood::Moore => (
+            asset_server.load("tile.png"),
+            TilemapType::Square,
+            TilemapTileSize {
+                x: CELL_SIZE,
+                y: CELL_SIZE,
+            },
+        ),
+        Neighborhood::Hex => (
+            asset_server.load("hex_tile.png"),
+            TilemapType::Hexagon(HexCoordSystem::RowOdd),
+            TilemapTileSize {
+                x: CELL_SIZE,
+                y: HEX_CELL_HEIGHT,
+            },
+        ),
+    };
 
     let map_size = TilemapSize {
-        x: MAP_SIZE.0,
-        y: MAP_SIZE.1,
+        x: looks.board.width(),
+        y: looks.board.height(),
     };
     let mut tile_storage = TileStorage::empty(map_size);
 
-    let map_type = TilemapType::Square;
-
     let tilemap_entity = commands.spawn_empty().id();
 
     for x in 0..map_size.x {
         for y in 0..map_size.y {
             let tile_pos = TilePos { x, y };
+            let (visible, color) = looks.look(x, y);
             let tile_entity = commands
                 .spawn(TileBundle {
                     position: tile_pos,
                     tilemap_id: TilemapId(tilemap_entity),
-                    color: TileColor(TEAM_COLORS[0]),
-                    visible: TileVisible(false),
+                    color,
+                    visible,
                     ..Default::default()
                 })
-                .insert(Cell(0, 0))
                 .id();
 
             tile_storage.set(&tile_pos, tile_entity);
         }
     }
 
-    let tile_size = TilemapTileSize {
-        x: CELL_SIZE,
-        y: CELL_SIZE,
-    };
     let grid_size = tile_size.into();
 
     commands.entity(tilemap_entity).insert(TilemapBundle {
@@ -98,151 +485,463 @@ fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
     });
 }
 
-fn update_map(
-    time: Res<Time>,
-    mut ticker: ResMut<TickDuration>,
-    mut tilemap_query: Query<(&TileStorage, &TilemapSize)>,
-    mut tile_query: Query<(&mut TileVisible, &mut TileColor, &mut Cell)>,
+/// Asks for the tilemap to be spawned again from scratch, after tiles went missing.
+struct RebuildTilemap;
+
+/// Swaps the tilemap for one of the right shape when the rule's neighborhood changes, and
+/// for a fresh one when asked to.
+fn rebuild_tilemap(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    rule: Res<RuleSet>,
+    looks: CellLooks,
+    mut rebuilds: EventReader<RebuildTilemap>,
+    tilemap_query: Query<(Entity, &TileStorage, &TilemapType)>,
 ) {
-    if ticker.0.tick(time.delta()).elapsed_secs_f64() < ticker.1 {
+    let asked = rebuilds.iter().count() > 0;
+    if !rule.is_changed() && !asked {
         return;
     }
 
-    ticker.0.reset();
+    for (tilemap_entity, tile_storage, map_type) in tilemap_query.iter() {
+        let neighborhood = match map_type {
+            TilemapType::Hexagon(_) => Neighborhood::Hex,
+            _ => Neighborhood::Moore,
+        };
 
-    for (tile_storage, map_size) in tilemap_query.iter_mut() {
-        // first loop to move cell.1 to cell.0, to actually update them
-        for x in 0..map_size.x {
-            for y in 0..map_size.y {
-                let cell = tile_storage.get(&TilePos { x, y }).unwrap();
-                let (mut visible, mut color, mut cell) = tile_query
-                    .get_mut(cell)
-                    .expect(&format!("Tile ({x},{y}) was not a Cell component"));
+        if neighborhood == rule.neighborhood && !asked {
+            continue;
+        }
 
-                *visible = TileVisible(cell.1 != 0);
-                *color = TileColor(TEAM_COLORS[cell.1]);
+        for tile_entity in tile_storage.iter().flatten() {
+            commands.entity(*tile_entity).despawn();
+        }
+        commands.entity(tilemap_entity).despawn();
 
-                cell.0 = cell.1;
-                cell.1 = 0;
+        spawn_tilemap(&mut commands, &asset_server, &looks, rule.neighborhood);
+    }
+}
+
+/// The board only runs in free play, the idle demo and while a scenario or match round is
+/// being played, so it holds still in menus and once a scenario or round is decided.
+fn simulating(state: Res<State<GameState>>) -> ShouldRun {
+    match state.current() {
+        GameState::Sandbox | GameState::Playing | GameState::Round | GameState::Demo => {
+            ShouldRun::Yes
+        }
+        _ => ShouldRun::No,
+    }
+}
+
+fn update_map(
+    time: Res<Time>,
+    (mut ticker, fast_forward): (ResMut<TickDuration>, Res<FastForward>),
+    (unlimited, mut run_to): (Res<UnlimitedSpeed>, ResMut<RunTo>),
+    mut sim: Simulation,
+    mut auto: Local<AutoSpeed>,
+) {
+    let mut generations = 0;
+    let mut auto_stepped = false;
+
+    if let Some(target) = run_to.0 {
+        // runs even while paused, in batches like unlimited speed, and stops exactly on target
+        let start = Instant::now();
+
+        while sim.counters.generation < target
+            && (generations == 0 || start.elapsed() < unlimited.frame_budget)
+            && !sim.pause_requested()
+        {
+            sim.step_generation();
+            generations += 1;
+        }
+
+        if sim.counters.generation >= target {
+            run_to.0 = None;
+            ticker.0.pause();
+        }
+    } else if let (true, Some(target)) = (unlimited.enabled, unlimited.auto) {
+        if !ticker.0.paused() {
+            // the target is still a hard limit, for when the board suddenly gets costlier
+            let start = Instant::now();
+            let batch = auto.batch as u32;
+
+            while generations < batch
+                && (generations == 0 || start.elapsed() < target)
+                && !sim.pause_requested()
+            {
+                sim.step_generation();
+                generations += 1;
+            }
+
+            auto.adapt(
+                time.delta_seconds_f64(),
+                target.as_secs_f64(),
+                start.elapsed().as_secs_f64(),
+                generations,
+            );
+            auto_stepped = true;
+        }
+    } else if unlimited.enabled {
+        if !ticker.0.paused() {
+            let start = Instant::now();
+
+            while (generations == 0 || start.elapsed() < unlimited.frame_budget)
+                && !sim.pause_requested()
+            {
+                sim.step_generation();
+                generations += 1;
             }
         }
+    } else if ticker.0.tick(time.delta()).elapsed_secs_f64() >= ticker.1 / fast_forward.speedup() {
+        // sped-up ticks can be shorter than a frame, so every tick that fell due is run
+        let due = (ticker.0.elapsed_secs_f64() * fast_forward.speedup() / ticker.1) as u32;
+        ticker.0.reset();
+
+        let start = Instant::now();
+        while generations < due.max(1)
+            && (generations == 0 || start.elapsed() < unlimited.frame_budget)
+            && !sim.pause_requested()
+        {
+            sim.step_generation();
+            generations += 1;
+        }
+    }
 
-        // second loop to update for next time
-        for x in 0..map_size.x {
-            for y in 0..map_size.y {
-                let tile_pos = &TilePos { x, y };
-                let neighbors =
-                    Neighbors::get_square_neighboring_positions(tile_pos, map_size, true)
-                        .entities(tile_storage);
-
-                let (team, neighbors) = {
-                    let neighbors = neighbors
-                        .iter()
-                        .filter(|&c| {
-                            if let Ok((_, _, cell)) = tile_query.get(*c) {
-                                cell.0 != 0
-                            } else {
-                                false
-                            }
-                        })
-                        .map(|n| {
-                            let (_, _, cell) = tile_query
-                                .get(*n)
-                                .expect(&format!("Tile ({x},{y}) is not a Cell component"));
-
-                            cell
-                        });
-
-                    let mut team = 0;
-                    let mut count = 0;
-
-                    for neighbor in neighbors {
-                        count += 1;
-
-                        if neighbor.0 == 1 {
-                            continue;
-                        } else if team == 0 {
-                            // set team to the first team of any found neighbor
-                            team = neighbor.0;
-                        } else if team != neighbor.0 {
-                            // if a neighbor is found with a different team than the first one, change team to neither and leave the loop
-                            // keep going to get the full count
-                            team = 1;
-                        }
-                    }
+    // the next frame after any other kind isn't held to what this one spent stepping
+    if !auto_stepped {
+        auto.stepped = 0.0;
+    }
 
-                    if team == 0 {
-                        team = 1;
-                    }
+    if sim.take_pause() {
+        run_to.0 = None;
+        ticker.0.pause();
+    }
 
-                    (team, count)
-                };
+    let counters = &mut sim.counters;
+    counters.window_generations += generations;
 
-                let cell = tile_storage.get(tile_pos).unwrap();
-                let (_, _, mut cell) = tile_query
-                    .get_mut(cell)
-                    .expect(&format!("Tile ({x},{y}) is not a Cell component"));
+    let elapsed = counters.window.tick(time.delta()).elapsed_secs_f64();
+    if elapsed >= 1.0 {
+        counters.generations_per_second = counters.window_generations as f64 / elapsed;
+        counters.window_generations = 0;
+        counters.window.reset();
+    }
+}
+
+/// Everything stepping the simulation touches.
+#[derive(SystemParam)]
+struct Simulation<'w, 's> {
+    board: ResMut<'w, Board>,
+    counters: ResMut<'w, SimCounters>,
+    rule: Res<'w, RuleSet>,
+    automaton: Res<'w, Automaton>,
+    script: ResMut<'w, ScriptRule>,
+    chances: Res<'w, Chances>,
+    rng: ResMut<'w, SimRng>,
+    spawner_query: Query<'w, 's, &'static Spawner>,
+    ant_settings: Res<'w, AntSettings>,
+    ant_query: Query<'w, 's, &'static mut Ant>,
+    observers: Observers<'w, 's>,
+    frozen: Res<'w, Frozen>,
+    local_rules: LocalRules<'w, 's>,
+    cap: Res<'w, PopulationCap>,
+    upper: ResMut<'w, UpperLayer>,
+}
 
-                if cell.0 != 0 && neighbors == 2 || neighbors == 3 {
-                    cell.1 = team;
-                } else {
-                    cell.1 = 0;
+/// Whatever is told about each generation once it's been stepped.
+#[derive(SystemParam)]
+struct Observers<'w, 's> {
+    stats: ResMut<'w, TeamStats>,
+    hooks: ResMut<'w, Hooks>,
+    deaths: DeathReports<'w, 's>,
+}
+
+/// Whatever overrides the board's rule for some of its cells, applied in order after each
+/// Life step so a zone's rule wins over a team's, and terrain bends the births of both.
+#[derive(SystemParam)]
+struct LocalRules<'w, 's> {
+    team_rules: Res<'w, TeamRules>,
+    zones: Res<'w, Zones>,
+    terrain: Res<'w, Terrain>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
+}
+
+impl LocalRules<'_, '_> {
+    fn overrides(&self) -> [&dyn StepOverride; 3] {
+        [&*self.team_rules, &*self.zones, &*self.terrain]
+    }
+
+    fn is_active(&self) -> bool {
+        self.overrides().iter().any(|o| o.is_active())
+    }
+}
+
+impl Simulation<'_, '_> {
+    /// Everything that happens once per generation, however many generations run per frame.
+    fn step_generation(&mut self) {
+        let _span = info_span!("step_generation").entered();
+
+        // only kept while the stats want each generation counted, for hooks, to redo
+        // local rules, block births over the population cap, couple a stacked layer, to
+        // put frozen regions back or to mark the front line
+        let before = (self.observers.stats.counts_generations()
+            || self.observers.deaths.is_wanted()
+            || self.observers.hooks.is_loaded()
+            || self.local_rules.is_active()
+            || self.cap.is_active()
+            || self.upper.is_active()
+            || !self.frozen.is_empty())
+        .then(|| self.board.0.clone());
+
+        // with Life turned off, the ants have the board to themselves
+        if self.ant_settings.life || self.ant_query.is_empty() {
+            self.step_rule();
+
+            // kept whenever any of these has something to do
+            if let Some(before) = &before {
+                if *self.automaton == Automaton::Life {
+                    let overrides = self.local_rules.overrides();
+                    apply_overrides(before, &mut self.board, &self.rule, &overrides);
+                    if self.cap.is_active() {
+                        let blocked = self.cap.apply(before, &mut self.board);
+                        self.observers.stats.count_blocked(blocked);
+                    }
+                }
+                if self.upper.is_active() {
+                    self.upper.step(before, &mut self.board);
+                }
+                for &area in &self.frozen.regions {
+                    self.board.copy_area(before, area);
                 }
             }
         }
+        self.counters.generation += 1;
+
+        let _span = info_span!("spawners_and_ants").entered();
+        for spawner in self.spawner_query.iter() {
+            spawner.emit(&mut self.board, self.counters.generation);
+        }
+
+        for mut ant in self.ant_query.iter_mut() {
+            ant.step(&mut self.board);
+        }
+
+        if let Some(before) = before {
+            if self.observers.stats.counts_generations() {
+                self.observers
+                    .stats
+                    .count_generation(&before, &self.board.0);
+            }
+            self.observers
+                .deaths
+                .report(&before, &self.board, self.rule.neighborhood);
+            let generation = self.counters.generation;
+            self.observers
+                .hooks
+                .after_generation(&before, &mut self.board.0, generation);
+        }
     }
-}
 
-fn mouse_input(
-    mouse: Res<Input<MouseButton>>,
-    keys: Res<Input<KeyCode>>,
-    windows: Res<Windows>,
-    tilemap_query: Query<(&TileStorage, &TilemapSize)>,
-    mut tile_query: Query<(&mut TileVisible, &mut TileColor, &mut Cell)>,
-) {
-    if mouse.just_pressed(MouseButton::Left) {
-        let window = windows.get_primary().unwrap();
-        let Some(position) = window.cursor_position() else { return };
+    /// Whether a hook or the board going quiet asked for the simulation to stop.
+    fn pause_requested(&self) -> bool {
+        self.observers.hooks.pause_requested() || self.observers.stats.activity.stop_requested()
+    }
 
-        let (x, y) = (
-            (position.x / CELL_SIZE).round() as u32,
-            (position.y / CELL_SIZE).round() as u32,
-        );
+    /// Clears both kinds of pause request, returning whether there was one.
+    fn take_pause(&mut self) -> bool {
+        let hooks = self.observers.hooks.take_pause();
+        self.observers.stats.activity.take_stop() || hooks
+    }
 
-        let (tile_storage, map_size) = tilemap_query.single();
+    fn step_rule(&mut self) {
+        let _span = info_span!("step_rule").entered();
 
-        if x >= map_size.x || y >= map_size.y {
-            return;
+        if let Automaton::Cyclic(cyclic) = *self.automaton {
+            self.board.step_cyclic(&cyclic, self.rule.neighborhood);
+        } else if *self.automaton == Automaton::Scripted {
+            self.script.step(&mut self.board, self.rule.neighborhood);
+        } else if self.chances.is_certain() {
+            self.board.step(&self.rule);
+        } else {
+            self.board
+                .step_stochastic(&self.rule, &self.chances, &mut self.rng);
         }
+    }
+}
 
-        // can unwrap because we check bounds in the previous statement
-        let cell = tile_storage.get(&TilePos { x, y }).unwrap();
-        let (mut visible, mut color, mut cell) = tile_query
-            .get_mut(cell)
-            .expect(&format!("Tile ({x},{y}) is not a Cell component"));
+/// Longest a frame spends bringing tiles in line with the board. A generation changing
+/// more tiles than that allows is drawn over the next few frames.
+const SYNC_BUDGET: Duration = Duration::from_millis(4);
+
+/// Tiles waiting to be brought in line with the board, with the board as it was when they
+/// were queued so the next change can be diffed against it.
+#[derive(Default)]
+struct TileQueue {
+    queued_from: Option<Bitboard>,
+    pending: VecDeque<(u32, u32)>,
+    /// Whether each tile is already pending, indexed like `Bitboard::get`.
+    is_pending: Vec<bool>,
+}
 
-        let team = if keys.pressed(KeyCode::LControl) {
-            if cell.0 == 3 {
-                0
-            } else {
-                3
+impl TileQueue {
+    fn push(&mut self, (x, y): (u32, u32), width: u32) {
+        let pending = &mut self.is_pending[(y * width + x) as usize];
+        if !*pending {
+            *pending = true;
+            self.pending.push_back((x, y));
+        }
+    }
+}
+
+/// Brings the tiles in line with the board, touching only the tiles of cells that changed
+/// so unchanged tiles don't read as changed to the renderer. Every tile is queued when the
+/// palette or fog changes, the board changes size or the tilemap comes back from being
+/// zoomed out. Each frame works through the queue for at most `SYNC_BUDGET`, always
+/// drawing cells as they are now, so a huge generation catches up over a few frames
+/// instead of hitching. Tiles that have gone missing are reported and the tilemap is
+/// spawned again, rather than the board going undrawn where they were.
+fn sync_tiles(
+    looks: CellLooks,
+    lod: Res<Lod>,
+    mut queue: Local<TileQueue>,
+    (mut errors, mut rebuilds): (EventWriter<AppError>, EventWriter<RebuildTilemap>),
+    storage_query: Query<&TileStorage>,
+    mut tile_query: Query<(&TilePos, &mut TileVisible, &mut TileColor)>,
+) {
+    // the tiles aren't drawn while zoomed out, so catch them up once the tilemap is back
+    if lod.active {
+        queue.queued_from = None;
+        return;
+    }
+
+    let board = &looks.board;
+    let (width, height) = (board.width(), board.height());
+    let full = looks.palette.is_changed()
+        || looks.fog.is_changed()
+        || queue
+            .queued_from
+            .as_ref()
+            .is_none_or(|from| (from.width(), from.height()) != (width, height));
+
+    if full {
+        let queue = &mut *queue;
+        queue.pending.clear();
+        queue.is_pending = vec![false; width as usize * height as usize];
+        for y in 0..height {
+            for x in 0..width {
+                queue.push((x, y), width);
             }
-        } else {
-            if cell.0 == 2 {
-                0
-            } else {
-                2
+        }
+        queue.queued_from = Some(board.0.clone());
+    } else if board.is_changed() {
+        let from = queue.queued_from.take().unwrap();
+        for y in 0..height {
+            for x in 0..width {
+                if from.get(x, y) != board.get(x, y) || from.is_wall(x, y) != board.is_wall(x, y) {
+                    queue.push((x, y), width);
+                }
             }
-        };
+        }
+        queue.queued_from = Some(board.0.clone());
+    }
+
+    if queue.pending.is_empty() {
+        return;
+    }
+    let Ok(storage) = storage_query.get_single() else {
+        return;
+    };
+
+    let _span = info_span!("sync_tiles").entered();
+    let start = Instant::now();
+    let (mut synced, mut missing) = (0, 0);
+
+    while let Some((x, y)) = queue.pending.pop_front() {
+        queue.is_pending[(y * width + x) as usize] = false;
+        match storage
+            .get(&TilePos::new(x, y))
+            .and_then(|entity| tile_query.get_mut(entity).ok())
+        {
+            Some(tile) => set_look(tile, looks.look(x, y)),
+            None => missing += 1,
+        }
+
+        // the clock is only read every so often, it costs more than a tile
+        synced += 1;
+        if synced % 1024 == 0 && start.elapsed() >= SYNC_BUDGET {
+            break;
+        }
+    }
+
+    // the new tilemap is spawned with every tile drawn as the board is now
+    if missing > 0 {
+        errors.send(AppError::new(
+            "tiles",
+            format!("{missing} tiles were missing, so the tilemap was rebuilt"),
+        ));
+        rebuilds.send(RebuildTilemap);
+    }
+}
+
+type TileLook<'a> = (&'a TilePos, Mut<'a, TileVisible>, Mut<'a, TileColor>);
 
-        cell.0 = team;
-        cell.1 = team;
-        *color = TileColor(TEAM_COLORS[team]);
-        *visible = TileVisible(team != 0);
+/// Gives a tile a look, leaving whatever part of it already matches alone.
+fn set_look(
+    (_, mut visible, mut color): TileLook,
+    (new_visible, new_color): (TileVisible, TileColor),
+) {
+    if visible.0 != new_visible.0 {
+        *visible = new_visible;
     }
+    if color.0 != new_color.0 {
+        *color = new_color;
+    }
+}
+
+/// Everything that decides how the board's cells are drawn.
+#[derive(SystemParam)]
+struct CellLooks<'w, 's> {
+    board: Res<'w, Board>,
+    palette: Res<'w, Palette>,
+    fog: Res<'w, Fog>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
 }
 
-fn keyboard_input(keys: Res<Input<KeyCode>>, mut ticker: ResMut<TickDuration>) {
+impl CellLooks<'_, '_> {
+    fn is_changed(&self) -> bool {
+        self.board.is_changed() || self.palette.is_changed() || self.fog.is_changed()
+    }
+
+    /// How the tile for (x, y) should be drawn.
+    fn look(&self, x: u32, y: u32) -> (TileVisible, TileColor) {
+        // fogged cells are covered whatever is in them, walls included
+        if !self.fog.is_visible(x, y) {
+            return (TileVisible(true), TileColor(FOG_COLOR));
+        }
+
+        if self.board.is_wall(x, y) {
+            return (TileVisible(true), TileColor(WALL_COLOR));
+        }
+
+        // values past the palette (e.g. teams painted into a cyclic board) wrap around
+        let team = self.board.get(x, y) as usize;
+        (
+            TileVisible(team != 0),
+            TileColor(self.palette[team % self.palette.len()]),
+        )
+    }
+}
+
+fn keyboard_input(
+    keys: Hotkeys,
+    mut ticker: ResMut<TickDuration>,
+    (mut unlimited, mut fast_forward): (ResMut<UnlimitedSpeed>, ResMut<FastForward>),
+    mut rule: ResMut<RuleSet>,
+    mut board: ResMut<Board>,
+    state: Res<State<GameState>>,
+) {
     if keys.just_pressed(KeyCode::Space) {
         if ticker.0.paused() {
             ticker.0.unpause();
@@ -250,4 +949,54 @@ fn keyboard_input(keys: Res<Input<KeyCode>>, mut ticker: ResMut<TickDuration>) {
             ticker.0.pause();
         }
     }
+
+    if keys.just_pressed(KeyCode::U) {
+        unlimited.enabled = !unlimited.enabled;
+    }
+
+    let held = keys.pressed(fast_forward.key);
+    if fast_forward.held != held {
+        fast_forward.held = held;
+    }
+
+    // scenarios pick their own rule and edges
+    if *state.current() != GameState::Sandbox {
+        return;
+    }
+
+    if keys.just_pressed(KeyCode::B) {
+        let current = Boundary::ALL
+            .iter()
+            .position(|&boundary| boundary == board.boundary())
+            .unwrap();
+        let next = Boundary::ALL[(current + 1) % Boundary::ALL.len()];
+        board.set_boundary(next);
+    }
+
+    if keys.just_pressed(KeyCode::H) {
+        *rule = RuleSet {
+            max_age: rule.max_age,
+            ..RuleSet::default_for(match rule.neighborhood {
+                Neighborhood::Moore => Neighborhood::Hex,
+                Neighborhood::Hex => Neighborhood::Moore,
+            })
+        };
+    }
+
+    // cycle through the presets that fit the current board shape
+    if keys.just_pressed(KeyCode::R) {
+        let presets: Vec<RuleSet> = PRESETS
+            .iter()
+            .map(|preset| RuleSet::parse(preset.rule).unwrap())
+            .filter(|preset| preset.neighborhood == rule.neighborhood)
+            .collect();
+
+        let current = presets
+            .iter()
+            .position(|&preset| preset == rule.without_max_age());
+        *rule = RuleSet {
+            max_age: rule.max_age,
+            ..presets[current.map_or(0, |i| (i + 1) % presets.len())]
+        };
+    }
 }
@@ -0,0 +1,170 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+
+use crate::camera::{tile_view_position, MainCamera};
+use crate::lang::Strings;
+use crate::tools::{BoardCursor, Tool};
+
+/// Colors markers can be given, by name.
+const MARKER_COLORS: [(&str, [u8; 3]); 5] = [
+    ("yellow", [255, 220, 60]),
+    ("red", [240, 80, 80]),
+    ("green", [90, 220, 110]),
+    ("cyan", [80, 210, 230]),
+    ("white", [240, 240, 240]),
+];
+
+/// Labelled dots the marker tool drops on tiles, drawn over the board to note things
+/// like where a gun goes or where two streams should meet. They're kept in autosaves.
+pub struct MarkerPlugin;
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct Marker {
+    pub x: u32,
+    pub y: u32,
+    pub text: String,
+    pub color: [u8; 3],
+}
+
+#[derive(Resource, Default)]
+pub struct Markers(pub Vec<Marker>);
+
+/// Label and color new markers are dropped with.
+#[derive(Resource)]
+struct MarkerSettings {
+    text: String,
+    color: [u8; 3],
+}
+
+impl Default for MarkerSettings {
+    fn default() -> Self {
+        Self {
+            text: String::new(),
+            color: MARKER_COLORS[0].1,
+        }
+    }
+}
+
+type CameraView<'a> = (&'a Camera, &'a Transform, &'a OrthographicProjection);
+
+impl Plugin for MarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Markers>()
+            .init_resource::<MarkerSettings>()
+            .add_system(place_marker)
+            .add_system(marker_panel)
+            .add_system(draw_markers.after(marker_panel));
+    }
+}
+
+fn place_marker(
+    mouse: Res<Input<MouseButton>>,
+    tool: Res<Tool>,
+    settings: Res<MarkerSettings>,
+    cursor: BoardCursor,
+    mut markers: ResMut<Markers>,
+) {
+    if *tool != Tool::Marker || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(TilePos { x, y }) = cursor.tile_pos() else {
+        return;
+    };
+
+    if let Some(index) = markers.0.iter().position(|m| (m.x, m.y) == (x, y)) {
+        markers.0.remove(index);
+        return;
+    }
+
+    markers.0.push(Marker {
+        x,
+        y,
+        text: settings.text.clone(),
+        color: settings.color,
+    });
+}
+
+/// Paints the markers behind egui's windows but over the board.
+fn draw_markers(
+    markers: Res<Markers>,
+    camera_query: Query<CameraView, With<MainCamera>>,
+    tilemap_query: Query<(&TilemapGridSize, &TilemapType, &Transform), With<TileStorage>>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    let (Ok(camera), Ok(tilemap)) = (camera_query.get_single(), tilemap_query.get_single()) else {
+        return;
+    };
+
+    let painter = egui_context
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background());
+    let radius = (tilemap.0.x / camera.2.scale * 0.35).max(2.0);
+
+    for marker in &markers.0 {
+        let tile = TilePos::new(marker.x, marker.y);
+        let Some(position) = tile_view_position(camera, tilemap, tile) else {
+            continue;
+        };
+
+        let position = egui::pos2(position.x, position.y);
+        let [r, g, b] = marker.color;
+        let color = egui::Color32::from_rgb(r, g, b);
+
+        painter.circle_filled(position, radius, color);
+        if !marker.text.is_empty() {
+            painter.text(
+                position + egui::vec2(radius + 3.0, 0.0),
+                egui::Align2::LEFT_CENTER,
+                &marker.text,
+                egui::FontId::proportional(13.0),
+                color,
+            );
+        }
+    }
+}
+
+fn marker_panel(
+    tool: Res<Tool>,
+    mut settings: ResMut<MarkerSettings>,
+    mut markers: ResMut<Markers>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if *tool != Tool::Marker {
+        return;
+    }
+
+    egui::Window::new(strings.get("markers.title"))
+        .id(egui::Id::new("markers"))
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let settings = &mut *settings;
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("markers.label"));
+                ui.text_edit_singleline(&mut settings.text);
+            });
+
+            ui.horizontal(|ui| {
+                for (name, color) in MARKER_COLORS {
+                    let [r, g, b] = color;
+                    let text = egui::RichText::new(strings.named("color", name))
+                        .color(egui::Color32::from_rgb(r, g, b));
+                    ui.selectable_value(&mut settings.color, color, text);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(strings.format("markers.count", &[("markers", &markers.0.len())]));
+                if ui.button(strings.get("markers.clear")).clicked() && !markers.0.is_empty() {
+                    markers.0.clear();
+                }
+            });
+
+            ui.label(strings.get("markers.help"));
+        });
+}
@@ -0,0 +1,173 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::camera::{tile_view_position, MainCamera};
+use crate::lang::Strings;
+use crate::tools::{BoardCursor, Tool};
+use crate::SimCounters;
+
+/// A ruler for the measure tool: click two tiles to get the offset and distance between
+/// them. The tool's panel also notes two generations and counts the steps between them,
+/// for timing things like glider syntheses.
+pub struct MeasurePlugin;
+
+#[derive(Resource, Default)]
+struct Ruler {
+    start: Option<TilePos>,
+    end: Option<TilePos>,
+    /// Generations noted with the panel's mark buttons.
+    marks: [Option<u64>; 2],
+}
+
+type CameraView<'a> = (&'a Camera, &'a Transform, &'a OrthographicProjection);
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Ruler>()
+            .add_system(place_ruler)
+            .add_system(measure_panel.after(place_ruler))
+            .add_system(draw_ruler.after(measure_panel));
+    }
+}
+
+/// The first click starts a ruler, the second ends it and the next starts over.
+fn place_ruler(
+    mouse: Res<Input<MouseButton>>,
+    tool: Res<Tool>,
+    cursor: BoardCursor,
+    mut ruler: ResMut<Ruler>,
+) {
+    if *tool != Tool::Measure || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(tile) = cursor.tile_pos() else {
+        return;
+    };
+
+    if ruler.start.is_none() || ruler.end.is_some() {
+        ruler.start = Some(tile);
+        ruler.end = None;
+    } else {
+        ruler.end = Some(tile);
+    }
+}
+
+/// Where the ruler ends: its second point, or the hovered tile until that's clicked.
+fn ruler_end(ruler: &Ruler, cursor: &BoardCursor) -> Option<TilePos> {
+    ruler.end.or_else(|| cursor.tile_pos())
+}
+
+fn draw_ruler(
+    tool: Res<Tool>,
+    ruler: Res<Ruler>,
+    cursor: BoardCursor,
+    camera_query: Query<CameraView, With<MainCamera>>,
+    tilemap_query: Query<(&TilemapGridSize, &TilemapType, &Transform), With<TileStorage>>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    let (Some(start), Some(end)) = (ruler.start, ruler_end(&ruler, &cursor)) else {
+        return;
+    };
+    if *tool != Tool::Measure {
+        return;
+    }
+
+    let (Ok(camera), Ok(tilemap)) = (camera_query.get_single(), tilemap_query.get_single()) else {
+        return;
+    };
+    let (Some(from), Some(to)) = (
+        tile_view_position(camera, tilemap, start),
+        tile_view_position(camera, tilemap, end),
+    ) else {
+        return;
+    };
+
+    let painter = egui_context
+        .ctx_mut()
+        .layer_painter(egui::LayerId::background());
+    let color = egui::Color32::from_rgb(255, 220, 60);
+    let (from, to) = (egui::pos2(from.x, from.y), egui::pos2(to.x, to.y));
+
+    painter.line_segment([from, to], egui::Stroke::new(2.0, color));
+    painter.circle_filled(from, 3.0, color);
+    painter.circle_filled(to, 3.0, color);
+}
+
+fn measure_panel(
+    tool: Res<Tool>,
+    counters: Res<SimCounters>,
+    cursor: BoardCursor,
+    mut ruler: ResMut<Ruler>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if *tool != Tool::Measure {
+        return;
+    }
+
+    let end = ruler_end(&ruler, &cursor);
+
+    egui::Window::new(strings.get("measure.title"))
+        .id(egui::Id::new("measure"))
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            match (ruler.start, end) {
+                (Some(start), Some(end)) => {
+                    let dx = end.x as i64 - start.x as i64;
+                    let dy = end.y as i64 - start.y as i64;
+
+                    ui.label(strings.format(
+                        "measure.ends",
+                        &[
+                            ("x1", &start.x),
+                            ("y1", &start.y),
+                            ("x2", &end.x),
+                            ("y2", &end.y),
+                        ],
+                    ));
+                    ui.label(strings.format("measure.offset", &[("dx", &dx), ("dy", &dy)]));
+                    ui.label(strings.format(
+                        "measure.distance",
+                        &[
+                            ("cells", &dx.abs().max(dy.abs())),
+                            ("steps", &(dx.abs() + dy.abs())),
+                            (
+                                "straight",
+                                &format!("{:.2}", ((dx * dx + dy * dy) as f64).sqrt()),
+                            ),
+                        ],
+                    ));
+                }
+                _ => {
+                    ui.label(strings.get("measure.help"));
+                }
+            }
+
+            ui.separator();
+            let generation = counters.generation;
+            ui.horizontal(|ui| {
+                for (name, mark) in ["A", "B"].into_iter().zip(&mut ruler.marks) {
+                    if ui
+                        .button(strings.format("measure.mark", &[("name", &name)]))
+                        .clicked()
+                    {
+                        *mark = Some(generation);
+                    }
+                    match mark {
+                        Some(mark) => {
+                            ui.label(strings.format("measure.marked", &[("generation", mark)]))
+                        }
+                        None => ui.label("-"),
+                    };
+                }
+            });
+
+            if let [Some(a), Some(b)] = ruler.marks {
+                let generations = b as i64 - a as i64;
+                ui.label(strings.format("measure.between", &[("generations", &generations)]));
+            }
+        });
+}
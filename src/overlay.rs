@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::CELL_SIZE;
+
+/// Fills `data` with one RGBA texel per cell of a `width` x `height` grid, from `texel(x, y)`
+/// counted from the bottom left like the board. Cells it returns `None` for are left alone.
+pub fn draw_board_texels(
+    data: &mut [u8],
+    (width, height): (u32, u32),
+    mut texel: impl FnMut(u32, u32) -> Option<[u8; 4]>,
+) {
+    // image rows go top to bottom while board rows go bottom to top
+    for y in 0..height {
+        let row = (height - 1 - y) as usize * width as usize;
+
+        for x in 0..width {
+            if let Some(texel) = texel(x, y) {
+                let offset = (row + x as usize) * 4;
+                data[offset..offset + 4].copy_from_slice(&texel);
+            }
+        }
+    }
+}
+
+/// A nearest-sampled texture of a `width` x `height` grid for drawing over the board, see
+/// `draw_board_texels`. Cells `texel` returns `None` for are transparent.
+pub fn board_overlay_image(
+    width: u32,
+    height: u32,
+    texel: impl FnMut(u32, u32) -> Option<[u8; 4]>,
+) -> Image {
+    let mut data = vec![0; width as usize * height as usize * 4];
+    draw_board_texels(&mut data, (width, height), texel);
+
+    let mut image = Image::new(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+    image
+}
+
+/// Sizes `sprite` to cover a square `width` x `height` board and centers it on the board at
+/// depth `z`, placed like the tilemap itself.
+pub fn place_over_board(
+    (width, height): (u32, u32),
+    sprite: &mut Sprite,
+    transform: &mut Transform,
+    z: f32,
+) {
+    let size = Vec2::new(width as f32, height as f32) * CELL_SIZE;
+    let map_size = TilemapSize {
+        x: width,
+        y: height,
+    };
+    let grid_size = TilemapGridSize {
+        x: CELL_SIZE,
+        y: CELL_SIZE,
+    };
+    let tilemap_transform =
+        get_tilemap_center_transform(&map_size, &grid_size, &TilemapType::Square, 0.0);
+
+    // tile positions are tile centers, so the board's corner is half a cell further out
+    let center = tilemap_transform.translation.truncate() + size / 2.0 - CELL_SIZE / 2.0;
+
+    sprite.custom_size = Some(size);
+    transform.translation = center.extend(z);
+}
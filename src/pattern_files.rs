@@ -0,0 +1,256 @@
+use std::fs;
+use std::path::Path;
+
+use crate::bitboard::Bitboard;
+use crate::macrocell;
+use crate::rle::{self, Clip};
+use crate::rules::RuleSet;
+
+/// A pattern file format. Only RLE and macrocell keep teams apart; the others store every
+/// live cell as team 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Format {
+    Rle,
+    /// Plaintext `.cells`: one row per line, `O` for live cells.
+    Plaintext,
+    /// Blocks of `.`/`*` rows, each placed by a `#P x y` line.
+    Life105,
+    /// One `x y` coordinate per live cell.
+    Life106,
+    /// Golly's quadtree format, which stays small for huge repetitive patterns.
+    Macrocell,
+}
+
+impl Format {
+    pub const ALL: [Format; 5] = [
+        Format::Rle,
+        Format::Plaintext,
+        Format::Life105,
+        Format::Life106,
+        Format::Macrocell,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Format::Rle => "RLE",
+            Format::Plaintext => "plaintext",
+            Format::Life105 => "Life 1.05",
+            Format::Life106 => "Life 1.06",
+            Format::Macrocell => "macrocell",
+        }
+    }
+
+    pub fn extension(self) -> &'static str {
+        match self {
+            Format::Rle => "rle",
+            Format::Plaintext => "cells",
+            Format::Life105 | Format::Life106 => "lif",
+            Format::Macrocell => "mc",
+        }
+    }
+
+    /// Guesses the format from the text itself: Life and macrocell files start with their
+    /// version, plaintext with `!` comments or bare rows, and RLE with `#` comments or its
+    /// header.
+    pub fn detect(text: &str) -> Format {
+        let first = text.lines().map(str::trim).find(|line| !line.is_empty());
+
+        match first {
+            Some("#Life 1.05") => Format::Life105,
+            Some("#Life 1.06") => Format::Life106,
+            Some(line) if line.starts_with("[M2]") => Format::Macrocell,
+            Some(line) if line.starts_with('!') => Format::Plaintext,
+            Some(line) if line.starts_with('#') || line.starts_with('x') => Format::Rle,
+            _ if text
+                .chars()
+                .all(|c| matches!(c, '.' | 'O' | '*') || c.is_whitespace()) =>
+            {
+                Format::Plaintext
+            }
+            _ => Format::Rle,
+        }
+    }
+}
+
+/// Reads a pattern file, picking the format by its extension, and by its header for Life
+/// files (whose extension doesn't say which version they are) or unknown extensions.
+pub fn load(path: &Path) -> Result<Clip, String> {
+    let text = fs::read_to_string(path).map_err(|error| format!("{}: {error}", path.display()))?;
+
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    let format = match extension.as_str() {
+        "rle" => Format::Rle,
+        "cells" => Format::Plaintext,
+        "mc" => Format::Macrocell,
+        _ => Format::detect(&text),
+    };
+
+    decode(format, &text).map_err(|error| format!("{}: {error}", path.display()))
+}
+
+/// Writes the (left, bottom, width, height) block of the board to `path` in `format`.
+pub fn save(
+    path: &Path,
+    format: Format,
+    board: &Bitboard,
+    area: (u32, u32, u32, u32),
+    rule: &RuleSet,
+) -> Result<(), String> {
+    fs::write(path, encode(format, board, area, rule))
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+pub fn decode(format: Format, text: &str) -> Result<Clip, String> {
+    match format {
+        Format::Rle => rle::decode(text),
+        Format::Plaintext => decode_cells(text),
+        Format::Life105 => decode_life_105(text),
+        Format::Life106 => decode_life_106(text),
+        Format::Macrocell => macrocell::decode(text),
+    }
+}
+
+pub fn encode(
+    format: Format,
+    board: &Bitboard,
+    area: (u32, u32, u32, u32),
+    rule: &RuleSet,
+) -> String {
+    match format {
+        Format::Rle => return rle::encode(board, area, rule),
+        Format::Macrocell => return macrocell::encode(board, area, rule),
+        _ => {}
+    }
+
+    // rows from the top, as every one of these formats lists them
+    let (left, bottom, width, height) = area;
+    let rows: Vec<Vec<bool>> = (0..height)
+        .rev()
+        .map(|y| {
+            (0..width)
+                .map(|x| board.get(left + x, bottom + y) != 0)
+                .collect()
+        })
+        .collect();
+
+    let mut text = String::new();
+    match format {
+        Format::Rle | Format::Macrocell => unreachable!(),
+        Format::Plaintext => {
+            text.push_str("!Name: selection\n");
+            write_rows(&mut text, &rows, 'O');
+        }
+        Format::Life105 => {
+            // positions are relative to the pattern's middle, as Life 1.05 readers expect
+            text.push_str("#Life 1.05\n");
+            text.push_str(&format!(
+                "#P {} {}\n",
+                -(width as i64 / 2),
+                -(height as i64 / 2)
+            ));
+            write_rows(&mut text, &rows, '*');
+        }
+        Format::Life106 => {
+            text.push_str("#Life 1.06\n");
+            for (y, row) in rows.iter().enumerate() {
+                for (x, _) in row.iter().enumerate().filter(|(_, &live)| live) {
+                    text.push_str(&format!("{x} {y}\n"));
+                }
+            }
+        }
+    }
+
+    text
+}
+
+/// One line per row with trailing dead cells left off, which readers treat as dead.
+fn write_rows(text: &mut String, rows: &[Vec<bool>], live: char) {
+    for row in rows {
+        let end = row.iter().rposition(|&cell| cell).map_or(0, |x| x + 1);
+
+        text.extend(row[..end].iter().map(|&cell| if cell { live } else { '.' }));
+        text.push('\n');
+    }
+}
+
+/// Plaintext: `!` comment lines, then one row per line from the top, `O` or `*` for live
+/// cells.
+fn decode_cells(text: &str) -> Result<Clip, String> {
+    let mut points = Vec::new();
+    let rows = text.lines().filter(|line| !line.starts_with('!'));
+
+    for (y, line) in rows.enumerate() {
+        for (x, c) in line.trim_end().chars().enumerate() {
+            match c {
+                'O' | '*' => points.push((x as i64, y as i64, 2)),
+                '.' => {}
+                _ => return Err(format!("unexpected `{c}` in plaintext pattern")),
+            }
+        }
+    }
+
+    Clip::from_points(&points)
+}
+
+/// Life 1.05's header line, then `#` lines, some of which (`#P x y`) place the `.`/`*`
+/// block of rows after them.
+fn decode_life_105(text: &str) -> Result<Clip, String> {
+    let mut lines = text.lines().map(str::trim);
+    if lines.next() != Some("#Life 1.05") {
+        return Err("expected a `#Life 1.05` header".to_owned());
+    }
+
+    let mut points = Vec::new();
+    let (mut left, mut y) = (0, 0);
+
+    for line in lines {
+        if let Some(position) = line.strip_prefix("#P") {
+            (left, y) = parse_coordinate(position)
+                .ok_or_else(|| format!("invalid block position `{line}`"))?;
+            continue;
+        }
+        if line.starts_with('#') {
+            continue;
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            match c {
+                '*' => points.push((left + x as i64, y, 2)),
+                '.' => {}
+                _ => return Err(format!("unexpected `{c}` in Life 1.05 pattern")),
+            }
+        }
+        y += 1;
+    }
+
+    Clip::from_points(&points)
+}
+
+/// Life 1.06's header line, then one `x y` coordinate per live cell.
+fn decode_life_106(text: &str) -> Result<Clip, String> {
+    let mut lines = text.lines().map(str::trim);
+    if lines.next() != Some("#Life 1.06") {
+        return Err("expected a `#Life 1.06` header".to_owned());
+    }
+
+    let points = lines
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| {
+            let (x, y) =
+                parse_coordinate(line).ok_or_else(|| format!("invalid coordinate `{line}`"))?;
+            Ok((x, y, 2))
+        })
+        .collect::<Result<Vec<_>, String>>()?;
+
+    Clip::from_points(&points)
+}
+
+/// Two whitespace separated integers.
+fn parse_coordinate(text: &str) -> Option<(i64, i64)> {
+    let (x, y) = text.trim().split_once(char::is_whitespace)?;
+    Some((x.parse().ok()?, y.trim().parse().ok()?))
+}
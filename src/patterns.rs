@@ -0,0 +1,170 @@
+use crate::bitboard::Bitboard;
+
+/// A built-in pattern in plaintext form, one row per line from the top, `O` for live cells.
+pub struct Pattern {
+    pub name: &'static str,
+    pub rows: &'static [&'static str],
+}
+
+pub const PATTERNS: &[Pattern] = &[
+    Pattern {
+        name: "glider",
+        rows: &[".O.", "..O", "OOO"],
+    },
+    Pattern {
+        name: "blinker",
+        rows: &["OOO"],
+    },
+    Pattern {
+        name: "r-pentomino",
+        rows: &[".OO", "OO.", ".O."],
+    },
+    Pattern {
+        name: "acorn",
+        rows: &[".O.....", "...O...", "OO..OOO"],
+    },
+    Pattern {
+        name: "diehard",
+        rows: &["......O.", "OO......", ".O...OOO"],
+    },
+    Pattern {
+        name: "lwss",
+        rows: &[".O..O", "O....", "O...O", "OOOO."],
+    },
+    Pattern {
+        name: "gosper-gun",
+        rows: &[
+            "........................O...........",
+            "......................O.O...........",
+            "............OO......OO............OO",
+            "...........O...O....OO............OO",
+            "OO........O.....O...OO..............",
+            "OO........O...O.OO....O.O...........",
+            "..........O.....O.......O...........",
+            "...........O...O....................",
+            "............OO......................",
+        ],
+    },
+];
+
+/// A gun among `PATTERNS`, with where and which way its gliders leave it.
+pub struct Gun {
+    pub pattern: &'static str,
+    /// Generations between gliders.
+    pub period: u32,
+    /// The diagonal its gliders travel along as drawn, one cell each way per 4 generations
+    /// with y pointing up.
+    pub heading: (i64, i64),
+    /// The middle of its first glider as drawn, from the pattern's bottom left corner.
+    pub exit: (i64, i64),
+}
+
+pub const GUNS: &[Gun] = &[Gun {
+    pattern: "gosper-gun",
+    period: 30,
+    heading: (1, -1),
+    exit: (24, -2),
+}];
+
+impl Gun {
+    pub fn pattern(&self) -> &'static Pattern {
+        find(self.pattern).unwrap()
+    }
+
+    /// How to mirror the gun so its gliders travel along `heading`, a diagonal.
+    pub fn aim(&self, heading: (i64, i64)) -> (bool, bool) {
+        (heading.0 != self.heading.0, heading.1 != self.heading.1)
+    }
+
+    /// Where the first glider comes out once mirrored, from the pattern's bottom left.
+    pub fn exit_flipped(&self, (flip_x, flip_y): (bool, bool)) -> (i64, i64) {
+        let pattern = self.pattern();
+        let (x, y) = self.exit;
+        (
+            if flip_x {
+                pattern.width() as i64 - 1 - x
+            } else {
+                x
+            },
+            if flip_y {
+                pattern.height() as i64 - 1 - y
+            } else {
+                y
+            },
+        )
+    }
+}
+
+pub fn find(name: &str) -> Option<&'static Pattern> {
+    PATTERNS.iter().find(|pattern| pattern.name == name)
+}
+
+impl Pattern {
+    pub fn width(&self) -> u32 {
+        self.rows.iter().map(|row| row.len()).max().unwrap_or(0) as u32
+    }
+
+    pub fn height(&self) -> u32 {
+        self.rows.len() as u32
+    }
+
+    /// Live cells as (x, y) offsets with y pointing up, matching tile positions.
+    pub fn cells(&self) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let height = self.height();
+
+        self.rows.iter().enumerate().flat_map(move |(row, line)| {
+            line.bytes()
+                .enumerate()
+                .filter(|&(_, c)| c == b'O')
+                .map(move |(x, _)| (x as u32, height - 1 - row as u32))
+        })
+    }
+
+    /// Live cells mirrored horizontally and/or vertically within the pattern's bounds.
+    pub fn cells_flipped(
+        &self,
+        flip_x: bool,
+        flip_y: bool,
+    ) -> impl Iterator<Item = (u32, u32)> + '_ {
+        let (width, height) = (self.width(), self.height());
+
+        self.cells().map(move |(x, y)| {
+            (
+                if flip_x { width - 1 - x } else { x },
+                if flip_y { height - 1 - y } else { y },
+            )
+        })
+    }
+
+    /// Places the pattern, mirrored as asked, with its bounding box's bottom left corner at
+    /// (left, bottom), clipping anything that doesn't fit on the board.
+    pub fn stamp(
+        &self,
+        board: &mut Bitboard,
+        (left, bottom): (i64, i64),
+        (flip_x, flip_y): (bool, bool),
+        team: u8,
+    ) {
+        for (x, y) in self.cells_flipped(flip_x, flip_y) {
+            let (x, y) = (left + x as i64, bottom + y as i64);
+
+            if (0..board.width() as i64).contains(&x) && (0..board.height() as i64).contains(&y) {
+                board.set(x as u32, y as u32, team);
+            }
+        }
+    }
+
+    /// Places the pattern centered on the board, clipping anything that doesn't fit.
+    pub fn stamp_centered(&self, board: &mut Bitboard, team: u8) {
+        let left = (board.width() / 2).saturating_sub(self.width() / 2);
+        let bottom = (board.height() / 2).saturating_sub(self.height() / 2);
+
+        for (x, y) in self.cells() {
+            let (x, y) = (left + x, bottom + y);
+
+            if x < board.width() && y < board.height() {
+                board.set(x, y, team);
+            }
+        }
+    }
+}
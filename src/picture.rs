@@ -0,0 +1,129 @@
+use bevy::prelude::*;
+use bevy::render::camera::RenderTarget;
+use bevy::render::view::RenderLayers;
+use bevy::window::WindowId;
+use bevy_egui::{egui, EguiRenderInputContainer, EguiRenderOutputContainer, EguiSystem};
+
+use crate::hotkeys::Hotkeys;
+
+/// The render layer overlays drawn over the board in the world are on, so the board's
+/// cameras can stop drawing them all at once.
+const OVERLAY_LAYER: u8 = 1;
+
+/// F9 toggles picture mode, which leaves only the board in its window for screenshots and
+/// video: every panel and egui drawing, the selection, paste and shape previews, and the
+/// diff and wrap overlays, frozen and locked regions, spawners and ants are hidden, and the
+/// mouse cursor too if the display settings say so. The hidden panels take no input either,
+/// so nothing invisible can be clicked and painting works right up to the window's edges.
+/// Terrain, zones and a stacked layer stay, being part of how the board runs. Toggling back
+/// shows everything as it was, since nothing is closed on the way in.
+pub struct PicturePlugin;
+
+#[derive(Resource)]
+pub struct PictureMode {
+    on: bool,
+    pub hide_cursor: bool,
+    /// Whether the cursor was showing before picture mode hid it, to put it back.
+    cursor_was_visible: Option<bool>,
+}
+
+impl Default for PictureMode {
+    fn default() -> Self {
+        Self {
+            on: false,
+            hide_cursor: true,
+            cursor_was_visible: None,
+        }
+    }
+}
+
+/// The render layers for something drawn over the board that picture mode hides.
+pub fn overlay_layer() -> RenderLayers {
+    RenderLayers::layer(OVERLAY_LAYER)
+}
+
+impl Plugin for PicturePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PictureMode>()
+            .add_system(toggle_picture_mode)
+            .add_system(show_overlays.after(toggle_picture_mode))
+            .add_system_to_stage(
+                CoreStage::PreUpdate,
+                block_egui_input
+                    .after(EguiSystem::ProcessInput)
+                    .before(EguiSystem::BeginFrame),
+            )
+            .add_system_to_stage(
+                CoreStage::PostUpdate,
+                hide_egui.after(EguiSystem::ProcessOutput),
+            );
+    }
+}
+
+fn toggle_picture_mode(
+    keys: Hotkeys,
+    mut windows: ResMut<Windows>,
+    mut picture: ResMut<PictureMode>,
+) {
+    if !keys.just_pressed(KeyCode::F9) {
+        return;
+    }
+    picture.on = !picture.on;
+
+    let Some(window) = windows.get_primary_mut() else {
+        return;
+    };
+    if picture.on && picture.hide_cursor {
+        picture.cursor_was_visible = Some(window.cursor_visible());
+        window.set_cursor_visibility(false);
+    } else if let Some(visible) = picture.cursor_was_visible.take() {
+        window.set_cursor_visibility(visible);
+    }
+}
+
+/// Has every camera drawing into the board's window draw the overlays too, unless picture
+/// mode is on. Checked every frame, since split views come and go with their cameras.
+fn show_overlays(
+    mut commands: Commands,
+    picture: Res<PictureMode>,
+    camera_query: Query<(Entity, &Camera, Option<&RenderLayers>)>,
+) {
+    let layers = if picture.on {
+        RenderLayers::layer(0)
+    } else {
+        RenderLayers::layer(0).with(OVERLAY_LAYER)
+    };
+
+    for (entity, camera, current) in camera_query.iter() {
+        if camera.target != RenderTarget::Window(WindowId::primary()) {
+            continue;
+        }
+        if current.copied().unwrap_or_default() != layers {
+            commands.entity(entity).insert(layers);
+        }
+    }
+}
+
+/// Throws away what egui drew into the board's window this frame. Panels in a detached
+/// window are left alone.
+fn hide_egui(picture: Res<PictureMode>, mut output: ResMut<EguiRenderOutputContainer>) {
+    if !picture.on {
+        return;
+    }
+    if let Some(output) = output.get_mut(&WindowId::primary()) {
+        output.shapes.clear();
+    }
+}
+
+/// Keeps the board window's input away from egui, which lays its panels out and hit-tests
+/// them even while none of them are drawn. The pointer is reported gone so nothing stays
+/// hovered from before.
+fn block_egui_input(picture: Res<PictureMode>, mut input: ResMut<EguiRenderInputContainer>) {
+    if !picture.on {
+        return;
+    }
+    if let Some(input) = input.get_mut(&WindowId::primary()) {
+        input.events.clear();
+        input.events.push(egui::Event::PointerGone);
+    }
+}
@@ -0,0 +1,265 @@
+use std::io::{self, BufRead, Write};
+use std::process;
+
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+use crate::bitboard::{Bitboard, Boundary};
+use crate::pattern_files::{self, Format};
+use crate::patterns;
+use crate::rle::{self, Clip};
+use crate::rules::RuleSet;
+use crate::simulation::Simulation;
+use crate::MAP_SIZE;
+
+/// Team ids reported on separately, as in the HUD.
+const TEAMS: [u8; 2] = [2, 3];
+/// Widest and tallest board `new_board` makes, the same as the largest clip RLE reads, so
+/// a whole board can always be read back from its region.
+const MAX_SIDE: u32 = rle::MAX_SIZE;
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(flatten)]
+    command: Command,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum Command {
+    NewBoard {
+        width: u32,
+        height: u32,
+        boundary: Option<String>,
+    },
+    LoadPattern {
+        name: Option<String>,
+        text: Option<String>,
+        x: Option<u32>,
+        y: Option<u32>,
+        #[serde(default)]
+        clear: bool,
+    },
+    Step {
+        generations: u64,
+        every: Option<u64>,
+    },
+    SetRule {
+        rule: String,
+    },
+    GetRegion {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    Stats,
+    Quit,
+}
+
+/// Runs `--pipe`: a headless board driven by one JSON command per line on stdin, each
+/// answered by one JSON line on stdout, then exits the process at the end of the input
+/// or on `quit`. The commands are those of the remote control server (see `remote.rs`)
+/// plus:
+///
+/// - `new_board` starts over with an empty `width` by `height` board, optionally with
+///   another `boundary`.
+/// - `step` takes an optional `every`, writing a `generation` event line that many
+///   generations apart on the way.
+/// - `stats` replies with the generation and the population overall and per team.
+pub fn run() -> ! {
+    let board = Bitboard::new(MAP_SIZE.0, MAP_SIZE.1);
+    let mut sim = Simulation::new(board, RuleSet::CONWAY);
+    let mut stdout = io::stdout().lock();
+
+    for line in io::stdin().lock().lines() {
+        let Ok(line) = line else {
+            break;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (id, result) = match serde_json::from_str::<Request>(&line) {
+            Ok(Request {
+                command: Command::Quit,
+                ..
+            }) => break,
+            Ok(Request { id, command }) => (id, handle(&mut sim, command, &mut stdout)),
+            Err(error) => (None, Err(format!("invalid command: {error}"))),
+        };
+
+        // a closed stdout means nobody's listening any more
+        if writeln!(stdout, "{}", reply(&id, result)).is_err() || stdout.flush().is_err() {
+            break;
+        }
+    }
+
+    process::exit(0);
+}
+
+fn handle(sim: &mut Simulation, command: Command, out: &mut impl Write) -> Result<Value, String> {
+    match command {
+        Command::NewBoard {
+            width,
+            height,
+            boundary,
+        } => {
+            if width == 0 || height == 0 {
+                return Err("the board needs at least one cell".to_owned());
+            }
+            if width > MAX_SIDE || height > MAX_SIDE {
+                return Err(format!(
+                    "boards can be at most {MAX_SIDE} cells wide and tall"
+                ));
+            }
+            let boundary = match boundary {
+                Some(name) => Boundary::from_name(&name)
+                    .ok_or_else(|| format!("unknown boundary `{name}`"))?,
+                None => sim.board.boundary(),
+            };
+
+            sim.board = Bitboard::new(width, height);
+            sim.board.set_boundary(boundary);
+            sim.generation = 0;
+            Ok(stats(&sim.board, sim.generation))
+        }
+        Command::LoadPattern {
+            name,
+            text,
+            x,
+            y,
+            clear,
+        } => {
+            let clip = pattern_clip(name, text, &sim.board)?;
+            if clear {
+                let boundary = sim.board.boundary();
+                sim.board = Bitboard::new(sim.board.width(), sim.board.height());
+                sim.board.set_boundary(boundary);
+            }
+
+            let x = x.unwrap_or(sim.board.width() / 2);
+            let y = y.unwrap_or(sim.board.height() / 2);
+            clip.stamp(&mut sim.board, clip.centered_on(x, y), |_, _| true);
+            Ok(stats(&sim.board, sim.generation))
+        }
+        Command::Step { generations, every } => {
+            for step in 1..=generations {
+                sim.step();
+
+                if every.is_some_and(|every| every > 0 && step.is_multiple_of(every)) {
+                    let mut event = stats(&sim.board, sim.generation);
+                    event["event"] = "generation".into();
+                    writeln!(out, "{event}").map_err(|error| error.to_string())?;
+                }
+            }
+            Ok(stats(&sim.board, sim.generation))
+        }
+        Command::SetRule { rule } => RuleSet::parse(&rule).map(|rule| {
+            sim.rule = rule;
+            json!({ "rule": rule.to_string() })
+        }),
+        Command::GetRegion {
+            x,
+            y,
+            width,
+            height,
+        } => region(&sim.board, &sim.rule, (x, y, width, height)),
+        Command::Stats => Ok(stats(&sim.board, sim.generation)),
+        Command::Quit => unreachable!("handled by `run`"),
+    }
+}
+
+/// The answer to a command: its result's fields along with `ok`, or `ok` and the `error`,
+/// tagged with the command's `id` if it had one.
+pub fn reply(id: &Option<Value>, result: Result<Value, String>) -> Value {
+    let mut message = match result {
+        Ok(Value::Object(mut fields)) => {
+            fields.insert("ok".to_owned(), Value::Bool(true));
+            Value::Object(fields)
+        }
+        Ok(_) => json!({ "ok": true }),
+        Err(error) => json!({ "ok": false, "error": error }),
+    };
+    if let Some(id) = id {
+        message["id"] = id.clone();
+    }
+
+    message
+}
+
+/// The pattern a `load_pattern` command names (`name`) or holds the file of (`text`),
+/// which has to fit on `board`.
+pub fn pattern_clip(
+    name: Option<String>,
+    text: Option<String>,
+    board: &Bitboard,
+) -> Result<Clip, String> {
+    let clip = match (name, text) {
+        (Some(name), None) => {
+            let pattern =
+                patterns::find(&name).ok_or_else(|| format!("no built-in pattern `{name}`"))?;
+            Clip {
+                width: pattern.width(),
+                height: pattern.height(),
+                cells: pattern.cells().map(|(x, y)| (x, y, 2)).collect(),
+            }
+        }
+        (None, Some(text)) => pattern_files::decode(Format::detect(&text), &text)?,
+        _ => return Err("expected the pattern's `name` or its file's `text`".to_owned()),
+    };
+
+    if clip.width > board.width() || clip.height > board.height() {
+        return Err(format!(
+            "the pattern is {}x{}, bigger than the board",
+            clip.width, clip.height
+        ));
+    }
+
+    Ok(clip)
+}
+
+/// The live cells of the (left, bottom, width, height) block as `[x, y, team]` triples
+/// and as RLE.
+pub fn region(
+    board: &Bitboard,
+    rule: &RuleSet,
+    (x, y, width, height): (u32, u32, u32, u32),
+) -> Result<Value, String> {
+    let fits =
+        |start: u32, size: u32, limit: u32| start.checked_add(size).is_some_and(|end| end <= limit);
+    if !fits(x, width, board.width()) || !fits(y, height, board.height()) {
+        return Err(format!(
+            "the region doesn't fit on the {}x{} board",
+            board.width(),
+            board.height()
+        ));
+    }
+
+    let cells: Vec<_> = Clip::copy(board, (x, y, width, height))
+        .cells
+        .into_iter()
+        .map(|(dx, dy, team)| [x + dx, y + dy, team as u32])
+        .collect();
+
+    Ok(json!({
+        "cells": cells,
+        "rle": rle::encode(board, (x, y, width, height), rule),
+    }))
+}
+
+/// The generation and the board's population, overall and per team.
+pub fn stats(board: &Bitboard, generation: u64) -> Value {
+    let teams: Vec<_> = TEAMS
+        .iter()
+        .map(|&team| board.team_population(team))
+        .collect();
+
+    json!({
+        "generation": generation,
+        "population": board.population(),
+        "teams": teams,
+    })
+}
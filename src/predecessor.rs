@@ -0,0 +1,201 @@
+use crate::bitboard::{Bitboard, Boundary};
+use crate::rules::RuleSet;
+
+/// What a predecessor search came up with.
+#[derive(Clone, Debug)]
+pub enum Search {
+    /// A board that steps into the target.
+    Found(Bitboard),
+    /// Every board within the search bounds was ruled out.
+    NoneWithinBounds,
+    /// The search tried as many cells as it was allowed to without settling either way.
+    GaveUp,
+}
+
+/// Searches for a board that steps into `target` under `rule`, with all its live cells
+/// within one cell of the target's. Walls and the boundary are kept as they are, and teams
+/// and ages are ignored, so the rule's age limit is too. Gives up after trying `budget`
+/// cell values.
+///
+/// Cells are decided one at a time in row order, and every cell whose next state only
+/// depends on cells decided so far is checked against the target straight away, cutting
+/// off whole branches of the search at once.
+pub fn find_predecessor(target: &Bitboard, rule: &RuleSet, budget: u64) -> Search {
+    let (width, height) = (target.width(), target.height());
+
+    let mut board = target.clone();
+    for y in 0..height {
+        for x in 0..width {
+            if board.get(x, y) != 0 {
+                board.set(x, y, 0);
+            }
+        }
+    }
+
+    let mut solver = Solver {
+        target,
+        rule: rule.without_max_age(),
+        cells: search_bounds(target),
+        checks: Vec::new(),
+        board,
+        tried: 0,
+        budget,
+    };
+
+    // each cell is checked as soon as the last of the cells it depends on is decided
+    let mut index = vec![None; width as usize * height as usize];
+    for (i, &(x, y)) in solver.cells.iter().enumerate() {
+        index[(y * width + x) as usize] = Some(i);
+    }
+    solver.checks = vec![Vec::new(); solver.cells.len()];
+    let mut fixed = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            if target.is_wall(x, y) {
+                continue;
+            }
+
+            let last = target
+                .neighbors(x, y, rule.neighborhood)
+                .chain([(x, y)])
+                .filter_map(|(nx, ny)| index[(ny * width + nx) as usize])
+                .max();
+            match last {
+                Some(last) => solver.checks[last].push((x, y)),
+                None => fixed.push((x, y)),
+            }
+        }
+    }
+
+    // cells out of reach of the search have to come out right with nothing around them
+    if !fixed.iter().all(|&(x, y)| solver.matches(x, y)) {
+        return Search::NoneWithinBounds;
+    }
+
+    match solver.search(0) {
+        Outcome::Found => Search::Found(solver.board),
+        Outcome::Exhausted => Search::NoneWithinBounds,
+        Outcome::OutOfBudget => Search::GaveUp,
+    }
+}
+
+/// The cells a predecessor may have alive: the target's live cells' bounding box grown by
+/// one cell on each side, less walls, in row order.
+fn search_bounds(target: &Bitboard) -> Vec<(u32, u32)> {
+    let (width, height) = (target.width(), target.height());
+    let mut bounds: Option<(u32, u32, u32, u32)> = None;
+
+    for y in 0..height {
+        for x in 0..width {
+            if target.get(x, y) != 0 {
+                bounds = Some(match bounds {
+                    Some((left, bottom, right, top)) => {
+                        (left.min(x), bottom.min(y), right.max(x), top.max(y))
+                    }
+                    None => (x, y, x, y),
+                });
+            }
+        }
+    }
+
+    let Some((left, bottom, right, top)) = bounds else {
+        return Vec::new();
+    };
+
+    let mut cells = Vec::new();
+    for y in bottom.saturating_sub(1)..=(top + 1).min(height - 1) {
+        for x in left.saturating_sub(1)..=(right + 1).min(width - 1) {
+            if !target.is_wall(x, y) {
+                cells.push((x, y));
+            }
+        }
+    }
+    cells
+}
+
+enum Outcome {
+    Found,
+    Exhausted,
+    OutOfBudget,
+}
+
+struct Solver<'a> {
+    target: &'a Bitboard,
+    rule: RuleSet,
+    /// The cells being decided, in the order they're decided in.
+    cells: Vec<(u32, u32)>,
+    /// The cells to check once each of `cells` is decided.
+    checks: Vec<Vec<(u32, u32)>>,
+    /// The predecessor so far, with undecided cells dead.
+    board: Bitboard,
+    tried: u64,
+    budget: u64,
+}
+
+impl Solver<'_> {
+    /// Whether cell (x, y) of the board steps into the target's.
+    fn matches(&self, x: u32, y: u32) -> bool {
+        let board = &self.board;
+        let on_halo = board.boundary() == Boundary::Halo
+            && (x == 0 || y == 0 || x == board.width() - 1 || y == board.height() - 1);
+
+        let count = board
+            .neighbors(x, y, self.rule.neighborhood)
+            .filter(|&(nx, ny)| board.get(nx, ny) != 0)
+            .count();
+        let mask = if board.get(x, y) != 0 {
+            self.rule.survival
+        } else {
+            self.rule.birth
+        };
+        let alive = mask & 1 << count != 0 && !on_halo;
+
+        alive == (self.target.get(x, y) != 0)
+    }
+
+    fn search(&mut self, depth: usize) -> Outcome {
+        if depth == self.cells.len() {
+            return self.verify();
+        }
+
+        let (x, y) = self.cells[depth];
+        // sparser predecessors first
+        for team in [0, 2] {
+            self.tried += 1;
+            if self.tried > self.budget {
+                return Outcome::OutOfBudget;
+            }
+
+            self.board.set(x, y, team);
+            if self.checks[depth]
+                .iter()
+                .all(|&(cx, cy)| self.matches(cx, cy))
+            {
+                match self.search(depth + 1) {
+                    Outcome::Exhausted => {}
+                    outcome => return outcome,
+                }
+            }
+        }
+
+        self.board.set(x, y, 0);
+        Outcome::Exhausted
+    }
+
+    /// Steps the finished predecessor for real, to be sure of it.
+    fn verify(&self) -> Outcome {
+        let mut next = self.board.clone();
+        next.step(&self.rule);
+
+        let (width, height) = (next.width(), next.height());
+        let same = (0..height)
+            .all(|y| (0..width).all(|x| (next.get(x, y) != 0) == (self.target.get(x, y) != 0)));
+
+        if same {
+            Outcome::Found
+        } else {
+            Outcome::Exhausted
+        }
+    }
+}
@@ -0,0 +1,161 @@
+//! Turning pictures into boards, so logos and drawings can be used as starting boards.
+
+use crate::bitboard::Bitboard;
+
+/// A picture as 8-bit RGBA pixels, in rows from the top like image files keep them.
+#[derive(Clone, Debug)]
+pub struct Raster {
+    pub width: u32,
+    pub height: u32,
+    pub pixels: Vec<[u8; 4]>,
+}
+
+/// How a picture is fitted to a board of another shape.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Fit {
+    /// Scaled to fit inside the board, leaving dead cells around it.
+    #[default]
+    Scale,
+    /// Scaled to cover the board, cropping what hangs over the edges.
+    Crop,
+}
+
+impl Fit {
+    pub const ALL: [Fit; 2] = [Fit::Scale, Fit::Crop];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Fit::Scale => "scale",
+            Fit::Crop => "crop",
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RasterOptions {
+    /// Luminance from 0 to 1 that cells are alive below, or above if `light_alive`.
+    pub threshold: f32,
+    /// Whether light pixels come alive, for pictures drawn light on dark.
+    pub light_alive: bool,
+    pub fit: Fit,
+    /// The hues, in degrees, of teams 2 and 3. Cells go to the team whose hue their
+    /// pixels are nearer, and to team 2 where they're too gray to tell. Without hues every
+    /// cell is on team 2.
+    pub team_hues: Option<[f32; 2]>,
+}
+
+impl Default for RasterOptions {
+    fn default() -> Self {
+        Self {
+            threshold: 0.5,
+            light_alive: false,
+            fit: Fit::Scale,
+            team_hues: None,
+        }
+    }
+}
+
+/// Pixels with less saturation than this are gray, and go to the first team.
+const MIN_SATURATION: f32 = 0.15;
+
+impl Raster {
+    /// A `width` by `height` board drawn from the picture. Each cell looks at the pixels it
+    /// covers, averaged when it covers more than one, and see-through pixels are dead.
+    pub fn to_board(&self, (width, height): (u32, u32), options: &RasterOptions) -> Bitboard {
+        let mut board = Bitboard::new(width, height);
+        if self.width == 0 || self.height == 0 || width == 0 || height == 0 {
+            return board;
+        }
+
+        // picture pixels per cell, and where the board's corner falls on the picture
+        let (across, down) = (
+            self.width as f64 / width as f64,
+            self.height as f64 / height as f64,
+        );
+        let scale = match options.fit {
+            Fit::Scale => across.max(down),
+            Fit::Crop => across.min(down),
+        };
+        let left = (self.width as f64 - width as f64 * scale) / 2.0;
+        let top = (self.height as f64 - height as f64 * scale) / 2.0;
+
+        for row in 0..height {
+            for x in 0..width {
+                let start = (left + x as f64 * scale, top + row as f64 * scale);
+                let Some([r, g, b, a]) = self.average(start, scale) else {
+                    continue;
+                };
+                if a < 0.5 {
+                    continue;
+                }
+
+                let luminance = 0.2126 * r + 0.7152 * g + 0.0722 * b;
+                if (luminance > options.threshold) != options.light_alive {
+                    continue;
+                }
+
+                let team = match options.team_hues {
+                    Some(hues) => nearest_team([r, g, b], hues),
+                    None => 2,
+                };
+                // board rows go bottom to top
+                board.set(x, height - 1 - row, team);
+            }
+        }
+
+        board
+    }
+
+    /// The average color of the `size` square of pixels from `(left, top)`, with channels
+    /// from 0 to 1, or `None` if it's off the picture. Always takes at least one pixel, so
+    /// pictures smaller than the board are blown up.
+    fn average(&self, (left, top): (f64, f64), size: f64) -> Option<[f32; 4]> {
+        let span = |start: f64, limit: u32| {
+            let first = start.floor().max(0.0);
+            let last = (start + size).floor().max(first + 1.0).min(limit as f64);
+            (first < limit as f64 && start + size > 0.0).then_some((first as u32, last as u32))
+        };
+        let (x0, x1) = span(left, self.width)?;
+        let (y0, y1) = span(top, self.height)?;
+
+        let mut sum = [0.0; 4];
+        for y in y0..y1 {
+            for x in x0..x1 {
+                let pixel = self.pixels[(y * self.width + x) as usize];
+                for (total, channel) in sum.iter_mut().zip(pixel) {
+                    *total += channel as f32 / 255.0;
+                }
+            }
+        }
+
+        let count = ((x1 - x0) * (y1 - y0)) as f32;
+        Some(sum.map(|total| total / count))
+    }
+}
+
+/// Team 2 or 3, whichever's hue the color is nearer, or 2 for grays.
+fn nearest_team([r, g, b]: [f32; 3], hues: [f32; 2]) -> u8 {
+    let (max, min) = (r.max(g).max(b), r.min(g).min(b));
+    let chroma = max - min;
+    if max == 0.0 || chroma / max < MIN_SATURATION {
+        return 2;
+    }
+
+    let hue = if max == r {
+        60.0 * ((g - b) / chroma).rem_euclid(6.0)
+    } else if max == g {
+        60.0 * ((b - r) / chroma + 2.0)
+    } else {
+        60.0 * ((r - g) / chroma + 4.0)
+    };
+    let distance = |team_hue: f32| {
+        let apart = (hue - team_hue).rem_euclid(360.0);
+        apart.min(360.0 - apart)
+    };
+
+    if distance(hues[1]) < distance(hues[0]) {
+        3
+    } else {
+        2
+    }
+}
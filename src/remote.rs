@@ -0,0 +1,346 @@
+use std::collections::HashMap;
+use std::io::ErrorKind;
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::Mutex;
+use std::thread;
+use std::time::Duration;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tungstenite::handshake::server::{self as handshake, ErrorResponse, Response};
+use tungstenite::http::StatusCode;
+use tungstenite::Message;
+
+use crate::bitboard::Bitboard;
+use crate::pipe::{pattern_clip, region, reply};
+use crate::rules::{Automaton, RuleSet};
+use crate::undo::UndoHistory;
+use crate::{update_map, Board, GameState, RunTo, SimCounters};
+
+/// Where the server listens. Only local programs can reach it, and web pages are turned
+/// away, see `reject_browsers`.
+const REMOTE_ADDR: &str = "127.0.0.1:9001";
+/// How long a connection waits for a message before sending what's queued for it.
+const POLL_INTERVAL: Duration = Duration::from_millis(20);
+
+/// A WebSocket server, built with the `remote` feature, that lets other programs drive the
+/// sandbox with JSON messages like `{"command": "step", "generations": 100}`:
+///
+/// - `load_pattern` stamps a built-in pattern (`name`) or the contents of a pattern file in
+///   any supported format (`text`) centered on (`x`, `y`), the board's middle by default,
+///   first clearing the board's cells if `clear` is true.
+/// - `step` runs `generations` generations like the HUD's "Run to", replying once done.
+/// - `set_rule` switches to the Life-like `rule`, e.g. `B36/S23`.
+/// - `get_region` replies with the live cells of the `width` by `height` block with its
+///   bottom left corner at (`x`, `y`), as `[x, y, team]` triples and as RLE.
+/// - `subscribe` and `unsubscribe` start and stop `generation` events, sent at most once a
+///   frame with the generation and population.
+///
+/// Every command is answered like `pipe::reply` answers `--pipe` commands.
+pub struct RemotePlugin;
+
+/// What connection threads tell the game.
+enum Incoming {
+    Connected(u32, Sender<String>),
+    Message(u32, String),
+    Disconnected(u32),
+}
+
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Option<Value>,
+    #[serde(flatten)]
+    command: Command,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+enum Command {
+    LoadPattern {
+        name: Option<String>,
+        text: Option<String>,
+        x: Option<u32>,
+        y: Option<u32>,
+        #[serde(default)]
+        clear: bool,
+    },
+    Step {
+        generations: u64,
+    },
+    SetRule {
+        rule: String,
+    },
+    GetRegion {
+        x: u32,
+        y: u32,
+        width: u32,
+        height: u32,
+    },
+    Subscribe,
+    Unsubscribe,
+}
+
+struct Client {
+    sender: Sender<String>,
+    subscribed: bool,
+}
+
+/// A `step` waiting for the board to reach its target generation.
+struct PendingStep {
+    client: u32,
+    id: Option<Value>,
+    target: u64,
+}
+
+#[derive(Resource)]
+struct RemoteServer {
+    // only touched by the game's systems, the mutex just makes the receiver `Sync`
+    incoming: Mutex<Receiver<Incoming>>,
+    clients: HashMap<u32, Client>,
+    pending: Vec<PendingStep>,
+    last_generation: u64,
+}
+
+impl RemoteServer {
+    fn send(&self, client: u32, message: Value) {
+        if let Some(client) = self.clients.get(&client) {
+            // fails only if the connection just closed
+            let _ = client.sender.send(message.to_string());
+        }
+    }
+
+    fn reply(&self, client: u32, id: &Option<Value>, result: Result<Value, String>) {
+        self.send(client, reply(id, result));
+    }
+}
+
+impl Plugin for RemotePlugin {
+    fn build(&self, app: &mut App) {
+        let listener = match TcpListener::bind(REMOTE_ADDR) {
+            Ok(listener) => listener,
+            Err(error) => {
+                warn!("couldn't start the remote control server on {REMOTE_ADDR}: {error}");
+                return;
+            }
+        };
+
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || accept(listener, sender));
+
+        app.insert_resource(RemoteServer {
+            incoming: Mutex::new(receiver),
+            clients: HashMap::new(),
+            pending: Vec::new(),
+            last_generation: 0,
+        })
+        .add_system(handle_messages.before(update_map))
+        .add_system(send_events.after(update_map));
+    }
+}
+
+fn accept(listener: TcpListener, incoming: Sender<Incoming>) {
+    for (id, stream) in (0..).zip(listener.incoming().flatten()) {
+        let incoming = incoming.clone();
+        thread::spawn(move || connection(id, stream, incoming));
+    }
+}
+
+/// Turns away handshakes carrying an `Origin` header. Browsers always send one, so a web
+/// page open in the user's browser can't drive the sandbox, while local tools don't.
+// the error type is tungstenite's
+#[allow(clippy::result_large_err)]
+fn reject_browsers(
+    request: &handshake::Request,
+    response: Response,
+) -> Result<Response, ErrorResponse> {
+    if !request.headers().contains_key("origin") {
+        return Ok(response);
+    }
+
+    let mut rejection = ErrorResponse::new(Some("web pages can't connect".to_owned()));
+    *rejection.status_mut() = StatusCode::FORBIDDEN;
+    Err(rejection)
+}
+
+fn connection(id: u32, stream: TcpStream, incoming: Sender<Incoming>) {
+    let Ok(mut socket) = tungstenite::accept_hdr(stream, reject_browsers) else {
+        return;
+    };
+    if socket
+        .get_ref()
+        .set_read_timeout(Some(POLL_INTERVAL))
+        .is_err()
+    {
+        return;
+    }
+
+    let (sender, outgoing) = mpsc::channel();
+    if incoming.send(Incoming::Connected(id, sender)).is_err() {
+        return;
+    }
+
+    'open: loop {
+        match socket.read_message() {
+            Ok(Message::Text(text)) => {
+                if incoming.send(Incoming::Message(id, text)).is_err() {
+                    break;
+                }
+            }
+            Ok(Message::Close(_)) => break,
+            Ok(_) => {}
+            // timed out waiting, which leaves the socket as it was
+            Err(tungstenite::Error::Io(error))
+                if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut) => {}
+            Err(_) => break,
+        }
+
+        for message in outgoing.try_iter() {
+            if socket.write_message(Message::Text(message)).is_err() {
+                break 'open;
+            }
+        }
+    }
+
+    let _ = incoming.send(Incoming::Disconnected(id));
+}
+
+fn handle_messages(
+    mut server: ResMut<RemoteServer>,
+    state: Res<State<GameState>>,
+    (counters, mut run_to): (Res<SimCounters>, ResMut<RunTo>),
+    (mut board, mut rule, mut automaton): (ResMut<Board>, ResMut<RuleSet>, ResMut<Automaton>),
+    mut history: ResMut<UndoHistory>,
+) {
+    let server = &mut *server;
+    let incoming: Vec<_> = server.incoming.get_mut().unwrap().try_iter().collect();
+
+    for incoming in incoming {
+        let (client, text) = match incoming {
+            Incoming::Connected(client, sender) => {
+                info!(client, "remote client connected");
+                let subscribed = false;
+                server.clients.insert(client, Client { sender, subscribed });
+                continue;
+            }
+            Incoming::Disconnected(client) => {
+                info!(client, "remote client disconnected");
+                server.clients.remove(&client);
+                server.pending.retain(|step| step.client != client);
+                continue;
+            }
+            Incoming::Message(client, text) => (client, text),
+        };
+
+        let Request { id, command } = match serde_json::from_str(&text) {
+            Ok(request) => request,
+            Err(error) => {
+                server.reply(client, &None, Err(format!("invalid command: {error}")));
+                continue;
+            }
+        };
+
+        // like the keyboard shortcuts, nothing but reading the board works outside free play
+        let editable = *state.current() == GameState::Sandbox;
+        let result = match command {
+            _ if !editable && !matches!(command, Command::GetRegion { .. }) => {
+                Err("the board can only be controlled in the sandbox".to_owned())
+            }
+            Command::LoadPattern {
+                name,
+                text,
+                x,
+                y,
+                clear,
+            } => pattern_clip(name, text, &board).map(|clip| {
+                history.record(&board);
+                if clear {
+                    let boundary = board.boundary();
+                    board.0 = Bitboard::new(board.width(), board.height());
+                    board.set_boundary(boundary);
+                }
+
+                let (x, y) = (
+                    x.unwrap_or(board.width() / 2),
+                    y.unwrap_or(board.height() / 2),
+                );
+                clip.stamp(&mut board.0, clip.centered_on(x, y), |_, _| true);
+                json!({ "population": board.population() })
+            }),
+            Command::Step { generations } => {
+                let Some(target) = run_to
+                    .0
+                    .unwrap_or(counters.generation)
+                    .checked_add(generations)
+                else {
+                    server.reply(client, &id, Err("too many generations".to_owned()));
+                    continue;
+                };
+                run_to.0 = Some(target);
+                server.pending.push(PendingStep { client, id, target });
+                continue;
+            }
+            Command::SetRule { rule: text } => RuleSet::parse(&text).map(|parsed| {
+                *rule = parsed;
+                *automaton = Automaton::Life;
+                json!({ "rule": parsed.to_string() })
+            }),
+            Command::GetRegion {
+                x,
+                y,
+                width,
+                height,
+            } => region(&board, &rule, (x, y, width, height)),
+            Command::Subscribe | Command::Unsubscribe => {
+                let subscribed = matches!(command, Command::Subscribe);
+                if let Some(client) = server.clients.get_mut(&client) {
+                    client.subscribed = subscribed;
+                }
+                Ok(json!({ "generation": counters.generation }))
+            }
+        };
+
+        server.reply(client, &id, result);
+    }
+}
+
+/// Answers finished steps and tells subscribers about new generations.
+fn send_events(
+    mut server: ResMut<RemoteServer>,
+    (board, counters, run_to): (Res<Board>, Res<SimCounters>, Res<RunTo>),
+) {
+    let server = &mut *server;
+
+    let (done, waiting) = std::mem::take(&mut server.pending)
+        .into_iter()
+        // stopped early from the HUD if there's no target left
+        .partition(|step| counters.generation >= step.target || run_to.0.is_none());
+    server.pending = waiting;
+
+    for step in done {
+        let result = if counters.generation >= step.target {
+            Ok(json!({ "generation": counters.generation }))
+        } else {
+            Err(format!("stopped at generation {}", counters.generation))
+        };
+        server.reply(step.client, &step.id, result);
+    }
+
+    if server.last_generation == counters.generation {
+        return;
+    }
+    server.last_generation = counters.generation;
+
+    let event = json!({
+        "event": "generation",
+        "generation": counters.generation,
+        "population": board.population(),
+    });
+    for (&id, client) in &server.clients {
+        if client.subscribed {
+            server.send(id, event.clone());
+        }
+    }
+}
@@ -0,0 +1,382 @@
+//! Replays: the board a run started from, every edit made to it along the way and the
+//! generation each was made at, so the run can be stepped through again exactly.
+//!
+//! A replay file is `MAGIC`, the format version as a little endian u16, then chunks, each
+//! a four byte tag, its length as a little endian u32 and that many bytes. The header
+//! chunk comes first and the board chunk is required; event chunks follow in order, and
+//! the end chunk, if any, holds where the run got to so playing it back can be checked.
+//! Readers skip chunks with tags they don't know, so later versions can add chunks
+//! without old files becoming unreadable or new files breaking old readers.
+//!
+//! Nothing in a file depends on how the engine stores boards: the rule is kept as its
+//! rule string, the boundary by name and the cells in the `Bitboard::cell_bytes` layout.
+//! A version that has to change a chunk's layout bumps `FORMAT_VERSION` and keeps the
+//! reader for the old layout, so files from every earlier version still load.
+
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::bitboard::{Bitboard, Boundary};
+use crate::rng::Rng;
+use crate::rules::{Chances, RuleSet};
+
+/// Starts every replay file.
+pub const MAGIC: [u8; 4] = *b"CBRP";
+/// The version of the format written. Every version up to it can be read.
+pub const FORMAT_VERSION: u16 = 1;
+/// Most events written to one event chunk.
+const EVENTS_PER_CHUNK: usize = 4096;
+/// Most cells a replay's board may have, so a damaged file can't exhaust memory.
+const MAX_CELLS: u64 = 1 << 24;
+
+const HEADER: [u8; 4] = *b"HEAD";
+const BOARD: [u8; 4] = *b"BORD";
+const EVENTS: [u8; 4] = *b"EVNT";
+const END: [u8; 4] = *b"END ";
+
+/// What a replay needs to know to start the run over.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Header {
+    /// The version of the game that recorded it, for telling where a replay came from.
+    pub engine_version: String,
+    pub rule: RuleSet,
+    pub chances: Chances,
+    /// What the simulation's randomness was seeded with when the recording started.
+    pub seed: u64,
+    /// The generation the recording started at.
+    pub generation: u64,
+    pub width: u32,
+    pub height: u32,
+    pub boundary: Boundary,
+}
+
+/// Something done to the board just before it stepped from `generation`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    /// A cell set to `state`: 0 for dead, a team, or `u8::MAX` for a wall.
+    Cell {
+        generation: u64,
+        x: u32,
+        y: u32,
+        state: u8,
+    },
+    /// The rule switched to one with the same neighborhood.
+    Rule { generation: u64, rule: RuleSet },
+}
+
+impl Event {
+    pub fn generation(&self) -> u64 {
+        match *self {
+            Event::Cell { generation, .. } | Event::Rule { generation, .. } => generation,
+        }
+    }
+}
+
+/// A recorded run, see the module docs.
+#[derive(Clone, Debug)]
+pub struct Replay {
+    pub header: Header,
+    pub start: Bitboard,
+    /// Edits in the order they were made, which is also by generation.
+    pub events: Vec<Event>,
+    /// The generation the recording stopped at and the board's `cell_hash` there.
+    pub end: Option<(u64, u64)>,
+}
+
+/// Where playing a replay back got to.
+#[derive(Clone, Debug)]
+pub struct Playback {
+    pub board: Bitboard,
+    pub generation: u64,
+    /// Whether the board matched the recorded end, or `None` if the replay has no end.
+    pub matches_end: Option<bool>,
+}
+
+impl Replay {
+    /// Steps the start board through every recorded edit up to the end, or up to the last
+    /// edit if the replay has no end.
+    pub fn play(&self) -> Playback {
+        let header = &self.header;
+        let mut board = self.start.clone();
+        let mut rule = header.rule;
+        let mut rng = Rng::new(header.seed);
+        let mut generation = header.generation;
+
+        let last = self
+            .end
+            .map(|(generation, _)| generation)
+            .or(self.events.last().map(Event::generation))
+            .unwrap_or(generation);
+        let mut events = self.events.iter().peekable();
+
+        loop {
+            while let Some(event) = events.next_if(|event| event.generation() <= generation) {
+                match *event {
+                    Event::Cell { x, y, state, .. } if x < board.width() && y < board.height() => {
+                        match state {
+                            u8::MAX => board.set_wall(x, y, true),
+                            team => board.set(x, y, team),
+                        }
+                    }
+                    Event::Cell { .. } => {}
+                    Event::Rule { rule: new, .. } => rule = new,
+                }
+            }
+            if generation >= last {
+                break;
+            }
+
+            step(&mut board, &rule, &header.chances, &mut rng);
+            generation += 1;
+        }
+
+        let matches_end = self.end.map(|(_, hash)| board.cell_hash() == hash);
+        Playback {
+            board,
+            generation,
+            matches_end,
+        }
+    }
+}
+
+/// Steps `board` one generation the way playing a replay back does, which is how the game
+/// steps a board with nothing but a rule on it.
+pub fn step(board: &mut Bitboard, rule: &RuleSet, chances: &Chances, rng: &mut Rng) {
+    if chances.is_certain() {
+        board.step(rule);
+    } else {
+        board.step_stochastic(rule, chances, rng);
+    }
+}
+
+/// The replay as a file in the current format version.
+pub fn encode(replay: &Replay) -> Vec<u8> {
+    let header = &replay.header;
+    let mut bytes = MAGIC.to_vec();
+    bytes.extend(FORMAT_VERSION.to_le_bytes());
+
+    let mut chunk = Vec::new();
+    put_str(&mut chunk, &header.engine_version);
+    put_str(&mut chunk, &header.rule.to_string());
+    chunk.extend(header.chances.birth.to_le_bytes());
+    chunk.extend(header.chances.survival.to_le_bytes());
+    chunk.extend(header.seed.to_le_bytes());
+    chunk.extend(header.generation.to_le_bytes());
+    chunk.extend(header.width.to_le_bytes());
+    chunk.extend(header.height.to_le_bytes());
+    put_str(&mut chunk, header.boundary.name());
+    put_chunk(&mut bytes, HEADER, &chunk);
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+    // writing into a Vec can't fail
+    encoder.write_all(&replay.start.cell_bytes()).unwrap();
+    put_chunk(&mut bytes, BOARD, &encoder.finish().unwrap());
+
+    for events in replay.events.chunks(EVENTS_PER_CHUNK) {
+        let mut chunk = (events.len() as u32).to_le_bytes().to_vec();
+        for event in events {
+            match event {
+                Event::Cell {
+                    generation,
+                    x,
+                    y,
+                    state,
+                } => {
+                    chunk.push(0);
+                    chunk.extend(generation.to_le_bytes());
+                    chunk.extend(x.to_le_bytes());
+                    chunk.extend(y.to_le_bytes());
+                    chunk.push(*state);
+                }
+                Event::Rule { generation, rule } => {
+                    chunk.push(1);
+                    chunk.extend(generation.to_le_bytes());
+                    put_str(&mut chunk, &rule.to_string());
+                }
+            }
+        }
+        put_chunk(&mut bytes, EVENTS, &chunk);
+    }
+
+    if let Some((generation, hash)) = replay.end {
+        let mut chunk = generation.to_le_bytes().to_vec();
+        chunk.extend(hash.to_le_bytes());
+        put_chunk(&mut bytes, END, &chunk);
+    }
+
+    bytes
+}
+
+/// Reads a replay written by `encode` in this or any earlier version.
+pub fn decode(bytes: &[u8]) -> Result<Replay, String> {
+    let mut reader = Reader(bytes);
+    if reader.take(4)? != MAGIC {
+        return Err("that isn't a replay".to_owned());
+    }
+    let version = reader.u16()?;
+    if version == 0 || version > FORMAT_VERSION {
+        return Err(format!(
+            "the replay is format version {version}, this game reads up to {FORMAT_VERSION}"
+        ));
+    }
+
+    let mut header = None;
+    let mut start = None;
+    let mut events = Vec::new();
+    let mut end = None;
+
+    while !reader.0.is_empty() {
+        let tag = reader.take(4)?;
+        let len = reader.u32()? as usize;
+        let mut chunk = Reader(reader.take(len)?);
+
+        match tag.try_into().unwrap() {
+            HEADER => header = Some(read_header(&mut chunk)?),
+            BOARD => {
+                let header = header.as_ref().ok_or("the replay's header is missing")?;
+                start = Some(read_board(chunk.0, header)?);
+            }
+            EVENTS => read_events(&mut chunk, &mut events)?,
+            END => end = Some((chunk.u64()?, chunk.u64()?)),
+            // from a later version, and nothing this one needs
+            _ => {}
+        }
+    }
+
+    let header = header.ok_or("the replay's header is missing")?;
+    let start = start.ok_or("the replay's board is missing")?;
+    if events
+        .windows(2)
+        .any(|pair: &[Event]| pair[0].generation() > pair[1].generation())
+    {
+        return Err("the replay's edits are out of order".to_owned());
+    }
+
+    Ok(Replay {
+        header,
+        start,
+        events,
+        end,
+    })
+}
+
+fn read_header(chunk: &mut Reader) -> Result<Header, String> {
+    let engine_version = chunk.str()?;
+    let rule = RuleSet::parse(&chunk.str()?)?;
+    let chances = Chances {
+        birth: f64::from_le_bytes(chunk.array()?),
+        survival: f64::from_le_bytes(chunk.array()?),
+    };
+    let seed = chunk.u64()?;
+    let generation = chunk.u64()?;
+    let (width, height) = (chunk.u32()?, chunk.u32()?);
+    let boundary = chunk.str()?;
+    let boundary =
+        Boundary::from_name(&boundary).ok_or_else(|| format!("unknown boundary `{boundary}`"))?;
+
+    if width == 0 || height == 0 || width as u64 * height as u64 > MAX_CELLS {
+        return Err(format!("a {width}x{height} board is too big to replay"));
+    }
+
+    Ok(Header {
+        engine_version,
+        rule,
+        chances,
+        seed,
+        generation,
+        width,
+        height,
+        boundary,
+    })
+}
+
+fn read_board(compressed: &[u8], header: &Header) -> Result<Bitboard, String> {
+    let len = header.width as u64 * header.height as u64 * 3;
+    let mut bytes = Vec::new();
+    DeflateDecoder::new(compressed)
+        .take(len + 1)
+        .read_to_end(&mut bytes)
+        .map_err(|_| "the replay's board is damaged".to_owned())?;
+
+    let mut board = Bitboard::new(header.width, header.height);
+    board.set_boundary(header.boundary);
+    if !board.set_cell_bytes(&bytes) {
+        return Err("the replay's board is damaged".to_owned());
+    }
+    Ok(board)
+}
+
+fn read_events(chunk: &mut Reader, events: &mut Vec<Event>) -> Result<(), String> {
+    let count = chunk.u32()?;
+
+    for _ in 0..count {
+        let kind = chunk.take(1)?[0];
+        let generation = chunk.u64()?;
+        events.push(match kind {
+            0 => Event::Cell {
+                generation,
+                x: chunk.u32()?,
+                y: chunk.u32()?,
+                state: chunk.take(1)?[0],
+            },
+            1 => Event::Rule {
+                generation,
+                rule: RuleSet::parse(&chunk.str()?)?,
+            },
+            // events can't be skipped without knowing their length
+            kind => return Err(format!("the replay has an unknown kind of edit ({kind})")),
+        });
+    }
+
+    Ok(())
+}
+
+fn put_chunk(bytes: &mut Vec<u8>, tag: [u8; 4], chunk: &[u8]) {
+    bytes.extend(tag);
+    bytes.extend((chunk.len() as u32).to_le_bytes());
+    bytes.extend(chunk);
+}
+
+fn put_str(bytes: &mut Vec<u8>, text: &str) {
+    bytes.extend((text.len() as u32).to_le_bytes());
+    bytes.extend(text.as_bytes());
+}
+
+/// The rest of a file or chunk, read from the front.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], String> {
+        if self.0.len() < len {
+            return Err("the replay is cut short".to_owned());
+        }
+        let (taken, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(taken)
+    }
+
+    fn array<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(u16::from_le_bytes(self.array()?))
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.array()?))
+    }
+
+    fn u64(&mut self) -> Result<u64, String> {
+        Ok(u64::from_le_bytes(self.array()?))
+    }
+
+    fn str(&mut self) -> Result<String, String> {
+        let len = self.u32()? as usize;
+        String::from_utf8(self.take(len)?.to_vec())
+            .map_err(|_| "the replay has text that isn't UTF-8".to_owned())
+    }
+}
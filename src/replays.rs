@@ -0,0 +1,400 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy::tasks::{AsyncComputeTaskPool, Task};
+use bevy_egui::{egui, EguiContext};
+use futures_lite::future;
+
+use crate::bitboard::Bitboard;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::replay::{self, Event, Header, Playback, Replay};
+use crate::rng::Rng;
+use crate::rules::{Automaton, Chances, RuleSet};
+use crate::{update_map, Board, GameState, SimCounters, SimRng};
+
+/// Where recorded replays are written, next to the working directory like `saves`.
+const REPLAYS_DIR: &str = "replays";
+
+/// Q opens the replay recorder, which records free play from the current board on: the
+/// rule, the seed the simulation's randomness restarts from and every edit, saved under
+/// `replays/` when the recording stops. Saved replays can be checked by playing them back
+/// off the main thread, which should reach the very board the recording stopped on.
+pub struct ReplayPlugin;
+
+/// A recording under way.
+struct Recording {
+    replay: Replay,
+    /// The board as of the last frame, stepped along with the real one. Wherever the two
+    /// differ, something other than the rule changed the board, and that goes in the
+    /// replay as an edit.
+    shadow: Bitboard,
+    generation: u64,
+    rule: RuleSet,
+    rng: Rng,
+}
+
+impl Recording {
+    fn start(board: &Bitboard, (rule, chances): (RuleSet, Chances), generation: u64) -> Self {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_nanos() as u64);
+
+        Recording {
+            replay: Replay {
+                header: Header {
+                    engine_version: env!("CARGO_PKG_VERSION").to_owned(),
+                    rule,
+                    chances,
+                    seed,
+                    generation,
+                    width: board.width(),
+                    height: board.height(),
+                    boundary: board.boundary(),
+                },
+                start: board.clone(),
+                events: Vec::new(),
+                end: None,
+            },
+            shadow: board.clone(),
+            generation,
+            rule,
+            rng: Rng::new(seed),
+        }
+    }
+
+    /// Catches the shadow board up to `generation` and records whatever tells it apart
+    /// from `board`.
+    fn sync(&mut self, board: &Bitboard, rule: RuleSet, generation: u64) {
+        let events = &mut self.replay.events;
+
+        if rule != self.rule {
+            events.push(Event::Rule {
+                generation: self.generation,
+                rule,
+            });
+            self.rule = rule;
+        }
+        for _ in self.generation..generation {
+            replay::step(
+                &mut self.shadow,
+                &self.rule,
+                &self.replay.header.chances,
+                &mut self.rng,
+            );
+        }
+        self.generation = generation;
+
+        let (shadow, real) = (self.shadow.cell_bytes(), board.cell_bytes());
+        if shadow == real {
+            return;
+        }
+        // ages come after the cell states, so an age that differs marks its cell too
+        let cells = (board.width() * board.height()) as usize;
+        for index in 0..cells {
+            let age = (cells + index * 2)..(cells + index * 2 + 2);
+            if shadow[index] == real[index] && shadow[age.clone()] == real[age] {
+                continue;
+            }
+            events.push(Event::Cell {
+                generation,
+                x: index as u32 % board.width(),
+                y: index as u32 / board.width(),
+                state: real[index],
+            });
+        }
+        self.shadow = board.clone();
+    }
+
+    /// The replay, ending on the board as of the last sync.
+    fn finish(mut self) -> Replay {
+        self.replay.end = Some((self.generation, self.shadow.cell_hash()));
+        self.replay
+    }
+}
+
+#[derive(Resource, Default)]
+struct Recorder {
+    open: bool,
+    recording: Option<Recording>,
+    /// Whether the recording should stop and be saved at the next sync.
+    stopping: bool,
+    /// Every saved replay with its header or why it couldn't be read, newest first, or
+    /// `None` until the directory is next read.
+    files: Option<Vec<(PathBuf, Result<Header, String>)>>,
+    /// The replay being played back to check it, and the playback.
+    checking: Option<(PathBuf, Task<Result<Playback, String>>)>,
+    message: Option<String>,
+}
+
+impl Recorder {
+    fn list(&mut self) -> &mut Vec<(PathBuf, Result<Header, String>)> {
+        self.files.get_or_insert_with(|| {
+            let Ok(entries) = fs::read_dir(REPLAYS_DIR) else {
+                return Vec::new();
+            };
+
+            let mut files: Vec<(PathBuf, Result<Header, String>)> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "cbr"))
+                .map(|path| {
+                    let header = read_replay(&path).map(|replay| replay.header);
+                    (path, header)
+                })
+                .collect();
+            // the names end in when they were recorded
+            files.sort_by(|(a, _), (b, _)| b.cmp(a));
+            files
+        })
+    }
+
+    /// Writes the finished recording out, returning what to tell the player.
+    fn save(&mut self, recording: Recording, strings: &Strings) -> String {
+        let replay = recording.finish();
+        let path = Path::new(REPLAYS_DIR).join(format!("replay-{}.cbr", replay.header.seed));
+        self.files = None;
+
+        let written = fs::create_dir_all(REPLAYS_DIR)
+            .and_then(|()| fs::write(&path, replay::encode(&replay)));
+        match written {
+            Ok(()) => {
+                info!(
+                    path = %path.display(),
+                    edits = replay.events.len(),
+                    generation = replay.end.map_or(0, |(generation, _)| generation),
+                    "saved a replay"
+                );
+                strings.format(
+                    "replay.saved",
+                    &[("path", &path.display()), ("edits", &replay.events.len())],
+                )
+            }
+            Err(error) => {
+                warn!(path = %path.display(), %error, "couldn't save a replay");
+                strings.format("replay.save-failed", &[("error", &error)])
+            }
+        }
+    }
+}
+
+fn read_replay(path: &Path) -> Result<Replay, String> {
+    let bytes = fs::read(path).map_err(|error| error.to_string())?;
+    replay::decode(&bytes)
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Recorder>()
+            .add_system(toggle_recorder)
+            .add_system(record.before(update_map))
+            .add_system(finish_check)
+            .add_system(recorder_panel.after(toggle_recorder).after(finish_check));
+    }
+}
+
+fn toggle_recorder(keys: Hotkeys, mut recorder: ResMut<Recorder>) {
+    if keys.just_pressed(KeyCode::Q) {
+        recorder.open = !recorder.open;
+        // picks up replays copied in while the recorder was closed
+        recorder.files = None;
+    }
+}
+
+/// Records the edits made since last frame, just before the board steps again, and stops
+/// the recording once free play moves on to something a replay can't follow.
+fn record(
+    state: Res<State<GameState>>,
+    (board, rule, chances, automaton): (Res<Board>, Res<RuleSet>, Res<Chances>, Res<Automaton>),
+    counters: Res<SimCounters>,
+    strings: Res<Strings>,
+    mut recorder: ResMut<Recorder>,
+) {
+    let Some(recording) = &mut recorder.recording else {
+        return;
+    };
+
+    let header = &recording.replay.header;
+    let followable = *state.current() == GameState::Sandbox
+        && *automaton == Automaton::Life
+        && (board.width(), board.height()) == (header.width, header.height)
+        && board.boundary() == header.boundary
+        && rule.neighborhood == header.rule.neighborhood
+        && *chances == header.chances
+        && counters.generation >= recording.generation;
+
+    if !followable {
+        let recording = recorder.recording.take().unwrap();
+        let saved = recorder.save(recording, &strings);
+        recorder.message = Some(format!("{} {saved}", strings.get("replay.interrupted")));
+        recorder.stopping = false;
+        return;
+    }
+
+    if board.is_changed() || rule.is_changed() {
+        recording.sync(&board, *rule, counters.generation);
+    }
+
+    if recorder.stopping {
+        let recording = recorder.recording.take().unwrap();
+        recorder.message = Some(recorder.save(recording, &strings));
+        recorder.stopping = false;
+    }
+}
+
+fn finish_check(strings: Res<Strings>, mut recorder: ResMut<Recorder>) {
+    let Some((_, task)) = &mut recorder.checking else {
+        return;
+    };
+    let Some(result) = future::block_on(future::poll_once(task)) else {
+        return;
+    };
+    let (path, _) = recorder.checking.take().unwrap();
+
+    let name = path.display();
+    recorder.message = Some(match result {
+        Ok(Playback {
+            generation,
+            matches_end: Some(true),
+            ..
+        }) => strings.format(
+            "replay.matched",
+            &[("name", &name), ("generation", &generation)],
+        ),
+        Ok(Playback {
+            generation,
+            matches_end: Some(false),
+            ..
+        }) => strings.format(
+            "replay.diverged",
+            &[("name", &name), ("generation", &generation)],
+        ),
+        Ok(Playback { generation, .. }) => strings.format(
+            "replay.no-end",
+            &[("name", &name), ("generation", &generation)],
+        ),
+        Err(error) => strings.format("replay.unreadable", &[("name", &name), ("error", &error)]),
+    });
+}
+
+fn recorder_panel(
+    state: Res<State<GameState>>,
+    (board, rule, chances): (Res<Board>, Res<RuleSet>, Res<Chances>),
+    (counters, mut sim_rng): (Res<SimCounters>, ResMut<SimRng>),
+    (mut recorder, strings): (ResMut<Recorder>, Res<Strings>),
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !recorder.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut check = None;
+
+    egui::Window::new(strings.get("replay.title"))
+        .id(egui::Id::new("replays"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let recorder = &mut *recorder;
+            ui.label(strings.get("replay.help"));
+
+            match &recorder.recording {
+                Some(recording) => {
+                    ui.label(strings.format(
+                        "replay.recording",
+                        &[
+                            (
+                                "generations",
+                                &(recording.generation - recording.replay.header.generation),
+                            ),
+                            ("edits", &recording.replay.events.len()),
+                        ],
+                    ));
+                    if ui.button(strings.get("replay.stop")).clicked() {
+                        recorder.stopping = true;
+                    }
+                }
+                None => {
+                    if ui
+                        .add_enabled(
+                            *state.current() == GameState::Sandbox,
+                            egui::Button::new(strings.get("replay.record")),
+                        )
+                        .clicked()
+                    {
+                        let recording =
+                            Recording::start(&board, (*rule, *chances), counters.generation);
+                        // the run's randomness has to start where the replay's will
+                        sim_rng.0 = Rng::new(recording.replay.header.seed);
+                        recorder.recording = Some(recording);
+                        recorder.message = None;
+                    }
+                }
+            }
+
+            if let Some(message) = &recorder.message {
+                ui.label(message);
+            }
+            ui.separator();
+
+            let checking = recorder.checking.is_some();
+            let files = recorder.list();
+            if files.is_empty() {
+                ui.label(strings.format("replay.none", &[("dir", &REPLAYS_DIR)]));
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    for (path, header) in files.iter() {
+                        ui.horizontal(|ui| {
+                            let name = path.file_name().unwrap_or_default().to_string_lossy();
+                            match header {
+                                Ok(header) => {
+                                    ui.label(strings.format(
+                                        "replay.file",
+                                        &[
+                                            ("name", &name),
+                                            ("rule", &header.rule),
+                                            ("width", &header.width),
+                                            ("height", &header.height),
+                                            ("version", &header.engine_version),
+                                        ],
+                                    ));
+                                    if ui
+                                        .add_enabled(
+                                            !checking,
+                                            egui::Button::new(strings.get("replay.check")),
+                                        )
+                                        .clicked()
+                                    {
+                                        check = Some(path.clone());
+                                    }
+                                }
+                                Err(error) => {
+                                    ui.label(format!("{name}: {error}"));
+                                }
+                            }
+                        });
+                    }
+                });
+
+            if checking {
+                ui.label(strings.get("replay.checking"));
+            }
+        });
+
+    if let Some(path) = check {
+        let file = path.clone();
+        let task = AsyncComputeTaskPool::get()
+            .spawn(async move { read_replay(&file).map(|replay| replay.play()) });
+        recorder.checking = Some((path, task));
+    }
+
+    if !open {
+        recorder.open = false;
+    }
+}
@@ -0,0 +1,283 @@
+use std::fmt::Write;
+
+use crate::bitboard::Bitboard;
+use crate::rules::RuleSet;
+
+/// Longest line `encode` writes, as LifeWiki and most editors expect.
+const LINE_LENGTH: usize = 70;
+/// Widest and tallest clip `decode` and `Clip::from_points` make, far past any board the
+/// game runs, so a few bytes of crafted input can't ask for gigabytes.
+pub const MAX_SIZE: u32 = 4096;
+/// Most live cells `decode` reads.
+const MAX_CELLS: usize = 1 << 22;
+
+/// A block of cells read from RLE, with y pointing up like tile positions.
+#[derive(Clone, Debug)]
+pub struct Clip {
+    pub width: u32,
+    pub height: u32,
+    /// (x, y, team) for every live cell.
+    pub cells: Vec<(u32, u32, u8)>,
+}
+
+impl Clip {
+    /// A clip of (x, y, team) cells with y pointing down, moved so it starts at zero. Cells
+    /// spread further than `MAX_SIZE` apart are an error.
+    pub fn from_points(points: &[(i64, i64, u8)]) -> Result<Clip, String> {
+        let (Some(left), Some(top)) = (
+            points.iter().map(|point| point.0).min(),
+            points.iter().map(|point| point.1).min(),
+        ) else {
+            return Err("the pattern has no live cells".to_owned());
+        };
+        let right = points.iter().map(|point| point.0).max().unwrap();
+        let bottom = points.iter().map(|point| point.1).max().unwrap();
+
+        let size = |extent: i128| {
+            u32::try_from(extent)
+                .ok()
+                .filter(|&size| size <= MAX_SIZE)
+                .ok_or_else(|| format!("the pattern is larger than {MAX_SIZE} cells across"))
+        };
+        let width = size(right as i128 - left as i128 + 1)?;
+        let height = size(bottom as i128 - top as i128 + 1)?;
+
+        Ok(Clip {
+            width,
+            height,
+            cells: points
+                .iter()
+                .map(|&(x, y, team)| ((x - left) as u32, (bottom - y) as u32, team))
+                .collect(),
+        })
+    }
+
+    /// The live cells in the (left, bottom, width, height) block of the board.
+    pub fn copy(board: &Bitboard, (left, bottom, width, height): (u32, u32, u32, u32)) -> Clip {
+        let mut cells = Vec::new();
+
+        for y in 0..height {
+            for x in 0..width {
+                let team = board.get(left + x, bottom + y);
+                if team != 0 {
+                    cells.push((x, y, team));
+                }
+            }
+        }
+
+        Clip {
+            width,
+            height,
+            cells,
+        }
+    }
+
+    /// Bottom left corner that puts the clip's middle on (x, y).
+    pub fn centered_on(&self, x: u32, y: u32) -> (i64, i64) {
+        (
+            x as i64 - (self.width / 2) as i64,
+            y as i64 - (self.height / 2) as i64,
+        )
+    }
+
+    /// Places the clip with its bottom left corner at (left, bottom), clipping anything
+    /// that doesn't fit on the board or that `keep` rejects.
+    pub fn stamp(
+        &self,
+        board: &mut Bitboard,
+        (left, bottom): (i64, i64),
+        mut keep: impl FnMut(u32, u32) -> bool,
+    ) {
+        for &(x, y, team) in &self.cells {
+            let (x, y) = (left + x as i64, bottom + y as i64);
+
+            if !(0..board.width() as i64).contains(&x) || !(0..board.height() as i64).contains(&y) {
+                continue;
+            }
+            if keep(x as u32, y as u32) {
+                board.set(x as u32, y as u32, team);
+            }
+        }
+    }
+}
+
+/// Most states the single letters `A` to `X` of multi-state RLE can name.
+const MAX_STATES: u8 = 24;
+
+/// Writes the (left, bottom, width, height) block of the board as RLE. Blocks holding only
+/// team 1 use plain `b`/`o`, anything else the multi-state letters, `A` for the team id 1.
+pub fn encode(
+    board: &Bitboard,
+    (left, bottom, width, height): (u32, u32, u32, u32),
+    rule: &RuleSet,
+) -> String {
+    let rows: Vec<Vec<u8>> = (0..height)
+        .rev()
+        .map(|y| {
+            (0..width)
+                .map(|x| board.get(left + x, bottom + y))
+                .collect()
+        })
+        .collect();
+    let two_state = rows.iter().flatten().all(|&team| team == 0 || team == 2);
+
+    let symbol = |team: u8| match (team, two_state) {
+        (0, true) => 'b',
+        (0, false) => '.',
+        (_, true) => 'o',
+        (team, false) => (b'A' + team.min(MAX_STATES) - 1) as char,
+    };
+
+    // runs of (count, symbol), with trailing dead cells dropped and blank rows merged
+    let mut runs: Vec<(u32, char)> = Vec::new();
+    let push = |runs: &mut Vec<(u32, char)>, count: u32, symbol: char| match runs.last_mut() {
+        Some((last, previous)) if *previous == symbol => *last += count,
+        _ => runs.push((count, symbol)),
+    };
+
+    for (i, row) in rows.iter().enumerate() {
+        if i > 0 {
+            push(&mut runs, 1, '$');
+        }
+
+        let end = row.iter().rposition(|&team| team != 0).map_or(0, |x| x + 1);
+        for &team in &row[..end] {
+            push(&mut runs, 1, symbol(team));
+        }
+    }
+
+    while matches!(runs.last(), Some((_, '$'))) {
+        runs.pop();
+    }
+    runs.push((1, '!'));
+
+    let mut rle = format!("x = {width}, y = {height}, rule = {}\n", rule);
+    let mut line = String::new();
+
+    for (count, symbol) in runs {
+        let mut token = String::new();
+        if count > 1 {
+            write!(token, "{count}").unwrap();
+        }
+        token.push(symbol);
+
+        if line.len() + token.len() > LINE_LENGTH {
+            rle.push_str(&line);
+            rle.push('\n');
+            line.clear();
+        }
+        line.push_str(&token);
+    }
+    rle.push_str(&line);
+    rle.push('\n');
+
+    rle
+}
+
+/// Reads RLE, taking `o` as team 1 and the multi-state letters `A` to `X` as team ids 1
+/// and up. Comment lines and the header are optional; the rule in the header is ignored.
+pub fn decode(text: &str) -> Result<Clip, String> {
+    let mut header = (0, 0);
+    let mut body = String::new();
+
+    for line in text.lines().map(str::trim) {
+        if line.starts_with('#') || line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('x') && body.is_empty() {
+            for field in line.split(',') {
+                let Some((key, value)) = field.split_once('=') else {
+                    continue;
+                };
+                let value = || {
+                    value
+                        .trim()
+                        .parse()
+                        .ok()
+                        .filter(|&size| size <= MAX_SIZE)
+                        .ok_or_else(|| format!("invalid size in header `{line}`"))
+                };
+
+                match key.trim() {
+                    "x" => header.0 = value()?,
+                    "y" => header.1 = value()?,
+                    _ => {}
+                }
+            }
+            continue;
+        }
+
+        body.push_str(line);
+    }
+
+    let mut rows: Vec<Vec<(u32, u8)>> = vec![Vec::new()];
+    let (mut x, mut width, mut cells) = (0u32, 0, 0);
+    let mut count = String::new();
+    let too_large = || format!("the pattern is larger than {MAX_SIZE} cells across");
+
+    for c in body.chars() {
+        let run = || match count.as_str() {
+            "" => Ok(1),
+            count => count.parse::<u32>().map_err(|_| too_large()),
+        };
+        // where the run ends, if that's still inside the largest clip
+        let run_to = |from: u32| {
+            from.checked_add(run()?)
+                .filter(|&to| to <= MAX_SIZE)
+                .ok_or_else(too_large)
+        };
+
+        match c {
+            '0'..='9' => {
+                count.push(c);
+                continue;
+            }
+            '!' => break,
+            c if c.is_whitespace() => continue,
+            '$' => {
+                let height = run_to(rows.len() as u32)?;
+                rows.resize_with(height as usize, Vec::new);
+                x = 0;
+            }
+            'b' | '.' => x = run_to(x)?,
+            'o' | 'A'..='X' => {
+                let team = if c == 'o' { 2 } else { c as u8 - b'A' + 1 };
+                let end = run_to(x)?;
+                cells += (end - x) as usize;
+                if cells > MAX_CELLS {
+                    return Err(format!("the pattern has more than {MAX_CELLS} live cells"));
+                }
+
+                rows.last_mut().unwrap().extend((x..end).map(|x| (x, team)));
+                x = end;
+            }
+            _ => return Err(format!("unsupported RLE symbol `{c}`")),
+        }
+
+        width = width.max(x);
+        count.clear();
+    }
+
+    let width = width.max(header.0);
+    let height = (rows.len() as u32).max(header.1);
+    if width == 0 || height == 0 {
+        return Err("the RLE is empty".to_owned());
+    }
+
+    let cells = rows
+        .iter()
+        .enumerate()
+        .flat_map(|(row, cells)| {
+            cells
+                .iter()
+                .map(move |&(x, team)| (x, height - 1 - row as u32, team))
+        })
+        .collect();
+
+    Ok(Clip {
+        width,
+        height,
+        cells,
+    })
+}
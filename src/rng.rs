@@ -0,0 +1,43 @@
+/// Small seedable generator (SplitMix64) so runs are reproducible from a single seed.
+#[derive(Clone, Debug)]
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+
+    /// A word where each bit is set independently with probability `p`, to 1/65536 resolution.
+    pub fn next_mask(&mut self, p: f64) -> u64 {
+        if p >= 1.0 {
+            return !0;
+        }
+        if p <= 0.0 {
+            return 0;
+        }
+
+        let threshold = (p * 65536.0) as u64;
+        let mut mask = 0;
+
+        for chunk in 0..16 {
+            let draw = self.next_u64();
+
+            for lane in 0..4 {
+                if draw >> (lane * 16) & 0xffff < threshold {
+                    mask |= 1 << (chunk * 4 + lane);
+                }
+            }
+        }
+
+        mask
+    }
+}
@@ -0,0 +1,244 @@
+use std::path::Path;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+
+use crate::autosave::{read_ron, write_ron};
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::rules::{Automaton, Neighborhood, RuleSet};
+use crate::GameState;
+
+/// Where settings that outlive a session are kept, next to the working directory.
+const SETTINGS_PATH: &str = "settings.ron";
+
+/// F2 opens the rule editor: a checkbox per neighbor count for births and survivals, the
+/// neighborhood and how long cells may live, all applied to the board as they're changed.
+/// Rules worth keeping can be named and saved to the settings file.
+pub struct RuleEditorPlugin;
+
+/// A custom rule saved under a name.
+#[derive(Clone, Serialize, Deserialize)]
+struct NamedRule {
+    name: String,
+    /// The rulestring, as `RuleSet::parse` reads it.
+    rule: String,
+}
+
+/// Everything kept in the settings file.
+#[derive(Default, Serialize, Deserialize)]
+struct Settings {
+    #[serde(default)]
+    custom_rules: Vec<NamedRule>,
+}
+
+#[derive(Resource, Default)]
+struct RuleEditor {
+    open: bool,
+    settings: Settings,
+    /// Name for the next saved rule.
+    name: String,
+    message: Option<String>,
+}
+
+impl Plugin for RuleEditorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RuleEditor>()
+            .add_startup_system(read_settings)
+            .add_system(toggle_editor)
+            .add_system(rule_editor.after(toggle_editor));
+    }
+}
+
+fn read_settings(mut editor: ResMut<RuleEditor>) {
+    let path = Path::new(SETTINGS_PATH);
+    if !path.exists() {
+        return;
+    }
+
+    match read_ron(path) {
+        Ok(settings) => editor.settings = settings,
+        Err(error) => warn!("ignoring unreadable settings {SETTINGS_PATH}: {error}"),
+    }
+}
+
+fn toggle_editor(keys: Hotkeys, mut editor: ResMut<RuleEditor>) {
+    if keys.just_pressed(KeyCode::F2) {
+        editor.open = !editor.open;
+    }
+}
+
+/// A row of checkboxes for the neighbor counts set in `mask`.
+fn count_boxes(ui: &mut egui::Ui, label: &str, mask: &mut u16, max_neighbors: u32) {
+    ui.horizontal(|ui| {
+        ui.label(label);
+        for count in 0..=max_neighbors {
+            let mut set = *mask & 1 << count != 0;
+            if ui.checkbox(&mut set, count.to_string()).changed() {
+                *mask ^= 1 << count;
+            }
+        }
+    });
+}
+
+fn rule_editor(
+    state: Res<State<GameState>>,
+    (mut rule, mut automaton): (ResMut<RuleSet>, ResMut<Automaton>),
+    (mut editor, strings): (ResMut<RuleEditor>, Res<Strings>),
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !editor.open {
+        return;
+    }
+
+    let mut open = true;
+    // edited on a copy so the rule only reads as changed when it actually is
+    let mut edited = *rule;
+    let mut loaded = None;
+    let mut deleted = None;
+    let mut save = false;
+
+    egui::Window::new(strings.get("rule-editor.title"))
+        .id(egui::Id::new("rule editor"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let editor = &mut *editor;
+
+            // scenarios pick their own rule
+            if *state.current() != GameState::Sandbox {
+                ui.label(strings.get("rule-editor.sandbox-only"));
+                return;
+            }
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("rule-editor.neighborhood"));
+                ui.selectable_value(
+                    &mut edited.neighborhood,
+                    Neighborhood::Moore,
+                    strings.get("rule-editor.moore"),
+                );
+                ui.selectable_value(
+                    &mut edited.neighborhood,
+                    Neighborhood::Hex,
+                    strings.get("rule-editor.hex"),
+                );
+            });
+
+            // counts past what the neighborhood has can't happen, so they're dropped
+            let max_neighbors = edited.neighborhood.max_neighbors();
+            let possible = (1 << (max_neighbors + 1)) - 1;
+            edited.birth &= possible;
+            edited.survival &= possible;
+
+            count_boxes(
+                ui,
+                strings.get("rule-editor.birth"),
+                &mut edited.birth,
+                max_neighbors,
+            );
+            count_boxes(
+                ui,
+                strings.get("rule-editor.survival"),
+                &mut edited.survival,
+                max_neighbors,
+            );
+
+            ui.horizontal(|ui| {
+                let mut limited = edited.max_age.is_some();
+                ui.checkbox(&mut limited, strings.get("rule-editor.states"));
+
+                let mut states = edited.max_age.map_or(3, |age| age as u32 + 1);
+                ui.add_enabled(
+                    limited,
+                    egui::DragValue::new(&mut states).clamp_range(2..=u16::MAX as u32),
+                );
+                edited.max_age = limited.then_some((states - 1) as u16);
+            });
+            ui.label(strings.get("rule-editor.states-help"));
+
+            ui.label(strings.format("rule-editor.rulestring", &[("rule", &edited)]));
+
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut editor.name);
+                save = ui
+                    .add_enabled(
+                        !editor.name.trim().is_empty(),
+                        egui::Button::new(strings.get("rule-editor.save")),
+                    )
+                    .clicked();
+            });
+
+            for (i, named) in editor.settings.custom_rules.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(format!("{} ({})", named.name, named.rule));
+                    if ui.small_button(strings.get("rule-editor.load")).clicked() {
+                        loaded = Some(i);
+                    }
+                    if ui.small_button("x").clicked() {
+                        deleted = Some(i);
+                    }
+                });
+            }
+
+            if let Some(message) = &editor.message {
+                ui.label(message);
+            }
+        });
+
+    if let Some(i) = loaded {
+        let named = &editor.settings.custom_rules[i];
+        match RuleSet::parse(&named.rule) {
+            Ok(parsed) => edited = parsed,
+            Err(error) => {
+                editor.message = Some(format!("{}: {error}", named.name));
+            }
+        }
+    }
+
+    if edited != *rule {
+        *rule = edited;
+        if *automaton != Automaton::Life {
+            *automaton = Automaton::Life;
+        }
+    }
+
+    if save || deleted.is_some() {
+        let editor = &mut *editor;
+        if let Some(i) = deleted {
+            editor.settings.custom_rules.remove(i);
+        } else {
+            let name = editor.name.trim().to_owned();
+            let named = NamedRule {
+                name: name.clone(),
+                rule: rule.to_string(),
+            };
+            // saving under a name already taken replaces that rule
+            match editor
+                .settings
+                .custom_rules
+                .iter_mut()
+                .find(|existing| existing.name == name)
+            {
+                Some(existing) => *existing = named,
+                None => editor.settings.custom_rules.push(named),
+            }
+        }
+
+        editor.message = write_ron(&editor.settings, Path::new(SETTINGS_PATH))
+            .err()
+            .map(|error| {
+                strings.format(
+                    "rule-editor.save-failed",
+                    &[("path", &SETTINGS_PATH), ("error", &error)],
+                )
+            });
+    }
+
+    if !open {
+        editor.open = false;
+    }
+}
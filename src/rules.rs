@@ -0,0 +1,279 @@
+use std::fmt;
+
+use bevy::prelude::*;
+
+use crate::bitboard::Bitboard;
+
+/// Which cells count as neighbors. Hex boards use odd-row offset coordinates, matching
+/// `HexCoordSystem::RowOdd`: odd rows sit half a cell to the right of even ones.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Neighborhood {
+    Moore,
+    Hex,
+}
+
+impl Neighborhood {
+    /// (dx, dy) offsets of the neighbors of a cell in row `y`.
+    pub fn offsets(self, y: u32) -> &'static [(i32, i32)] {
+        match self {
+            Neighborhood::Moore => &[
+                (-1, -1),
+                (0, -1),
+                (1, -1),
+                (-1, 0),
+                (1, 0),
+                (-1, 1),
+                (0, 1),
+                (1, 1),
+            ],
+            Neighborhood::Hex if y.is_multiple_of(2) => {
+                &[(-1, -1), (0, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)]
+            }
+            Neighborhood::Hex => &[(0, -1), (1, -1), (-1, 0), (1, 0), (0, 1), (1, 1)],
+        }
+    }
+
+    /// (dx, dy) offsets of the neighbors sharing an edge with a cell in row `y`, which is
+    /// what regions are connected through so they don't leak across diagonals.
+    pub fn adjacent(self, y: u32) -> &'static [(i32, i32)] {
+        match self {
+            Neighborhood::Moore => &[(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Neighborhood::Hex => self.offsets(y),
+        }
+    }
+
+    pub fn max_neighbors(self) -> u32 {
+        match self {
+            Neighborhood::Moore => 8,
+            Neighborhood::Hex => 6,
+        }
+    }
+}
+
+/// An outer-totalistic rule: bit `n` of `birth`/`survival` is set if a dead/live cell
+/// with `n` live neighbors is alive next generation.
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RuleSet {
+    pub birth: u16,
+    pub survival: u16,
+    pub neighborhood: Neighborhood,
+    /// Cells that have been alive for more than this many generations die regardless
+    /// of their neighbors.
+    pub max_age: Option<u16>,
+}
+
+/// Chances that a birth or survival the rule allows actually happens, drawn from the
+/// simulation's seeded RNG so stochastic runs stay reproducible.
+#[derive(Resource, Clone, Copy, Debug, PartialEq)]
+pub struct Chances {
+    pub birth: f64,
+    pub survival: f64,
+}
+
+impl Chances {
+    pub const CERTAIN: Chances = Chances {
+        birth: 1.0,
+        survival: 1.0,
+    };
+
+    pub fn is_certain(&self) -> bool {
+        self.birth >= 1.0 && self.survival >= 1.0
+    }
+}
+
+impl Default for Chances {
+    fn default() -> Self {
+        Chances::CERTAIN
+    }
+}
+
+/// The classic cyclic cellular automaton: a cell in state `k` is consumed by state `k + 1`
+/// (wrapping at `states`) once at least `threshold` of its neighbors are in that state.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CyclicRule {
+    pub states: u8,
+    pub threshold: u8,
+}
+
+impl Default for CyclicRule {
+    /// The 3 state, threshold 3 rule, which settles into spirals quickly.
+    fn default() -> Self {
+        CyclicRule {
+            states: 3,
+            threshold: 3,
+        }
+    }
+}
+
+/// Which automaton the board runs. Both use the `RuleSet`'s neighborhood.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Automaton {
+    /// Life-like rules from the `RuleSet`.
+    #[default]
+    Life,
+    Cyclic(CyclicRule),
+    /// The loaded `ScriptRule`.
+    Scripted,
+}
+
+pub struct RulePreset {
+    pub name: &'static str,
+    pub rule: &'static str,
+}
+
+pub const PRESETS: &[RulePreset] = &[
+    RulePreset {
+        name: "Conway's Life",
+        rule: "B3/S23",
+    },
+    RulePreset {
+        name: "HighLife",
+        rule: "B36/S23",
+    },
+    RulePreset {
+        name: "Day & Night",
+        rule: "B3678/S34678",
+    },
+    RulePreset {
+        name: "Seeds",
+        rule: "B2/S",
+    },
+    RulePreset {
+        name: "Hex Life",
+        rule: "B2/S34H",
+    },
+    RulePreset {
+        name: "Hex Seeds",
+        rule: "B2/SH",
+    },
+];
+
+impl RuleSet {
+    pub const CONWAY: RuleSet = RuleSet {
+        birth: 1 << 3,
+        survival: 1 << 2 | 1 << 3,
+        neighborhood: Neighborhood::Moore,
+        max_age: None,
+    };
+
+    /// Parses a rulestring like `B3/S23`, with a trailing `H` selecting the hex neighborhood
+    /// and an optional `/A<n>` before that limiting cells to `n` generations of life.
+    pub fn parse(rule: &str) -> Result<RuleSet, String> {
+        let rule = rule.trim();
+        let (rule, neighborhood) = match rule.strip_suffix(['H', 'h']) {
+            Some(rule) => (rule, Neighborhood::Hex),
+            None => (rule, Neighborhood::Moore),
+        };
+
+        let (rule, max_age) = match rule.rsplit_once('/') {
+            Some((rest, age)) if age.starts_with(['A', 'a']) => {
+                let max_age = age[1..]
+                    .parse()
+                    .map_err(|_| format!("invalid max age in `{age}`"))?;
+                (rest, Some(max_age))
+            }
+            _ => (rule, None),
+        };
+
+        let (birth, survival) = rule
+            .split_once('/')
+            .ok_or_else(|| format!("expected B.../S... in rule `{rule}`"))?;
+
+        let counts = |part: &str, prefix: char| -> Result<u16, String> {
+            let digits = part
+                .strip_prefix([prefix, prefix.to_ascii_lowercase()])
+                .ok_or_else(|| format!("expected `{prefix}` at the start of `{part}`"))?;
+
+            digits
+                .chars()
+                .try_fold(0u16, |mask, c| match c.to_digit(10) {
+                    Some(n) if n <= neighborhood.max_neighbors() => Ok(mask | 1 << n),
+                    _ => Err(format!("invalid neighbor count `{c}` in `{part}`")),
+                })
+        };
+
+        Ok(RuleSet {
+            birth: counts(birth, 'B')?,
+            survival: counts(survival, 'S')?,
+            neighborhood,
+            max_age,
+        })
+    }
+
+    /// The first preset using `neighborhood`, used when switching board shapes.
+    pub fn default_for(neighborhood: Neighborhood) -> RuleSet {
+        PRESETS
+            .iter()
+            .map(|preset| RuleSet::parse(preset.rule).unwrap())
+            .find(|rule| rule.neighborhood == neighborhood)
+            .unwrap()
+    }
+
+    /// Name of the preset this rule is, ignoring any age limit.
+    pub fn name(&self) -> Option<&'static str> {
+        let unlimited = self.without_max_age();
+
+        PRESETS
+            .iter()
+            .find(|preset| RuleSet::parse(preset.rule) == Ok(unlimited))
+            .map(|preset| preset.name)
+    }
+
+    pub fn without_max_age(self) -> RuleSet {
+        RuleSet {
+            max_age: None,
+            ..self
+        }
+    }
+}
+
+/// Redoes the step that took cell (x, y) from `before` to `board` as if it went by `rule`,
+/// for rules applying to only part of a board. Neighbors are counted the way `rule` counts
+/// them, newborns join the team with the most parents and walls are left alone.
+pub fn apply_rule_at(before: &Bitboard, board: &mut Bitboard, x: u32, y: u32, rule: &RuleSet) {
+    if before.is_wall(x, y) {
+        return;
+    }
+
+    let count = before
+        .neighbors(x, y, rule.neighborhood)
+        .filter(|&(nx, ny)| before.get(nx, ny) != 0)
+        .count();
+    let (mask, team) = match before.get(x, y) {
+        0 => (rule.birth, before.resolve_team(x, y, rule.neighborhood)),
+        team => (rule.survival, team),
+    };
+
+    match (mask & 1 << count != 0, board.get(x, y)) {
+        (true, 0) => board.set(x, y, team),
+        (false, next) if next != 0 => board.set(x, y, 0),
+        _ => {}
+    }
+}
+
+impl Default for RuleSet {
+    fn default() -> Self {
+        RuleSet::CONWAY
+    }
+}
+
+impl fmt::Display for RuleSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let digits = |mask: u16| -> String {
+            (0..=8)
+                .filter(|n| mask & 1 << n != 0)
+                .map(|n| char::from(b'0' + n))
+                .collect()
+        };
+
+        write!(f, "B{}/S{}", digits(self.birth), digits(self.survival))?;
+        if let Some(max_age) = self.max_age {
+            write!(f, "/A{max_age}")?;
+        }
+        if self.neighborhood == Neighborhood::Hex {
+            write!(f, "H")?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,355 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use serde::{Deserialize, Serialize};
+
+use crate::autosave::{read_ron, write_ron, Snapshot};
+use crate::bitboard::Bitboard;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::markers::Markers;
+use crate::rules::{Chances, RuleSet};
+use crate::scenario::BoardSetup;
+use crate::{Board, GameState, SimCounters, TickDuration};
+
+/// Where named saves are kept, next to the working directory like `assets`.
+const SAVES_DIR: &str = "saves";
+/// Most cells along a thumbnail's longer side.
+const THUMBNAIL_CELLS: u32 = 32;
+/// How big thumbnails are drawn in the browser, in points.
+const THUMBNAIL_POINTS: f32 = 48.0;
+
+/// F5 opens the saves browser, which keeps free play boards in named slots under
+/// `saves/` and loads them back, showing each slot's thumbnail, generation, rule and date.
+pub struct SavesPlugin;
+
+/// A named save as written to the saves directory.
+#[derive(Serialize, Deserialize)]
+struct SaveSlot {
+    name: String,
+    generation: u64,
+    /// Seconds since the Unix epoch.
+    saved_at: u64,
+    /// The board shrunk down, one row per line from the top, `O` where any cell was live.
+    thumbnail: Vec<String>,
+    snapshot: Snapshot,
+}
+
+impl SaveSlot {
+    /// The file a slot called `name` goes in. Anything but letters and digits is replaced,
+    /// so names can't climb out of the directory.
+    fn path(name: &str) -> PathBuf {
+        let stem: String = name
+            .trim()
+            .chars()
+            .map(|c| {
+                if c.is_alphanumeric() {
+                    c.to_ascii_lowercase()
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        Path::new(SAVES_DIR).join(stem).with_extension("ron")
+    }
+}
+
+/// `board` shrunk so its longer side is at most `THUMBNAIL_CELLS` across.
+fn thumbnail(board: &Bitboard) -> Vec<String> {
+    let step = board
+        .width()
+        .max(board.height())
+        .div_ceil(THUMBNAIL_CELLS)
+        .max(1);
+    let (columns, rows) = (board.width().div_ceil(step), board.height().div_ceil(step));
+
+    (0..rows)
+        .rev()
+        .map(|row| {
+            (0..columns)
+                .map(|column| {
+                    let live = (row * step..((row + 1) * step).min(board.height())).any(|y| {
+                        (column * step..((column + 1) * step).min(board.width()))
+                            .any(|x| board.get(x, y) != 0)
+                    });
+                    if live {
+                        'O'
+                    } else {
+                        '.'
+                    }
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// `seconds` since the Unix epoch as a UTC date and time.
+fn format_date(seconds: u64) -> String {
+    let (days, time) = (seconds / 86_400, seconds % 86_400);
+
+    // civil date from days since 1970-01-01, after Howard Hinnant's algorithm
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + (month <= 2) as i64;
+
+    format!(
+        "{year}-{month:02}-{day:02} {:02}:{:02} UTC",
+        time / 3600,
+        time % 3600 / 60
+    )
+}
+
+#[derive(Resource, Default)]
+struct SavesBrowser {
+    open: bool,
+    /// Name for the next save.
+    name: String,
+    /// Every readable slot with the file it came from, newest first, or `None` until the
+    /// directory is next read.
+    slots: Option<Vec<(PathBuf, SaveSlot)>>,
+    /// The slot picked to load, which `load_slot` restores since it needs the whole board.
+    loading: Option<usize>,
+    message: Option<String>,
+}
+
+impl SavesBrowser {
+    fn list(&mut self) -> &mut Vec<(PathBuf, SaveSlot)> {
+        self.slots.get_or_insert_with(|| {
+            let Ok(entries) = fs::read_dir(SAVES_DIR) else {
+                return Vec::new();
+            };
+
+            let mut slots: Vec<(PathBuf, SaveSlot)> = entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+                .filter_map(|path| match read_ron(&path) {
+                    Ok(slot) => Some((path, slot)),
+                    Err(error) => {
+                        warn!("ignoring unreadable save {}: {error}", path.display());
+                        None
+                    }
+                })
+                .collect();
+            slots.sort_by_key(|(_, slot)| std::cmp::Reverse(slot.saved_at));
+            slots
+        })
+    }
+}
+
+impl Plugin for SavesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SavesBrowser>()
+            .add_system(toggle_browser)
+            .add_system(saves_window.after(toggle_browser))
+            .add_system(load_slot.after(saves_window));
+    }
+}
+
+fn toggle_browser(keys: Hotkeys, mut browser: ResMut<SavesBrowser>) {
+    if keys.just_pressed(KeyCode::F5) {
+        browser.open = !browser.open;
+        // picks up saves copied in while the browser was closed
+        browser.slots = None;
+    }
+}
+
+/// Paints `thumbnail` into a square of the browser.
+fn show_thumbnail(ui: &mut egui::Ui, thumbnail: &[String]) {
+    let (rect, _) =
+        ui.allocate_exact_size(egui::Vec2::splat(THUMBNAIL_POINTS), egui::Sense::hover());
+    let painter = ui.painter_at(rect);
+    painter.rect_filled(rect, 2.0, egui::Color32::from_gray(20));
+
+    let rows = thumbnail.len().max(1) as f32;
+    let columns = thumbnail.iter().map(String::len).max().unwrap_or(0).max(1) as f32;
+    let cell = THUMBNAIL_POINTS / rows.max(columns);
+
+    for (y, row) in thumbnail.iter().enumerate() {
+        for (x, _) in row.bytes().enumerate().filter(|&(_, c)| c == b'O') {
+            let corner = rect.min + egui::vec2(x as f32, y as f32) * cell;
+            painter.rect_filled(
+                egui::Rect::from_min_size(corner, egui::Vec2::splat(cell)),
+                0.0,
+                egui::Color32::LIGHT_GRAY,
+            );
+        }
+    }
+}
+
+fn saves_window(
+    state: Res<State<GameState>>,
+    mut browser: ResMut<SavesBrowser>,
+    (board, rule, counters, markers): (Res<Board>, Res<RuleSet>, Res<SimCounters>, Res<Markers>),
+    (chances, ticker): (Res<Chances>, Res<TickDuration>),
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !browser.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut save = false;
+    let mut load = None;
+    let mut delete = None;
+    let sandbox = *state.current() == GameState::Sandbox;
+
+    egui::Window::new(strings.get("saves.title"))
+        .id(egui::Id::new("saves"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let browser = &mut *browser;
+
+            if !sandbox {
+                ui.label(strings.get("saves.sandbox-only"));
+                return;
+            }
+
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut browser.name);
+                let button = egui::Button::new(strings.get("saves.save"));
+                save = ui
+                    .add_enabled(!browser.name.trim().is_empty(), button)
+                    .clicked();
+            });
+
+            if let Some(message) = &browser.message {
+                ui.label(message);
+            }
+            ui.separator();
+
+            let slots = browser.list();
+            if slots.is_empty() {
+                ui.label(strings.format("saves.none", &[("dir", &SAVES_DIR)]));
+            }
+
+            egui::ScrollArea::vertical()
+                .max_height(360.0)
+                .show(ui, |ui| {
+                    for (i, (_, slot)) in slots.iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            show_thumbnail(ui, &slot.thumbnail);
+                            ui.vertical(|ui| {
+                                ui.strong(&slot.name);
+                                let boundary = strings.named("boundary", &slot.snapshot.boundary);
+                                ui.label(strings.format(
+                                    "saves.slot",
+                                    &[
+                                        ("generation", &slot.generation),
+                                        ("rule", &slot.snapshot.rule),
+                                        ("boundary", &boundary),
+                                    ],
+                                ));
+                                ui.label(format_date(slot.saved_at));
+                                ui.horizontal(|ui| {
+                                    if ui.button(strings.get("saves.load")).clicked() {
+                                        load = Some(i);
+                                    }
+                                    if ui.button(strings.get("saves.delete")).clicked() {
+                                        delete = Some(i);
+                                    }
+                                });
+                            });
+                        });
+                    }
+                });
+        });
+
+    if save {
+        let name = browser.name.trim().to_owned();
+        let saved_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        let slot = SaveSlot {
+            name: name.clone(),
+            generation: counters.generation,
+            saved_at,
+            thumbnail: thumbnail(&board),
+            snapshot: Snapshot::take(&board, &rule, &chances, ticker.1, &markers),
+        };
+
+        let path = SaveSlot::path(&name);
+        let written = fs::create_dir_all(SAVES_DIR)
+            .map_err(|error| error.to_string())
+            .and_then(|()| write_ron(&slot, &path));
+        browser.message = Some(match written {
+            Ok(()) => {
+                info!(
+                    path = %path.display(),
+                    generation = counters.generation,
+                    population = board.population(),
+                    "saved a board"
+                );
+                let path = path.display();
+                strings.format("saves.saved", &[("name", &name), ("path", &path)])
+            }
+            Err(error) => {
+                warn!(path = %path.display(), %error, "couldn't save a board");
+                strings.format("saves.save-failed", &[("error", &error)])
+            }
+        });
+        browser.slots = None;
+    }
+
+    if load.is_some() {
+        browser.loading = load;
+    }
+    if let Some(i) = delete {
+        let (path, slot) = browser.list().remove(i);
+        if let Err(error) = fs::remove_file(&path) {
+            let message = strings.format(
+                "saves.delete-failed",
+                &[("name", &slot.name), ("error", &error)],
+            );
+            browser.message = Some(message);
+            browser.slots = None;
+        }
+    }
+
+    if !open {
+        browser.open = false;
+    }
+}
+
+fn load_slot(
+    mut browser: ResMut<SavesBrowser>,
+    (mut chances, mut ticker): (ResMut<Chances>, ResMut<TickDuration>),
+    strings: Res<Strings>,
+    mut setup: BoardSetup,
+) {
+    let Some(i) = browser.loading.take() else {
+        return;
+    };
+
+    let (path, slot) = &browser.list()[i];
+    let message = match slot.snapshot.restore(&mut setup, &mut chances, &mut ticker) {
+        Ok(()) => {
+            info!(path = %path.display(), generation = slot.generation, "loaded a board");
+            strings.format("saves.loaded", &[("name", &slot.name)])
+        }
+        Err(error) => {
+            warn!(path = %path.display(), %error, "couldn't load a board");
+            strings.format(
+                "saves.load-failed",
+                &[("name", &slot.name), ("error", &error)],
+            )
+        }
+    };
+    browser.message = Some(message);
+}
@@ -0,0 +1,540 @@
+use std::fs;
+use std::marker::PhantomData;
+use std::path::{Path, PathBuf};
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use serde::Deserialize;
+
+use crate::ants::Ant;
+use crate::balance::{cap_setting, PopulationCap};
+use crate::bitboard::{Bitboard, Boundary};
+use crate::freeze::Frozen;
+use crate::hotkeys::Hotkeys;
+use crate::inspect::RestartTimeline;
+use crate::lang::Strings;
+use crate::markers::{Marker, Markers};
+use crate::picture::overlay_layer;
+use crate::rng::Rng;
+use crate::rules::{Automaton, RuleSet};
+use crate::simulation::StepOverride;
+use crate::spawners::Spawner;
+use crate::stack::UpperLayer;
+use crate::team_rules::TeamRules;
+use crate::terrain::Terrain;
+use crate::tournament::Tournament;
+use crate::undo::UndoHistory;
+use crate::zones::Zones;
+use crate::{Board, GameState, SimCounters, SimRng, MAP_SIZE, SIM_SEED};
+
+const SCENARIO_DIR: &str = "assets/scenarios";
+const LOCK_COLOR: Color = Color::rgba(1.0, 1.0, 1.0, 0.08);
+
+/// A puzzle: a starting board, regions the player can't edit, and something to achieve.
+/// Scenario files are RON in `assets/scenarios`.
+#[derive(Clone, Debug, Deserialize)]
+pub struct Scenario {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub rule: String,
+    #[serde(default = "default_boundary")]
+    pub boundary: String,
+    /// Rows of the board from the top: `.` empty, `#` wall, `1`/`2` a team's cell, `n`
+    /// a cell on neither team. Missing rows and columns are empty.
+    pub cells: Vec<String>,
+    /// `(left, top, width, height)` rectangles, counted in cells from the top left.
+    #[serde(default)]
+    pub locked: Vec<(u32, u32, u32, u32)>,
+    pub objective: Objective,
+    /// Most live cells either team may have before its births are blocked, for as long as
+    /// the scenario lasts.
+    #[serde(default)]
+    pub population_cap: Option<u32>,
+}
+
+fn default_boundary() -> String {
+    Boundary::default().name().to_owned()
+}
+
+/// What the player has to achieve. Teams are numbered 1 and 2, as in the rest of the UI.
+#[derive(Clone, Copy, Debug, Deserialize)]
+pub enum Objective {
+    /// Keep at least one of `team`'s cells alive for `generations` generations.
+    Survive { team: u8, generations: u64 },
+    /// Wipe out every one of `team`'s cells within `generations` generations.
+    Eliminate { team: u8, generations: u64 },
+}
+
+impl Objective {
+    fn describe(self, strings: &Strings) -> String {
+        let (key, team, generations) = match self {
+            Objective::Survive { team, generations } => ("scenario.survive", team, generations),
+            Objective::Eliminate { team, generations } => ("scenario.eliminate", team, generations),
+        };
+        strings.format(key, &[("team", &team), ("generations", &generations)])
+    }
+
+    /// Whether the scenario is won (`Some(true)`), lost (`Some(false)`) or still going
+    /// after `elapsed` generations.
+    fn evaluate(self, board: &Bitboard, elapsed: u64) -> Option<bool> {
+        match self {
+            Objective::Survive { team, generations } => {
+                if board.team_population(team + 1) == 0 {
+                    Some(false)
+                } else if elapsed >= generations {
+                    Some(true)
+                } else {
+                    None
+                }
+            }
+            Objective::Eliminate { team, generations } => {
+                if board.team_population(team + 1) == 0 {
+                    Some(true)
+                } else if elapsed >= generations {
+                    Some(false)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+impl Scenario {
+    pub fn load(path: &Path) -> Result<Scenario, String> {
+        let text = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        let scenario: Scenario = ron::from_str(&text).map_err(|error| error.to_string())?;
+
+        // catch mistakes while the scenario is picked rather than halfway through setting it up
+        RuleSet::parse(&scenario.rule)?;
+        Boundary::from_name(&scenario.boundary)
+            .ok_or_else(|| format!("unknown boundary `{}`", scenario.boundary))?;
+
+        let (Objective::Survive { team, .. } | Objective::Eliminate { team, .. }) =
+            scenario.objective;
+        if !(1..=2).contains(&team) {
+            return Err(format!("the objective's team {team} isn't 1 or 2"));
+        }
+
+        let fits = |start: u32, length: u32, side: u32| {
+            start.checked_add(length).is_some_and(|end| end <= side)
+        };
+        for &(left, top, width, height) in &scenario.locked {
+            if !fits(left, width, MAP_SIZE.0) || !fits(top, height, MAP_SIZE.1) {
+                return Err(format!(
+                    "the locked region ({left}, {top}, {width}, {height}) doesn't fit on the board"
+                ));
+            }
+        }
+
+        Ok(scenario)
+    }
+
+    fn board(&self) -> Bitboard {
+        let mut board = Bitboard::new(MAP_SIZE.0, MAP_SIZE.1);
+        board.set_boundary(Boundary::from_name(&self.boundary).unwrap_or_default());
+
+        for (row, line) in self.cells.iter().enumerate().take(MAP_SIZE.1 as usize) {
+            let y = MAP_SIZE.1 - 1 - row as u32;
+
+            for (x, c) in line.chars().enumerate().take(MAP_SIZE.0 as usize) {
+                let x = x as u32;
+
+                match c {
+                    '#' => board.set_wall(x, y, true),
+                    'n' => board.set(x, y, 1),
+                    '1' => board.set(x, y, 2),
+                    '2' => board.set(x, y, 3),
+                    _ => {}
+                }
+            }
+        }
+
+        board
+    }
+}
+
+/// The scenario being played, if any, and the generation it started at.
+#[derive(Resource, Default)]
+pub struct ActiveScenario {
+    scenario: Option<(Scenario, u64)>,
+}
+
+impl ActiveScenario {
+    /// Whether the player may not edit (x, y), in board coordinates.
+    pub fn is_locked(&self, x: u32, y: u32) -> bool {
+        let Some((scenario, _)) = &self.scenario else {
+            return false;
+        };
+
+        let top = MAP_SIZE.1 - 1 - y;
+        scenario
+            .locked
+            .iter()
+            .any(|&(left, top_edge, width, height)| {
+                (left..left + width).contains(&x) && (top_edge..top_edge + height).contains(&top)
+            })
+    }
+}
+
+/// Scenario files found the last time the menu was opened.
+#[derive(Resource, Default)]
+struct ScenarioList(Vec<(PathBuf, Result<Scenario, String>)>);
+
+#[derive(Component)]
+pub(crate) struct LockOverlay;
+
+pub struct ScenarioPlugin;
+
+impl Plugin for ScenarioPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActiveScenario>()
+            .init_resource::<ScenarioList>()
+            .add_system(open_menu)
+            .add_system_set(
+                SystemSet::on_enter(GameState::ScenarioSelect).with_system(list_scenarios),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::ScenarioSelect).with_system(scenario_menu),
+            )
+            .add_system_set(
+                SystemSet::on_update(GameState::Playing)
+                    .with_system(evaluate_objective.after(crate::update_map))
+                    .with_system(objective_panel),
+            )
+            .add_system_set(SystemSet::on_update(GameState::Won).with_system(result_window))
+            .add_system_set(SystemSet::on_update(GameState::Lost).with_system(result_window));
+    }
+}
+
+/// Entities that belong to one board setup and go away with it.
+type BoardEntities = Or<(With<Spawner>, With<Ant>, With<LockOverlay>)>;
+
+/// Everything starting or leaving a scenario, or any other fresh board, resets.
+#[derive(SystemParam)]
+pub(crate) struct BoardSetup<'w, 's> {
+    commands: Commands<'w, 's>,
+    active: ResMut<'w, ActiveScenario>,
+    board: ResMut<'w, Board>,
+    rule: ResMut<'w, RuleSet>,
+    automaton: ResMut<'w, Automaton>,
+    counters: Res<'w, SimCounters>,
+    rng: ResMut<'w, SimRng>,
+    history: ResMut<'w, UndoHistory>,
+    restarts: EventWriter<'w, 's, RestartTimeline>,
+    extras: BoardExtras<'w, 's>,
+    cap: ResMut<'w, PopulationCap>,
+    agent_query: Query<'w, 's, Entity, BoardEntities>,
+    tilemap_query: Query<'w, 's, (&'static TilemapGridSize, &'static Transform), With<TileStorage>>,
+}
+
+/// What's kept alongside the board and goes with it when it's replaced.
+#[derive(SystemParam)]
+pub(crate) struct BoardExtras<'w, 's> {
+    markers: ResMut<'w, Markers>,
+    frozen: ResMut<'w, Frozen>,
+    terrain: ResMut<'w, Terrain>,
+    zones: ResMut<'w, Zones>,
+    upper: ResMut<'w, UpperLayer>,
+    team_rules: ResMut<'w, TeamRules>,
+    #[system_param(ignore)]
+    _marker: PhantomData<&'s ()>,
+}
+
+impl BoardExtras<'_, '_> {
+    fn clear(&mut self) {
+        self.markers.0.clear();
+        self.frozen.regions.clear();
+        if !self.terrain.is_empty() {
+            self.terrain.clear();
+        }
+        if !self.zones.is_empty() {
+            self.zones.clear();
+        }
+        if self.upper.is_active() {
+            self.upper.remove();
+        }
+        if self.team_rules.is_active() {
+            self.team_rules.clear();
+        }
+    }
+}
+
+impl BoardSetup<'_, '_> {
+    fn start(&mut self, scenario: Scenario) {
+        // already validated when loaded
+        self.reset(scenario.board());
+        *self.rule = RuleSet::parse(&scenario.rule).unwrap();
+        *self.automaton = Automaton::Life;
+        self.rng.0 = Rng::new(SIM_SEED);
+        self.set_population_cap(scenario.population_cap);
+
+        if let Ok((grid_size, tilemap_transform)) = self.tilemap_query.get_single() {
+            for &(left, top, width, height) in &scenario.locked {
+                let size = Vec2::new(width as f32 * grid_size.x, height as f32 * grid_size.y);
+                // tile positions are tile centers, so the region's corner is half a cell out
+                let corner = Vec2::new(left as f32, (MAP_SIZE.1 - top - height) as f32)
+                    * Vec2::new(grid_size.x, grid_size.y)
+                    - Vec2::new(grid_size.x, grid_size.y) / 2.0;
+                let center = tilemap_transform.translation.truncate() + corner + size / 2.0;
+
+                self.commands.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: LOCK_COLOR,
+                            custom_size: Some(size),
+                            ..default()
+                        },
+                        transform: Transform::from_translation(center.extend(2.0)),
+                        ..default()
+                    },
+                    LockOverlay,
+                    overlay_layer(),
+                ));
+            }
+        }
+
+        self.active.scenario = Some((scenario, self.counters.generation));
+    }
+
+    pub fn sandbox(&mut self) {
+        self.reset(Bitboard::new(MAP_SIZE.0, MAP_SIZE.1));
+    }
+
+    /// Switches to a board and Life rule from somewhere other than a scenario.
+    pub fn load(&mut self, board: Bitboard, rule: RuleSet) {
+        self.reset(board);
+        *self.rule = rule;
+        *self.automaton = Automaton::Life;
+    }
+
+    /// Replaces the board, leaving any scenario and removing everything placed on (and
+    /// all undo history, markers, frozen regions, terrain, zones, any stacked layer and the
+    /// population cap for) the old board. The rule is kept, as are the zones' rules, but both teams go back to
+    /// playing by it.
+    pub fn reset(&mut self, board: Bitboard) {
+        for entity in self.agent_query.iter() {
+            self.commands.entity(entity).despawn();
+        }
+
+        self.board.0 = board;
+        self.active.scenario = None;
+        self.history.clear();
+        self.restarts.send(RestartTimeline);
+        self.extras.clear();
+        if self.cap.is_active() {
+            self.cap.0 = None;
+        }
+    }
+
+    /// Caps both teams' populations for as long as the current board lasts.
+    pub fn set_population_cap(&mut self, cap: Option<u32>) {
+        self.cap.0 = cap;
+    }
+
+    /// Puts back markers saved along with the board just loaded.
+    pub fn place_markers(&mut self, markers: Vec<Marker>) {
+        self.extras.markers.0 = markers;
+    }
+}
+
+fn open_menu(keys: Hotkeys, mut state: ResMut<State<GameState>>) {
+    if keys.just_pressed(KeyCode::Escape) && *state.current() != GameState::ScenarioSelect {
+        // fails only if a transition is already queued this frame
+        let _ = state.push(GameState::ScenarioSelect);
+    }
+}
+
+fn list_scenarios(mut list: ResMut<ScenarioList>) {
+    list.0.clear();
+
+    let Ok(entries) = fs::read_dir(SCENARIO_DIR) else {
+        return;
+    };
+
+    let mut paths: Vec<PathBuf> = entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "ron"))
+        .collect();
+    paths.sort();
+
+    list.0 = paths
+        .into_iter()
+        .map(|path| {
+            let scenario = Scenario::load(&path);
+            (path, scenario)
+        })
+        .collect();
+}
+
+fn scenario_menu(
+    mut egui_context: ResMut<EguiContext>,
+    list: Res<ScenarioList>,
+    mut state: ResMut<State<GameState>>,
+    mut tournament: ResMut<Tournament>,
+    mut setup: BoardSetup,
+    strings: Res<Strings>,
+) {
+    egui::Window::new(strings.get("scenario.menu"))
+        .id(egui::Id::new("scenarios"))
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            if list.0.is_empty() {
+                ui.label(strings.format("scenario.none", &[("dir", &SCENARIO_DIR)]));
+            }
+
+            for (path, scenario) in &list.0 {
+                match scenario {
+                    Ok(scenario) => {
+                        if ui.button(&scenario.name).clicked() {
+                            setup.start(scenario.clone());
+                            let _ = state.replace(GameState::Playing);
+                        }
+                        ui.label(&scenario.description);
+                        ui.label(scenario.objective.describe(&strings));
+                    }
+                    Err(error) => {
+                        ui.colored_label(
+                            egui::Color32::LIGHT_RED,
+                            format!("{}: {error}", path.display()),
+                        );
+                    }
+                }
+                ui.separator();
+            }
+
+            ui.horizontal(|ui| {
+                let rounds = egui::Slider::new(&mut tournament.best_of, 1..=9);
+                ui.add(rounds.text(strings.get("scenario.rounds")));
+                let cap = cap_setting(ui, &strings, tournament.population_cap);
+                if tournament.population_cap != cap {
+                    tournament.population_cap = cap;
+                }
+
+                if ui.button(strings.get("scenario.start-match")).clicked() {
+                    tournament.begin(false);
+                    let _ = state.replace(GameState::Round);
+                }
+                if ui
+                    .button(strings.get("scenario.start-championship"))
+                    .clicked()
+                {
+                    tournament.begin(true);
+                    let _ = state.replace(GameState::Designing);
+                }
+            });
+            ui.separator();
+
+            ui.horizontal(|ui| {
+                if ui.button(strings.get("scenario.sandbox")).clicked() {
+                    setup.sandbox();
+                    let _ = state.replace(GameState::Sandbox);
+                }
+                // only when the menu was opened on top of something to go back to
+                if !state.inactives().is_empty()
+                    && ui.button(strings.get("scenario.back")).clicked()
+                {
+                    let _ = state.pop();
+                }
+            });
+        });
+}
+
+fn evaluate_objective(
+    board: Res<Board>,
+    counters: Res<SimCounters>,
+    active: Res<ActiveScenario>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let Some((scenario, start)) = &active.scenario else {
+        return;
+    };
+
+    match scenario
+        .objective
+        .evaluate(&board, counters.generation - start)
+    {
+        Some(true) => {
+            let _ = state.set(GameState::Won);
+        }
+        Some(false) => {
+            let _ = state.set(GameState::Lost);
+        }
+        None => {}
+    }
+}
+
+fn objective_panel(
+    mut egui_context: ResMut<EguiContext>,
+    counters: Res<SimCounters>,
+    active: Res<ActiveScenario>,
+    strings: Res<Strings>,
+) {
+    let Some((scenario, start)) = &active.scenario else {
+        return;
+    };
+
+    egui::Window::new(strings.get("scenario.objective"))
+        .id(egui::Id::new("objective"))
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(&scenario.name);
+            ui.label(scenario.objective.describe(&strings));
+
+            let generations = match scenario.objective {
+                Objective::Survive { generations, .. }
+                | Objective::Eliminate { generations, .. } => generations,
+            };
+            ui.label(strings.format(
+                "scenario.generation",
+                &[
+                    ("generation", &(counters.generation - start)),
+                    ("generations", &generations),
+                ],
+            ));
+        });
+}
+
+fn result_window(
+    mut egui_context: ResMut<EguiContext>,
+    mut state: ResMut<State<GameState>>,
+    strings: Res<Strings>,
+    mut setup: BoardSetup,
+) {
+    let won = *state.current() == GameState::Won;
+
+    egui::Window::new(strings.get(if won {
+        "scenario.complete"
+    } else {
+        "scenario.failed"
+    }))
+    .id(egui::Id::new("scenario result"))
+    .resizable(false)
+    .collapsible(false)
+    .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+    .show(egui_context.ctx_mut(), |ui| {
+        ui.horizontal(|ui| {
+            if ui.button(strings.get("scenario.retry")).clicked() {
+                if let Some((scenario, _)) = setup.active.scenario.clone() {
+                    setup.start(scenario);
+                    let _ = state.set(GameState::Playing);
+                }
+            }
+            if ui.button(strings.get("scenario.menu")).clicked() {
+                let _ = state.set(GameState::ScenarioSelect);
+            }
+            if ui.button(strings.get("scenario.sandbox")).clicked() {
+                setup.sandbox();
+                let _ = state.set(GameState::Sandbox);
+            }
+        });
+    });
+}
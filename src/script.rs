@@ -0,0 +1,219 @@
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Array, CallFnOptions, Dynamic, Engine, Scope, AST, INT};
+
+use crate::bitboard::Bitboard;
+use crate::hooks::Hooks;
+use crate::lang::Strings;
+use crate::rules::{Automaton, Neighborhood};
+
+const DEFAULT_SCRIPT: &str = "assets/scripts/life.rhai";
+/// Operations one `next_state` call may take before it's considered stuck.
+const MAX_OPERATIONS: u64 = 10_000;
+/// Results remembered before the cache is cleared, to bound its memory.
+const MAX_CACHED: usize = 1 << 20;
+
+/// Cell state followed by the neighbor count and up to 8 neighbor states.
+type CacheKey = [u8; 10];
+
+/// A rule written as a rhai `next_state(cell, neighbors)` function, loaded at runtime.
+/// Scripts can't touch the filesystem or loop forever, and are assumed to be pure, so
+/// each distinct neighborhood is only evaluated once.
+#[derive(Resource)]
+pub struct ScriptRule {
+    engine: Engine,
+    ast: Option<AST>,
+    cache: HashMap<CacheKey, u8>,
+    path: String,
+    error: Option<String>,
+}
+
+/// A rhai engine that can't load modules, print, or run any one call for long.
+pub fn sandboxed_engine() -> Engine {
+    let mut engine = Engine::new();
+    engine
+        .set_module_resolver(DummyModuleResolver::new())
+        .set_max_operations(MAX_OPERATIONS)
+        .set_max_call_levels(32)
+        .set_max_expr_depths(64, 32)
+        .set_max_string_size(1024)
+        .set_max_array_size(1024)
+        .on_print(|_| {})
+        .on_debug(|_, _, _| {});
+    engine
+}
+
+impl Default for ScriptRule {
+    fn default() -> Self {
+        Self {
+            engine: sandboxed_engine(),
+            ast: None,
+            cache: HashMap::new(),
+            path: DEFAULT_SCRIPT.to_owned(),
+            error: None,
+        }
+    }
+}
+
+impl ScriptRule {
+    fn load(&mut self) -> Result<(), String> {
+        let ast = self
+            .engine
+            .compile_file(PathBuf::from(&self.path))
+            .map_err(|error| error.to_string())?;
+
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == "next_state" && f.params.len() == 2)
+        {
+            return Err("the script has no `next_state(cell, neighbors)` function".to_owned());
+        }
+
+        self.ast = Some(ast);
+        self.cache.clear();
+        Ok(())
+    }
+
+    /// Advances the board one generation with the script. A script error stops the
+    /// script, leaving the board as it was, until it's loaded again.
+    pub fn step(&mut self, board: &mut Bitboard, neighborhood: Neighborhood) {
+        let Some(ast) = &self.ast else {
+            return;
+        };
+
+        if self.cache.len() > MAX_CACHED {
+            self.cache.clear();
+        }
+
+        let (engine, cache) = (&self.engine, &mut self.cache);
+        let mut error = None;
+        let mut scope = Scope::new();
+        let mut next = board.clone();
+
+        next.step_cells(neighborhood, |cell, neighbors| {
+            if error.is_some() {
+                return cell;
+            }
+
+            let mut key = [0; 10];
+            key[0] = cell;
+            key[1] = neighbors.len() as u8;
+            key[2..2 + neighbors.len()].copy_from_slice(neighbors);
+
+            if let Some(&state) = cache.get(&key) {
+                return state;
+            }
+
+            let neighbors: Array = neighbors.iter().map(|&n| Dynamic::from(n as INT)).collect();
+            let result = engine
+                .call_fn_with_options::<INT>(
+                    CallFnOptions::new().eval_ast(false),
+                    &mut scope,
+                    ast,
+                    "next_state",
+                    (cell as INT, neighbors),
+                )
+                .map_err(|error| error.to_string())
+                .and_then(|state| {
+                    u8::try_from(state)
+                        .map_err(|_| format!("next_state returned {state}, expected 0-255"))
+                });
+
+            match result {
+                Ok(state) => {
+                    cache.insert(key, state);
+                    state
+                }
+                Err(message) => {
+                    error = Some(message);
+                    cell
+                }
+            }
+        });
+
+        match error {
+            Some(error) => {
+                self.ast = None;
+                self.error = Some(error);
+            }
+            None => *board = next,
+        }
+    }
+}
+
+pub struct ScriptPlugin;
+
+impl Plugin for ScriptPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ScriptRule>()
+            .init_resource::<Hooks>()
+            .add_system(script_panel);
+    }
+}
+
+fn script_panel(
+    mut egui_context: ResMut<EguiContext>,
+    mut script: ResMut<ScriptRule>,
+    mut automaton: ResMut<Automaton>,
+    mut hooks: ResMut<Hooks>,
+    strings: Res<Strings>,
+) {
+    egui::Window::new(strings.get("script.title"))
+        .id(egui::Id::new("script"))
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let script = &mut *script;
+
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut script.path);
+
+                if ui.button(strings.get("script.load")).clicked() {
+                    match script.load() {
+                        Ok(()) => {
+                            script.error = None;
+                            *automaton = Automaton::Scripted;
+                        }
+                        Err(error) => script.error = Some(error),
+                    }
+                }
+            });
+
+            if *automaton == Automaton::Scripted && ui.button(strings.get("script.stop")).clicked()
+            {
+                *automaton = Automaton::Life;
+            }
+
+            if let Some(error) = &script.error {
+                ui.colored_label(egui::Color32::LIGHT_RED, error);
+            }
+
+            ui.separator();
+            let hooks = &mut *hooks;
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("script.hooks"));
+                ui.text_edit_singleline(&mut hooks.path);
+
+                if hooks.is_loaded() {
+                    if ui.button(strings.get("script.unload")).clicked() {
+                        hooks.unload();
+                    }
+                } else if ui.button(strings.get("script.load")).clicked() {
+                    hooks.error = hooks.load().err();
+                    hooks.log.clear();
+                }
+            });
+
+            for line in &hooks.log {
+                ui.label(line);
+            }
+            if let Some(error) = &hooks.error {
+                ui.colored_label(egui::Color32::LIGHT_RED, error);
+            }
+        });
+}
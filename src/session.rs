@@ -0,0 +1,193 @@
+use std::path::Path;
+use std::time::Duration;
+
+use bevy::app::AppExit;
+use bevy::prelude::*;
+use bevy::window::{WindowCloseRequested, WindowId, WindowMode, WindowResized};
+use serde::{Deserialize, Serialize};
+
+use crate::autosave::{read_ron, write_ron, Autosave, Snapshot};
+use crate::camera::{refit_camera, MainCamera};
+use crate::lang::Strings;
+use crate::markers::Markers;
+use crate::rules::{Chances, RuleSet};
+use crate::scenario::BoardSetup;
+use crate::tools::Tool;
+use crate::{Board, GameState, TickDuration, UnlimitedSpeed};
+
+/// Where the session is kept between launches, next to the autosave.
+pub const SESSION_PATH: &str = "session.ron";
+/// Frames without a resize before the restored camera is left alone. The window settles
+/// into its restored size over the first few frames, and each resize refits the camera.
+const SETTLE_FRAMES: u32 = 3;
+
+/// Writes the session on exit: the free play board, camera, tool, speed, window and language, and
+/// puts all of it back on the next launch. Where egui windows were isn't kept, since egui
+/// only exposes that with its persistence feature.
+pub struct SessionPlugin;
+
+#[derive(Serialize, Deserialize)]
+struct Session {
+    /// The board and its settings, if the app was closed in free play.
+    snapshot: Option<Snapshot>,
+    camera: (f32, f32, f32), // x, y, scale
+    /// Index into `Tool::ALL`.
+    tool: usize,
+    paused: bool,
+    unlimited: bool,
+    /// Auto speed's target frame time in milliseconds, if it was on. Missing from sessions
+    /// written before there was auto speed.
+    #[serde(default)]
+    auto_speed: Option<u64>,
+    window: (f32, f32),
+    fullscreen: bool,
+    /// Missing from sessions written before languages could be picked.
+    #[serde(default)]
+    language: Option<String>,
+}
+
+/// The camera waiting to be put back once the window stops resizing.
+#[derive(Resource, Default)]
+struct PendingCamera {
+    camera: Option<(Vec3, f32)>,
+    settled_frames: u32,
+}
+
+impl Plugin for SessionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingCamera>()
+            .add_startup_system_to_stage(StartupStage::PostStartup, restore_session)
+            .add_system(restore_camera.after(refit_camera))
+            .add_system_to_stage(CoreStage::Last, save_session);
+    }
+}
+
+fn restore_session(
+    mut setup: BoardSetup,
+    (mut chances, mut ticker): (ResMut<Chances>, ResMut<TickDuration>),
+    (mut tool, mut unlimited): (ResMut<Tool>, ResMut<UnlimitedSpeed>),
+    (mut windows, mut autosave): (ResMut<Windows>, ResMut<Autosave>),
+    (mut pending, mut strings): (ResMut<PendingCamera>, ResMut<Strings>),
+) {
+    let path = Path::new(SESSION_PATH);
+    if !path.exists() {
+        return;
+    }
+    let session: Session = match read_ron(path) {
+        Ok(session) => session,
+        Err(error) => {
+            warn!("ignoring unreadable session {SESSION_PATH}: {error}");
+            return;
+        }
+    };
+
+    if let Some(snapshot) = &session.snapshot {
+        match snapshot.restore(&mut setup, &mut chances, &mut ticker) {
+            // the session is at least as new as any autosave
+            Ok(()) => autosave.forget_recovered(),
+            Err(error) => warn!("couldn't restore the session's board: {error}"),
+        }
+    }
+
+    if let Some(&(restored, _)) = Tool::ALL.get(session.tool) {
+        *tool = restored;
+    }
+    unlimited.enabled = session.unlimited;
+    unlimited.auto = session.auto_speed.map(Duration::from_millis);
+    if let Some(language) = &session.language {
+        if let Err(error) = strings.choose(language) {
+            warn!("couldn't switch back to language {language}: {error}");
+        }
+    }
+    if session.paused {
+        ticker.0.pause();
+    } else {
+        ticker.0.unpause();
+    }
+
+    if let Some(window) = windows.get_primary_mut() {
+        window.set_resolution(session.window.0, session.window.1);
+        if session.fullscreen {
+            window.set_mode(WindowMode::BorderlessFullscreen);
+        }
+    }
+
+    let (x, y, scale) = session.camera;
+    pending.camera = Some((Vec3::new(x, y, 0.0), scale));
+}
+
+fn restore_camera(
+    mut resized: EventReader<WindowResized>,
+    mut pending: ResMut<PendingCamera>,
+    mut camera_query: Query<(&mut Transform, &mut OrthographicProjection), With<MainCamera>>,
+) {
+    let Some((translation, scale)) = pending.camera else {
+        return;
+    };
+    let Ok((mut transform, mut projection)) = camera_query.get_single_mut() else {
+        return;
+    };
+
+    transform.translation = translation.truncate().extend(transform.translation.z);
+    projection.scale = scale;
+
+    if resized.iter().count() > 0 {
+        pending.settled_frames = 0;
+    } else {
+        pending.settled_frames += 1;
+        if pending.settled_frames >= SETTLE_FRAMES {
+            pending.camera = None;
+        }
+    }
+}
+
+/// Writes the session once the window is closing or the app is exiting.
+fn save_session(
+    (mut close_requested, mut exit): (EventReader<WindowCloseRequested>, EventReader<AppExit>),
+    state: Res<State<GameState>>,
+    (board, rule, chances, markers): (Res<Board>, Res<RuleSet>, Res<Chances>, Res<Markers>),
+    (ticker, unlimited, tool): (Res<TickDuration>, Res<UnlimitedSpeed>, Res<Tool>),
+    (windows, strings): (Res<Windows>, Res<Strings>),
+    camera_query: Query<(&Transform, &OrthographicProjection), With<MainCamera>>,
+    mut saved: Local<bool>,
+) {
+    let closing = close_requested
+        .iter()
+        .any(|event| event.id == WindowId::primary());
+    if !(closing || exit.iter().count() > 0) || *saved {
+        return;
+    }
+    *saved = true;
+
+    let (Some(window), Ok((transform, projection))) =
+        (windows.get_primary(), camera_query.get_single())
+    else {
+        return;
+    };
+
+    // scenarios and matches set up their own boards, so only free play is worth keeping
+    let snapshot = (*state.current() == GameState::Sandbox)
+        .then(|| Snapshot::take(&board, &rule, &chances, ticker.1, &markers));
+    let session = Session {
+        snapshot,
+        camera: (
+            transform.translation.x,
+            transform.translation.y,
+            projection.scale,
+        ),
+        tool: Tool::ALL
+            .iter()
+            .position(|&(candidate, _)| candidate == *tool)
+            .unwrap_or(0),
+        paused: ticker.0.paused(),
+        unlimited: unlimited.enabled,
+        auto_speed: unlimited.auto.map(|target| target.as_millis() as u64),
+        window: (window.width(), window.height()),
+        fullscreen: window.mode() != WindowMode::Windowed,
+        language: Some(strings.code.clone()),
+    };
+
+    if let Err(error) = write_ron(&session, Path::new(SESSION_PATH)) {
+        warn!("couldn't save the session to {SESSION_PATH}: {error}");
+    }
+}
@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::energy::Energy;
+use crate::lang::Strings;
+use crate::picture::overlay_layer;
+use crate::tools::{BoardCursor, Brush, Symmetry, Tool};
+use crate::undo::UndoHistory;
+use crate::{Board, TEAM_COLORS};
+
+/// The line, rectangle and ellipse tools: drag between two tiles to preview the shape, and
+/// release to draw it as one undo step.
+pub struct ShapesPlugin;
+
+/// The shape being dragged out, from the press to the tile under the cursor.
+#[derive(Resource, Default)]
+struct Drag {
+    start: Option<TilePos>,
+    end: Option<TilePos>,
+}
+
+/// Whether rectangles and ellipses are drawn filled or as outlines.
+#[derive(Resource)]
+struct ShapeSettings {
+    filled: bool,
+}
+
+impl Default for ShapeSettings {
+    fn default() -> Self {
+        Self { filled: true }
+    }
+}
+
+type TilemapGeometry = (
+    &'static TilemapSize,
+    &'static TilemapGridSize,
+    &'static TilemapType,
+    &'static Transform,
+);
+
+/// A cell of the shape being dragged out.
+#[derive(Component)]
+struct ShapePreview;
+
+impl Plugin for ShapesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Drag>()
+            .init_resource::<ShapeSettings>()
+            .add_system(draw_shape)
+            .add_system(preview_shape.after(draw_shape))
+            .add_system(shape_panel);
+    }
+}
+
+/// Tiles on the straight line from `from` to `to`, both ends included (Bresenham).
+fn line_tiles(from: TilePos, to: TilePos) -> Vec<TilePos> {
+    let (mut x, mut y) = (from.x as i64, from.y as i64);
+    let (dx, dy) = (to.x as i64 - x, to.y as i64 - y);
+    let (step_x, step_y) = (dx.signum(), dy.signum());
+    let (dx, dy) = (dx.abs(), -dy.abs());
+
+    let mut error = dx + dy;
+    let mut tiles = vec![from];
+
+    while (x, y) != (to.x as i64, to.y as i64) {
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+
+        tiles.push(TilePos::new(x as u32, y as u32));
+    }
+
+    tiles
+}
+
+/// Tiles in the box with corners `from` and `to` that `inside` accepts, or with `filled`
+/// off only those with a side on a tile it rejects.
+fn area_tiles(
+    from: TilePos,
+    to: TilePos,
+    filled: bool,
+    inside: impl Fn(i64, i64) -> bool,
+) -> Vec<TilePos> {
+    let (left, right) = (from.x.min(to.x) as i64, from.x.max(to.x) as i64);
+    let (bottom, top) = (from.y.min(to.y) as i64, from.y.max(to.y) as i64);
+    let inside =
+        |x: i64, y: i64| (left..=right).contains(&x) && (bottom..=top).contains(&y) && inside(x, y);
+
+    let mut tiles = Vec::new();
+    for y in bottom..=top {
+        for x in left..=right {
+            let edge = [(1, 0), (-1, 0), (0, 1), (0, -1)]
+                .iter()
+                .any(|&(dx, dy)| !inside(x + dx, y + dy));
+
+            if inside(x, y) && (filled || edge) {
+                tiles.push(TilePos::new(x as u32, y as u32));
+            }
+        }
+    }
+
+    tiles
+}
+
+fn shape_tiles(tool: Tool, from: TilePos, to: TilePos, filled: bool) -> Vec<TilePos> {
+    match tool {
+        Tool::Line => line_tiles(from, to),
+        Tool::Rectangle => area_tiles(from, to, filled, |_, _| true),
+        Tool::Ellipse => {
+            // measured to the outer edges of the corner tiles, so thin boxes still get cells
+            let center = Vec2::new(from.x as f32 + to.x as f32, from.y as f32 + to.y as f32) / 2.0;
+            let radii = Vec2::new(
+                from.x.abs_diff(to.x) as f32 + 1.0,
+                from.y.abs_diff(to.y) as f32 + 1.0,
+            ) / 2.0;
+
+            area_tiles(from, to, filled, |x, y| {
+                ((Vec2::new(x as f32, y as f32) - center) / radii).length_squared() <= 1.0
+            })
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn is_shape(tool: Tool) -> bool {
+    matches!(tool, Tool::Line | Tool::Rectangle | Tool::Ellipse)
+}
+
+/// Commits the dragged shape on release. The whole shape is charged up front and nothing
+/// is drawn if the team can't afford it.
+fn draw_shape(
+    mouse: Res<Input<MouseButton>>,
+    (settings, symmetry): (Res<ShapeSettings>, Res<Symmetry>),
+    (tool, brush): (Res<Tool>, Res<Brush>),
+    cursor: BoardCursor,
+    mut drag: ResMut<Drag>,
+    (mut energy, mut history): (ResMut<Energy>, ResMut<UndoHistory>),
+    mut board: ResMut<Board>,
+) {
+    if !is_shape(*tool) {
+        if drag.start.is_some() {
+            *drag = Drag::default();
+        }
+        return;
+    }
+
+    if mouse.just_pressed(MouseButton::Left) {
+        drag.start = cursor.tile_pos();
+    }
+
+    if mouse.pressed(MouseButton::Left) {
+        let end = cursor.tile_pos().or(drag.end);
+        if drag.end != end {
+            drag.end = end;
+        }
+        return;
+    }
+
+    if drag.start.is_none() && drag.end.is_none() {
+        return;
+    }
+    let Drag { start, end } = std::mem::take(&mut *drag);
+    let (Some(start), Some(end)) = (start, end) else {
+        return;
+    };
+
+    let team = brush.team;
+    let shape = shape_tiles(*tool, start, end, settings.filled);
+    let tiles: Vec<(u32, u32)> = symmetry
+        .mirror(
+            shape.iter().map(|tile| (tile.x, tile.y)),
+            (board.width(), board.height()),
+        )
+        .into_iter()
+        .filter(|&(x, y)| !cursor.is_locked(TilePos::new(x, y)))
+        .filter(|&(x, y)| !board.is_wall(x, y) && board.get(x, y) != team)
+        .collect();
+
+    if tiles.is_empty() || !energy.try_spend(team, tiles.len() as f32) {
+        return;
+    }
+
+    history.record(&board);
+    for (x, y) in tiles {
+        board.set(x, y, team);
+    }
+}
+
+/// Shows the shape being dragged as see-through cells in the team's color.
+fn preview_shape(
+    mut commands: Commands,
+    (settings, symmetry): (Res<ShapeSettings>, Res<Symmetry>),
+    (tool, brush, drag): (Res<Tool>, Res<Brush>, Res<Drag>),
+    tilemap_query: Query<TilemapGeometry, With<TileStorage>>,
+    preview_query: Query<Entity, With<ShapePreview>>,
+) {
+    if !drag.is_changed() && !settings.is_changed() && !symmetry.is_changed() && !brush.is_changed()
+    {
+        return;
+    }
+
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn();
+    }
+
+    let (Some(start), Some(end)) = (drag.start, drag.end) else {
+        return;
+    };
+    let Ok((map_size, grid_size, map_type, tilemap_transform)) = tilemap_query.get_single() else {
+        return;
+    };
+
+    let mut color = TEAM_COLORS[brush.team as usize % TEAM_COLORS.len()];
+    color.set_a(0.5);
+
+    let shape = shape_tiles(*tool, start, end, settings.filled);
+    let tiles = symmetry.mirror(
+        shape.iter().map(|tile| (tile.x, tile.y)),
+        (map_size.x, map_size.y),
+    );
+
+    for (x, y) in tiles {
+        let center = TilePos::new(x, y).center_in_world(grid_size, map_type);
+
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(Vec2::new(grid_size.x, grid_size.y)),
+                    ..default()
+                },
+                transform: Transform::from_translation(
+                    tilemap_transform.translation + center.extend(2.0),
+                ),
+                ..default()
+            },
+            ShapePreview,
+            overlay_layer(),
+        ));
+    }
+}
+
+fn shape_panel(
+    tool: Res<Tool>,
+    mut settings: ResMut<ShapeSettings>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !matches!(*tool, Tool::Rectangle | Tool::Ellipse) {
+        return;
+    }
+
+    egui::Window::new(strings.get("shape.title"))
+        .id(egui::Id::new("shape"))
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut settings.filled, true, strings.get("shape.filled"));
+                ui.selectable_value(&mut settings.filled, false, strings.get("shape.outline"));
+            });
+
+            ui.label(strings.get("shape.help"));
+        });
+}
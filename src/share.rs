@@ -0,0 +1,77 @@
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::bitboard::{Bitboard, Boundary};
+use crate::rle;
+use crate::rules::RuleSet;
+use crate::MAP_SIZE;
+
+/// Marks a share code and its version, so other text pasted by mistake is rejected early.
+const PREFIX: &str = "cb1-";
+/// Most bytes a share code may inflate to. Together with `rle::decode`'s limits and the
+/// board having to be the usual size, this keeps a crafted code from exhausting memory.
+const MAX_INFLATED: u64 = 1 << 20;
+
+/// A short code holding the whole board, its edges and the rule: the board's RLE behind
+/// a boundary and a rule line, deflated and base64 encoded. Walls aren't kept, and only
+/// boards of the usual size can be shared.
+pub fn encode(board: &Bitboard, rule: &RuleSet) -> String {
+    let text = format!(
+        "{}\n{rule}\n{}",
+        board.boundary().name(),
+        rle::encode(board, (0, 0, board.width(), board.height()), rule)
+    );
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    // writing into a Vec can't fail
+    encoder.write_all(text.as_bytes()).unwrap();
+    let compressed = encoder.finish().unwrap();
+
+    format!(
+        "{PREFIX}{}",
+        base64::encode_config(compressed, base64::URL_SAFE_NO_PAD)
+    )
+}
+
+/// Reads a code from `encode` back into a board of the usual size and its rule.
+pub fn decode(code: &str) -> Result<(Bitboard, RuleSet), String> {
+    let code: String = code.chars().filter(|c| !c.is_whitespace()).collect();
+    let data = code
+        .strip_prefix(PREFIX)
+        .ok_or_else(|| "that isn't a share code".to_owned())?;
+
+    let compressed = base64::decode_config(data, base64::URL_SAFE_NO_PAD)
+        .map_err(|_| "the share code is damaged".to_owned())?;
+    let mut text = String::new();
+    DeflateDecoder::new(&compressed[..])
+        .take(MAX_INFLATED)
+        .read_to_string(&mut text)
+        .map_err(|_| "the share code is damaged".to_owned())?;
+
+    let mut lines = text.splitn(3, '\n');
+    let (Some(boundary), Some(rule), Some(cells)) = (lines.next(), lines.next(), lines.next())
+    else {
+        return Err("the share code is damaged".to_owned());
+    };
+
+    let boundary =
+        Boundary::from_name(boundary).ok_or_else(|| format!("unknown boundary `{boundary}`"))?;
+    let rule = RuleSet::parse(rule)?;
+
+    let clip = rle::decode(cells)?;
+    if (clip.width, clip.height) != MAP_SIZE {
+        return Err(format!(
+            "the share code is for a {} x {} board, not {} x {}",
+            clip.width, clip.height, MAP_SIZE.0, MAP_SIZE.1
+        ));
+    }
+
+    let mut board = Bitboard::new(MAP_SIZE.0, MAP_SIZE.1);
+    board.set_boundary(boundary);
+    clip.stamp(&mut board, (0, 0), |_, _| true);
+
+    Ok((board, rule))
+}
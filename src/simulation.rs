@@ -0,0 +1,124 @@
+use crate::bitboard::Bitboard;
+use crate::extra::{CellExtra, ExtraLayer};
+use crate::rules::RuleSet;
+
+/// A board stepped under a fixed rule, counting generations, with none of the game's
+/// spawners, ants or scripts. Each cell can carry an `E` payload, see `CellExtra`.
+#[derive(Clone, Debug)]
+pub struct Simulation<E = ()> {
+    pub board: Bitboard,
+    pub rule: RuleSet,
+    pub generation: u64,
+    pub extra: ExtraLayer<E>,
+}
+
+impl Simulation {
+    pub fn new(board: Bitboard, rule: RuleSet) -> Self {
+        Simulation::with_extra(board, rule)
+    }
+}
+
+impl<E: CellExtra> Simulation<E> {
+    /// A simulation whose cells all start with the default payload.
+    pub fn with_extra(board: Bitboard, rule: RuleSet) -> Self {
+        Self {
+            extra: ExtraLayer::new(board.width(), board.height()),
+            board,
+            rule,
+            generation: 0,
+        }
+    }
+
+    pub fn step(&mut self) {
+        self.step_with(&[]);
+    }
+
+    /// Steps a generation, then has each active override redo its part of it. The board
+    /// from before the step is only kept while something needs it.
+    pub fn step_with(&mut self, overrides: &[&dyn StepOverride]) {
+        let before =
+            (!E::INERT || overrides.iter().any(|o| o.is_active())).then(|| self.board.clone());
+        self.board.step(&self.rule);
+
+        if let Some(before) = &before {
+            apply_overrides(before, &mut self.board, &self.rule, overrides);
+            if !E::INERT {
+                self.extra.after_step(before, &mut self.board);
+            }
+        }
+        self.generation += 1;
+    }
+
+    /// Runs `generations` generations.
+    pub fn step_n(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.step();
+        }
+    }
+}
+
+/// Something redoing part of each step after it, from the board as it was before, like
+/// cells under a rule of their own.
+pub trait StepOverride {
+    /// Whether there's anything to redo, so whether the board before a step has to be kept.
+    fn is_active(&self) -> bool;
+
+    /// Redoes its cells for the step that took `before` to `board` under `rule`.
+    fn apply(&self, before: &Bitboard, board: &mut Bitboard, rule: &RuleSet);
+}
+
+/// Has the active ones of `overrides` redo their parts of the step that took `before` to
+/// `board`, in order, so a later one wins where they overlap.
+pub fn apply_overrides(
+    before: &Bitboard,
+    board: &mut Bitboard,
+    rule: &RuleSet,
+    overrides: &[&dyn StepOverride],
+) {
+    for o in overrides.iter().filter(|o| o.is_active()) {
+        o.apply(before, board, rule);
+    }
+}
+
+/// How the same board fared under two rules, see `diverge`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Divergence {
+    /// The first generation the two boards differed in, or `None` if they never did.
+    pub first_difference: Option<u64>,
+    /// Both boards' populations at every generation, starting with the shared board.
+    pub populations: Vec<(u32, u32)>,
+    /// How many cells differed between the boards after the last generation.
+    pub differing: u32,
+}
+
+/// Runs copies of `board` for `generations` generations under each of `rules`, noting when
+/// and how far they drift apart.
+pub fn diverge(board: &Bitboard, rules: (RuleSet, RuleSet), generations: u64) -> Divergence {
+    let mut left = Simulation::new(board.clone(), rules.0);
+    let mut right = Simulation::new(board.clone(), rules.1);
+    let mut first_difference = None;
+    let mut populations = vec![(board.population(), board.population())];
+
+    for _ in 0..generations {
+        left.step();
+        right.step();
+        populations.push((left.board.population(), right.board.population()));
+
+        if first_difference.is_none() && !left.board.same_cells(&right.board) {
+            first_difference = Some(left.generation);
+        }
+    }
+
+    let mut differing = 0;
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            differing += (left.board.get(x, y) != right.board.get(x, y)) as u32;
+        }
+    }
+
+    Divergence {
+        first_difference,
+        populations,
+        differing,
+    }
+}
@@ -0,0 +1,178 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+
+use crate::bitboard::Bitboard;
+use crate::hotkeys::Hotkeys;
+use crate::lod::Lod;
+use crate::{
+    sync_tiles, update_map, CellLooks, FastForward, RunTo, SimCounters, TickDuration,
+    UnlimitedSpeed,
+};
+
+/// Ticks shorter than this go by too quickly for fading between generations to show.
+const MIN_SMOOTHED_TICK: f64 = 0.1;
+
+/// S toggles slow motion smoothing: at slow speeds, cells being born fade in and dying
+/// cells fade out over the tick, instead of the whole board jumping a generation at once.
+pub struct SlowMotionPlugin;
+
+#[derive(Resource, Default)]
+struct Smoothing {
+    enabled: bool,
+    /// The board as it was last seen, with its generation.
+    last: Option<(Bitboard, u64)>,
+    /// The generation the shown one is fading in from, while the fade is under way.
+    fading_from: Option<Bitboard>,
+    /// Where a fade cut short started from. Its tiles are left part way through, and the
+    /// tiles are only synced where cells change again, so they're finished off by hand.
+    cut_short: Option<Bitboard>,
+}
+
+impl Plugin for SlowMotionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Smoothing>()
+            .add_system(toggle_smoothing)
+            .add_system(track_generations.after(update_map))
+            .add_system(
+                fade_tiles
+                    .after(sync_tiles)
+                    .after(track_generations)
+                    .after(toggle_smoothing),
+            );
+    }
+}
+
+fn toggle_smoothing(keys: Hotkeys, mut smoothing: ResMut<Smoothing>) {
+    if keys.just_pressed(KeyCode::S) {
+        smoothing.enabled = !smoothing.enabled;
+    }
+}
+
+/// Starts a fade whenever the board steps one generation at a slow enough speed. Edits
+/// and bigger jumps show up immediately.
+fn track_generations(
+    looks: CellLooks,
+    counters: Res<SimCounters>,
+    (ticker, fast_forward): (Res<TickDuration>, Res<FastForward>),
+    (unlimited, run_to): (Res<UnlimitedSpeed>, Res<RunTo>),
+    mut smoothing: ResMut<Smoothing>,
+) {
+    let slow = ticker.1 / fast_forward.speedup() >= MIN_SMOOTHED_TICK
+        && !unlimited.enabled
+        && run_to.0.is_none();
+
+    if !smoothing.enabled || !slow {
+        if smoothing.last.is_some() || smoothing.fading_from.is_some() {
+            smoothing.last = None;
+            smoothing.cut_short = smoothing.fading_from.take();
+        }
+        return;
+    }
+    if !looks.board.is_changed() && smoothing.last.is_some() {
+        return;
+    }
+
+    let last = smoothing.last.take();
+    if let Some(from) = smoothing.fading_from.take() {
+        smoothing.cut_short = Some(from);
+    }
+    smoothing.fading_from = last
+        .filter(|(board, generation)| {
+            generation + 1 == counters.generation
+                && (board.width(), board.height()) == (looks.board.width(), looks.board.height())
+        })
+        .map(|(board, _)| board);
+    smoothing.last = Some((looks.board.0.clone(), counters.generation));
+}
+
+/// Blends the tiles that changed in the last generation from how they looked to how they
+/// look now, as far through the tick as the simulation is.
+fn fade_tiles(
+    looks: CellLooks,
+    (ticker, fast_forward, lod): (Res<TickDuration>, Res<FastForward>, Res<Lod>),
+    mut smoothing: ResMut<Smoothing>,
+    storage_query: Query<&TileStorage>,
+    mut tile_query: Query<(&mut TileVisible, &mut TileColor)>,
+) {
+    let Ok(storage) = storage_query.get_single() else {
+        return;
+    };
+
+    // every tile the cut short fade touched differs between its start and the board now
+    let board = &looks.board;
+    let cut_short = smoothing.cut_short.take().filter(|cut_short| {
+        !lod.active && (cut_short.width(), cut_short.height()) == (board.width(), board.height())
+    });
+    if let Some(cut_short) = cut_short {
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                if cut_short.get(x, y) == board.get(x, y) {
+                    continue;
+                }
+                let Some(entity) = storage.get(&TilePos::new(x, y)) else {
+                    continue;
+                };
+                if let Ok((mut visible, mut color)) = tile_query.get_mut(entity) {
+                    (*visible, *color) = looks.look(x, y);
+                }
+            }
+        }
+    }
+
+    let Some(from) = &smoothing.fading_from else {
+        return;
+    };
+
+    let progress =
+        (ticker.0.elapsed_secs_f64() * fast_forward.speedup() / ticker.1).clamp(0.0, 1.0) as f32;
+    let done = progress >= 1.0;
+
+    // the tiles aren't drawn while zoomed out, and sync_tiles catches them up afterwards
+    if !lod.active {
+        let board = &looks.board;
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let (old, new) = (from.get(x, y), board.get(x, y));
+                if old == new || board.is_wall(x, y) || !looks.fog.is_visible(x, y) {
+                    continue;
+                }
+                let Some(entity) = storage.get(&TilePos::new(x, y)) else {
+                    continue;
+                };
+                let Ok((mut visible, mut color)) = tile_query.get_mut(entity) else {
+                    continue;
+                };
+
+                if done {
+                    (*visible, *color) = looks.look(x, y);
+                    continue;
+                }
+
+                *visible = TileVisible(true);
+                *color = TileColor(blend(&looks, old, new, progress));
+            }
+        }
+    }
+
+    if done {
+        smoothing.fading_from = None;
+    }
+}
+
+/// `progress` of the way from a cell of the `old` team to one of the `new` team, with
+/// dead cells see-through.
+fn blend(looks: &CellLooks, old: u8, new: u8, progress: f32) -> Color {
+    let palette = &looks.palette;
+    let color = |team: u8| palette[team as usize % palette.len()];
+
+    match (old, new) {
+        (0, new) => *color(new).set_a(progress),
+        (old, 0) => *color(old).set_a(1.0 - progress),
+        (old, new) => {
+            let [r0, g0, b0, a0] = color(old).as_rgba_f32();
+            let [r1, g1, b1, a1] = color(new).as_rgba_f32();
+            let mix = |from: f32, to: f32| from + (to - from) * progress;
+            Color::rgba(mix(r0, r1), mix(g0, g1), mix(b0, b1), mix(a0, a1))
+        }
+    }
+}
@@ -0,0 +1,141 @@
+//! Boards packed small for keeping a lot of them around, like the edit history does.
+//! A board can be packed as its difference from another one, usually the board just
+//! before it, which leaves mostly zeros when little changed between the two.
+
+use std::io::{Read, Write};
+
+use flate2::read::DeflateDecoder;
+use flate2::write::DeflateEncoder;
+use flate2::Compression;
+
+use crate::bitboard::{Bitboard, Boundary};
+
+/// A board packed by `pack`: its `Bitboard::cell_bytes`, XORed with the base board's if
+/// it was packed against one, with runs of zeros shortened and the result deflated.
+#[derive(Clone, Debug)]
+pub struct PackedBoard {
+    width: u32,
+    height: u32,
+    boundary: Boundary,
+    /// Whether the bytes are the difference from a base board rather than the cells.
+    delta: bool,
+    bytes: Vec<u8>,
+}
+
+/// Packs `board`, as its difference from `base` if there is one. A base of another size
+/// is ignored and the board packed whole.
+pub fn pack(board: &Bitboard, base: Option<&Bitboard>) -> PackedBoard {
+    let size = (board.width(), board.height());
+    let base = base.filter(|base| (base.width(), base.height()) == size);
+
+    let mut cells = board.cell_bytes();
+    if let Some(base) = base {
+        for (cell, base) in cells.iter_mut().zip(base.cell_bytes()) {
+            *cell ^= base;
+        }
+    }
+
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::fast());
+    // writing into a Vec can't fail
+    encoder.write_all(&shorten_zeros(&cells)).unwrap();
+
+    PackedBoard {
+        width: size.0,
+        height: size.1,
+        boundary: board.boundary(),
+        delta: base.is_some(),
+        bytes: encoder.finish().unwrap(),
+    }
+}
+
+impl PackedBoard {
+    /// Whether unpacking needs the board this one was packed against.
+    pub fn is_delta(&self) -> bool {
+        self.delta
+    }
+
+    /// Bytes the packed board takes up.
+    pub fn packed_size(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// The board again, given the same `base` it was packed against. `None` if it was
+    /// packed against a base and `base` is missing or the wrong size.
+    pub fn unpack(&self, base: Option<&Bitboard>) -> Option<Bitboard> {
+        let cells = self.width as usize * self.height as usize * 3;
+
+        let mut shortened = Vec::new();
+        DeflateDecoder::new(&self.bytes[..])
+            .read_to_end(&mut shortened)
+            .ok()?;
+        let mut bytes = lengthen_zeros(&shortened, cells)?;
+
+        if self.delta {
+            let base =
+                base.filter(|base| (base.width(), base.height()) == (self.width, self.height))?;
+            for (byte, base) in bytes.iter_mut().zip(base.cell_bytes()) {
+                *byte ^= base;
+            }
+        }
+
+        let mut board = Bitboard::new(self.width, self.height);
+        board.set_boundary(self.boundary);
+        board.set_cell_bytes(&bytes).then_some(board)
+    }
+}
+
+/// Writes each run of zeros as a single zero followed by the run's length, seven bits
+/// at a time with the high bit set on all but the last. Other bytes are kept as they are.
+fn shorten_zeros(bytes: &[u8]) -> Vec<u8> {
+    let mut shortened = Vec::new();
+    let mut rest = bytes;
+
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte != 0 {
+            shortened.push(byte);
+            rest = tail;
+            continue;
+        }
+
+        let run = rest.iter().take_while(|&&byte| byte == 0).count();
+        shortened.push(0);
+        let mut length = run;
+        while length >= 0x80 {
+            shortened.push(length as u8 | 0x80);
+            length >>= 7;
+        }
+        shortened.push(length as u8);
+        rest = &rest[run..];
+    }
+
+    shortened
+}
+
+/// Undoes `shorten_zeros`, or `None` if the bytes don't come to `len` of them.
+fn lengthen_zeros(shortened: &[u8], len: usize) -> Option<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(len);
+    let mut rest = shortened.iter();
+
+    while let Some(&byte) = rest.next() {
+        if byte != 0 {
+            bytes.push(byte);
+            continue;
+        }
+
+        let (mut run, mut shift) = (0usize, 0);
+        loop {
+            let part = *rest.next()?;
+            run |= ((part & 0x7f) as usize).checked_shl(shift)?;
+            shift += 7;
+            if part & 0x80 == 0 {
+                break;
+            }
+        }
+        if bytes.len() + run > len {
+            return None;
+        }
+        bytes.resize(bytes.len() + run, 0);
+    }
+
+    (bytes.len() == len).then_some(bytes)
+}
@@ -0,0 +1,302 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::energy::{Energy, SPAWNER_COST_PER_CELL};
+use crate::lang::Strings;
+use crate::patterns::{Pattern, PATTERNS};
+use crate::picture::overlay_layer;
+use crate::tools::{position_markers, BoardCursor, OnTile, Tool};
+use crate::{CELL_SIZE, TEAM_COLORS};
+
+/// Diagonal a spawner aims its pattern in. Patterns are drawn heading south-east.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    NorthEast,
+    NorthWest,
+    SouthEast,
+    SouthWest,
+}
+
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::NorthWest,
+        Direction::NorthEast,
+        Direction::SouthWest,
+        Direction::SouthEast,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Direction::NorthEast => "NE",
+            Direction::NorthWest => "NW",
+            Direction::SouthEast => "SE",
+            Direction::SouthWest => "SW",
+        }
+    }
+
+    /// The diagonal closest to heading (dx, dy), with y pointing up.
+    pub fn toward(dx: i64, dy: i64) -> Direction {
+        match (dx < 0, dy > 0) {
+            (false, true) => Direction::NorthEast,
+            (true, true) => Direction::NorthWest,
+            (false, false) => Direction::SouthEast,
+            (true, false) => Direction::SouthWest,
+        }
+    }
+
+    pub fn is_west(self) -> bool {
+        matches!(self, Direction::NorthWest | Direction::SouthWest)
+    }
+
+    pub fn is_north(self) -> bool {
+        matches!(self, Direction::NorthEast | Direction::NorthWest)
+    }
+}
+
+/// A tile that stamps a pattern next to itself every `period` generations.
+#[derive(Component, Clone, Copy, Debug)]
+pub struct Spawner {
+    pub x: u32,
+    pub y: u32,
+    pub pattern: usize, // index into PATTERNS
+    pub direction: Direction,
+    pub period: u32,
+    pub team: u8,
+}
+
+impl Spawner {
+    /// Stamps the pattern if `generation` is one of this spawner's beats. The pattern's
+    /// bounding box sits diagonally off the spawner so the spawner itself stays clear.
+    pub fn emit(&self, board: &mut Bitboard, generation: u64) {
+        if !generation.is_multiple_of(self.period as u64) {
+            return;
+        }
+
+        let pattern = &PATTERNS[self.pattern];
+        let (width, height) = (pattern.width() as i64, pattern.height() as i64);
+
+        let left = if self.direction.is_west() {
+            self.x as i64 - width
+        } else {
+            self.x as i64 + 1
+        };
+        let bottom = if self.direction.is_north() {
+            self.y as i64 + 1
+        } else {
+            self.y as i64 - height
+        };
+
+        let flips = (self.direction.is_west(), self.direction.is_north());
+        pattern.stamp(board, (left, bottom), flips, self.team);
+    }
+}
+
+impl OnTile for Spawner {
+    fn tile(&self) -> TilePos {
+        TilePos::new(self.x, self.y)
+    }
+}
+
+/// Settings new spawners are placed with.
+#[derive(Resource)]
+struct SpawnerSettings {
+    pattern: usize,
+    direction: Direction,
+    period: u32,
+    team: u8,
+}
+
+impl Default for SpawnerSettings {
+    fn default() -> Self {
+        Self {
+            pattern: PATTERNS.iter().position(|p| p.name == "glider").unwrap(),
+            direction: Direction::SouthEast,
+            period: 30,
+            team: 2,
+        }
+    }
+}
+
+/// Longest side, in points, of a pattern's thumbnail in the picker.
+const THUMBNAIL_SIZE: f32 = 32.0;
+
+/// One picture per entry of `PATTERNS`, drawn once at startup so the picker can show each
+/// pattern instead of just its name.
+#[derive(Resource, Default)]
+struct Thumbnails {
+    images: Vec<(Handle<Image>, egui::TextureId)>,
+}
+
+impl Thumbnails {
+    fn show(&self, ui: &mut egui::Ui, pattern: usize) {
+        let pattern_size = Vec2::new(
+            PATTERNS[pattern].width() as f32,
+            PATTERNS[pattern].height() as f32,
+        );
+        let size = pattern_size * THUMBNAIL_SIZE / pattern_size.max_element();
+
+        ui.add_sized(
+            [THUMBNAIL_SIZE, THUMBNAIL_SIZE],
+            egui::Image::new(self.images[pattern].1, [size.x, size.y]),
+        );
+    }
+}
+
+pub struct SpawnerPlugin;
+
+impl Plugin for SpawnerPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SpawnerSettings>()
+            .init_resource::<Thumbnails>()
+            .add_startup_system(draw_thumbnails)
+            .add_system(place_spawner)
+            .add_system(position_markers::<Spawner>)
+            .add_system(spawner_panel);
+    }
+}
+
+/// One texel per cell, live cells in white.
+fn thumbnail_image(pattern: &Pattern) -> Image {
+    let (width, height) = (pattern.width(), pattern.height());
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 0],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+
+    // image rows go top to bottom while pattern cells go bottom to top
+    for (x, y) in pattern.cells() {
+        let offset = ((height - 1 - y) * width + x) as usize * 4;
+        image.data[offset..offset + 4].copy_from_slice(&[255; 4]);
+    }
+
+    image
+}
+
+fn draw_thumbnails(
+    mut thumbnails: ResMut<Thumbnails>,
+    mut images: ResMut<Assets<Image>>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    thumbnails.images = PATTERNS
+        .iter()
+        .map(|pattern| {
+            let handle = images.add(thumbnail_image(pattern));
+            let texture = egui_context.add_image(handle.clone_weak());
+            (handle, texture)
+        })
+        .collect();
+}
+
+fn place_spawner(
+    mut commands: Commands,
+    mouse: Res<Input<MouseButton>>,
+    tool: Res<Tool>,
+    settings: Res<SpawnerSettings>,
+    cursor: BoardCursor,
+    mut energy: ResMut<Energy>,
+    spawner_query: Query<(Entity, &Spawner)>,
+) {
+    if *tool != Tool::Spawner || !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(TilePos { x, y }) = cursor.tile_pos() else {
+        return;
+    };
+
+    if let Some((entity, _)) = spawner_query.iter().find(|(_, s)| (s.x, s.y) == (x, y)) {
+        commands.entity(entity).despawn();
+        return;
+    }
+
+    let cells = PATTERNS[settings.pattern].cells().count() as f32;
+    if !energy.try_spend(settings.team, cells * SPAWNER_COST_PER_CELL) {
+        return;
+    }
+
+    commands.spawn((
+        Spawner {
+            x,
+            y,
+            pattern: settings.pattern,
+            direction: settings.direction,
+            period: settings.period,
+            team: settings.team,
+        },
+        SpriteBundle {
+            sprite: Sprite {
+                color: TEAM_COLORS[settings.team as usize],
+                custom_size: Some(Vec2::splat(CELL_SIZE * 0.6)),
+                ..default()
+            },
+            ..default()
+        },
+        overlay_layer(),
+    ));
+}
+
+fn spawner_panel(
+    tool: Res<Tool>,
+    mut settings: ResMut<SpawnerSettings>,
+    thumbnails: Res<Thumbnails>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if *tool != Tool::Spawner {
+        return;
+    }
+
+    egui::Window::new(strings.get("spawner.title"))
+        .id(egui::Id::new("spawner"))
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let settings = &mut *settings;
+
+            ui.horizontal(|ui| {
+                thumbnails.show(ui, settings.pattern);
+
+                egui::ComboBox::from_label(strings.get("spawner.pattern"))
+                    .selected_text(PATTERNS[settings.pattern].name)
+                    .show_ui(ui, |ui| {
+                        for (i, pattern) in PATTERNS.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                thumbnails.show(ui, i);
+                                ui.selectable_value(&mut settings.pattern, i, pattern.name);
+                            });
+                        }
+                    });
+            });
+
+            ui.horizontal(|ui| {
+                for direction in Direction::ALL {
+                    let name = strings.named("direction", direction.name());
+                    ui.selectable_value(&mut settings.direction, direction, name);
+                }
+                ui.label(strings.get("spawner.direction"));
+            });
+
+            let period = egui::Slider::new(&mut settings.period, 1..=200);
+            ui.add(period.text(strings.get("spawner.period")));
+
+            ui.horizontal(|ui| {
+                for team in [2, 3] {
+                    let name = strings.format("spawner.team", &[("team", &(team - 1))]);
+                    ui.selectable_value(&mut settings.team, team, name);
+                }
+            });
+
+            ui.label(strings.get("spawner.help"));
+        });
+}
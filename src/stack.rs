@@ -0,0 +1,262 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::layers::{self, Coupling};
+use crate::overlay::{board_overlay_image, place_over_board};
+use crate::rng::Rng;
+use crate::rules::{Neighborhood, RuleSet};
+use crate::{Board, SimCounters, SIM_SEED};
+
+/// Live cells of the upper layer, blended over the board.
+const UPPER_COLOR: [u8; 4] = [250, 140, 40, 170];
+/// Share of cells alive in a random upper layer.
+const SOUP_DENSITY: u64 = 12;
+
+/// L opens a panel for stacking a second layer over the board. It steps under its own
+/// rule every generation and is then coupled to the board, e.g. as predators eating the
+/// board's cells, and is drawn blended over it.
+pub struct StackPlugin;
+
+/// The layer stacked over the board, if there is one.
+#[derive(Resource)]
+pub struct UpperLayer {
+    open: bool,
+    board: Option<Bitboard>,
+    rule: RuleSet,
+    rule_text: String,
+    coupling: Coupling,
+    error: Option<String>,
+}
+
+impl Default for UpperLayer {
+    fn default() -> Self {
+        Self {
+            open: false,
+            board: None,
+            rule: RuleSet::CONWAY,
+            rule_text: RuleSet::CONWAY.to_string(),
+            coupling: Coupling::Predation,
+            error: None,
+        }
+    }
+}
+
+impl UpperLayer {
+    pub fn is_active(&self) -> bool {
+        self.board.is_some()
+    }
+
+    pub fn remove(&mut self) {
+        self.board = None;
+    }
+
+    /// Steps the layer and couples it to `board`, which was just stepped from `before`.
+    pub fn step(&mut self, before: &Bitboard, board: &mut Bitboard) {
+        let Some(upper) = &mut self.board else {
+            return;
+        };
+        // the layer is dropped when the board changes shape, but not until next frame
+        if (upper.width(), upper.height()) != (board.width(), board.height()) {
+            return;
+        }
+
+        layers::step_upper(self.coupling, &self.rule, before, (board, upper));
+    }
+}
+
+#[derive(Component)]
+struct UpperSprite;
+
+impl Plugin for StackPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UpperLayer>()
+            .add_startup_system(spawn_upper_sprite)
+            .add_system(toggle_stack)
+            .add_system(fit_layer)
+            .add_system(stack_panel.after(toggle_stack).after(fit_layer))
+            .add_system(draw_upper.after(stack_panel));
+    }
+}
+
+fn spawn_upper_sprite(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        UpperSprite,
+    ));
+}
+
+fn toggle_stack(keys: Hotkeys, mut layer: ResMut<UpperLayer>) {
+    if keys.just_pressed(KeyCode::L) {
+        layer.open = !layer.open;
+    }
+}
+
+/// Drops the layer when the board changes size or shape under it.
+fn fit_layer(
+    (board, rule): (Res<Board>, Res<RuleSet>),
+    strings: Res<Strings>,
+    mut layer: ResMut<UpperLayer>,
+) {
+    let Some(upper) = &layer.board else {
+        return;
+    };
+
+    if (upper.width(), upper.height()) != (board.width(), board.height())
+        || layer.rule.neighborhood != rule.neighborhood
+    {
+        layer.board = None;
+        layer.error = Some(strings.get("layers.removed").to_owned());
+    }
+}
+
+fn stack_panel(
+    (board, rule, counters): (Res<Board>, Res<RuleSet>, Res<SimCounters>),
+    mut layer: ResMut<UpperLayer>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !layer.open {
+        return;
+    }
+
+    let mut open = true;
+
+    egui::Window::new(strings.get("layers.title"))
+        .id(egui::Id::new("layers"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let layer = &mut *layer;
+
+            ui.horizontal(|ui| {
+                ui.label(strings.get("layers.upper-rule"));
+                ui.text_edit_singleline(&mut layer.rule_text);
+
+                if ui.button(strings.get("layers.apply")).clicked() {
+                    layer.error = match RuleSet::parse(&layer.rule_text) {
+                        Ok(parsed) if parsed.neighborhood != rule.neighborhood => {
+                            Some(strings.get("layers.mismatch").to_owned())
+                        }
+                        Ok(parsed) => {
+                            layer.rule = parsed;
+                            None
+                        }
+                        Err(error) => Some(error),
+                    };
+                }
+            });
+
+            ui.horizontal(|ui| {
+                for coupling in Coupling::ALL {
+                    let name = strings.named("coupling", coupling.name());
+                    ui.selectable_value(&mut layer.coupling, coupling, name);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let mut stacked = None;
+
+                if ui.button(strings.get("layers.soup")).clicked() {
+                    let mut upper = Bitboard::new(board.width(), board.height());
+                    upper.set_boundary(board.boundary());
+
+                    let mut rng = Rng::new(SIM_SEED ^ counters.generation);
+                    for y in 0..upper.height() {
+                        for x in 0..upper.width() {
+                            if rng.next_u64() % 100 < SOUP_DENSITY {
+                                upper.set(x, y, 2);
+                            }
+                        }
+                    }
+                    stacked = Some(upper);
+                }
+                if ui.button(strings.get("layers.copy")).clicked() {
+                    stacked = Some(board.0.clone());
+                }
+                if layer.board.is_some() && ui.button(strings.get("layers.remove")).clicked() {
+                    layer.board = None;
+                }
+
+                if stacked.is_some() {
+                    if layer.rule.neighborhood == rule.neighborhood {
+                        layer.board = stacked;
+                        layer.error = None;
+                    } else {
+                        layer.error = Some(strings.get("layers.mismatch").to_owned());
+                    }
+                }
+            });
+
+            if let Some(error) = &layer.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            match &layer.board {
+                Some(upper) => ui.label(strings.format(
+                    "layers.populations",
+                    &[
+                        ("below", &board.population()),
+                        ("above", &upper.population()),
+                    ],
+                )),
+                None => ui.label(strings.get("layers.none")),
+            };
+        });
+
+    if !open {
+        layer.open = false;
+    }
+}
+
+/// Redraws the upper layer's texture over the board whenever it changes.
+fn draw_upper(
+    (layer, rule): (Res<UpperLayer>, Res<RuleSet>),
+    mut images: ResMut<Assets<Image>>,
+    mut sprite_query: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<UpperSprite>,
+    >,
+) {
+    let Ok((mut handle, mut sprite, mut transform, mut visibility)) = sprite_query.get_single_mut()
+    else {
+        return;
+    };
+
+    // the texture is a square grid, so hex layers step but aren't drawn
+    let shown = layer
+        .board
+        .as_ref()
+        .filter(|_| rule.neighborhood == Neighborhood::Moore);
+    if visibility.is_visible != shown.is_some() {
+        visibility.is_visible = shown.is_some();
+    }
+    let Some(upper) = shown else {
+        return;
+    };
+    if !(layer.is_changed() || rule.is_changed()) {
+        return;
+    }
+
+    let (width, height) = (upper.width(), upper.height());
+    let image = board_overlay_image(width, height, |x, y| {
+        (upper.get(x, y) != 0).then_some(UPPER_COLOR)
+    });
+
+    match images.get_mut(&handle) {
+        Some(existing) => *existing = image,
+        None => *handle = images.add(image),
+    }
+
+    place_over_board((width, height), &mut sprite, &mut transform, 0.6);
+}
@@ -0,0 +1,392 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+use bevy_egui::egui;
+
+use crate::bitboard::Bitboard;
+use crate::census::Census;
+use crate::detach::PanelContext;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::rules::{Neighborhood, RuleSet};
+use crate::Board;
+
+/// T toggles a window breaking the board down by team: population now and at its peak,
+/// births and deaths so far, share of the territory and center of mass. Under Conway's
+/// rule it also counts the common objects on the board. It also tracks how much of the
+/// board changes each generation, and can stop the simulation once that dies down.
+pub struct StatsPlugin;
+
+/// Team ids the window reports on.
+const TEAMS: [u8; 2] = [2, 3];
+/// Generations the board's activity is averaged over.
+const ACTIVITY_WINDOW: usize = 16;
+
+/// Running totals for one team.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TeamTally {
+    pub population: u32,
+    pub peak: u32,
+    pub births: u64,
+    pub deaths: u64,
+    /// Births undone because the team was at its population cap.
+    pub blocked: u64,
+    /// Fraction of the board's open cells the team holds, see `territory`.
+    pub territory: f32,
+    /// Mean tile position of the team's cells, if it has any.
+    pub center: Option<Vec2>,
+}
+
+impl TeamTally {
+    /// The tally as rows of the stats window, each under the key of its label.
+    fn rows(&self) -> [(&'static str, String); 7] {
+        [
+            ("stats.population", self.population.to_string()),
+            ("stats.peak", self.peak.to_string()),
+            ("stats.births", self.births.to_string()),
+            ("stats.deaths", self.deaths.to_string()),
+            ("stats.births-blocked", self.blocked.to_string()),
+            ("stats.territory", format!("{:.1}%", self.territory * 100.0)),
+            (
+                "stats.center",
+                match self.center {
+                    Some(center) => format!("({:.0}, {:.0})", center.x, center.y),
+                    None => "-".to_owned(),
+                },
+            ),
+        ]
+    }
+}
+
+/// Fraction of the board's open cells that change each generation, averaged over the last
+/// few. Unlike looking for a repeating board it also settles for chaotic rules that never
+/// quite repeat but have stopped doing much.
+pub struct Activity {
+    recent: VecDeque<f32>,
+    /// Whether to stop the simulation once the activity has stayed under `threshold` for
+    /// `patience` generations.
+    pub auto_stop: bool,
+    pub threshold: f32,
+    pub patience: u32,
+    /// Generations in a row the activity has been under the threshold.
+    quiet: u32,
+    stop_requested: bool,
+}
+
+impl Default for Activity {
+    fn default() -> Self {
+        Self {
+            recent: VecDeque::with_capacity(ACTIVITY_WINDOW),
+            auto_stop: false,
+            threshold: 0.001,
+            patience: 100,
+            quiet: 0,
+            stop_requested: false,
+        }
+    }
+}
+
+impl Activity {
+    /// The rolling activity, once a generation has been counted.
+    pub fn rolling(&self) -> Option<f32> {
+        (!self.recent.is_empty())
+            .then(|| self.recent.iter().sum::<f32>() / self.recent.len() as f32)
+    }
+
+    fn record(&mut self, changed: u32, open: u32) {
+        if self.recent.len() == ACTIVITY_WINDOW {
+            self.recent.pop_front();
+        }
+        self.recent.push_back(changed as f32 / open.max(1) as f32);
+
+        if self.rolling().unwrap_or(0.0) < self.threshold {
+            self.quiet += 1;
+        } else {
+            self.quiet = 0;
+        }
+        if self.auto_stop && self.quiet >= self.patience {
+            self.stop_requested = true;
+            self.quiet = 0;
+        }
+    }
+
+    /// Whether the board has gone quiet for long enough to stop, clearing the request.
+    pub fn take_stop(&mut self) -> bool {
+        std::mem::take(&mut self.stop_requested)
+    }
+
+    pub fn stop_requested(&self) -> bool {
+        self.stop_requested
+    }
+
+    fn reset(&mut self) {
+        self.recent.clear();
+        self.quiet = 0;
+    }
+}
+
+/// Per-team statistics. Births, deaths and peaks are counted every generation by the
+/// simulation while the window is open; the rest is measured from the board each frame.
+#[derive(Resource, Default)]
+pub struct TeamStats {
+    pub open: bool,
+    pub teams: [TeamTally; 2],
+    /// Known objects on the board with how many there are, or `None` unless the rule is
+    /// Conway's, which the dictionary is for.
+    pub objects: Option<Vec<(&'static str, u32)>>,
+    /// Groups of cells that aren't known objects.
+    pub unknown_objects: u32,
+    pub activity: Activity,
+}
+
+impl TeamStats {
+    /// Whether the simulation should call `count_generation` every generation.
+    pub fn counts_generations(&self) -> bool {
+        self.open || self.activity.auto_stop
+    }
+
+    /// Counts the births and deaths between two consecutive generations, any new peak and
+    /// how much of the board changed.
+    pub fn count_generation(&mut self, before: &Bitboard, after: &Bitboard) {
+        let mut population = [0; 2];
+        let (mut changed, mut open) = (0, 0);
+
+        for y in 0..after.height() {
+            for x in 0..after.width() {
+                let (old, new) = (before.get(x, y), after.get(x, y));
+
+                if let Some(team) = tally_index(new) {
+                    population[team] += 1;
+                }
+                if !after.is_wall(x, y) {
+                    open += 1;
+                }
+                if old == new {
+                    continue;
+                }
+                changed += 1;
+                if let Some(team) = tally_index(old) {
+                    self.teams[team].deaths += 1;
+                }
+                if let Some(team) = tally_index(new) {
+                    self.teams[team].births += 1;
+                }
+            }
+        }
+
+        for (tally, population) in self.teams.iter_mut().zip(population) {
+            tally.peak = tally.peak.max(population);
+        }
+        self.activity.record(changed, open);
+    }
+
+    /// Adds births the population cap blocked, indexed by team id - 2.
+    pub fn count_blocked(&mut self, blocked: [u64; 2]) {
+        for (tally, blocked) in self.teams.iter_mut().zip(blocked) {
+            tally.blocked += blocked;
+        }
+    }
+
+    /// Starts the totals over from the current board.
+    fn reset(&mut self) {
+        self.teams = Default::default();
+        self.activity.reset();
+    }
+}
+
+fn tally_index(team: u8) -> Option<usize> {
+    TEAMS.iter().position(|&candidate| candidate == team)
+}
+
+/// Open (non-wall) cells each team holds, indexed like `Bitboard::get`: its own live cells
+/// plus the dead regions bordered by its cells alone.
+pub fn territory(board: &Bitboard, neighborhood: Neighborhood) -> [u32; 4] {
+    let mut held = [0; 4];
+
+    for region in board.regions(neighborhood) {
+        let (x, y) = region[0];
+        if board.is_wall(x, y) {
+            continue;
+        }
+
+        let team = board.get(x, y);
+        if team != 0 {
+            held[team as usize % 4] += region.len() as u32;
+            continue;
+        }
+
+        let mut owner = None;
+        let bordered_by_one = region.iter().all(|&(x, y)| {
+            neighborhood.adjacent(y).iter().all(|&(dx, dy)| {
+                match board.offset(x, y, dx, dy).map(|(x, y)| board.get(x, y)) {
+                    None | Some(0) => true,
+                    Some(team) => *owner.get_or_insert(team) == team,
+                }
+            })
+        });
+
+        if let (true, Some(team)) = (bordered_by_one, owner) {
+            held[team as usize % 4] += region.len() as u32;
+        }
+    }
+
+    held
+}
+
+impl Plugin for StatsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TeamStats>()
+            .init_resource::<Census>()
+            .add_system(toggle_stats)
+            .add_system(measure_board)
+            .add_system(stats_window.after(measure_board));
+    }
+}
+
+fn toggle_stats(keys: Hotkeys, mut stats: ResMut<TeamStats>) {
+    if keys.just_pressed(KeyCode::T) {
+        stats.open = !stats.open;
+    }
+}
+
+fn measure_board(
+    (board, rule): (Res<Board>, Res<RuleSet>),
+    census: Res<Census>,
+    mut stats: ResMut<TeamStats>,
+) {
+    if !stats.open || !(board.is_changed() || rule.is_changed() || stats.is_changed()) {
+        return;
+    }
+
+    let mut sums = [(0, Vec2::ZERO); 2];
+    for y in 0..board.height() {
+        for x in 0..board.width() {
+            if let Some(team) = tally_index(board.get(x, y)) {
+                sums[team].0 += 1;
+                sums[team].1 += Vec2::new(x as f32, y as f32);
+            }
+        }
+    }
+
+    let held = territory(&board, rule.neighborhood);
+    let open = (0..board.height())
+        .flat_map(|y| (0..board.width()).map(move |x| (x, y)))
+        .filter(|&(x, y)| !board.is_wall(x, y))
+        .count()
+        .max(1) as f32;
+
+    // bypasses change detection so measuring doesn't retrigger itself next frame
+    let stats = stats.bypass_change_detection();
+    for ((tally, (population, sum)), team) in stats.teams.iter_mut().zip(sums).zip(TEAMS) {
+        tally.population = population;
+        tally.peak = tally.peak.max(population);
+        tally.territory = held[team as usize] as f32 / open;
+        tally.center = (population > 0).then(|| sum / population as f32);
+    }
+
+    if *rule == RuleSet::CONWAY {
+        let (objects, unknown) = census.count(&board);
+        stats.objects = Some(objects);
+        stats.unknown_objects = unknown;
+    } else {
+        stats.objects = None;
+    }
+}
+
+fn stats_window(mut stats: ResMut<TeamStats>, strings: Res<Strings>, mut panels: PanelContext) {
+    if !stats.open {
+        return;
+    }
+
+    let mut open = true;
+    let mut reset = false;
+
+    egui::Window::new(strings.get("stats.title"))
+        .id(egui::Id::new("team stats"))
+        .open(&mut open)
+        .resizable(false)
+        .show(panels.ctx(), |ui| {
+            egui::Grid::new("team stats").striped(true).show(ui, |ui| {
+                ui.label("");
+                for team in TEAMS {
+                    ui.label(strings.format("stats.team", &[("team", &(team - 1))]));
+                }
+                ui.end_row();
+
+                let rows = stats.teams.map(|tally| tally.rows());
+                for row in 0..rows[0].len() {
+                    ui.label(strings.get(rows[0][row].0));
+                    for team in &rows {
+                        ui.label(&team[row].1);
+                    }
+                    ui.end_row();
+                }
+            });
+
+            ui.separator();
+            match &stats.objects {
+                Some(objects) => {
+                    for (name, count) in objects {
+                        let name = strings.named("object", name);
+                        ui.label(
+                            strings.format("stats.objects", &[("count", count), ("name", &name)]),
+                        );
+                    }
+                    if stats.unknown_objects > 0 {
+                        let count = stats.unknown_objects;
+                        ui.label(strings.format("stats.other-objects", &[("count", &count)]));
+                    }
+                }
+                None => {
+                    ui.label(strings.get("stats.conway-only"));
+                }
+            }
+
+            ui.separator();
+            match stats.activity.rolling() {
+                Some(activity) => ui.label(strings.format(
+                    "stats.activity",
+                    &[("percent", &format!("{:.2}", activity * 100.0))],
+                )),
+                None => ui.label(strings.get("stats.activity-unmeasured")),
+            };
+
+            // settings, so changing them doesn't have the board measured again
+            let activity = &mut stats.bypass_change_detection().activity;
+            ui.checkbox(&mut activity.auto_stop, strings.get("stats.auto-stop"));
+            ui.add_enabled_ui(activity.auto_stop, |ui| {
+                ui.horizontal(|ui| {
+                    let mut percent = activity.threshold * 100.0;
+                    ui.add(
+                        egui::DragValue::new(&mut percent)
+                            .clamp_range(0.01..=10.0)
+                            .speed(0.01)
+                            .prefix(strings.get("stats.under"))
+                            .suffix("%"),
+                    );
+                    activity.threshold = percent / 100.0;
+
+                    ui.add(
+                        egui::DragValue::new(&mut activity.patience)
+                            .clamp_range(10..=10_000)
+                            .prefix(strings.get("stats.for"))
+                            .suffix(strings.get("stats.generations")),
+                    );
+                });
+                if activity.quiet > 0 {
+                    let quiet = activity.quiet;
+                    ui.label(strings.format("stats.quiet", &[("generations", &quiet)]));
+                }
+            });
+
+            ui.separator();
+            reset = ui.button(strings.get("stats.reset")).clicked();
+        });
+
+    if reset {
+        stats.reset();
+    }
+    if !open {
+        stats.open = false;
+    }
+}
@@ -0,0 +1,219 @@
+use bevy::prelude::*;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+use bevy::render::texture::ImageSampler;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::compare::board_texel;
+use crate::lang::Strings;
+use crate::rules::RuleSet;
+use crate::scenario::ActiveScenario;
+use crate::simulation::Simulation;
+use crate::undo::UndoHistory;
+use crate::Board;
+
+/// Longest side of the sub-board's picture, in points.
+const VIEW_SIZE: f32 = 256.0;
+/// Generations the "Step 10" button runs.
+const STEP_BATCH: u64 = 10;
+
+/// A picture-in-picture copy of a selected region, run on its own under the main rule
+/// with everything outside it dead, then written back over the region or thrown away.
+/// Opened from the clipboard panel.
+pub struct SubBoardPlugin;
+
+/// Sent to open the (left, bottom, width, height) block of the board as a sub-board.
+pub struct OpenSubBoard(pub (u32, u32, u32, u32));
+
+/// The open sub-board, if any, and the region of the main board it came from.
+#[derive(Resource, Default)]
+pub struct SubBoard {
+    sim: Option<Simulation>,
+    origin: (u32, u32),
+    running: bool,
+    /// Kept from one sub-board to the next so egui only ever knows one texture.
+    picture: Option<(Handle<Image>, egui::TextureId)>,
+}
+
+impl SubBoard {
+    /// Whether a sub-board is open and stepping every frame.
+    pub fn is_running(&self) -> bool {
+        self.running && self.sim.is_some()
+    }
+}
+
+impl Plugin for SubBoardPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SubBoard>()
+            .add_event::<OpenSubBoard>()
+            .add_system(open_sub_board)
+            .add_system(run_sub_board.after(open_sub_board))
+            .add_system(draw_sub_board.after(run_sub_board))
+            .add_system(sub_board_panel.after(draw_sub_board));
+    }
+}
+
+fn open_sub_board(
+    mut events: EventReader<OpenSubBoard>,
+    (board, rule): (Res<Board>, Res<RuleSet>),
+    mut sub_board: ResMut<SubBoard>,
+) {
+    for &OpenSubBoard((left, bottom, width, height)) in events.iter() {
+        let mut region = Bitboard::new(width, height);
+        for y in 0..height {
+            for x in 0..width {
+                if board.is_wall(left + x, bottom + y) {
+                    region.set_wall(x, y, true);
+                } else {
+                    region.set(x, y, board.get(left + x, bottom + y));
+                }
+            }
+        }
+
+        sub_board.sim = Some(Simulation::new(region, *rule));
+        sub_board.origin = (left, bottom);
+        sub_board.running = false;
+    }
+}
+
+fn run_sub_board(mut sub_board: ResMut<SubBoard>) {
+    if !sub_board.running {
+        return;
+    }
+    if let Some(sim) = &mut sub_board.sim {
+        sim.step();
+    }
+}
+
+fn draw_sub_board(
+    mut sub_board: ResMut<SubBoard>,
+    mut images: ResMut<Assets<Image>>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !sub_board.is_changed() {
+        return;
+    }
+    let Some(sim) = &sub_board.sim else {
+        return;
+    };
+
+    let (width, height) = (sim.board.width(), sim.board.height());
+    let mut image = Image::new_fill(
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        &[0, 0, 0, 255],
+        TextureFormat::Rgba8UnormSrgb,
+    );
+    image.sampler_descriptor = ImageSampler::nearest();
+
+    // image rows go top to bottom while board rows go bottom to top
+    for y in 0..height {
+        let row = (height - 1 - y) as usize * width as usize;
+        for x in 0..width {
+            let texel = board_texel(&sim.board, x, y);
+            if texel[3] != 0 {
+                let offset = (row + x as usize) * 4;
+                image.data[offset..offset + 4].copy_from_slice(&texel);
+            }
+        }
+    }
+
+    // only swapped in place, so the change doesn't count as a new frame of the sub-board
+    let sub_board = sub_board.bypass_change_detection();
+    match &sub_board.picture {
+        Some((handle, _)) => {
+            if let Some(existing) = images.get_mut(handle) {
+                *existing = image;
+            }
+        }
+        None => {
+            let handle = images.add(image);
+            let texture = egui_context.add_image(handle.clone_weak());
+            sub_board.picture = Some((handle, texture));
+        }
+    }
+}
+
+fn sub_board_panel(
+    mut sub_board: ResMut<SubBoard>,
+    scenario: Res<ActiveScenario>,
+    (mut board, mut history): (ResMut<Board>, ResMut<UndoHistory>),
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    let (Some(sim), Some((_, texture))) = (&sub_board.sim, &sub_board.picture) else {
+        return;
+    };
+
+    let size = Vec2::new(sim.board.width() as f32, sim.board.height() as f32);
+    let size = size * VIEW_SIZE / size.max_element();
+    let (generation, texture) = (sim.generation, *texture);
+
+    let mut open = true;
+    let (mut step, mut toggle, mut commit) = (0, false, false);
+
+    egui::Window::new(strings.get("sub-board.title"))
+        .id(egui::Id::new("sub-board"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.image(texture, [size.x, size.y]);
+            ui.label(strings.format("sub-board.generation", &[("generation", &generation)]));
+
+            ui.horizontal(|ui| {
+                let running = if sub_board.running {
+                    "sub-board.pause"
+                } else {
+                    "sub-board.run"
+                };
+                toggle = ui.button(strings.get(running)).clicked();
+                if ui.button(strings.get("sub-board.step")).clicked() {
+                    step = 1;
+                }
+                let batch = strings.format("sub-board.step-batch", &[("generations", &STEP_BATCH)]);
+                if ui.button(batch).clicked() {
+                    step = STEP_BATCH;
+                }
+                commit = ui.button(strings.get("sub-board.commit")).clicked();
+            });
+        });
+
+    // only touched when something happens, so the picture isn't redrawn every frame
+    if toggle {
+        sub_board.running = !sub_board.running;
+    }
+    if step > 0 {
+        if let Some(sim) = &mut sub_board.sim {
+            sim.step_n(step);
+        }
+    }
+
+    if commit {
+        let sim = sub_board.sim.take().unwrap();
+        let (left, bottom) = sub_board.origin;
+
+        history.record(&board);
+        for y in 0..sim.board.height() {
+            for x in 0..sim.board.width() {
+                let (bx, by) = (left + x, bottom + y);
+                if scenario.is_locked(bx, by) {
+                    continue;
+                }
+
+                if sim.board.is_wall(x, y) {
+                    board.set_wall(bx, by, true);
+                } else {
+                    board.set(bx, by, sim.board.get(x, y));
+                }
+            }
+        }
+    }
+
+    if !open {
+        sub_board.sim = None;
+    }
+}
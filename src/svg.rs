@@ -0,0 +1,84 @@
+use std::fmt::Write;
+use std::fs;
+use std::path::Path;
+
+use bevy::prelude::Color;
+
+use crate::bitboard::Bitboard;
+
+/// Side of a cell in the SVG's own units. It scales freely, so this only sets the size
+/// it's shown at when nothing else does.
+const CELL_UNITS: u32 = 10;
+
+/// Writes the (left, bottom, width, height) block of the board to `path` as an SVG, with
+/// one square per live cell in its team's color from `palette`.
+pub fn save(
+    path: &Path,
+    board: &Bitboard,
+    area: (u32, u32, u32, u32),
+    palette: &[Color],
+) -> Result<(), String> {
+    fs::write(path, encode(board, area, palette))
+        .map_err(|error| format!("{}: {error}", path.display()))
+}
+
+pub fn encode(board: &Bitboard, area: (u32, u32, u32, u32), palette: &[Color]) -> String {
+    let (left, bottom, width, height) = area;
+    let (view_width, view_height) = (width * CELL_UNITS, height * CELL_UNITS);
+
+    let mut svg = String::new();
+    // crisp edges keep neighboring cells from showing hairline seams between them
+    let _ = writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{view_width}\" \
+         height=\"{view_height}\" viewBox=\"0 0 {view_width} {view_height}\" \
+         shape-rendering=\"crispEdges\">"
+    );
+
+    // a group per team, so the color is written once and each cell is just its square
+    let mut teams: Vec<u8> = Vec::new();
+    for y in 0..height {
+        for x in 0..width {
+            let team = board.get(left + x, bottom + y);
+            if team != 0 && !teams.contains(&team) {
+                teams.push(team);
+            }
+        }
+    }
+    teams.sort_unstable();
+
+    for team in teams {
+        let [r, g, b, a] = palette[team as usize % palette.len()].as_rgba_f32();
+        let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+        let _ = write!(
+            svg,
+            "<g fill=\"#{:02x}{:02x}{:02x}\"",
+            channel(r),
+            channel(g),
+            channel(b)
+        );
+        if a < 1.0 {
+            let _ = write!(svg, " fill-opacity=\"{a:.3}\"");
+        }
+        svg.push_str(">\n");
+
+        // rows from the top, as SVG's y axis runs down
+        for row in 0..height {
+            let y = height - 1 - row;
+            for x in 0..width {
+                if board.get(left + x, bottom + y) == team {
+                    let _ = writeln!(
+                        svg,
+                        "<rect x=\"{}\" y=\"{}\" width=\"{CELL_UNITS}\" height=\"{CELL_UNITS}\"/>",
+                        x * CELL_UNITS,
+                        row * CELL_UNITS
+                    );
+                }
+            }
+        }
+        svg.push_str("</g>\n");
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::rules::{apply_rule_at, RuleSet};
+use crate::simulation::StepOverride;
+
+/// Teams that can be given a rule of their own, as board team ids.
+const TEAMS: [u8; 2] = [2, 3];
+
+/// I opens a panel giving either team its own rule, e.g. one team playing Conway's rule
+/// against the other playing HighLife. Each cell goes by its team's rule: live cells by
+/// the team they're on, dead cells by the team they'd be born into. Team rules don't age
+/// cells, so their age limits are dropped.
+pub struct TeamRulesPlugin;
+
+/// The rule each team plays by in place of the board's, indexed by team id - 2.
+#[derive(Resource, Default)]
+pub struct TeamRules {
+    open: bool,
+    rules: [Option<RuleSet>; 2],
+    rule_texts: [String; 2],
+    error: Option<String>,
+}
+
+impl TeamRules {
+    /// Puts both teams back on the board's rule. The typed rules are kept to apply again.
+    pub fn clear(&mut self) {
+        self.rules = [None, None];
+    }
+
+    fn rule_for(&self, team: u8, rule: &RuleSet) -> Option<&RuleSet> {
+        let own = self.rules[TEAMS.iter().position(|&candidate| candidate == team)?].as_ref()?;
+        // a team can't count its neighbors differently from the rest of the board
+        (own.neighborhood == rule.neighborhood).then_some(own)
+    }
+}
+
+impl StepOverride for TeamRules {
+    fn is_active(&self) -> bool {
+        self.rules.iter().any(Option::is_some)
+    }
+
+    /// Redoes the births and survivals of teams with a rule of their own for the step that
+    /// took `before` to `board` under `rule`. Cells born to neither team, or to both at
+    /// once, go by the board's rule.
+    fn apply(&self, before: &Bitboard, board: &mut Bitboard, rule: &RuleSet) {
+        if !self.is_active() {
+            return;
+        }
+
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let team = match before.get(x, y) {
+                    0 => before.resolve_team(x, y, rule.neighborhood),
+                    team => team,
+                };
+                if let Some(own) = self.rule_for(team, rule) {
+                    apply_rule_at(before, board, x, y, own);
+                }
+            }
+        }
+    }
+}
+
+impl Plugin for TeamRulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<TeamRules>()
+            .add_system(toggle_team_rules)
+            .add_system(team_rules_panel.after(toggle_team_rules));
+    }
+}
+
+fn toggle_team_rules(keys: Hotkeys, mut team_rules: ResMut<TeamRules>) {
+    if keys.just_pressed(KeyCode::I) {
+        team_rules.open = !team_rules.open;
+    }
+}
+
+fn team_rules_panel(
+    rule: Res<RuleSet>,
+    (mut team_rules, strings): (ResMut<TeamRules>, Res<Strings>),
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if !team_rules.open {
+        return;
+    }
+
+    let mut open = true;
+
+    egui::Window::new(strings.get("team-rules.title"))
+        .id(egui::Id::new("team rules"))
+        .open(&mut open)
+        .resizable(false)
+        .show(egui_context.ctx_mut(), |ui| {
+            let team_rules = &mut *team_rules;
+
+            for (i, team) in TEAMS.into_iter().enumerate() {
+                ui.horizontal(|ui| {
+                    ui.label(strings.format("team-rules.team", &[("team", &(team - 1))]));
+                    ui.text_edit_singleline(&mut team_rules.rule_texts[i]);
+
+                    if ui.button(strings.get("team-rules.apply")).clicked() {
+                        match RuleSet::parse(&team_rules.rule_texts[i]) {
+                            Ok(parsed) => {
+                                team_rules.rules[i] = Some(parsed.without_max_age());
+                                team_rules.error = None;
+                            }
+                            Err(error) => team_rules.error = Some(error),
+                        }
+                    }
+                    if team_rules.rules[i].is_some()
+                        && ui.button(strings.get("team-rules.clear")).clicked()
+                    {
+                        team_rules.rules[i] = None;
+                    }
+                });
+
+                let playing = match team_rules.rules[i] {
+                    Some(own) if own.neighborhood != rule.neighborhood => {
+                        strings.get("team-rules.mismatch").to_owned()
+                    }
+                    Some(own) => strings.format("team-rules.playing", &[("rule", &own)]),
+                    None => strings.format("team-rules.playing", &[("rule", &*rule)]),
+                };
+                ui.label(playing);
+            }
+
+            if let Some(error) = &team_rules.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+
+            ui.label(strings.get("team-rules.help"));
+        });
+
+    if !open {
+        team_rules.open = false;
+    }
+}
@@ -0,0 +1,265 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::lang::Strings;
+use crate::overlay::{board_overlay_image, place_over_board};
+use crate::rules::{Neighborhood, RuleSet};
+use crate::simulation::StepOverride;
+use crate::tools::{BoardCursor, Tool};
+use crate::Board;
+
+const FERTILE_COLOR: [u8; 4] = [70, 150, 60, 110];
+const BARREN_COLOR: [u8; 4] = [150, 110, 70, 110];
+
+/// Ground painted under the board with the terrain tool. Fertile ground births cells as if
+/// they had one more live neighbor than they do, and barren ground as if they had one
+/// fewer. Survival isn't affected.
+pub struct TerrainPlugin;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Ground {
+    #[default]
+    Plain,
+    Fertile,
+    Barren,
+}
+
+impl Ground {
+    const ALL: [Ground; 3] = [Ground::Fertile, Ground::Barren, Ground::Plain];
+
+    fn name(self) -> &'static str {
+        match self {
+            Ground::Plain => "plain",
+            Ground::Fertile => "fertile",
+            Ground::Barren => "barren",
+        }
+    }
+
+    /// How many neighbors a dead cell on this ground counts as having, given `count`.
+    fn birth_count(self, count: u32) -> Option<u32> {
+        match self {
+            Ground::Plain => Some(count),
+            Ground::Fertile => Some(count + 1),
+            Ground::Barren => count.checked_sub(1),
+        }
+    }
+}
+
+/// The ground under each cell, indexed like `Bitboard::get`. Starts out all plain and is
+/// cleared whenever the board changes size.
+#[derive(Resource, Default)]
+pub struct Terrain {
+    size: (u32, u32),
+    ground: Vec<Ground>,
+    /// Cells that aren't plain, so stepping can skip the terrain when there's none.
+    marked: usize,
+}
+
+impl Terrain {
+    pub fn is_empty(&self) -> bool {
+        self.marked == 0
+    }
+
+    pub fn get(&self, x: u32, y: u32) -> Ground {
+        if x >= self.size.0 || y >= self.size.1 {
+            return Ground::Plain;
+        }
+        self.ground[(y * self.size.0 + x) as usize]
+    }
+
+    fn set(&mut self, x: u32, y: u32, ground: Ground) {
+        let cell = &mut self.ground[(y * self.size.0 + x) as usize];
+        self.marked =
+            self.marked + (ground != Ground::Plain) as usize - (*cell != Ground::Plain) as usize;
+        *cell = ground;
+    }
+
+    pub fn clear(&mut self) {
+        self.ground.fill(Ground::Plain);
+        self.marked = 0;
+    }
+}
+
+impl StepOverride for Terrain {
+    fn is_active(&self) -> bool {
+        !self.is_empty()
+    }
+
+    /// Redoes the rule's births on fertile and barren ground for the step that took
+    /// `before` to `board`. Only meaningful for birth/survival rules.
+    fn apply(&self, before: &Bitboard, board: &mut Bitboard, rule: &RuleSet) {
+        if self.is_empty() || self.size != (board.width(), board.height()) {
+            return;
+        }
+
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                let ground = self.get(x, y);
+                if ground == Ground::Plain || before.get(x, y) != 0 || before.is_wall(x, y) {
+                    continue;
+                }
+
+                let count = before
+                    .neighbors(x, y, rule.neighborhood)
+                    .filter(|&(nx, ny)| before.get(nx, ny) != 0)
+                    .count() as u32;
+                let born = ground
+                    .birth_count(count)
+                    .is_some_and(|count| rule.birth & 1 << count != 0);
+
+                match (born, board.get(x, y)) {
+                    (true, 0) => board.set(x, y, before.resolve_team(x, y, rule.neighborhood)),
+                    (false, team) if team != 0 => board.set(x, y, 0),
+                    _ => {}
+                }
+            }
+        }
+    }
+}
+
+/// Ground the terrain tool paints.
+#[derive(Resource)]
+struct TerrainBrush(Ground);
+
+impl Default for TerrainBrush {
+    fn default() -> Self {
+        Self(Ground::Fertile)
+    }
+}
+
+#[derive(Component)]
+struct TerrainSprite;
+
+impl Plugin for TerrainPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Terrain>()
+            .init_resource::<TerrainBrush>()
+            .add_startup_system(spawn_terrain_sprite)
+            .add_system(fit_terrain)
+            .add_system(paint_terrain.after(fit_terrain))
+            .add_system(terrain_panel.after(paint_terrain))
+            .add_system(draw_terrain.after(terrain_panel));
+    }
+}
+
+fn spawn_terrain_sprite(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        TerrainSprite,
+    ));
+}
+
+/// Starts the terrain over when the board changes size.
+fn fit_terrain(board: Res<Board>, mut terrain: ResMut<Terrain>) {
+    let size = (board.width(), board.height());
+    if terrain.size != size {
+        *terrain = Terrain {
+            size,
+            ground: vec![Ground::Plain; size.0 as usize * size.1 as usize],
+            marked: 0,
+        };
+    }
+}
+
+/// Holding the left button paints ground over every tile the cursor passes.
+fn paint_terrain(
+    mouse: Res<Input<MouseButton>>,
+    (tool, brush): (Res<Tool>, Res<TerrainBrush>),
+    cursor: BoardCursor,
+    mut terrain: ResMut<Terrain>,
+) {
+    if *tool != Tool::Terrain || !mouse.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(TilePos { x, y }) = cursor.tile_pos() else {
+        return;
+    };
+
+    if terrain.get(x, y) != brush.0 {
+        terrain.set(x, y, brush.0);
+    }
+}
+
+fn terrain_panel(
+    tool: Res<Tool>,
+    mut brush: ResMut<TerrainBrush>,
+    mut terrain: ResMut<Terrain>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if *tool != Tool::Terrain {
+        return;
+    }
+
+    egui::Window::new(strings.get("terrain.title"))
+        .id(egui::Id::new("terrain"))
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for ground in Ground::ALL {
+                    let name = strings.named("ground", ground.name());
+                    ui.selectable_value(&mut brush.0, ground, name);
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.label(strings.format("terrain.marked", &[("cells", &terrain.marked)]));
+                if ui.button(strings.get("terrain.clear")).clicked() && !terrain.is_empty() {
+                    terrain.clear();
+                }
+            });
+
+            ui.label(strings.get("terrain.help"));
+        });
+}
+
+/// Redraws the ground texture under the board whenever the terrain changes.
+fn draw_terrain(
+    (terrain, rule): (Res<Terrain>, Res<RuleSet>),
+    mut images: ResMut<Assets<Image>>,
+    mut sprite_query: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<TerrainSprite>,
+    >,
+) {
+    let Ok((mut handle, mut sprite, mut transform, mut visibility)) = sprite_query.get_single_mut()
+    else {
+        return;
+    };
+
+    // the texture is a square grid, so it's only drawn under square boards
+    let shown = !terrain.is_empty() && rule.neighborhood == Neighborhood::Moore;
+    if visibility.is_visible != shown {
+        visibility.is_visible = shown;
+    }
+    if !shown || !(terrain.is_changed() || rule.is_changed()) {
+        return;
+    }
+
+    let (width, height) = terrain.size;
+    let image = board_overlay_image(width, height, |x, y| match terrain.get(x, y) {
+        Ground::Plain => None,
+        Ground::Fertile => Some(FERTILE_COLOR),
+        Ground::Barren => Some(BARREN_COLOR),
+    });
+
+    match images.get_mut(&handle) {
+        Some(existing) => *existing = image,
+        None => *handle = images.add(image),
+    }
+
+    // just under the tiles, but not so far down the 2d camera clips it
+    place_over_board((width, height), &mut sprite, &mut transform, -0.05);
+}
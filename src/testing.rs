@@ -0,0 +1,177 @@
+//! Random boards and rules for property tests, and a check that every stepping backend
+//! agrees on them. Only built with the `test-support` feature.
+
+use std::fmt;
+
+use crate::bitboard::{Bitboard, Boundary};
+use crate::patterns;
+use crate::rng::Rng;
+use crate::rules::{Neighborhood, RuleSet};
+
+/// A way of stepping a board one generation.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// `Bitboard::step`, the word-at-a-time version the game runs.
+    Bitboard,
+    /// `Bitboard::step_naive`, the cell-by-cell reference.
+    Naive,
+    /// `Bitboard::step_cells`, the per-cell engine scripts and cyclic rules run on, given
+    /// the rule as a closure. It doesn't know about teams or ages, so only liveness is
+    /// compared, and only for rules without an age limit.
+    Cells,
+}
+
+impl Backend {
+    pub const ALL: [Backend; 3] = [Backend::Bitboard, Backend::Naive, Backend::Cells];
+
+    pub fn step(self, board: &mut Bitboard, rule: &RuleSet) {
+        match self {
+            Backend::Bitboard => board.step(rule),
+            Backend::Naive => board.step_naive(rule),
+            Backend::Cells => board.step_cells(rule.neighborhood, |state, neighbors| {
+                let count = neighbors.iter().filter(|&&n| n != 0).count();
+                let mask = if state != 0 {
+                    rule.survival
+                } else {
+                    rule.birth
+                };
+
+                match mask & 1 << count != 0 {
+                    false => 0,
+                    true if state != 0 => state,
+                    true => 2,
+                }
+            }),
+        }
+    }
+
+    /// Whether this backend is expected to match `Backend::Naive` on `rule`.
+    fn supports(self, rule: &RuleSet) -> bool {
+        self != Backend::Cells || rule.max_age.is_none()
+    }
+}
+
+/// The first cell where a backend disagreed with the naive reference.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Mismatch {
+    pub backend: Backend,
+    /// Generations run when the boards first differed, counting from 1.
+    pub generation: u64,
+    pub x: u32,
+    pub y: u32,
+    /// Team of the cell on the reference board, 0 if dead.
+    pub expected: u8,
+    pub found: u8,
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} has {} at ({}, {}) in generation {} where the reference has {}",
+            self.backend, self.found, self.x, self.y, self.generation, self.expected
+        )
+    }
+}
+
+/// A `width` by `height` board under `boundary` with about a third of its cells alive,
+/// split between teams 2 and 3, and a few walls.
+pub fn random_board(rng: &mut Rng, (width, height): (u32, u32), boundary: Boundary) -> Bitboard {
+    let mut board = Bitboard::new(width, height);
+    board.set_boundary(boundary);
+
+    for y in 0..height {
+        for x in 0..width {
+            match rng.next_u64() % 32 {
+                0..=4 => board.set(x, y, 2),
+                5..=9 => board.set(x, y, 3),
+                10 => board.set_wall(x, y, true),
+                _ => {}
+            }
+        }
+    }
+
+    board
+}
+
+/// A `size` by `size` board with just the built-in pattern `name` on it as team 2, its
+/// bottom left corner at `(left, bottom)`.
+pub fn board_with(name: &str, size: u32, (left, bottom): (i64, i64)) -> Bitboard {
+    let mut board = Bitboard::new(size, size);
+    patterns::find(name)
+        .unwrap()
+        .stamp(&mut board, (left, bottom), (false, false), 2);
+    board
+}
+
+/// Any outer-totalistic rule for `neighborhood`, sometimes with an age limit.
+pub fn random_rule(rng: &mut Rng, neighborhood: Neighborhood) -> RuleSet {
+    let counts = (1u16 << (neighborhood.max_neighbors() + 1)) - 1;
+
+    RuleSet {
+        birth: rng.next_u64() as u16 & counts,
+        survival: rng.next_u64() as u16 & counts,
+        neighborhood,
+        max_age: rng
+            .next_u64()
+            .is_multiple_of(4)
+            .then(|| (rng.next_u64() % 8) as u16),
+    }
+}
+
+pub fn random_boundary(rng: &mut Rng) -> Boundary {
+    Boundary::ALL[rng.next_u64() as usize % Boundary::ALL.len()]
+}
+
+/// Steps a copy of `board` on every backend for `generations` generations, comparing each
+/// to the naive reference after every one.
+pub fn check_backends(board: &Bitboard, rule: &RuleSet, generations: u64) -> Result<(), Mismatch> {
+    let mut reference = board.clone();
+    let mut boards: Vec<_> = Backend::ALL
+        .into_iter()
+        .filter(|&backend| backend != Backend::Naive && backend.supports(rule))
+        .map(|backend| (backend, board.clone()))
+        .collect();
+
+    for generation in 1..=generations {
+        Backend::Naive.step(&mut reference, rule);
+
+        for (backend, board) in &mut boards {
+            backend.step(board, rule);
+
+            if let Some(mismatch) = first_difference(&reference, board, *backend) {
+                return Err(Mismatch {
+                    generation,
+                    ..mismatch
+                });
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn first_difference(reference: &Bitboard, board: &Bitboard, backend: Backend) -> Option<Mismatch> {
+    for y in 0..reference.height() {
+        for x in 0..reference.width() {
+            let (expected, found) = (reference.get(x, y), board.get(x, y));
+            let differs = match backend {
+                Backend::Cells => (expected != 0) != (found != 0),
+                _ => expected != found,
+            };
+
+            if differs {
+                return Some(Mismatch {
+                    backend,
+                    generation: 0,
+                    x,
+                    y,
+                    expected,
+                    found,
+                });
+            }
+        }
+    }
+
+    None
+}
@@ -0,0 +1,428 @@
+use std::collections::HashSet;
+
+use bevy::ecs::system::SystemParam;
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::camera::{cursor_in_view, MainCamera};
+use crate::energy::Energy;
+use crate::hotkeys::Hotkeys;
+use crate::hud::PointerOverUi;
+use crate::rules::RuleSet;
+use crate::scenario::ActiveScenario;
+use crate::undo::UndoHistory;
+use crate::Board;
+
+/// Most cells one click of the fill tool changes, so misclicking into a huge open area
+/// of an imported board doesn't stall the frame.
+const FILL_LIMIT: usize = 1 << 16;
+
+/// The editing tool applied by left clicks.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tool {
+    /// Toggles cells of the brush's team.
+    #[default]
+    Paint,
+    /// Clears cells, leaving walls alone.
+    Erase,
+    /// Toggles immutable walls.
+    Wall,
+    /// Places or removes pattern spawners.
+    Spawner,
+    /// Places or removes Langton's ants.
+    Ant,
+    /// Drags a straight line of the brush's team.
+    Line,
+    /// Drags a rectangle of team cells, like `Line`.
+    Rectangle,
+    /// Drags an ellipse of team cells inside the dragged box, like `Line`.
+    Ellipse,
+    /// Fills the region of matching cells around the clicked one with the brush's team.
+    Fill,
+    /// Drops or removes labelled markers.
+    Marker,
+    /// Measures between two clicked tiles.
+    Measure,
+    /// Shows the recorded history of a clicked cell.
+    Inspect,
+    /// Paints fertile or barren ground under the board.
+    Terrain,
+    /// Paints zones that run under rules of their own.
+    Zone,
+    /// Drags out the area Ctrl+Shift+C copies.
+    Select,
+    /// Stamps the clip pasted with Ctrl+Shift+V. Not bound to a key, since it needs a clip.
+    Paste,
+}
+
+impl Tool {
+    pub const ALL: [(Tool, KeyCode); 15] = [
+        (Tool::Paint, KeyCode::Key1),
+        (Tool::Wall, KeyCode::Key2),
+        (Tool::Spawner, KeyCode::Key3),
+        (Tool::Ant, KeyCode::Key4),
+        (Tool::Select, KeyCode::Key5),
+        (Tool::Line, KeyCode::Key6),
+        (Tool::Rectangle, KeyCode::Key7),
+        (Tool::Ellipse, KeyCode::Key8),
+        (Tool::Fill, KeyCode::Key9),
+        (Tool::Erase, KeyCode::Key0),
+        (Tool::Marker, KeyCode::Minus),
+        (Tool::Measure, KeyCode::Equals),
+        (Tool::Terrain, KeyCode::LBracket),
+        (Tool::Zone, KeyCode::RBracket),
+        (Tool::Inspect, KeyCode::Semicolon),
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Tool::Paint => "paint",
+            Tool::Erase => "erase",
+            Tool::Wall => "wall",
+            Tool::Spawner => "spawner",
+            Tool::Ant => "ant",
+            Tool::Line => "line",
+            Tool::Rectangle => "rectangle",
+            Tool::Ellipse => "ellipse",
+            Tool::Fill => "fill",
+            Tool::Marker => "marker",
+            Tool::Measure => "measure",
+            Tool::Inspect => "inspect",
+            Tool::Terrain => "terrain",
+            Tool::Zone => "zone",
+            Tool::Select => "select",
+            Tool::Paste => "paste",
+        }
+    }
+}
+
+/// Positions every edit is mirrored to, so symmetric soups and fair two-team starts can be
+/// drawn by hand. Mirroring is exact on square boards; hex rows are offset, so there it's
+/// only approximate.
+#[derive(Resource, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    #[default]
+    None,
+    /// Mirrored left to right.
+    Horizontal,
+    /// Mirrored top to bottom.
+    Vertical,
+    /// Mirrored both ways, four copies in all.
+    FourFold,
+    /// Turned half way around the board's center.
+    Rotational,
+}
+
+impl Symmetry {
+    pub const ALL: [Symmetry; 5] = [
+        Symmetry::None,
+        Symmetry::Horizontal,
+        Symmetry::Vertical,
+        Symmetry::FourFold,
+        Symmetry::Rotational,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Symmetry::None => "no",
+            Symmetry::Horizontal => "horizontal",
+            Symmetry::Vertical => "vertical",
+            Symmetry::FourFold => "4-fold",
+            Symmetry::Rotational => "rotational",
+        }
+    }
+
+    /// `tiles` along with their mirror images on a `width` by `height` board, each once.
+    pub fn mirror(
+        self,
+        tiles: impl IntoIterator<Item = (u32, u32)>,
+        (width, height): (u32, u32),
+    ) -> Vec<(u32, u32)> {
+        let flips: &[(bool, bool)] = match self {
+            Symmetry::None => &[(false, false)],
+            Symmetry::Horizontal => &[(false, false), (true, false)],
+            Symmetry::Vertical => &[(false, false), (false, true)],
+            Symmetry::FourFold => &[(false, false), (true, false), (false, true), (true, true)],
+            Symmetry::Rotational => &[(false, false), (true, true)],
+        };
+
+        let mut seen = HashSet::new();
+        let mut images = Vec::new();
+
+        for (x, y) in tiles {
+            for &(flip_x, flip_y) in flips {
+                let image = (
+                    if flip_x { width - 1 - x } else { x },
+                    if flip_y { height - 1 - y } else { y },
+                );
+
+                if seen.insert(image) {
+                    images.push(image);
+                }
+            }
+        }
+
+        images
+    }
+}
+
+/// Cell value the editing tools draw with, picked from the toolbar's palette: a team,
+/// 1 for cells on neither team, or 0 to erase.
+#[derive(Resource)]
+pub struct Brush {
+    pub team: u8,
+}
+
+impl Default for Brush {
+    fn default() -> Self {
+        Self { team: 2 }
+    }
+}
+
+pub struct ToolsPlugin;
+
+impl Plugin for ToolsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tool>()
+            .init_resource::<Symmetry>()
+            .init_resource::<Brush>()
+            .add_system(select_tool)
+            .add_system(cycle_symmetry)
+            .add_system(mouse_input)
+            .add_system(show_pointing);
+    }
+}
+
+/// Maps the cursor onto the board's tilemap.
+#[derive(SystemParam)]
+pub struct BoardCursor<'w, 's> {
+    windows: Res<'w, Windows>,
+    over_ui: Res<'w, PointerOverUi>,
+    scenario: Res<'w, ActiveScenario>,
+    camera_query: Query<
+        'w,
+        's,
+        (
+            &'static Camera,
+            &'static Transform,
+            &'static OrthographicProjection,
+        ),
+        With<MainCamera>,
+    >,
+    tilemap_query: Query<
+        'w,
+        's,
+        (
+            &'static TilemapSize,
+            &'static TilemapGridSize,
+            &'static TilemapType,
+            &'static Transform,
+        ),
+        Without<Camera2d>,
+    >,
+}
+
+/// What the cursor is over, as far as editing the board goes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Pointing {
+    /// Over a UI window.
+    Ui,
+    /// Outside the main view or the window, or there's no board to point at.
+    Nothing,
+    /// In the main view but past the board's edges.
+    OffBoard,
+    /// On a tile the scenario doesn't let the player edit.
+    Locked(TilePos),
+    Tile(TilePos),
+}
+
+impl BoardCursor<'_, '_> {
+    /// Whether the scenario keeps the player from editing this tile.
+    pub fn is_locked(&self, tile: TilePos) -> bool {
+        self.scenario.is_locked(tile.x, tile.y)
+    }
+
+    /// What the cursor is over. The cursor is projected through the main camera's
+    /// viewport, position and zoom, then into the tilemap's own space, so picking holds
+    /// up however the view is zoomed, panned, split or resized.
+    pub fn pointing(&self) -> Pointing {
+        if self.over_ui.0 {
+            return Pointing::Ui;
+        }
+
+        let (Some(window), Ok(camera), Ok(tilemap)) = (
+            self.windows.get_primary(),
+            self.camera_query.get_single(),
+            self.tilemap_query.get_single(),
+        ) else {
+            return Pointing::Nothing;
+        };
+        let (camera, camera_transform, projection) = camera;
+        let (map_size, grid_size, map_type, tilemap_transform) = tilemap;
+
+        let Some(offset) = cursor_in_view(window, camera) else {
+            return Pointing::Nothing;
+        };
+        let world = camera_transform.translation.truncate() + offset * projection.scale;
+        let local = world - tilemap_transform.translation.truncate();
+
+        match TilePos::from_world_pos(&local, map_size, grid_size, map_type) {
+            None => Pointing::OffBoard,
+            Some(tile) if self.is_locked(tile) => Pointing::Locked(tile),
+            Some(tile) => Pointing::Tile(tile),
+        }
+    }
+
+    /// Tile under the cursor, or `None` if the cursor is outside the main view or the board,
+    /// over a UI window, or over a region the scenario doesn't let the player edit.
+    pub fn tile_pos(&self) -> Option<TilePos> {
+        match self.pointing() {
+            Pointing::Tile(tile) => Some(tile),
+            _ => None,
+        }
+    }
+}
+
+/// Something drawn as a marker sprite over one tile of the board.
+pub trait OnTile: Component {
+    fn tile(&self) -> TilePos;
+}
+
+/// Keeps markers over their tiles, including after the tilemap changes shape.
+pub fn position_markers<T: OnTile>(
+    tilemap_query: Query<(&TilemapGridSize, &TilemapType, &Transform), Without<T>>,
+    mut marker_query: Query<(&T, &mut Transform)>,
+) {
+    let Ok((grid_size, map_type, tilemap_transform)) = tilemap_query.get_single() else {
+        return;
+    };
+
+    for (marker, mut transform) in marker_query.iter_mut() {
+        let center = marker.tile().center_in_world(grid_size, map_type);
+        let translation = tilemap_transform.translation + center.extend(1.0);
+
+        if transform.translation != translation {
+            transform.translation = translation;
+        }
+    }
+}
+
+/// Shows a not-allowed cursor while it's over the view but can't edit what's under it.
+fn show_pointing(cursor: BoardCursor, mut egui_context: ResMut<EguiContext>) {
+    if matches!(cursor.pointing(), Pointing::OffBoard | Pointing::Locked(_)) {
+        egui_context.ctx_mut().output().cursor_icon = egui::CursorIcon::NotAllowed;
+    }
+}
+
+fn select_tool(keys: Hotkeys, mut tool: ResMut<Tool>) {
+    for (candidate, key) in Tool::ALL {
+        if keys.just_pressed(key) {
+            *tool = candidate;
+        }
+    }
+}
+
+fn cycle_symmetry(keys: Hotkeys, mut symmetry: ResMut<Symmetry>) {
+    if keys.just_pressed(KeyCode::M) {
+        let index = Symmetry::ALL
+            .iter()
+            .position(|&s| s == *symmetry)
+            .unwrap_or(0);
+        *symmetry = Symmetry::ALL[(index + 1) % Symmetry::ALL.len()];
+    }
+}
+
+fn mouse_input(
+    mouse: Res<Input<MouseButton>>,
+    (rule, symmetry): (Res<RuleSet>, Res<Symmetry>),
+    (tool, brush): (Res<Tool>, Res<Brush>),
+    cursor: BoardCursor,
+    mut energy: ResMut<Energy>,
+    mut board: ResMut<Board>,
+    mut history: ResMut<UndoHistory>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(TilePos { x, y }) = cursor.tile_pos() else {
+        return;
+    };
+
+    let size = (board.width(), board.height());
+    let mirror = |tiles: Vec<(u32, u32)>| -> Vec<(u32, u32)> {
+        symmetry
+            .mirror(tiles, size)
+            .into_iter()
+            .filter(|&(x, y)| !cursor.is_locked(TilePos::new(x, y)))
+            .collect()
+    };
+
+    match *tool {
+        Tool::Paint => {
+            let team = brush.team;
+            let team = if board.get(x, y) == team { 0 } else { team };
+
+            // clearing a cell is free, bringing one to life costs its team
+            let cells = mirror(vec![(x, y)]);
+            if team == 0 || energy.try_spend(team, cells.len() as f32) {
+                history.record(&board);
+                for (x, y) in cells {
+                    board.set(x, y, team);
+                }
+            }
+        }
+        Tool::Erase => {
+            let cells: Vec<_> = mirror(vec![(x, y)])
+                .into_iter()
+                .filter(|&(x, y)| board.get(x, y) != 0)
+                .collect();
+
+            if !cells.is_empty() {
+                history.record(&board);
+                for (x, y) in cells {
+                    board.set(x, y, 0);
+                }
+            }
+        }
+        Tool::Wall => {
+            let wall = !board.is_wall(x, y);
+            history.record(&board);
+            for (x, y) in mirror(vec![(x, y)]) {
+                board.set_wall(x, y, wall);
+            }
+        }
+        Tool::Fill => {
+            let team = brush.team;
+            if board.is_wall(x, y) || board.get(x, y) == team {
+                return;
+            }
+
+            let Some(region) = board.region(x, y, rule.neighborhood, FILL_LIMIT) else {
+                return;
+            };
+            let cells = mirror(region);
+
+            if energy.try_spend(team, cells.len() as f32) {
+                history.record(&board);
+                for (x, y) in cells {
+                    board.set(x, y, team);
+                }
+            }
+        }
+        // handled by their own plugins
+        Tool::Spawner
+        | Tool::Ant
+        | Tool::Line
+        | Tool::Rectangle
+        | Tool::Ellipse
+        | Tool::Marker
+        | Tool::Measure
+        | Tool::Inspect
+        | Tool::Terrain
+        | Tool::Zone
+        | Tool::Select
+        | Tool::Paste => {}
+    }
+}
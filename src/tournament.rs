@@ -0,0 +1,286 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::lang::Strings;
+use crate::scenario::BoardSetup;
+use crate::{update_map, Board, GameState, SimCounters, SimRng, MAP_SIZE};
+
+/// Generations a round lasts if neither team is wiped out first.
+const ROUND_GENERATIONS: u64 = 500;
+
+/// A best-of-N team match: each round starts from a fresh mirrored soup, or from seeds
+/// the players designed in a championship, and is won by wiping the other team out or by
+/// having more cells when time runs out.
+#[derive(Resource)]
+pub struct Tournament {
+    pub best_of: u32,
+    /// Whether the players design each round's seeds, see `ChampionshipPlugin`.
+    pub championship: bool,
+    /// The board the next round starts from, if not a soup.
+    pub seeded: Option<Bitboard>,
+    /// Most live cells either team may have in a round, see `PopulationCap`.
+    pub population_cap: Option<u32>,
+    /// Winner of each round played so far, `None` for a draw.
+    rounds: Vec<Option<u8>>,
+    round_start: u64,
+}
+
+impl Default for Tournament {
+    fn default() -> Self {
+        Self {
+            best_of: 3,
+            championship: false,
+            seeded: None,
+            population_cap: None,
+            rounds: Vec::new(),
+            round_start: 0,
+        }
+    }
+}
+
+impl Tournament {
+    pub fn begin(&mut self, championship: bool) {
+        self.championship = championship;
+        self.seeded = None;
+        self.rounds.clear();
+    }
+
+    /// Where the next round starts: designing seeds in a championship, or the round itself.
+    pub fn next_round(&self) -> GameState {
+        if self.championship {
+            GameState::Designing
+        } else {
+            GameState::Round
+        }
+    }
+
+    pub fn rounds_played(&self) -> usize {
+        self.rounds.len()
+    }
+
+    /// Rounds won by team id `team`.
+    pub fn score(&self, team: u8) -> usize {
+        self.rounds
+            .iter()
+            .filter(|&&winner| winner == Some(team))
+            .count()
+    }
+
+    /// Whether a team has won more rounds than the other could still catch up on.
+    fn is_decided(&self) -> bool {
+        let needed = self.best_of as usize / 2 + 1;
+        self.score(2) >= needed
+            || self.score(3) >= needed
+            || self.rounds.len() >= self.best_of as usize
+    }
+
+    fn winner(&self) -> Option<u8> {
+        match self.score(2).cmp(&self.score(3)) {
+            std::cmp::Ordering::Greater => Some(2),
+            std::cmp::Ordering::Less => Some(3),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+pub struct TournamentPlugin;
+
+impl Plugin for TournamentPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tournament>()
+            .add_system_set(SystemSet::on_enter(GameState::Round).with_system(start_round))
+            .add_system_set(
+                SystemSet::on_update(GameState::Round)
+                    .with_system(judge_round.after(update_map))
+                    .with_system(round_panel),
+            )
+            .add_system_set(SystemSet::on_update(GameState::RoundOver).with_system(round_over))
+            .add_system_set(SystemSet::on_update(GameState::MatchOver).with_system(match_over));
+    }
+}
+
+/// Fills a box on the left of the board with a random soup for team 1 and mirrors it
+/// onto the right for team 2, so neither side starts ahead.
+fn mirrored_soup(rng: &mut SimRng) -> Bitboard {
+    let mut board = Bitboard::new(MAP_SIZE.0, MAP_SIZE.1);
+    let (width, height) = (board.width(), board.height());
+
+    for y in height / 4..height * 3 / 4 {
+        for x in width / 8..width / 2 - 4 {
+            if rng.next_u64().is_multiple_of(3) {
+                board.set(x, y, 2);
+                board.set(width - 1 - x, y, 3);
+            }
+        }
+    }
+
+    board
+}
+
+fn start_round(
+    mut setup: BoardSetup,
+    mut tournament: ResMut<Tournament>,
+    mut rng: ResMut<SimRng>,
+    counters: Res<SimCounters>,
+) {
+    let board = tournament
+        .seeded
+        .take()
+        .unwrap_or_else(|| mirrored_soup(&mut rng));
+    setup.reset(board);
+    setup.set_population_cap(tournament.population_cap);
+    tournament.round_start = counters.generation;
+}
+
+fn judge_round(
+    board: Res<Board>,
+    counters: Res<SimCounters>,
+    mut tournament: ResMut<Tournament>,
+    mut state: ResMut<State<GameState>>,
+) {
+    let (team_1, team_2) = (board.team_population(2), board.team_population(3));
+    let out_of_time = counters.generation - tournament.round_start >= ROUND_GENERATIONS;
+
+    if !(out_of_time || team_1 == 0 || team_2 == 0) {
+        return;
+    }
+
+    let winner = match team_1.cmp(&team_2) {
+        std::cmp::Ordering::Greater => Some(2),
+        std::cmp::Ordering::Less => Some(3),
+        std::cmp::Ordering::Equal => None,
+    };
+    tournament.rounds.push(winner);
+
+    let next = if tournament.is_decided() {
+        GameState::MatchOver
+    } else {
+        GameState::RoundOver
+    };
+    let _ = state.set(next);
+}
+
+fn winner_name(strings: &Strings, winner: Option<u8>) -> String {
+    match winner {
+        Some(team) => strings.format("match.team-wins", &[("team", &(team - 1))]),
+        None => strings.get("match.draw").to_owned(),
+    }
+}
+
+fn round_panel(
+    mut egui_context: ResMut<EguiContext>,
+    counters: Res<SimCounters>,
+    tournament: Res<Tournament>,
+    strings: Res<Strings>,
+) {
+    egui::Window::new(strings.get("match.title"))
+        .id(egui::Id::new("match"))
+        .title_bar(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.label(strings.format(
+                "match.round",
+                &[
+                    ("round", &(tournament.rounds.len() + 1)),
+                    ("best-of", &tournament.best_of),
+                    ("first", &tournament.score(2)),
+                    ("second", &tournament.score(3)),
+                ],
+            ));
+            ui.label(strings.format(
+                "match.generation",
+                &[
+                    (
+                        "generation",
+                        &(counters.generation - tournament.round_start),
+                    ),
+                    ("generations", &ROUND_GENERATIONS),
+                ],
+            ));
+        });
+}
+
+fn round_over(
+    mut egui_context: ResMut<EguiContext>,
+    tournament: Res<Tournament>,
+    mut state: ResMut<State<GameState>>,
+    strings: Res<Strings>,
+) {
+    egui::Window::new(strings.get("match.round-over"))
+        .id(egui::Id::new("round over"))
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let winner = tournament.rounds.last().copied().flatten();
+            ui.label(strings.format(
+                "match.round-result",
+                &[
+                    ("round", &tournament.rounds.len()),
+                    ("result", &winner_name(&strings, winner)),
+                ],
+            ));
+            ui.label(strings.format(
+                "match.score",
+                &[
+                    ("first", &tournament.score(2)),
+                    ("second", &tournament.score(3)),
+                ],
+            ));
+
+            if ui.button(strings.get("match.next-round")).clicked() {
+                let _ = state.set(tournament.next_round());
+            }
+        });
+}
+
+fn match_over(
+    mut egui_context: ResMut<EguiContext>,
+    mut tournament: ResMut<Tournament>,
+    mut state: ResMut<State<GameState>>,
+    strings: Res<Strings>,
+    mut setup: BoardSetup,
+) {
+    egui::Window::new(strings.get("match.over"))
+        .id(egui::Id::new("match over"))
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.heading(match tournament.winner() {
+                Some(team) => strings.format("match.winner", &[("team", &(team - 1))]),
+                None => strings.get("match.drawn").to_owned(),
+            });
+            ui.label(strings.format(
+                "match.final-score",
+                &[
+                    ("first", &tournament.score(2)),
+                    ("second", &tournament.score(3)),
+                ],
+            ));
+
+            for (round, &winner) in tournament.rounds.iter().enumerate() {
+                ui.label(strings.format(
+                    "match.round-result",
+                    &[
+                        ("round", &(round + 1)),
+                        ("result", &winner_name(&strings, winner)),
+                    ],
+                ));
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(strings.get("match.rematch")).clicked() {
+                    let championship = tournament.championship;
+                    tournament.begin(championship);
+                    let _ = state.set(tournament.next_round());
+                }
+                if ui.button(strings.get("match.sandbox")).clicked() {
+                    setup.sandbox();
+                    let _ = state.set(GameState::Sandbox);
+                }
+            });
+        });
+}
@@ -0,0 +1,209 @@
+use std::path::Path;
+
+use bevy::input::mouse::MouseWheel;
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::census::Census;
+use crate::hotkeys::Hotkeys;
+use crate::lang::Strings;
+use crate::rules::RuleSet;
+use crate::session::SESSION_PATH;
+use crate::tools::Tool;
+use crate::{Board, FastForward, TickDuration};
+
+/// F1 starts a guided tour of the basics, one prompt at a time, each moving on once the
+/// player has done what it asks. It's offered by itself on the first launch.
+pub struct TutorialPlugin;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    Welcome,
+    PlaceCell,
+    Run,
+    Pause,
+    FastForward,
+    Camera,
+    PickTool,
+    Glider,
+    Done,
+}
+
+impl Step {
+    const ALL: [Step; 9] = [
+        Step::Welcome,
+        Step::PlaceCell,
+        Step::Run,
+        Step::Pause,
+        Step::FastForward,
+        Step::Camera,
+        Step::PickTool,
+        Step::Glider,
+        Step::Done,
+    ];
+
+    /// The prompt's key in the language bundles.
+    fn key(self) -> &'static str {
+        match self {
+            Step::Welcome => "tutorial.welcome",
+            Step::PlaceCell => "tutorial.place-cell",
+            Step::Run => "tutorial.run",
+            Step::Pause => "tutorial.pause",
+            Step::FastForward => "tutorial.fast-forward",
+            Step::Camera => "tutorial.camera",
+            Step::PickTool => "tutorial.pick-tool",
+            Step::Glider => "tutorial.glider",
+            Step::Done => "tutorial.done",
+        }
+    }
+
+    fn next(self) -> Option<Step> {
+        let i = Step::ALL.iter().position(|&step| step == self)?;
+        Step::ALL.get(i + 1).copied()
+    }
+}
+
+#[derive(Resource, Default)]
+struct Tutorial {
+    /// The step being prompted, while the tutorial runs.
+    step: Option<Step>,
+    /// The board's population and the tool when the step started, to tell when the
+    /// player changed them.
+    population: u32,
+    tool: Tool,
+}
+
+impl Tutorial {
+    fn start(&mut self, step: Option<Step>, board: &Board, tool: Tool) {
+        self.step = step;
+        self.population = board.population();
+        self.tool = tool;
+    }
+}
+
+impl Plugin for TutorialPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Tutorial>()
+            .add_startup_system(offer_tutorial)
+            .add_system(toggle_tutorial)
+            .add_system(check_step.after(toggle_tutorial))
+            .add_system(tutorial_window.after(check_step));
+    }
+}
+
+/// Opens the tutorial's welcome on the first launch, told apart by there being no session
+/// saved yet.
+fn offer_tutorial(mut tutorial: ResMut<Tutorial>) {
+    if !Path::new(SESSION_PATH).exists() {
+        tutorial.step = Some(Step::Welcome);
+    }
+}
+
+fn toggle_tutorial(
+    keys: Hotkeys,
+    (board, tool): (Res<Board>, Res<Tool>),
+    mut tutorial: ResMut<Tutorial>,
+) {
+    if keys.just_pressed(KeyCode::F1) {
+        let step = match tutorial.step {
+            Some(_) => None,
+            None => Some(Step::PlaceCell),
+        };
+        tutorial.start(step, &board, *tool);
+    }
+}
+
+/// Moves on once the player has done what the current step asks.
+fn check_step(
+    (mouse, mut scroll): (Res<Input<MouseButton>>, EventReader<MouseWheel>),
+    (board, rule, census): (Res<Board>, Res<RuleSet>, Res<Census>),
+    (ticker, fast_forward, tool): (Res<TickDuration>, Res<FastForward>, Res<Tool>),
+    mut tutorial: ResMut<Tutorial>,
+) {
+    let scrolled = scroll.iter().count() > 0;
+    let Some(step) = tutorial.step else {
+        return;
+    };
+
+    let done = match step {
+        // these wait for a button in the prompt
+        Step::Welcome | Step::Done => false,
+        Step::PlaceCell => board.population() > tutorial.population,
+        Step::Run => !ticker.0.paused(),
+        Step::Pause => ticker.0.paused(),
+        Step::FastForward => fast_forward.held,
+        Step::Camera => scrolled || mouse.just_pressed(MouseButton::Middle),
+        Step::PickTool => *tool != tutorial.tool,
+        Step::Glider => {
+            board.is_changed()
+                && *rule == RuleSet::CONWAY
+                && census
+                    .count(&board)
+                    .0
+                    .iter()
+                    .any(|&(name, count)| name == "glider" && count > 0)
+        }
+    };
+
+    if done {
+        tutorial.start(step.next(), &board, *tool);
+    }
+}
+
+fn tutorial_window(
+    (board, tool, strings): (Res<Board>, Res<Tool>, Res<Strings>),
+    mut tutorial: ResMut<Tutorial>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    let Some(step) = tutorial.step else {
+        return;
+    };
+
+    let mut next = None;
+    let number = Step::ALL
+        .iter()
+        .position(|&candidate| candidate == step)
+        .unwrap_or(0);
+
+    egui::Window::new(strings.get("tutorial.title"))
+        .id(egui::Id::new("tutorial"))
+        .collapsible(false)
+        .resizable(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            ui.set_max_width(360.0);
+            ui.label(strings.get(step.key()));
+
+            ui.horizontal(|ui| match step {
+                Step::Welcome => {
+                    if ui.button(strings.get("tutorial.start")).clicked() {
+                        next = Some(step.next());
+                    }
+                    if ui.button(strings.get("tutorial.no-thanks")).clicked() {
+                        next = Some(None);
+                    }
+                }
+                Step::Done => {
+                    if ui.button(strings.get("tutorial.finish")).clicked() {
+                        next = Some(None);
+                    }
+                }
+                _ => {
+                    if ui.button(strings.get("tutorial.skip")).clicked() {
+                        next = Some(step.next());
+                    }
+                    if ui.button(strings.get("tutorial.quit")).clicked() {
+                        next = Some(None);
+                    }
+                    ui.label(strings.format(
+                        "tutorial.progress",
+                        &[("step", &number), ("steps", &(Step::ALL.len() - 2))],
+                    ));
+                }
+            });
+        });
+
+    if let Some(next) = next {
+        tutorial.start(next, &board, *tool);
+    }
+}
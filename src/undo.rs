@@ -0,0 +1,348 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::hotkeys::Hotkeys;
+use crate::inspect::RestartTimeline;
+use crate::lang::Strings;
+use crate::snapshot::{self, PackedBoard};
+use crate::{Board, GameState};
+
+/// Most boards the history keeps before forgetting the oldest.
+const MAX_CHECKPOINTS: usize = 1024;
+/// Most checkpoints in a row kept as differences from their parents before one is kept
+/// whole again, so going back to one never unpacks more than this many boards.
+const MAX_DELTA_CHAIN: usize = 16;
+
+/// Ctrl+Z undoes the last edit to the sandbox board, Ctrl+Y or Ctrl+Shift+Z redoes it.
+/// Editing after undoing starts a new branch instead of throwing the undone edits away,
+/// and the history panel switches between branches.
+pub struct UndoPlugin;
+
+/// A board the history can go back to, and the boards that followed it.
+struct Checkpoint {
+    board: PackedBoard,
+    /// How many checkpoints up the nearest one kept whole is.
+    chain: usize,
+    parent: Option<usize>,
+    children: Vec<usize>,
+    /// The child redo goes to: the one most recently left or created.
+    active: Option<usize>,
+}
+
+/// A tree of boards from before each edit, plus the boards undo left behind. Tools call
+/// `record` just before they change the board; undoing also rewinds whatever the
+/// simulation did since. Checkpoints are kept in slots so indices stay valid when old ones
+/// are forgotten, and their boards are packed, mostly as differences from their parents.
+#[derive(Resource, Default)]
+pub struct UndoHistory {
+    checkpoints: Vec<Option<Checkpoint>>,
+    /// The checkpoint the board was last recorded as or restored to.
+    head: Option<usize>,
+    /// The head's board unpacked, since every edit is compared against it.
+    head_board: Option<Bitboard>,
+    len: usize,
+}
+
+impl UndoHistory {
+    pub fn record(&mut self, board: &Bitboard) {
+        // editing right after undoing branches off the checkpoint undo restored
+        if !self.is_head_board(board) {
+            self.add(board);
+        }
+    }
+
+    /// Forgets everything, for when the board is replaced by one the edits don't apply to.
+    pub fn clear(&mut self) {
+        *self = UndoHistory::default();
+    }
+
+    /// Number of checkpoints kept.
+    pub fn checkpoint_count(&self) -> usize {
+        self.len
+    }
+
+    /// Bytes the kept boards take up packed, plus the unpacked head.
+    pub fn memory_used(&self) -> usize {
+        let packed: usize = self
+            .checkpoints
+            .iter()
+            .flatten()
+            .map(|checkpoint| checkpoint.board.packed_size())
+            .sum();
+        let head = self.head_board.as_ref().map_or(0, |board| {
+            board.width() as usize * board.height() as usize * 3
+        });
+
+        packed + head
+    }
+
+    fn undo(&mut self, board: &mut Bitboard) {
+        let Some(head) = self.head else {
+            return;
+        };
+
+        if !self.is_head_board(board) {
+            // keep the board being left so redo can come back to it
+            self.add(board);
+            *board = self.set_head(head).clone();
+        } else if let Some(parent) = self.checkpoint(head).parent {
+            self.jump(parent, board);
+        }
+    }
+
+    /// Goes back down the branch last undone along, dropping whatever the simulation did
+    /// since the undo.
+    fn redo(&mut self, board: &mut Bitboard) {
+        let Some(child) = self.head.and_then(|head| self.checkpoint(head).active) else {
+            return;
+        };
+
+        *board = self.set_head(child).clone();
+    }
+
+    /// Restores the board of checkpoint `to`, keeping the board being left if it isn't saved yet.
+    fn jump(&mut self, to: usize, board: &mut Bitboard) {
+        if self.head.is_some() && !self.is_head_board(board) {
+            self.add(board);
+        }
+
+        // redo from any checkpoint on the way back down leads here again
+        let mut node = to;
+        while let Some(parent) = self.checkpoint(node).parent {
+            self.checkpoint_mut(parent).active = Some(node);
+            node = parent;
+        }
+
+        *board = self.set_head(to).clone();
+    }
+
+    fn is_head_board(&self, board: &Bitboard) -> bool {
+        self.head_board
+            .as_ref()
+            .is_some_and(|head| head.same_cells(board))
+    }
+
+    /// Makes checkpoint `index` the head, returning its board.
+    fn set_head(&mut self, index: usize) -> &Bitboard {
+        let board = self.board(index);
+        self.head = Some(index);
+        self.head_board.insert(board)
+    }
+
+    /// Unpacks the board of checkpoint `index`, along with the ones it was packed against.
+    fn board(&self, index: usize) -> Bitboard {
+        if let (Some(head), true) = (&self.head_board, self.head == Some(index)) {
+            return head.clone();
+        }
+
+        let checkpoint = self.checkpoint(index);
+        let base = checkpoint
+            .board
+            .is_delta()
+            .then(|| self.board(checkpoint.parent.unwrap()));
+        // checkpoints are only ever packed against their parents
+        checkpoint.board.unpack(base.as_ref()).unwrap()
+    }
+
+    /// Adds a checkpoint after the head and makes it the head and the head's redo target.
+    fn add(&mut self, board: &Bitboard) {
+        if self.len == MAX_CHECKPOINTS {
+            self.forget_root();
+        }
+
+        let base = self
+            .head
+            .filter(|&head| self.checkpoint(head).chain + 1 < MAX_DELTA_CHAIN)
+            .and(self.head_board.as_ref());
+        let packed = snapshot::pack(board, base);
+        let chain = match (packed.is_delta(), self.head) {
+            (true, Some(head)) => self.checkpoint(head).chain + 1,
+            _ => 0,
+        };
+
+        let index = self.checkpoints.len();
+        self.checkpoints.push(Some(Checkpoint {
+            board: packed,
+            chain,
+            parent: self.head,
+            children: Vec::new(),
+            active: None,
+        }));
+        self.len += 1;
+
+        if let Some(head) = self.head {
+            let parent = self.checkpoint_mut(head);
+            parent.children.push(index);
+            parent.active = Some(index);
+        }
+        self.head = Some(index);
+        self.head_board = Some(board.clone());
+    }
+
+    /// Drops the oldest checkpoint along with every branch off it that doesn't lead to the head.
+    fn forget_root(&mut self) {
+        let Some(mut keep) = self.head else {
+            return;
+        };
+        while let Some(parent) = self.checkpoint(keep).parent {
+            if self.checkpoint(parent).parent.is_none() {
+                break;
+            }
+            keep = parent;
+        }
+        let Some(root) = self.checkpoint(keep).parent else {
+            return;
+        };
+
+        // the kept checkpoint becomes the root, so it can't be a difference from the old one
+        if self.checkpoint(keep).board.is_delta() {
+            let board = snapshot::pack(&self.board(keep), None);
+            let checkpoint = self.checkpoint_mut(keep);
+            checkpoint.board = board;
+            checkpoint.chain = 0;
+        }
+
+        let mut forgotten = vec![root];
+        while let Some(index) = forgotten.pop() {
+            let checkpoint = self.checkpoints[index].take().unwrap();
+            self.len -= 1;
+            forgotten.extend(
+                checkpoint
+                    .children
+                    .into_iter()
+                    .filter(|&child| child != keep),
+            );
+        }
+
+        self.checkpoint_mut(keep).parent = None;
+    }
+
+    fn checkpoint(&self, index: usize) -> &Checkpoint {
+        self.checkpoints[index].as_ref().unwrap()
+    }
+
+    fn checkpoint_mut(&mut self, index: usize) -> &mut Checkpoint {
+        self.checkpoints[index].as_mut().unwrap()
+    }
+
+    /// The checkpoints from the oldest kept one to the head, then on down the redo targets.
+    fn timeline(&self) -> Vec<usize> {
+        let Some(head) = self.head else {
+            return Vec::new();
+        };
+
+        let mut timeline = vec![head];
+        while let Some(parent) = self.checkpoint(*timeline.last().unwrap()).parent {
+            timeline.push(parent);
+        }
+        timeline.reverse();
+
+        while let Some(child) = self.checkpoint(*timeline.last().unwrap()).active {
+            timeline.push(child);
+        }
+
+        timeline
+    }
+}
+
+impl Plugin for UndoPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<UndoHistory>()
+            .add_system(undo_redo)
+            .add_system(history_panel);
+    }
+}
+
+fn undo_redo(
+    keys: Hotkeys,
+    state: Res<State<GameState>>,
+    mut history: ResMut<UndoHistory>,
+    mut board: ResMut<Board>,
+    mut restarts: EventWriter<RestartTimeline>,
+) {
+    // undoing during a scenario or match would rewind it
+    if *state.current() != GameState::Sandbox
+        || !keys.any_pressed([KeyCode::LControl, KeyCode::RControl])
+    {
+        return;
+    }
+
+    let shift = keys.any_pressed([KeyCode::LShift, KeyCode::RShift]);
+
+    if keys.just_pressed(KeyCode::Z) && !shift {
+        history.undo(&mut board);
+        restarts.send(RestartTimeline);
+    } else if keys.just_pressed(KeyCode::Y) || (keys.just_pressed(KeyCode::Z) && shift) {
+        history.redo(&mut board);
+        restarts.send(RestartTimeline);
+    }
+}
+
+/// The current line of history as a row of steps, and the branches off the current step.
+fn history_panel(
+    state: Res<State<GameState>>,
+    mut history: ResMut<UndoHistory>,
+    mut board: ResMut<Board>,
+    mut restarts: EventWriter<RestartTimeline>,
+    strings: Res<Strings>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if *state.current() != GameState::Sandbox || history.head.is_none() {
+        return;
+    }
+
+    egui::Window::new(strings.get("history.title"))
+        .id(egui::Id::new("history"))
+        .resizable(false)
+        .anchor(egui::Align2::RIGHT_CENTER, [-8.0, 0.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let timeline = history.timeline();
+            let head = history.head.unwrap();
+            let mut jump = None;
+
+            ui.horizontal_wrapped(|ui| {
+                for (step, &index) in timeline.iter().enumerate() {
+                    // steps with other branches off them are starred
+                    let branched = history.checkpoint(index).children.len() > 1;
+                    let text = format!("{}{}", step + 1, if branched { "*" } else { "" });
+
+                    if ui.selectable_label(index == head, text).clicked() && index != head {
+                        jump = Some(index);
+                    }
+                }
+            });
+
+            let branches = &history.checkpoint(head).children;
+            if branches.len() > 1 {
+                ui.horizontal(|ui| {
+                    ui.label(strings.get("history.branches"));
+                    for (branch, &child) in branches.iter().enumerate() {
+                        let active = history.checkpoint(head).active == Some(child);
+                        if ui
+                            .selectable_label(active, (branch + 1).to_string())
+                            .clicked()
+                        {
+                            jump = Some(child);
+                        }
+                    }
+                });
+            }
+
+            ui.horizontal(|ui| {
+                if ui.button(strings.get("history.undo")).clicked() {
+                    history.undo(&mut board);
+                    restarts.send(RestartTimeline);
+                }
+                if ui.button(strings.get("history.redo")).clicked() {
+                    history.redo(&mut board);
+                    restarts.send(RestartTimeline);
+                }
+            });
+
+            if let Some(index) = jump {
+                history.jump(index, &mut board);
+                restarts.send(RestartTimeline);
+            }
+        });
+}
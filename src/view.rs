@@ -0,0 +1,96 @@
+//! A read-only look at a board for analysing simulations from other crates. What's here
+//! is kept stable: it only grows, and hashes never change between versions.
+
+use crate::bitboard::Bitboard;
+use crate::extra::CellExtra;
+use crate::simulation::Simulation;
+
+/// Read-only statistics over a board, e.g. `BoardView::new(&board).population(2)`.
+#[derive(Clone, Copy, Debug)]
+pub struct BoardView<'a> {
+    board: &'a Bitboard,
+}
+
+/// A live cell, as `BoardView::iter_live` yields them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct LiveCell {
+    pub x: u32,
+    pub y: u32,
+    pub team: u8,
+}
+
+impl<'a> BoardView<'a> {
+    pub fn new(board: &'a Bitboard) -> Self {
+        Self { board }
+    }
+
+    /// The board being looked at, for anything this doesn't cover.
+    pub fn board(&self) -> &'a Bitboard {
+        self.board
+    }
+
+    pub fn width(&self) -> u32 {
+        self.board.width()
+    }
+
+    pub fn height(&self) -> u32 {
+        self.board.height()
+    }
+
+    /// Every live cell, row by row from the bottom left.
+    pub fn iter_live(&self) -> impl Iterator<Item = LiveCell> + 'a {
+        let board = self.board;
+
+        (0..board.height()).flat_map(move |y| {
+            (0..board.width()).filter_map(move |x| {
+                let team = board.get(x, y);
+                (team != 0).then_some(LiveCell { x, y, team })
+            })
+        })
+    }
+
+    /// Number of live cells on `team`. Cells on neither team are team 1.
+    pub fn population(&self, team: u8) -> u32 {
+        self.board.team_population(team)
+    }
+
+    /// Number of live cells on any team.
+    pub fn total_population(&self) -> u32 {
+        self.board.population()
+    }
+
+    /// The smallest (left, bottom, width, height) block holding every live cell, or `None`
+    /// if nothing is alive. It doesn't account for cells wrapping around the edges.
+    pub fn bounding_box(&self) -> Option<(u32, u32, u32, u32)> {
+        let (mut min, mut max) = ((u32::MAX, u32::MAX), (0, 0));
+        let mut any = false;
+
+        for LiveCell { x, y, .. } in self.iter_live() {
+            min = (min.0.min(x), min.1.min(y));
+            max = (max.0.max(x), max.1.max(y));
+            any = true;
+        }
+
+        any.then(|| (min.0, min.1, max.0 - min.0 + 1, max.1 - min.1 + 1))
+    }
+
+    /// A hash of the board's size, cells, teams and walls, the same as
+    /// `Bitboard::cell_hash`. It never changes between versions, so it can be written
+    /// down to spot when a run repeats or differs from an earlier one.
+    pub fn hash(&self) -> u64 {
+        self.board.cell_hash()
+    }
+}
+
+impl<'a> From<&'a Bitboard> for BoardView<'a> {
+    fn from(board: &'a Bitboard) -> Self {
+        BoardView::new(board)
+    }
+}
+
+impl<E: CellExtra> Simulation<E> {
+    /// The simulation's board as it stands.
+    pub fn view(&self) -> BoardView<'_> {
+        BoardView::new(&self.board)
+    }
+}
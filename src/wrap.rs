@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+
+use crate::bitboard::Boundary;
+use crate::compare::board_texel;
+use crate::fog::Fog;
+use crate::hotkeys::Hotkeys;
+use crate::overlay::{board_overlay_image, place_over_board};
+use crate::picture::overlay_layer;
+use crate::rules::{Neighborhood, RuleSet};
+use crate::{Board, CELL_SIZE};
+
+/// How many rows and columns of the opposite edges are previewed past each edge.
+const PREVIEW_DEPTH: u32 = 8;
+/// How opaque the previewed cells are, out of 255.
+const PREVIEW_ALPHA: u8 = 90;
+
+/// W toggles faded copies of the opposite edges drawn just outside a toroidal board, so
+/// you can see what a glider is about to wrap into.
+pub struct WrapPreviewPlugin;
+
+#[derive(Resource, Default)]
+struct WrapPreview {
+    enabled: bool,
+}
+
+#[derive(Component)]
+struct PreviewSprite;
+
+impl Plugin for WrapPreviewPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<WrapPreview>()
+            .add_startup_system(spawn_preview_sprite)
+            .add_system(toggle_preview)
+            .add_system(draw_preview.after(toggle_preview));
+    }
+}
+
+fn spawn_preview_sprite(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        PreviewSprite,
+        overlay_layer(),
+    ));
+}
+
+fn toggle_preview(keys: Hotkeys, mut preview: ResMut<WrapPreview>) {
+    if keys.just_pressed(KeyCode::W) {
+        preview.enabled = !preview.enabled;
+    }
+}
+
+/// Redraws the ring of previewed cells around the board whenever the board changes.
+fn draw_preview(
+    (board, rule, fog): (Res<Board>, Res<RuleSet>, Res<Fog>),
+    preview: Res<WrapPreview>,
+    mut images: ResMut<Assets<Image>>,
+    mut sprite_query: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<PreviewSprite>,
+    >,
+) {
+    let Ok((mut handle, mut sprite, mut transform, mut visibility)) = sprite_query.get_single_mut()
+    else {
+        return;
+    };
+
+    // the texture is a square grid, so hex boards aren't previewed
+    let shown = preview.enabled
+        && board.boundary() == Boundary::Toroidal
+        && rule.neighborhood == Neighborhood::Moore;
+
+    if visibility.is_visible != shown {
+        visibility.is_visible = shown;
+    }
+    if !shown || !(board.is_changed() || preview.is_changed() || fog.is_changed()) {
+        return;
+    }
+
+    let (width, height) = (board.width(), board.height());
+    let depth = PREVIEW_DEPTH.min(width).min(height);
+    let (outer_width, outer_height) = (width + depth * 2, height + depth * 2);
+    let image = board_overlay_image(outer_width, outer_height, |outer_x, outer_y| {
+        let inside_y = (depth..depth + height).contains(&outer_y);
+        if inside_y && (depth..depth + width).contains(&outer_x) {
+            return None;
+        }
+
+        let x = (outer_x + width - depth) % width;
+        let y = (outer_y + height - depth) % height;
+        if !fog.is_visible(x, y) {
+            return None;
+        }
+
+        let [r, g, b, a] = board_texel(&board, x, y);
+        (a != 0).then_some([r, g, b, PREVIEW_ALPHA])
+    });
+
+    match images.get_mut(&handle) {
+        Some(existing) => *existing = image,
+        None => *handle = images.add(image),
+    }
+
+    // centered on the board like the zoomed-out board sprite, the ring falling outside it
+    place_over_board((width, height), &mut sprite, &mut transform, 0.5);
+    sprite.custom_size = Some(Vec2::new(outer_width as f32, outer_height as f32) * CELL_SIZE);
+}
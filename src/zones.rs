@@ -0,0 +1,272 @@
+use bevy::prelude::*;
+use bevy_ecs_tilemap::prelude::*;
+use bevy_egui::{egui, EguiContext};
+
+use crate::bitboard::Bitboard;
+use crate::lang::Strings;
+use crate::overlay::{board_overlay_image, place_over_board};
+use crate::rules::{apply_rule_at, Neighborhood, RuleSet};
+use crate::simulation::StepOverride;
+use crate::tools::{BoardCursor, Tool};
+use crate::Board;
+
+/// The background tint of each zone, faint enough for the cells to stand out over it.
+const ZONE_TINTS: [[u8; 4]; 4] = [
+    [90, 130, 220, 45],
+    [220, 90, 90, 45],
+    [90, 200, 120, 45],
+    [210, 180, 70, 45],
+];
+
+/// Zones painted over the board with the zone tool, each running under a rule of its own.
+/// Cells go by the rule of the zone they're in whatever their neighbors' zones, and cells
+/// outside every zone, or in a zone without a rule, go by the board's rule.
+pub struct ZonesPlugin;
+
+/// A zone's rule, `None` while it goes by the board's.
+#[derive(Default)]
+struct Zone {
+    rule: Option<RuleSet>,
+    rule_text: String,
+}
+
+/// The zone of each cell, indexed like `Bitboard::get`, with 0 for none and each zone's
+/// number one past its index. Cleared whenever the board changes size or is replaced,
+/// though the zones keep their rules.
+#[derive(Resource, Default)]
+pub struct Zones {
+    size: (u32, u32),
+    cells: Vec<u8>,
+    /// Cells in a zone, so stepping can skip the zones when there are none.
+    marked: usize,
+    zones: [Zone; ZONE_TINTS.len()],
+}
+
+impl Zones {
+    pub fn is_empty(&self) -> bool {
+        self.marked == 0
+    }
+
+    fn get(&self, x: u32, y: u32) -> u8 {
+        if x >= self.size.0 || y >= self.size.1 {
+            return 0;
+        }
+        self.cells[(y * self.size.0 + x) as usize]
+    }
+
+    fn set(&mut self, x: u32, y: u32, zone: u8) {
+        let cell = &mut self.cells[(y * self.size.0 + x) as usize];
+        self.marked = self.marked + (zone != 0) as usize - (*cell != 0) as usize;
+        *cell = zone;
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(0);
+        self.marked = 0;
+    }
+
+    /// The rule cell (x, y) goes by instead of `rule`, if its zone has one it can use.
+    fn rule_at(&self, x: u32, y: u32, rule: &RuleSet) -> Option<&RuleSet> {
+        let zone = self.get(x, y).checked_sub(1)?;
+        let own = self.zones[zone as usize].rule.as_ref()?;
+        // neighbors are counted the same way across the whole board
+        (own.neighborhood == rule.neighborhood).then_some(own)
+    }
+}
+
+impl StepOverride for Zones {
+    fn is_active(&self) -> bool {
+        !self.is_empty()
+    }
+
+    /// Redoes the births and survivals in zones with a rule of their own for the step that
+    /// took `before` to `board` under `rule`.
+    fn apply(&self, before: &Bitboard, board: &mut Bitboard, rule: &RuleSet) {
+        if self.is_empty() || self.size != (board.width(), board.height()) {
+            return;
+        }
+
+        for y in 0..board.height() {
+            for x in 0..board.width() {
+                if let Some(own) = self.rule_at(x, y, rule) {
+                    apply_rule_at(before, board, x, y, own);
+                }
+            }
+        }
+    }
+}
+
+/// Zone the zone tool paints, 0 erasing zones.
+#[derive(Resource)]
+struct ZoneBrush(u8);
+
+impl Default for ZoneBrush {
+    fn default() -> Self {
+        Self(1)
+    }
+}
+
+#[derive(Component)]
+struct ZoneSprite;
+
+impl Plugin for ZonesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Zones>()
+            .init_resource::<ZoneBrush>()
+            .add_startup_system(spawn_zone_sprite)
+            .add_system(fit_zones)
+            .add_system(paint_zones.after(fit_zones))
+            .add_system(zones_panel.after(paint_zones))
+            .add_system(draw_zones.after(zones_panel));
+    }
+}
+
+fn spawn_zone_sprite(mut commands: Commands) {
+    commands.spawn((
+        SpriteBundle {
+            visibility: Visibility { is_visible: false },
+            ..default()
+        },
+        ZoneSprite,
+    ));
+}
+
+/// Starts the zones over when the board changes size.
+fn fit_zones(board: Res<Board>, mut zones: ResMut<Zones>) {
+    let size = (board.width(), board.height());
+    if zones.size != size {
+        zones.size = size;
+        zones.cells = vec![0; size.0 as usize * size.1 as usize];
+        zones.marked = 0;
+    }
+}
+
+/// Holding the left button paints the brush's zone over every tile the cursor passes.
+fn paint_zones(
+    mouse: Res<Input<MouseButton>>,
+    (tool, brush): (Res<Tool>, Res<ZoneBrush>),
+    cursor: BoardCursor,
+    mut zones: ResMut<Zones>,
+) {
+    if *tool != Tool::Zone || !mouse.pressed(MouseButton::Left) {
+        return;
+    }
+
+    let Some(TilePos { x, y }) = cursor.tile_pos() else {
+        return;
+    };
+
+    if zones.get(x, y) != brush.0 {
+        zones.set(x, y, brush.0);
+    }
+}
+
+fn zones_panel(
+    (tool, rule, strings): (Res<Tool>, Res<RuleSet>, Res<Strings>),
+    mut brush: ResMut<ZoneBrush>,
+    mut zones: ResMut<Zones>,
+    mut egui_context: ResMut<EguiContext>,
+) {
+    if *tool != Tool::Zone {
+        return;
+    }
+
+    egui::Window::new(strings.get("zones.title"))
+        .id(egui::Id::new("zones"))
+        .resizable(false)
+        .anchor(egui::Align2::LEFT_BOTTOM, [8.0, -8.0])
+        .show(egui_context.ctx_mut(), |ui| {
+            let mut error = None;
+
+            for (i, &[r, g, b, _]) in ZONE_TINTS.iter().enumerate() {
+                let number = i as u8 + 1;
+
+                ui.horizontal(|ui| {
+                    let label =
+                        egui::RichText::new(strings.format("zones.zone", &[("zone", &number)]))
+                            .color(egui::Color32::from_rgb(r, g, b));
+                    ui.selectable_value(&mut brush.0, number, label);
+
+                    // edited in place, so only the zone's text reads as changed
+                    let zone = &mut zones.bypass_change_detection().zones[i];
+                    ui.text_edit_singleline(&mut zone.rule_text);
+
+                    if ui.button(strings.get("zones.apply")).clicked() {
+                        match RuleSet::parse(&zone.rule_text) {
+                            Ok(parsed) => zone.rule = Some(parsed),
+                            Err(message) => error = Some(message),
+                        }
+                    }
+                    if zone.rule.is_some() && ui.button(strings.get("zones.clear")).clicked() {
+                        zone.rule = None;
+                    }
+                });
+
+                match zones.zones[i].rule {
+                    Some(own) if own.neighborhood != rule.neighborhood => {
+                        ui.label(strings.get("zones.mismatch"));
+                    }
+                    Some(own) => {
+                        ui.label(strings.format("zones.runs", &[("rule", &own)]));
+                    }
+                    None => {}
+                }
+            }
+
+            ui.horizontal(|ui| {
+                ui.selectable_value(&mut brush.0, 0, strings.get("zones.erase"));
+                ui.label(strings.format("zones.marked", &[("cells", &zones.marked)]));
+                if ui.button(strings.get("zones.clear-all")).clicked() && !zones.is_empty() {
+                    zones.clear();
+                }
+            });
+
+            if let Some(error) = error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            ui.label(strings.get("zones.help"));
+        });
+}
+
+/// Redraws the zone tints under the board whenever the zones change.
+fn draw_zones(
+    (zones, rule): (Res<Zones>, Res<RuleSet>),
+    mut images: ResMut<Assets<Image>>,
+    mut sprite_query: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            &mut Transform,
+            &mut Visibility,
+        ),
+        With<ZoneSprite>,
+    >,
+) {
+    let Ok((mut handle, mut sprite, mut transform, mut visibility)) = sprite_query.get_single_mut()
+    else {
+        return;
+    };
+
+    // the texture is a square grid, so it's only drawn under square boards
+    let shown = !zones.is_empty() && rule.neighborhood == Neighborhood::Moore;
+    if visibility.is_visible != shown {
+        visibility.is_visible = shown;
+    }
+    if !shown || !(zones.is_changed() || rule.is_changed()) {
+        return;
+    }
+
+    let (width, height) = zones.size;
+    let image = board_overlay_image(width, height, |x, y| {
+        let zone = zones.get(x, y).checked_sub(1)?;
+        Some(ZONE_TINTS[zone as usize])
+    });
+
+    match images.get_mut(&handle) {
+        Some(existing) => *existing = image,
+        None => *handle = images.add(image),
+    }
+
+    // over the terrain but still under the tiles
+    place_over_board((width, height), &mut sprite, &mut transform, -0.04);
+}
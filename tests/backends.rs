@@ -0,0 +1,35 @@
+//! Every stepping backend has to agree with the naive reference on random boards.
+
+use conway_bevy::rng::Rng;
+use conway_bevy::rules::Neighborhood;
+use conway_bevy::testing::{check_backends, random_board, random_boundary, random_rule};
+
+/// Runs `cases` random boards under random rules for `neighborhood`.
+fn backends_agree(seed: u64, neighborhood: Neighborhood, cases: u32) {
+    let mut rng = Rng::new(seed);
+
+    for case in 0..cases {
+        // widths on both sides of the 64 cell word boundary
+        let size = (
+            1 + rng.next_u64() as u32 % 140,
+            1 + rng.next_u64() as u32 % 40,
+        );
+        let boundary = random_boundary(&mut rng);
+        let board = random_board(&mut rng, size, boundary);
+        let rule = random_rule(&mut rng, neighborhood);
+
+        if let Err(mismatch) = check_backends(&board, &rule, 12) {
+            panic!("case {case}: {mismatch} on a {size:?} {boundary:?} board under {rule}");
+        }
+    }
+}
+
+#[test]
+fn backends_agree_on_moore_rules() {
+    backends_agree(1, Neighborhood::Moore, 64);
+}
+
+#[test]
+fn backends_agree_on_hex_rules() {
+    backends_agree(2, Neighborhood::Hex, 64);
+}
@@ -0,0 +1,40 @@
+//! Boards run under two rules side by side, as the divergence report does.
+
+use conway_bevy::bitboard::Bitboard;
+use conway_bevy::patterns;
+use conway_bevy::rules::RuleSet;
+use conway_bevy::simulation::diverge;
+
+#[test]
+fn glider_runs_the_same_under_highlife() {
+    let mut board = Bitboard::new(32, 32);
+    patterns::find("glider")
+        .unwrap()
+        .stamp(&mut board, (8, 20), (false, false), 2);
+    let highlife = RuleSet::parse("B36/S23").unwrap();
+
+    let divergence = diverge(&board, (RuleSet::CONWAY, highlife), 40);
+
+    assert_eq!(divergence.first_difference, None);
+    assert_eq!(divergence.differing, 0);
+    assert_eq!(divergence.populations.len(), 41);
+    assert!(divergence
+        .populations
+        .iter()
+        .all(|&counts| counts == (5, 5)));
+}
+
+#[test]
+fn block_dies_at_once_without_survival_on_three() {
+    let mut board = Bitboard::new(8, 8);
+    for (x, y) in [(3, 3), (4, 3), (3, 4), (4, 4)] {
+        board.set(x, y, 2);
+    }
+    let no_three = RuleSet::parse("B3/S2").unwrap();
+
+    let divergence = diverge(&board, (RuleSet::CONWAY, no_three), 5);
+
+    assert_eq!(divergence.first_difference, Some(1));
+    assert_eq!(divergence.populations[..2], [(4, 4), (4, 0)]);
+    assert_eq!(divergence.differing, 4);
+}
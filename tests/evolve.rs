@@ -0,0 +1,61 @@
+//! The genetic search scores patterns the way they run, and never loses its best.
+
+use conway_bevy::evolve::{fitness, Evolution, EvolveConfig, Objective};
+
+/// A seed box of `size` with the given cells alive, (x, y) with y from the bottom.
+fn seed(size: u32, live: &[(u32, u32)]) -> Vec<bool> {
+    let mut cells = vec![false; (size * size) as usize];
+    for &(x, y) in live {
+        cells[(y * size + x) as usize] = true;
+    }
+    cells
+}
+
+#[test]
+fn objectives_score_known_patterns() {
+    let block = seed(8, &[(3, 3), (4, 3), (3, 4), (4, 4)]);
+    let blinker = seed(8, &[(2, 4), (3, 4), (4, 4)]);
+    let glider = seed(8, &[(1, 2), (2, 2), (3, 2), (3, 3), (2, 4)]);
+    let config = |objective| EvolveConfig {
+        objective,
+        ..EvolveConfig::default()
+    };
+
+    let population = config(Objective::Population { generation: 10 });
+    assert_eq!(fitness(&population, &block), 4.0);
+    assert_eq!(fitness(&population, &glider), 5.0);
+
+    // settled the first generation it matches one of the two before it
+    let lifespan = config(Objective::Lifespan { limit: 100 });
+    assert_eq!(fitness(&lifespan, &block), 1.0);
+    assert_eq!(fitness(&lifespan, &blinker), 2.0);
+    assert_eq!(fitness(&lifespan, &seed(8, &[(3, 3)])), 1.0);
+
+    // the glider heads right until it runs into the bottom edge, the block stays put
+    let far_edge = config(Objective::FarEdge { limit: 400 });
+    assert_eq!(fitness(&far_edge, &block), 4.0);
+    assert!(fitness(&far_edge, &glider) > 20.0);
+}
+
+#[test]
+fn breeding_keeps_the_best_and_is_reproducible() {
+    let config = EvolveConfig {
+        objective: Objective::Population { generation: 30 },
+        population: 12,
+        ..EvolveConfig::default()
+    };
+    let mut evolution = Evolution::new(config.clone(), 5);
+    let mut again = Evolution::new(config, 5);
+
+    let mut best = evolution.best().fitness;
+    for _ in 0..4 {
+        evolution.step();
+        again.step();
+        assert!(evolution.best().fitness >= best);
+        best = evolution.best().fitness;
+    }
+
+    assert_eq!(evolution.generation, 4);
+    assert_eq!(evolution.candidates().len(), 12);
+    assert_eq!(evolution.candidates(), again.candidates());
+}
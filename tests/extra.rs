@@ -0,0 +1,56 @@
+//! Per-cell payloads riding along with the board as it steps.
+
+use conway_bevy::extra::CellExtra;
+use conway_bevy::rules::RuleSet;
+use conway_bevy::simulation::Simulation;
+use conway_bevy::testing::board_with;
+
+/// Generations the cell's current team has held it.
+#[derive(Clone, Debug, Default)]
+struct Held(u32);
+
+impl CellExtra for Held {
+    fn update(&mut self, before: u8, after: u8) {
+        self.0 = if after != 0 && after == before {
+            self.0 + 1
+        } else {
+            0
+        };
+    }
+}
+
+/// Ground nothing can be born on.
+#[derive(Clone, Debug, Default)]
+struct Barren(bool);
+
+impl CellExtra for Barren {
+    fn allows_birth(&self, _team: u8) -> bool {
+        !self.0
+    }
+}
+
+#[test]
+fn blinker_center_is_held_while_its_ends_flip() {
+    // a horizontal blinker over x 4..7 on row 5
+    let mut sim: Simulation<Held> =
+        Simulation::with_extra(board_with("blinker", 12, (4, 5)), RuleSet::CONWAY);
+    sim.step_n(6);
+
+    assert_eq!(sim.extra.get(5, 5).0, 6);
+    assert_eq!(sim.extra.get(4, 5).0, 0);
+    assert_eq!(sim.extra.get(0, 0).0, 0);
+}
+
+#[test]
+fn barren_cells_refuse_births() {
+    let mut sim: Simulation<Barren> =
+        Simulation::with_extra(board_with("blinker", 12, (4, 5)), RuleSet::CONWAY);
+    sim.extra.get_mut(5, 6).0 = true;
+
+    // the blinker can't turn upright, and the center left alone dies next step
+    sim.step();
+    assert_eq!(sim.board.get(5, 6), 0);
+    assert_eq!(sim.board.population(), 2);
+    sim.step();
+    assert_eq!(sim.board.population(), 0);
+}
@@ -0,0 +1,102 @@
+//! Known patterns run for a known number of generations, to check that changes to the
+//! stepping code don't change how boards evolve.
+
+use conway_bevy::bitboard::{Bitboard, Boundary};
+use conway_bevy::patterns;
+use conway_bevy::rules::RuleSet;
+use conway_bevy::simulation::Simulation;
+use conway_bevy::testing::board_with;
+
+#[test]
+fn glider_moves_one_cell_diagonally_every_four_generations() {
+    let mut sim = Simulation::new(board_with("glider", 32, (8, 20)), RuleSet::CONWAY);
+
+    for period in 1..=4 {
+        sim.step_n(4);
+
+        // right and down the screen, which is down the board since its y points up
+        let expected = board_with("glider", 32, (8 + period, 20 - period));
+        assert!(sim.board.same_cells(&expected), "after {period} periods");
+        assert_eq!(sim.board.cell_hash(), expected.cell_hash());
+    }
+    assert_eq!(sim.generation, 16);
+}
+
+#[test]
+fn glider_wraps_around_a_torus() {
+    let mut board = board_with("glider", 16, (4, 4));
+    board.set_boundary(Boundary::Toroidal);
+    let start = board.clone();
+
+    // 16 periods of 4 generations take it all the way around both ways
+    let mut sim = Simulation::new(board, RuleSet::CONWAY);
+    sim.step_n(64);
+
+    assert!(sim.board.same_cells(&start));
+}
+
+#[test]
+fn r_pentomino_stabilizes_at_116_cells_in_generation_1103() {
+    // big enough that its six gliders are still in open space by then
+    let size = 1024;
+    let mut board = Bitboard::new(size, size);
+    patterns::find("r-pentomino")
+        .unwrap()
+        .stamp_centered(&mut board, 2);
+
+    let mut sim = Simulation::new(board, RuleSet::CONWAY);
+    sim.step_n(1102);
+    assert_ne!(sim.board.population(), 116);
+
+    sim.step();
+    assert_eq!(sim.board.population(), 116);
+}
+
+#[test]
+fn hash_tells_boards_apart() {
+    let board = board_with("glider", 32, (8, 8));
+    let moved = board_with("glider", 32, (9, 8));
+    let mut walled = board.clone();
+    walled.set_wall(0, 0, true);
+
+    assert_eq!(board.cell_hash(), board.clone().cell_hash());
+    assert_ne!(board.cell_hash(), moved.cell_hash());
+    assert_ne!(board.cell_hash(), walled.cell_hash());
+    assert_ne!(board.cell_hash(), Bitboard::new(32, 32).cell_hash());
+}
+
+#[test]
+fn guns_fire_along_every_diagonal_they_are_aimed_at() {
+    for gun in patterns::GUNS {
+        for heading in [(1, 1), (1, -1), (-1, 1), (-1, -1)] {
+            let flips = gun.aim(heading);
+            let mut board = Bitboard::new(160, 160);
+            gun.pattern().stamp(&mut board, (62, 75), flips, 2);
+            let (exit_x, exit_y) = gun.exit_flipped(flips);
+            let exit = (62 + exit_x, 75 + exit_y);
+
+            let mut sim = Simulation::new(board, RuleSet::CONWAY);
+            sim.step_n(gun.period as u64 * 4);
+
+            // four gliders out, all on the exit's diagonal and ahead of it
+            let (width, height) = (gun.pattern().width() as i64, gun.pattern().height() as i64);
+            let mut outside = 0;
+            for y in 0..160 {
+                for x in 0..160 {
+                    let (cx, cy) = (x as i64, y as i64);
+                    if sim.board.get(x, y) == 0
+                        || (62..62 + width).contains(&cx) && (75..75 + height).contains(&cy)
+                    {
+                        continue;
+                    }
+                    outside += 1;
+
+                    let (along_x, along_y) = ((cx - exit.0) * heading.0, (cy - exit.1) * heading.1);
+                    assert!(along_x >= -2 && along_y >= -2, "{heading:?}: ({x}, {y})");
+                    assert!((along_x - along_y).abs() <= 3, "{heading:?}: ({x}, {y})");
+                }
+            }
+            assert_eq!(outside, 20, "{heading:?}");
+        }
+    }
+}
@@ -0,0 +1,57 @@
+//! Two boards stacked on each other and coupled after every step.
+
+use conway_bevy::bitboard::Bitboard;
+use conway_bevy::layers::{Coupling, LayeredSimulation};
+use conway_bevy::rules::RuleSet;
+use conway_bevy::simulation::Simulation;
+
+fn layered(lower: Bitboard, upper: Bitboard, coupling: Coupling) -> LayeredSimulation {
+    LayeredSimulation {
+        lower: Simulation::new(lower, RuleSet::CONWAY),
+        upper: Simulation::new(upper, RuleSet::CONWAY),
+        coupling,
+    }
+}
+
+/// A still life two cells across with its bottom left corner at `(left, bottom)`.
+fn block_at(size: u32, (left, bottom): (u32, u32)) -> Bitboard {
+    let mut board = Bitboard::new(size, size);
+    for (x, y) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        board.set(left + x, bottom + y, 2);
+    }
+    board
+}
+
+#[test]
+fn predators_starve_over_an_empty_board_and_eat_a_full_one() {
+    let mut sim = layered(
+        Bitboard::new(12, 12),
+        block_at(12, (5, 5)),
+        Coupling::Predation,
+    );
+    sim.step();
+    assert_eq!(sim.upper.board.population(), 0);
+
+    let mut sim = layered(
+        block_at(12, (5, 5)),
+        block_at(12, (5, 5)),
+        Coupling::Predation,
+    );
+    sim.step();
+    assert_eq!(sim.upper.board.population(), 4);
+    assert_eq!(sim.lower.board.population(), 0);
+}
+
+#[test]
+fn sheltered_cells_outlive_their_rule() {
+    let mut lower = Bitboard::new(12, 12);
+    lower.set(2, 2, 2);
+
+    let mut sim = layered(lower.clone(), block_at(12, (2, 2)), Coupling::Shelter);
+    sim.step_n(4);
+    assert_eq!(sim.lower.board.get(2, 2), 2);
+
+    let mut sim = layered(lower, block_at(12, (2, 2)), Coupling::None);
+    sim.step();
+    assert_eq!(sim.lower.board.population(), 0);
+}
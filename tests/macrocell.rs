@@ -0,0 +1,31 @@
+//! Macrocell reads back what it writes, and deep trees over nothing load at once.
+
+use conway_bevy::macrocell;
+use conway_bevy::rules::RuleSet;
+use conway_bevy::testing::board_with;
+
+#[test]
+fn a_glider_reads_back_as_written() {
+    let board = board_with("glider", 16, (4, 4));
+    let text = macrocell::encode(&board, (0, 0, 16, 16), &RuleSet::CONWAY);
+    let clip = macrocell::decode(&text).unwrap();
+    assert_eq!((clip.width, clip.height, clip.cells.len()), (3, 3, 5));
+}
+
+#[test]
+fn deep_empty_chains_are_rejected_without_walking_them() {
+    // every branch points all four children at the one below, a 2^32 square of nothing
+    let states = std::iter::once("1 0 0 0 0".to_owned()).chain((2..=32).map(|level| {
+        let child = level - 1;
+        format!("{level} {child} {child} {child} {child}")
+    }));
+    let leaves = std::iter::once("$".to_owned()).chain((4..=32).map(|level| {
+        let child = level - 3;
+        format!("{level} {child} {child} {child} {child}")
+    }));
+
+    for lines in [states.collect::<Vec<_>>(), leaves.collect()] {
+        let text = format!("[M2] (golly 4.2)\n#R B3/S23\n{}\n", lines.join("\n"));
+        assert!(macrocell::decode(&text).is_err());
+    }
+}
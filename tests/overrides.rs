@@ -0,0 +1,49 @@
+//! Overrides redoing part of each step, like the game's team rules, zones and terrain.
+
+use conway_bevy::bitboard::Bitboard;
+use conway_bevy::rules::RuleSet;
+use conway_bevy::simulation::{Simulation, StepOverride};
+use conway_bevy::testing::board_with;
+
+/// Births team 2 on each of its cells every step, whatever the rule says.
+#[derive(Default)]
+struct Sown(Vec<(u32, u32)>);
+
+impl StepOverride for Sown {
+    fn is_active(&self) -> bool {
+        !self.0.is_empty()
+    }
+
+    fn apply(&self, _before: &Bitboard, board: &mut Bitboard, _rule: &RuleSet) {
+        for &(x, y) in &self.0 {
+            board.set(x, y, 2);
+        }
+    }
+}
+
+#[test]
+fn defaulted_overrides_step_like_a_plain_step() {
+    let board = board_with("glider", 16, (4, 4));
+    let mut plain = Simulation::new(board.clone(), RuleSet::CONWAY);
+    let mut overridden = Simulation::new(board, RuleSet::CONWAY);
+    let sown = Sown::default();
+
+    for _ in 0..8 {
+        plain.step();
+        overridden.step_with(&[&sown]);
+    }
+    assert!(overridden.board.same_cells(&plain.board));
+    assert_eq!(overridden.generation, 8);
+}
+
+#[test]
+fn active_overrides_redo_their_cells() {
+    let mut sim = Simulation::new(board_with("blinker", 12, (4, 5)), RuleSet::CONWAY);
+    let sown = Sown(vec![(10, 10)]);
+
+    sim.step_with(&[&sown]);
+    assert_eq!(sim.board.get(10, 10), 2);
+    // the rest of the board still went by the rule, turning the blinker upright
+    assert_eq!(sim.board.get(4, 5), 0);
+    assert_ne!(sim.board.get(5, 6), 0);
+}
@@ -0,0 +1,93 @@
+//! Predecessor searches checked against trying every board there is on tiny boards.
+
+use conway_bevy::bitboard::Bitboard;
+use conway_bevy::predecessor::{find_predecessor, Search};
+use conway_bevy::rules::RuleSet;
+
+fn board_from_bits(size: u32, bits: u32) -> Bitboard {
+    let mut board = Bitboard::new(size, size);
+    for i in 0..size * size {
+        if bits & 1 << i != 0 {
+            board.set(i % size, i / size, 2);
+        }
+    }
+    board
+}
+
+fn bits_of(board: &Bitboard) -> u32 {
+    let size = board.width();
+    (0..size * size)
+        .filter(|&i| board.get(i % size, i / size) != 0)
+        .fold(0, |bits, i| bits | 1 << i)
+}
+
+#[test]
+fn blinker_steps_back_into_its_other_phase() {
+    let mut blinker = Bitboard::new(7, 7);
+    for x in 2..5 {
+        blinker.set(x, 3, 2);
+    }
+
+    let Search::Found(predecessor) = find_predecessor(&blinker, &RuleSet::CONWAY, 1_000_000) else {
+        panic!("the blinker has a predecessor");
+    };
+
+    let mut stepped = predecessor.clone();
+    stepped.step(&RuleSet::CONWAY);
+    assert!(stepped.same_cells(&blinker));
+}
+
+/// The cells within one cell of the live cells' bounding box, like the search's bounds.
+fn bounds_of(size: u32, bits: u32) -> u32 {
+    let live: Vec<(u32, u32)> = (0..size * size)
+        .filter(|&i| bits & 1 << i != 0)
+        .map(|i| (i % size, i / size))
+        .collect();
+    let (Some(left), Some(right)) = (
+        live.iter().map(|c| c.0).min(),
+        live.iter().map(|c| c.0).max(),
+    ) else {
+        return 0;
+    };
+    let (bottom, top) = (
+        live.iter().map(|c| c.1).min().unwrap(),
+        live.iter().map(|c| c.1).max().unwrap(),
+    );
+
+    (0..size * size)
+        .filter(|&i| {
+            let (x, y) = (i % size, i / size);
+            x + 1 >= left && x <= right + 1 && y + 1 >= bottom && y <= top + 1
+        })
+        .fold(0, |mask, i| mask | 1 << i)
+}
+
+#[test]
+fn agrees_with_every_3x3_board() {
+    // what every 3x3 board steps into
+    let next: Vec<u32> = (0..1 << 9)
+        .map(|bits| {
+            let mut board = board_from_bits(3, bits);
+            board.step(&RuleSet::CONWAY);
+            bits_of(&board)
+        })
+        .collect();
+
+    for bits in 0..1 << 9 {
+        let target = board_from_bits(3, bits);
+        let bounds = bounds_of(3, bits);
+        let reachable =
+            (0..1 << 9).any(|before: u32| before & !bounds == 0 && next[before as usize] == bits);
+
+        match find_predecessor(&target, &RuleSet::CONWAY, u64::MAX) {
+            Search::Found(predecessor) => {
+                assert_eq!(bits_of(&predecessor) & !bounds, 0);
+                let mut stepped = predecessor;
+                stepped.step(&RuleSet::CONWAY);
+                assert_eq!(bits_of(&stepped), bits);
+            }
+            Search::NoneWithinBounds => assert!(!reachable, "{bits:09b}"),
+            Search::GaveUp => panic!("an unlimited search gave up"),
+        }
+    }
+}
@@ -0,0 +1,84 @@
+//! Pictures turn into boards the way they look: dark cells alive, the right way up.
+
+use conway_bevy::raster::{Fit, Raster, RasterOptions};
+
+const BLACK: [u8; 4] = [0, 0, 0, 255];
+const WHITE: [u8; 4] = [255, 255, 255, 255];
+const RED: [u8; 4] = [230, 20, 20, 255];
+const BLUE: [u8; 4] = [20, 20, 230, 255];
+
+fn raster(width: u32, height: u32, pixel: impl Fn(u32, u32) -> [u8; 4]) -> Raster {
+    Raster {
+        width,
+        height,
+        pixels: (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| pixel(x, y))
+            .collect(),
+    }
+}
+
+#[test]
+fn dark_pixels_come_alive_with_the_top_row_on_top() {
+    // a black top row over white
+    let picture = raster(4, 4, |_, y| if y == 0 { BLACK } else { WHITE });
+    let board = picture.to_board((4, 4), &RasterOptions::default());
+
+    assert_eq!(board.population(), 4);
+    assert!((0..4).all(|x| board.get(x, 3) == 2));
+
+    let inverted = picture.to_board(
+        (4, 4),
+        &RasterOptions {
+            light_alive: true,
+            ..RasterOptions::default()
+        },
+    );
+    assert_eq!(inverted.population(), 12);
+    assert_eq!(inverted.get(0, 3), 0);
+}
+
+#[test]
+fn scaling_averages_and_fits_or_crops() {
+    // an 8x4 picture, black on its left half
+    let picture = raster(8, 4, |x, _| if x < 4 { BLACK } else { WHITE });
+
+    // fitted inside a 4x4 board it's squashed into the middle two rows
+    let fitted = picture.to_board((4, 4), &RasterOptions::default());
+    assert_eq!(fitted.population(), 4);
+    assert_eq!(fitted.get(0, 0), 0);
+    assert_eq!(fitted.get(0, 1), 2);
+    assert_eq!(fitted.get(2, 1), 0);
+
+    // cropped to fill it, only the middle four columns are left
+    let cropped = picture.to_board(
+        (4, 4),
+        &RasterOptions {
+            fit: Fit::Crop,
+            ..RasterOptions::default()
+        },
+    );
+    assert_eq!(cropped.population(), 8);
+    assert!((0..4).all(|y| cropped.get(1, y) == 2 && cropped.get(2, y) == 0));
+}
+
+#[test]
+fn hues_pick_teams_and_clear_pixels_stay_dead() {
+    let picture = raster(3, 1, |x, _| match x {
+        0 => RED,
+        1 => BLUE,
+        _ => [0, 0, 0, 0],
+    });
+    let board = picture.to_board(
+        (3, 1),
+        &RasterOptions {
+            threshold: 0.6,
+            team_hues: Some([0.0, 240.0]),
+            ..RasterOptions::default()
+        },
+    );
+
+    assert_eq!(board.get(0, 0), 2);
+    assert_eq!(board.get(1, 0), 3);
+    assert_eq!(board.get(2, 0), 0);
+}
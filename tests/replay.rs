@@ -0,0 +1,140 @@
+//! Replays keep everything needed to play a run back, and survive a trip through a file.
+
+use conway_bevy::bitboard::{Bitboard, Boundary};
+use conway_bevy::patterns;
+use conway_bevy::replay::{self, Event, Header, Replay, FORMAT_VERSION, MAGIC};
+use conway_bevy::rng::Rng;
+use conway_bevy::rules::{Chances, RuleSet};
+use conway_bevy::testing::random_board;
+
+fn header(rule: RuleSet, chances: Chances, (width, height): (u32, u32)) -> Header {
+    Header {
+        engine_version: "0.1.0".to_owned(),
+        rule,
+        chances,
+        seed: 42,
+        generation: 10,
+        width,
+        height,
+        boundary: Boundary::Toroidal,
+    }
+}
+
+/// A random board run for a while under a chancy rule, with walls, cells and a rule
+/// change dropped in along the way, and the replay of it.
+fn recorded_run() -> (Replay, Bitboard) {
+    let chances = Chances {
+        birth: 0.9,
+        survival: 0.95,
+    };
+    let header = header(RuleSet::parse("B3/S23/A8").unwrap(), chances, (70, 30));
+    let start = random_board(&mut Rng::new(5), (70, 30), Boundary::Toroidal);
+
+    let events = vec![
+        Event::Cell {
+            generation: 12,
+            x: 3,
+            y: 4,
+            state: 2,
+        },
+        Event::Cell {
+            generation: 12,
+            x: 69,
+            y: 29,
+            state: u8::MAX,
+        },
+        Event::Rule {
+            generation: 20,
+            rule: RuleSet::parse("B36/S23/A8").unwrap(),
+        },
+        Event::Cell {
+            generation: 31,
+            x: 10,
+            y: 0,
+            state: 0,
+        },
+    ];
+
+    let mut board = start.clone();
+    let mut rule = header.rule;
+    let mut rng = Rng::new(header.seed);
+    for generation in 10..40 {
+        match generation {
+            12 => {
+                board.set(3, 4, 2);
+                board.set_wall(69, 29, true);
+            }
+            20 => rule = RuleSet::parse("B36/S23/A8").unwrap(),
+            31 => board.set(10, 0, 0),
+            _ => {}
+        }
+        board.step_stochastic(&rule, &chances, &mut rng);
+    }
+
+    let replay = Replay {
+        header,
+        start,
+        events,
+        end: Some((40, board.cell_hash())),
+    };
+    (replay, board)
+}
+
+#[test]
+fn a_replay_plays_back_to_the_board_it_recorded() {
+    let (replay, board) = recorded_run();
+    let playback = replay::decode(&replay::encode(&replay)).unwrap().play();
+
+    assert_eq!(playback.generation, 40);
+    assert_eq!(playback.matches_end, Some(true));
+    assert!(playback.board.same_cells(&board));
+}
+
+#[test]
+fn a_replay_survives_a_trip_through_a_file() {
+    let (replay, _) = recorded_run();
+    let decoded = replay::decode(&replay::encode(&replay)).unwrap();
+
+    assert_eq!(decoded.header, replay.header);
+    assert_eq!(decoded.events, replay.events);
+    assert_eq!(decoded.end, replay.end);
+    assert_eq!(decoded.start.cell_bytes(), replay.start.cell_bytes());
+}
+
+#[test]
+fn chunks_from_later_versions_are_skipped() {
+    let mut board = Bitboard::new(8, 8);
+    board.set_boundary(Boundary::Toroidal);
+    patterns::find("glider")
+        .unwrap()
+        .stamp(&mut board, (2, 2), (false, false), 2);
+    let replay = Replay {
+        header: header(RuleSet::CONWAY, Chances::CERTAIN, (8, 8)),
+        start: board,
+        events: Vec::new(),
+        end: None,
+    };
+
+    let mut bytes = replay::encode(&replay);
+    bytes.extend(b"NEW!");
+    bytes.extend(3u32.to_le_bytes());
+    bytes.extend([1, 2, 3]);
+
+    let decoded = replay::decode(&bytes).unwrap();
+    assert!(decoded.start.same_cells(&replay.start));
+}
+
+#[test]
+fn damaged_and_newer_replays_are_refused() {
+    let (replay, _) = recorded_run();
+    let bytes = replay::encode(&replay);
+
+    assert!(replay::decode(&bytes[..bytes.len() - 3]).is_err());
+    assert!(replay::decode(b"not a replay at all").is_err());
+
+    let mut newer = MAGIC.to_vec();
+    newer.extend((FORMAT_VERSION + 1).to_le_bytes());
+    newer.extend(&bytes[6..]);
+    let error = replay::decode(&newer).unwrap_err();
+    assert!(error.contains("format version"));
+}
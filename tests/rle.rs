@@ -0,0 +1,45 @@
+//! RLE reads back what it writes, and clips turn away input asking for huge patterns.
+
+use conway_bevy::bitboard::Bitboard;
+use conway_bevy::rle::{self, Clip};
+use conway_bevy::rules::RuleSet;
+
+#[test]
+fn a_glider_reads_back_as_written() {
+    let clip = rle::decode("x = 3, y = 3, rule = B3/S23\nbo$2bo$3o!").unwrap();
+    assert_eq!((clip.width, clip.height, clip.cells.len()), (3, 3, 5));
+
+    let mut board = Bitboard::new(8, 8);
+    clip.stamp(&mut board, (2, 2), |_, _| true);
+    let text = rle::encode(&board, (2, 2, 3, 3), &RuleSet::CONWAY);
+    let again = rle::decode(&text).unwrap();
+    assert_eq!((again.width, again.height, again.cells.len()), (3, 3, 5));
+}
+
+#[test]
+fn oversized_runs_and_headers_are_errors() {
+    for text in [
+        "4294967295$o!",
+        "99999999999999999999o!",
+        "4000000000b4000000000bo!",
+        "5000o!",
+        "x = 100000, y = 3\no!",
+        "x = 3, y = 4294967296\no!",
+    ] {
+        assert!(rle::decode(text).is_err(), "{text:?} decoded");
+    }
+
+    let widest = format!("{}o!", rle::MAX_SIZE);
+    assert_eq!(rle::decode(&widest).unwrap().width, rle::MAX_SIZE);
+}
+
+#[test]
+fn points_spread_too_far_apart_are_an_error() {
+    let far = rle::MAX_SIZE as i64;
+    assert!(Clip::from_points(&[(0, 0, 2), (far, 0, 2)]).is_err());
+    assert!(Clip::from_points(&[(0, 0, 2), (0, 1 << 61, 2)]).is_err());
+    assert!(Clip::from_points(&[(i64::MIN, 0, 2), (i64::MAX, 0, 2)]).is_err());
+
+    let clip = Clip::from_points(&[(-3, 5, 2), (far - 4, 5, 3)]).unwrap();
+    assert_eq!((clip.width, clip.height), (rle::MAX_SIZE, 1));
+}
@@ -0,0 +1,52 @@
+//! Packed boards unpack to exactly the board they were packed from, ages included.
+
+use conway_bevy::bitboard::Boundary;
+use conway_bevy::rng::Rng;
+use conway_bevy::rules::RuleSet;
+use conway_bevy::snapshot;
+use conway_bevy::testing::random_board;
+
+#[test]
+fn boards_unpack_whole_and_against_the_generation_before() {
+    let mut rng = Rng::new(7);
+    // an age limit makes the ages matter to how the board goes on
+    let rule = RuleSet::parse("B3/S23/A12").unwrap();
+
+    for size in [(1, 1), (63, 9), (130, 40)] {
+        let mut before = random_board(&mut rng, size, Boundary::Toroidal);
+        before.step(&rule);
+        let mut board = before.clone();
+        board.step(&rule);
+
+        let whole = snapshot::pack(&board, None);
+        let delta = snapshot::pack(&board, Some(&before));
+        assert!(!whole.is_delta() && delta.is_delta());
+        assert!(delta.unpack(None).is_none());
+
+        for mut unpacked in [
+            whole.unpack(None).unwrap(),
+            delta.unpack(Some(&before)).unwrap(),
+        ] {
+            assert!(unpacked.same_cells(&board));
+            assert_eq!(unpacked.cell_bytes(), board.cell_bytes());
+
+            let mut expected = board.clone();
+            for _ in 0..20 {
+                unpacked.step(&rule);
+                expected.step(&rule);
+            }
+            assert!(unpacked.same_cells(&expected));
+        }
+    }
+}
+
+#[test]
+fn a_board_packed_against_itself_is_tiny() {
+    let board = random_board(&mut Rng::new(3), (256, 256), Boundary::Bounded);
+
+    let whole = snapshot::pack(&board, None);
+    let delta = snapshot::pack(&board, Some(&board));
+
+    assert!(delta.packed_size() < 64);
+    assert!(delta.packed_size() < whole.packed_size());
+}
@@ -0,0 +1,52 @@
+//! The board statistics other crates analyse simulations with.
+
+use conway_bevy::bitboard::Bitboard;
+use conway_bevy::patterns;
+use conway_bevy::rules::RuleSet;
+use conway_bevy::simulation::Simulation;
+use conway_bevy::view::{BoardView, LiveCell};
+
+#[test]
+fn a_glider_is_counted_boxed_and_hashed() {
+    let glider = patterns::find("glider").unwrap();
+    let mut board = Bitboard::new(32, 32);
+    glider.stamp(&mut board, (4, 6), (false, false), 2);
+    board.set(30, 30, 3);
+
+    let view = BoardView::new(&board);
+    assert_eq!(view.total_population(), 6);
+    assert_eq!((view.population(2), view.population(3)), (5, 1));
+    assert_eq!(view.iter_live().count(), 6);
+    assert!(view.iter_live().any(|cell| cell
+        == LiveCell {
+            x: 30,
+            y: 30,
+            team: 3
+        }));
+    assert_eq!(view.bounding_box(), Some((4, 6, 27, 25)));
+    assert_eq!(view.hash(), board.cell_hash());
+
+    assert_eq!(BoardView::new(&Bitboard::new(8, 8)).bounding_box(), None);
+}
+
+#[test]
+fn a_simulation_is_viewed_as_it_steps() {
+    let mut board = Bitboard::new(16, 16);
+    patterns::find("glider")
+        .unwrap()
+        .stamp(&mut board, (2, 2), (false, false), 2);
+    let mut simulation = Simulation::new(board, RuleSet::CONWAY);
+
+    let start = simulation.view().hash();
+    let (_, _, width, height) = simulation.view().bounding_box().unwrap();
+    simulation.step_n(4);
+
+    // a glider is the same shape again four generations on, one cell further along
+    let view = simulation.view();
+    assert_eq!(view.population(2), 5);
+    assert_eq!(
+        view.bounding_box().map(|(_, _, w, h)| (w, h)),
+        Some((width, height))
+    );
+    assert_ne!(view.hash(), start);
+}